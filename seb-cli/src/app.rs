@@ -52,15 +52,15 @@ pub fn resolve_entry_resolver(entry_resolver: EntryResolver) -> eyre::Result<Ent
 
 pub fn check_entry_field_duplication(bib: &Biblio, name: &str, value: &str) -> eyre::Result<()> {
     trace!("Checking current bibliography for possible duplicate {name} of '{value}'");
-    if bib.contains_field(name, |f| &**f == value) {
+    if bib.find_by_field(name, value).is_empty() {
+        trace!("No duplicate found!");
+        Ok(())
+    } else {
         Err(eyre!(
             "An entry already exists with a {} field with the value of '{}'.",
             name,
             value
         ))
-    } else {
-        trace!("No duplicate found!");
-        Ok(())
     }
 }
 
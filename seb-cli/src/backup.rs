@@ -0,0 +1,422 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use eyre::{eyre, Context};
+use seb::{
+    ast::{Biblio, BiblioResolver},
+    file::{FormatFile, Sidecar},
+    format::{BibTex, InputEncoding, Reader},
+};
+
+use crate::diff::Diff;
+
+/// The directory, next to the bibliography file, that the backups are kept in.
+pub const BACKUP_DIR: &str = ".seb-backups";
+
+// Backups are named `<stem>.<timestamp>.<ext>` so that the extension of the file is kept and the
+// names sort in the order the backups were made.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%6f";
+const TIMESTAMP_LEN: usize = 21;
+
+/// Copies the file at `path` to a new backup and removes all but the `keep` most recent backups
+/// of the file.
+///
+/// The sidecar file of the file, see [`Sidecar`], is backed up with it so that a restored file
+/// has the fields it stored in the sidecar file at the time. Returns the backup that was made, or
+/// `None` when `keep` is zero and nothing is backed up.
+pub fn backup(path: &Path, keep: usize) -> eyre::Result<Option<PathBuf>> {
+    if keep == 0 {
+        return Ok(None);
+    }
+
+    let dir = backup_dir(path);
+    fs::create_dir_all(&dir)
+        .wrap_err_with(|| eyre!("Cannot create the {} directory", dir.display()))?;
+
+    let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let backup = dir.join(backup_name(path, &timestamp));
+    fs::copy(path, &backup).wrap_err_with(|| eyre!("Cannot back up {}", path.display()))?;
+    let sidecar = sidecar_path(path);
+    if sidecar.exists() {
+        fs::copy(&sidecar, sidecar_path(&backup))
+            .wrap_err_with(|| eyre!("Cannot back up {}", sidecar.display()))?;
+    }
+    log::trace!("backed up {} to {}", path.display(), backup.display());
+
+    for old in backups(path)?.into_iter().skip(keep) {
+        log::trace!("removing the old backup {}", old.display());
+        remove_backup(&old)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Returns the backups of the file at `path`, the most recent first.
+pub fn backups(path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let dir = backup_dir(path);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).wrap_err_with(|| eyre!("Cannot read {}", dir.display())),
+    };
+
+    let mut backups = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|backup| is_backup_of(path, backup))
+        .collect::<Vec<_>>();
+    backups.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(backups)
+}
+
+/// Restores the file at `path` from the most recent backup, which is then removed so that the
+/// next undo restores the backup before it.
+///
+/// The `confirm` function is given a summary of the changes made by restoring the backup and the
+/// file is only restored when it returns `true`. The file and the backup are read in the
+/// `encoding`, or in the detected encoding when it is `None`, and are compared by line when either
+/// cannot be parsed, so that a broken file can still be restored.
+pub fn undo<F>(path: &Path, encoding: Option<InputEncoding>, confirm: F) -> eyre::Result<String>
+where
+    F: FnOnce(&str) -> bool,
+{
+    let Some(backup) = backups(path)?.into_iter().next() else {
+        return Ok(format!("No backups of {} to restore", path.display()));
+    };
+
+    let summary = format!(
+        "Restoring {} from {}:\n{}",
+        path.display(),
+        backup.display(),
+        changes(path, &backup, encoding)?
+    );
+    if !confirm(&summary) {
+        return Ok("The bibliography was not changed".to_owned());
+    }
+
+    restore(path, backup)?;
+    remove_backup(&backup)?;
+    Ok(summary)
+}
+
+/// Re-reads the file at `path` in the `encoding` after it was written and, when it no longer
+/// parses, restores it from the `backup` made before the write and returns an error.
+///
+/// The file is not restored when no backup was made before the write, as an older backup would
+/// undo more than the failed write. The backup is kept so that the failed write can still be
+/// undone by hand.
+pub fn verify(
+    path: &Path,
+    backup: Option<&Path>,
+    encoding: Option<InputEncoding>,
+) -> eyre::Result<()> {
+    let err = match open(path, encoding).and_then(|mut file| file.read_ast()) {
+        Ok(Ok(_)) => return Ok(()),
+        Ok(Err(resolver)) => eyre::Report::new(resolver),
+        Err(err) => eyre::Report::new(err),
+    };
+
+    let Some(backup) = backup else {
+        return Err(err.wrap_err(format!(
+            "{} no longer parses after it was written and no backup was made to restore it from",
+            path.display()
+        )));
+    };
+
+    restore(path, backup)?;
+    Err(err.wrap_err(format!(
+        "{} no longer parses after it was written so it was restored from {}",
+        path.display(),
+        backup.display()
+    )))
+}
+
+/// Reads the bibliography file at `path` in the `encoding` only to compare it, so entries missing
+/// required fields are kept.
+pub fn read(path: &Path, encoding: Option<InputEncoding>) -> eyre::Result<Biblio> {
+    let biblio = open(path, encoding)
+        .and_then(|mut file| file.read_ast())
+        .wrap_err_with(|| eyre!("Cannot read {}", path.display()))?;
+    Ok(biblio.unwrap_or_else(BiblioResolver::into_partial))
+}
+
+// The changes made by restoring the `backup` over the file at `path`, which are the entries added,
+// removed and changed, or the number of lines added and removed when either cannot be parsed.
+fn changes(path: &Path, backup: &Path, encoding: Option<InputEncoding>) -> eyre::Result<String> {
+    let compared = read(path, encoding).and_then(|current| Ok((current, read(backup, encoding)?)));
+    let err = match compared {
+        Ok((current, prior)) => return Ok(Diff::new(&current, &prior).to_string()),
+        Err(err) => err,
+    };
+    log::warn!("{err:#} - the changes are compared by line");
+
+    let read_raw = |path: &Path| {
+        fs::read(path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .wrap_err_with(|| eyre!("Cannot read {}", path.display()))
+    };
+    let (current, prior) = (read_raw(path)?, read_raw(backup)?);
+    let (current, prior) = (
+        current.lines().collect::<HashSet<_>>(),
+        prior.lines().collect::<HashSet<_>>(),
+    );
+    Ok(format!(
+        "{} lines removed and {} lines added",
+        current.difference(&prior).count(),
+        prior.difference(&current).count()
+    ))
+}
+
+fn open(path: &Path, encoding: Option<InputEncoding>) -> Result<FormatFile<BibTex>, seb::Error> {
+    FormatFile::open(path).map(|file| file.with_encoding(encoding))
+}
+
+// Copies the `backup`, and the backup of the sidecar file when there is one, over the file at
+// `path` and its sidecar file.
+fn restore(path: &Path, backup: &Path) -> eyre::Result<()> {
+    fs::copy(backup, path).wrap_err_with(|| eyre!("Cannot restore {}", path.display()))?;
+    let sidecar = sidecar_path(backup);
+    if sidecar.exists() {
+        let path = sidecar_path(path);
+        fs::copy(&sidecar, &path).wrap_err_with(|| eyre!("Cannot restore {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn remove_backup(backup: &Path) -> eyre::Result<()> {
+    fs::remove_file(backup)
+        .wrap_err_with(|| eyre!("Cannot remove the backup {}", backup.display()))?;
+    let sidecar = sidecar_path(backup);
+    if sidecar.exists() {
+        fs::remove_file(&sidecar)
+            .wrap_err_with(|| eyre!("Cannot remove the backup {}", sidecar.display()))?;
+    }
+    Ok(())
+}
+
+// The backup of the sidecar file is the sidecar file of the backup.
+fn sidecar_path(path: &Path) -> PathBuf {
+    Sidecar::new(path, Vec::new()).path().to_owned()
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUP_DIR)
+}
+
+fn backup_name(path: &Path, timestamp: &str) -> String {
+    let (stem, ext) = stem_and_ext(path);
+    format!("{stem}.{timestamp}.{ext}")
+}
+
+fn is_backup_of(path: &Path, backup: &Path) -> bool {
+    let (stem, ext) = stem_and_ext(path);
+    backup
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .as_deref()
+        .and_then(|name| name.strip_prefix(&format!("{stem}.")))
+        .and_then(|name| name.strip_suffix(&format!(".{ext}")))
+        .is_some_and(|timestamp| {
+            timestamp.len() == TIMESTAMP_LEN
+                && timestamp.chars().all(|c| c.is_ascii_digit() || c == 'T')
+        })
+}
+
+fn stem_and_ext(path: &Path) -> (String, String) {
+    let lossy = |s: Option<&std::ffi::OsStr>| s.unwrap_or_default().to_string_lossy().into_owned();
+    (lossy(path.file_stem()), lossy(path.extension()))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{
+        fixture::{FileWriteStr, PathChild},
+        TempDir,
+    };
+
+    use seb::ast::FieldQuery;
+
+    use super::*;
+
+    #[test]
+    fn undo_restores_the_prior_content_exactly() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        let prior = "@manual{a,\n    title = {Prior},\n}\n\n% a comment\n";
+        file.write_str(prior).unwrap();
+
+        backup(&file, 5).unwrap();
+        file.write_str("@manual{b, title = {Changed}}\n").unwrap();
+
+        let output = undo(&file, None, |summary| {
+            assert!(summary.ends_with("- b\n+ a"));
+            true
+        })
+        .unwrap();
+
+        assert!(output.starts_with("Restoring"));
+        assert_eq!(prior, fs::read_to_string(file.path()).unwrap());
+        assert!(backups(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_restores_a_file_that_does_not_parse() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        let prior = "@manual{a, title = {Prior}}\n";
+        file.write_str(prior).unwrap();
+
+        backup(&file, 5).unwrap();
+        file.write_str("@manual{a, title = {Pri}or}}\n").unwrap();
+
+        let output = undo(&file, None, |summary| {
+            assert!(summary.ends_with("1 lines removed and 1 lines added"));
+            true
+        })
+        .unwrap();
+
+        assert!(output.starts_with("Restoring"));
+        assert_eq!(prior, fs::read_to_string(file.path()).unwrap());
+        assert!(backups(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_restores_the_sidecar_file_with_the_bibliography() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        let sidecar = dir.child("refs.bib.json");
+        let prior = "@manual{a, title = {A}, x-seb-sidecar = {abstract}}\n";
+        let prior_sidecar = "{\"a\": {\"abstract\": \"Prior\"}}\n";
+        file.write_str(prior).unwrap();
+        sidecar.write_str(prior_sidecar).unwrap();
+
+        backup(&file, 5).unwrap();
+        // `seb rm a` rewrites the sidecar file without the fields of the removed entry
+        file.write_str("").unwrap();
+        sidecar.write_str("{}\n").unwrap();
+
+        undo(&file, None, |_| true).unwrap();
+
+        assert_eq!(prior, fs::read_to_string(file.path()).unwrap());
+        assert_eq!(prior_sidecar, fs::read_to_string(sidecar.path()).unwrap());
+        // the backup of the sidecar file is removed with the restored backup
+        let left = fs::read_dir(dir.child(BACKUP_DIR).path()).unwrap().count();
+        assert_eq!(0, left);
+    }
+
+    #[test]
+    fn declined_undo_keeps_the_file_and_backup() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        file.write_str("@manual{a, title = {Prior}}\n").unwrap();
+
+        backup(&file, 5).unwrap();
+        file.write_str("@manual{b, title = {Changed}}\n").unwrap();
+
+        assert_eq!(
+            "The bibliography was not changed",
+            undo(&file, None, |_| false).unwrap()
+        );
+        assert_eq!(
+            "@manual{b, title = {Changed}}\n",
+            fs::read_to_string(file.path()).unwrap()
+        );
+        assert_eq!(1, backups(&file).unwrap().len());
+    }
+
+    #[test]
+    fn broken_write_is_restored_from_the_backup() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        let prior = "@manual{a, title = {Prior}}\n";
+        file.write_str(prior).unwrap();
+
+        let made = backup(&file, 5).unwrap();
+        // a compose that didn't escape the braces of the title
+        file.write_str("@manual{a, title = {Pri}or}}\n").unwrap();
+
+        let err = verify(&file, made.as_deref(), None).unwrap_err();
+
+        assert!(err.to_string().contains("restored from"));
+        assert_eq!(prior, fs::read_to_string(file.path()).unwrap());
+        assert_eq!(1, backups(&file).unwrap().len());
+    }
+
+    #[test]
+    fn broken_write_is_not_restored_from_an_older_backup() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        file.write_str("@manual{a, title = {Older}}\n").unwrap();
+        backup(&file, 5).unwrap();
+        file.write_str("@manual{a, title = {Prior}}\n").unwrap();
+
+        // backups are turned off for this write
+        assert_eq!(None, backup(&file, 0).unwrap());
+        let broken = "@manual{a, title = {Pri}or}}\n";
+        file.write_str(broken).unwrap();
+
+        let err = verify(&file, None, None).unwrap_err();
+
+        assert!(err.to_string().contains("no backup was made"));
+        assert_eq!(broken, fs::read_to_string(file.path()).unwrap());
+    }
+
+    #[test]
+    fn backup_is_read_in_the_given_encoding() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        // "Müller" in Latin-1
+        fs::write(
+            file.path(),
+            b"@manual{a, author = {M\xfcller}, title = {A}}\n",
+        )
+        .unwrap();
+
+        let biblio = read(&file, Some(InputEncoding::Latin1)).unwrap();
+
+        let author = biblio.get("a").unwrap().get_field("author").unwrap();
+        assert_eq!("Müller", &**author);
+    }
+
+    #[test]
+    fn valid_write_is_kept() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        file.write_str("@manual{a, title = {Prior}}\n").unwrap();
+
+        let made = backup(&file, 5).unwrap();
+        file.write_str("@manual{b, title = {Changed}}\n").unwrap();
+
+        verify(&file, made.as_deref(), None).unwrap();
+        assert_eq!(
+            "@manual{b, title = {Changed}}\n",
+            fs::read_to_string(file.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn only_the_most_recent_backups_are_kept() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        let other = dir.child("other.bib");
+        other.write_str("").unwrap();
+        backup(&other, 5).unwrap();
+
+        for title in ["First", "Second", "Third"] {
+            file.write_str(&format!("@manual{{a, title = {{{title}}}}}\n"))
+                .unwrap();
+            backup(&file, 2).unwrap();
+        }
+
+        let kept = backups(&file).unwrap();
+        assert_eq!(2, kept.len());
+        assert!(fs::read_to_string(&kept[0]).unwrap().contains("Third"));
+        assert!(fs::read_to_string(&kept[1]).unwrap().contains("Second"));
+        assert_eq!(1, backups(&other).unwrap().len());
+    }
+}
@@ -1,17 +1,144 @@
+use std::path::PathBuf;
+
 use crate::{
     app::{self, resolve_entry_resolver, take_first_resolvable, user_select_resolvable},
-    interact::{user_resolve_entry, user_select_map},
+    feed::{self, FEED_FIELD},
+    interact::{user_build_entry, user_multi_select, user_resolve_entry, user_select_map},
+    page::Page,
+    pdf,
 };
 
-use seb::ast::{Biblio, BiblioResolver, Entry};
+use seb::{
+    ast::{
+        lowercase_doi, normalize_title, store_arxiv_id, Biblio, BiblioResolver, Entry, EntryKind,
+        FieldQuery, Resolver, Similarity, ADDED_FIELD, INCOMPLETE_FIELD, SOURCE_FIELD,
+    },
+    format::BibTex,
+    normalize::{collapse_quoted_whitespace, VERBATIM_FIELDS},
+    provider::{Providers, Query},
+};
 
-use clap::Subcommand;
+use chrono::NaiveDate;
+use clap::{Args, Subcommand};
 use eyre::{eyre, Context};
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
+
+/// The options shared by every `add` subcommand.
+#[derive(Args)]
+#[allow(clippy::module_name_repetitions)]
+#[allow(clippy::struct_excessive_bools)] // each flag is a separate command line option
+pub struct AddOptions {
+    /// Don't stamp the entry with the provenance fields `x-seb-source` and `x-seb-added`.
+    #[clap(long, global = true)]
+    no_provenance: bool,
+
+    /// Add the entry without checking for an entry with a similar title and author.
+    ///
+    /// By default a warning is shown when the bibliography has an entry that is likely the
+    /// same work, such as the same paper added by title instead of DOI, and the entry is only
+    /// added when confirmed.
+    #[clap(long, global = true)]
+    pub(super) force: bool,
+
+    /// Add the entry found even when it is missing required fields.
+    ///
+    /// The entry is added as a `misc` entry with the `x-seb-incomplete` field listing the
+    /// kind it was meant to be and the missing fields, which the `check` command reports.
+    #[clap(long, global = true)]
+    allow_incomplete: bool,
+
+    /// Collapse the runs of whitespace, such as double spaces and newlines, in the field values
+    /// to single spaces like `seb normalize`.
+    #[clap(long, global = true)]
+    collapse_whitespace: bool,
+
+    /// Add the entry found as this entry type instead of the type given by the provider, such
+    /// as `inproceedings` for a conference paper that a provider lists as an article.
+    ///
+    /// The fields are kept and a field the type requires is taken from a field naming the same
+    /// kind of venue, so the `journal` of an article becomes the `booktitle` of the paper.
+    #[clap(long = "as", global = true, value_name = "TYPE", parse(from_str))]
+    kind: Option<EntryKind<'static>>,
+
+    /// How titles are compared when checking for a similar entry, from the config file.
+    #[clap(skip)]
+    pub(super) similarity: Similarity,
+
+    /// Whether the cite keys generated for the entries added are transliterated to ASCII, from
+    /// the config file.
+    #[clap(skip)]
+    pub(super) transliterate_keys: bool,
+}
+
+impl AddOptions {
+    /// Converts the `entry` found into the entry type chosen with `--as`, when it is another
+    /// type.
+    ///
+    /// An entry missing the fields required by the type is added as an incomplete entry when
+    /// `--allow-incomplete` is set.
+    fn convert(&self, entry: Entry) -> Result<Entry, Resolver> {
+        match &self.kind {
+            Some(kind) if *kind != entry.kind() => match entry.convert(kind.clone()) {
+                Ok(converted) => Ok(converted),
+                Err(resolver) if self.allow_incomplete => {
+                    let missing = resolver.required_fields().collect::<Vec<_>>().join(", ");
+                    warn!("Adding the entry as {kind} without the required fields: {missing}");
+                    Ok(resolver.resolve_incomplete())
+                }
+                Err(resolver) => Err(resolver),
+            },
+            _ => Ok(entry),
+        }
+    }
+
+    /// Sets whether the cite keys generated for the entries of the `resolver` are transliterated.
+    fn transliterate(&self, mut resolver: BiblioResolver) -> BiblioResolver {
+        for entry in resolver.unresolved() {
+            entry.set_transliterate(self.transliterate_keys);
+        }
+        resolver
+    }
+
+    /// Builds the entry of the `resolver`, which is missing required fields, as an incomplete
+    /// entry of the entry type chosen with `--as`, or of its own type.
+    fn resolve_incomplete(&self, resolver: Resolver) -> Entry {
+        let resolver = match &self.kind {
+            // the entry type chosen may not require the fields that are missing
+            Some(kind) => match resolver.resolve_incomplete().convert(kind.clone()) {
+                Ok(mut entry) => {
+                    entry.remove_field(INCOMPLETE_FIELD);
+                    return entry;
+                }
+                Err(resolver) => resolver,
+            },
+            None => resolver,
+        };
+        let missing = resolver.required_fields().collect::<Vec<_>>().join(", ");
+        warn!(
+            "Adding the entry as {} without the required fields: {missing}",
+            resolver.kind()
+        );
+        resolver.resolve_incomplete()
+    }
+}
 
 #[derive(Subcommand)]
 #[allow(clippy::module_name_repetitions)]
 pub enum AddCommands {
+    /// Search for an astronomy entry by NASA ADS bibcode
+    ///
+    /// The ADS API requires a token which is read from the `SEB_ADS_TOKEN` environment variable.
+    #[clap(arg_required_else_help = true)]
+    Ads {
+        /// The bibcode to search for
+        bibcode: String,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
     /// Search for reference by doi
     #[clap(arg_required_else_help = true)]
     Doi {
@@ -24,6 +151,35 @@ pub enum AddCommands {
         #[clap(long)]
         cite: Option<String>,
     },
+    /// Add the new articles of an RSS or Atom feed, such as the table of contents of a journal
+    ///
+    /// The entry of each article is searched for by the DOI in the feed, or by its title when it
+    /// has no DOI, and the articles to add are selected in interactive mode. Only the articles
+    /// published since the feed was last fetched are added unless `--all` is used.
+    #[clap(arg_required_else_help = true)]
+    Feed {
+        /// The URL of the feed
+        url: String,
+
+        /// Add every article in the feed, not only the articles since the last fetch.
+        #[clap(long)]
+        all: bool,
+
+        /// The bibliography file, next to which the time each feed is fetched is recorded.
+        #[clap(skip)]
+        file: Option<PathBuf>,
+    },
+    /// Add an entry by filling in its fields
+    ///
+    /// The kind of entry is chosen first, then the value of each required field is entered
+    /// followed by any optional fields. Cancelling a prompt leaves the bibliography unchanged.
+    Manual {
+        /// The cite key of the new entry
+        ///
+        /// A cite key is generated from the field values when this is not set.
+        #[clap(long)]
+        cite: Option<String>,
+    },
     /// Search for entry by IETF RFC number
     #[clap(arg_required_else_help = true)]
     Ietf {
@@ -36,6 +192,22 @@ pub enum AddCommands {
         #[clap(long)]
         cite: Option<String>,
     },
+    /// Search for the entry of a PDF file by its DOI
+    ///
+    /// The DOI is read from the metadata of the PDF or the text of its first pages, when no DOI
+    /// is found then the title of the PDF is used to search for the entry instead.
+    #[clap(arg_required_else_help = true)]
+    Pdf {
+        /// The path of the PDF file
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
     /// Search for reference by ISBN
     #[clap(arg_required_else_help = true)]
     Isbn {
@@ -48,6 +220,55 @@ pub enum AddCommands {
         #[clap(long)]
         cite: Option<String>,
     },
+    /// Search for a standard by its number, such as `IEEE 754` or `IEEE Std 802.11-2020`
+    ///
+    /// Only IEEE standards are searched for using the IEEE Xplore API, which requires a key
+    /// read from the `SEB_IEEE_TOKEN` environment variable. The latest revision is added when
+    /// the number has no year.
+    #[clap(arg_required_else_help = true)]
+    Standard {
+        /// The organization and number of the standard
+        standard: String,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
+    /// Add an entry for every DOI found in some text
+    ///
+    /// The text, such as the references section of a paper, is scanned for DOIs and the entry of
+    /// each DOI is searched for and added. The DOIs already in the bibliography are skipped, as
+    /// are the entries similar to an existing entry unless `--force` is used.
+    Text {
+        /// The text to scan for DOIs
+        ///
+        /// The text is read from stdin when this is not set or is `-`.
+        text: Option<String>,
+    },
+    /// Add the entry of an article page, such as the page of a paper on a publisher's website
+    ///
+    /// The entry is built from the citation metadata that publishers add to their article pages.
+    /// Pages that require signing in cannot be read.
+    #[clap(arg_required_else_help = true)]
+    Url {
+        /// The URL of the article page
+        url: String,
+
+        /// Fetch the BibTeX of the article instead of reading the page metadata
+        ///
+        /// The BibTeX is fetched from the citation export link of the page, or found by the DOI
+        /// of the page like `seb add doi`. The page metadata is only used when neither is found.
+        #[clap(long)]
+        bibtex: bool,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
     /// Search for entry using the title
     #[clap(arg_required_else_help = true)]
     Title {
@@ -64,47 +285,120 @@ pub enum AddCommands {
 
 type DynError = Box<dyn std::error::Error>;
 
+// A title shorter than this is likely truncated.
+const MIN_TITLE_LEN: usize = 8;
+// A year before this is likely a parsing error rather than an early work.
+const MIN_YEAR: i32 = 1500;
+
 impl AddCommands {
-    pub(super) fn execute(
+    pub(super) fn execute<F>(
         self,
         biblio: &mut Biblio,
+        providers: &Providers,
         mut interact: bool,
-    ) -> Result<String, DynError> {
-        // take a copy of interact bool and use it to determine if the first resolvable item should
-        // be resolved
-        let resolve = interact;
-        let search_results = self.search_entries(biblio, &mut interact)?;
-
-        let mut entry = if interact {
-            user_select_resolvable(search_results)?.or_else(resolve_entry_resolver)?
+        options: &AddOptions,
+        confirm_similar: Option<F>,
+    ) -> Result<String, DynError>
+    where
+        F: FnOnce(&Entry, &[&Entry]) -> bool,
+    {
+        let skip_similar = confirm_similar.is_some();
+        match self {
+            AddCommands::Text { text } => {
+                let dois = pdf::find_dois(&read_text(text)?);
+                return Ok(add_dois(
+                    biblio,
+                    providers,
+                    &dois,
+                    options,
+                    skip_similar,
+                    None,
+                ));
+            }
+            AddCommands::Feed { url, all, file } => {
+                let since = match &file {
+                    Some(file) if !all => feed::last_fetched(file, &url)?,
+                    _ => None,
+                };
+                let dois = feed_dois(&url, since, interact)?;
+                let added = add_dois(biblio, providers, &dois, options, skip_similar, Some(&url));
+                if let Some(file) = &file {
+                    if let Err(err) = feed::record_fetch(file, &url) {
+                        warn!("Cannot record the fetch of the feed: {err:#}");
+                    }
+                }
+                return Ok(added);
+            }
+            _ => {}
+        }
+
+        let mut entry = if let AddCommands::Manual { cite } = self {
+            let mut entry = user_build_entry(cite, options.transliterate_keys)?;
+            entry.set_field(SOURCE_FIELD, "manual");
+            entry
         } else {
-            match take_first_resolvable(search_results) {
-                Err(mut resolver) if resolve => {
-                    user_resolve_entry(&mut resolver)?;
-                    resolver.resolve()?
+            // take a copy of interact bool and use it to determine if the first resolvable item
+            // should be resolved
+            let resolve = interact;
+            let search_results = self
+                .search_entries(biblio, providers, options, &mut interact)?
+                .map_err(|resolver| options.transliterate(resolver));
+
+            let entry = if interact {
+                options.convert(
+                    user_select_resolvable(search_results)?.or_else(resolve_entry_resolver)?,
+                )
+            } else {
+                match take_first_resolvable(search_results) {
+                    Err(mut resolver) if resolve => {
+                        user_resolve_entry(&mut resolver)?;
+                        options.convert(resolver.resolve()?)
+                    }
+                    Err(resolver) if options.allow_incomplete => {
+                        Ok(options.resolve_incomplete(resolver))
+                    }
+                    e => options.convert(e?),
                 }
-                e => e?,
+            };
+            let mut entry = match entry {
+                Ok(entry) => entry,
+                // the fields required by the entry type chosen with `--as` are asked for too
+                Err(resolver) if resolve => resolve_entry_resolver(resolver)?,
+                Err(resolver) => return Err(resolver.into()),
+            };
+
+            // the entries of a page are found without a provider
+            if matches!(self, AddCommands::Url { .. }) && entry.get_field(SOURCE_FIELD).is_none() {
+                entry.set_field(SOURCE_FIELD, "url");
             }
+            self.set_cite(&mut entry);
+            entry
         };
 
-        self.set_cite(&mut entry);
-
-        let cite = entry.cite().to_owned();
-        biblio.insert(entry);
-        info!("Entry with cite '{cite}' added to bibliography");
-        Ok(cite)
+        stamp(&mut entry, options);
+        Ok(insert(biblio, entry, &options.similarity, confirm_similar))
     }
 
     fn search_entries(
         &self,
         biblio: &Biblio,
+        providers: &Providers,
+        options: &AddOptions,
         interact: &mut bool,
     ) -> eyre::Result<Result<Biblio, BiblioResolver>> {
         match self {
+            AddCommands::Ads { bibcode, .. } => {
+                debug!("ads subcommand called with a value of '{bibcode}'");
+                app::check_entry_field_duplication(biblio, "bibcode", bibcode)?;
+                // not wrapped so that the message for a missing token explains how to set it up
+                Ok(providers.lookup(&Query::Bibcode(bibcode))?)
+            }
             AddCommands::Doi { doi, .. } => {
                 debug!("doi subcommand called with a value of '{doi}'");
                 app::check_entry_field_duplication(biblio, "doi", doi)?;
-                seb::entries_by_doi(doi).wrap_err_with(|| eyre!("Cannot find the entry"))
+                providers
+                    .lookup(&Query::Doi(doi))
+                    .wrap_err_with(|| eyre!("Cannot find the entry"))
             }
             AddCommands::Ietf { rfc_number, .. } => {
                 debug!("ietf subcommand called with value of '{}'", &rfc_number);
@@ -113,41 +407,68 @@ impl AddCommands {
                         && contains_field(e, "series", "Request for Comment")
                         && contains_field(e, "number", rfc_number.to_string().as_str())
                 });
-                seb::entries_by_rfc(*rfc_number).wrap_err_with(|| eyre!("Cannot find the entry"))
+                providers
+                    .lookup(&Query::Rfc(*rfc_number))
+                    .wrap_err_with(|| eyre!("Cannot find the entry"))
             }
             AddCommands::Isbn { isbn, .. } => {
                 debug!("isbn subcommand called with value of '{isbn}'");
                 app::check_entry_field_duplication(biblio, "isbn", isbn)?;
-                seb::entries_by_isbn(isbn).wrap_err_with(|| eyre!("Cannot find the entry"))
+                providers
+                    .lookup(&Query::Isbn(isbn))
+                    .wrap_err_with(|| eyre!("Cannot find the entry"))
+            }
+            AddCommands::Feed { .. } | AddCommands::Manual { .. } | AddCommands::Text { .. } => {
+                unreachable!("manual entries, feeds and text are not searched for")
+            }
+            AddCommands::Pdf { path, .. } => {
+                debug!("pdf subcommand called with value of '{}'", path.display());
+                let pdf = pdf::read(path)?;
+                match (pdf.doi, pdf.title) {
+                    (Some(doi), _) => {
+                        info!("Found the DOI '{doi}' in the PDF");
+                        app::check_entry_field_duplication(biblio, "doi", &doi)?;
+                        providers
+                            .lookup(&Query::Doi(&doi))
+                            .wrap_err_with(|| eyre!("Cannot find the entry"))
+                    }
+                    (None, Some(title)) => {
+                        info!("No DOI found in the PDF - searching by the title '{title}'");
+                        search_by_title(biblio, providers, &title, interact)
+                    }
+                    (None, None) => Err(eyre!("No DOI or title found in '{}'", path.display())),
+                }
+            }
+            AddCommands::Standard { standard, .. } => {
+                debug!("standard subcommand called with a value of '{standard}'");
+                let query = Query::Standard(standard);
+                if providers.consulted(&query).next().is_none() {
+                    return Err(eyre!(
+                        "Cannot search for the standard '{standard}' - only IEEE standards, such \
+                        as 'IEEE 754', can be searched for, other standards can be added using \
+                        `seb add manual`"
+                    ));
+                }
+                // not wrapped so that the message for a missing key explains how to set it up
+                Ok(providers.lookup(&query)?)
             }
             AddCommands::Title { title, .. } => {
                 debug!("title subcommand called with value of '{title}'");
-                // We don't check duplication with title because it is unlikely to be a complete
-                // title or to match fully with an existing entry.
-                // Instead we will perform the search for entry stubs (doi, title) and once
-                // one is selected then perform the duplication check on the doi.
-                trace!("Searching parts of an entry by title..");
-                let mut entry_stubs = seb::entry_stubs_by_title(title)?;
-
-                let index = if *interact {
-                    user_select_map("Confirm entry", &entry_stubs, |(_, title)| title.clone())?
-                } else {
-                    trace!("Interact mode not enabled - taking first entry part");
-                    0
-                };
-
-                *interact = false;
-
-                let doi = entry_stubs.remove(index).0;
-                app::check_entry_field_duplication(biblio, "doi", &doi)?;
-                seb::entries_by_doi(&doi).wrap_err_with(|| eyre!("Cannot find the entry"))
+                search_by_title(biblio, providers, title, interact)
+            }
+            AddCommands::Url { url, bibtex, .. } => {
+                debug!("url subcommand called with value of '{url}'");
+                search_page(biblio, providers, url, *bibtex, options.transliterate_keys)
             }
         }
     }
 
     fn set_cite(self, entry: &mut Entry) {
         match self {
-            AddCommands::Doi {
+            AddCommands::Ads {
+                cite: Some(cite), ..
+            }
+            | AddCommands::Doi {
                 cite: Some(cite), ..
             }
             | AddCommands::Ietf {
@@ -155,17 +476,642 @@ impl AddCommands {
             }
             | AddCommands::Isbn {
                 cite: Some(cite), ..
+            }
+            | AddCommands::Pdf {
+                cite: Some(cite), ..
+            }
+            | AddCommands::Standard {
+                cite: Some(cite), ..
+            }
+            | AddCommands::Url {
+                cite: Some(cite), ..
             } => entry.set_cite(cite),
             _ => {}
         }
     }
 }
 
-fn contains_field(entry: &Entry, field_name: &str, value: &str) -> bool {
-    use seb::ast::FieldQuery;
+/// Lowercases the DOI of the `entry`, stamps its provenance, unless disabled by the `options`, and
+/// logs any [`quality_warnings`].
+fn stamp(entry: &mut Entry, options: &AddOptions) {
+    // providers return DOIs in mixed case
+    lowercase_doi(entry);
+    // the arXiv ID matches a preprint with its published version
+    store_arxiv_id(entry);
+    if options.collapse_whitespace {
+        let collapsed = entry
+            .fields()
+            .into_iter()
+            .filter(|field| !VERBATIM_FIELDS.contains(&&*field.name))
+            .filter_map(|field| {
+                Some((
+                    field.name.to_string(),
+                    collapse_quoted_whitespace(&field.value)?,
+                ))
+            })
+            .collect::<Vec<_>>();
+        for (name, value) in collapsed {
+            entry.set_field(&name, value);
+        }
+    }
+    if options.no_provenance {
+        entry.remove_field(SOURCE_FIELD);
+    } else {
+        // the source is stamped by the provider that found the entry
+        entry.set_field(
+            ADDED_FIELD,
+            chrono::Local::now().format("%Y-%m-%d").to_string(),
+        );
+    }
+
+    let this_year = chrono::Datelike::year(&chrono::Local::now());
+    for warning in quality_warnings(entry, this_year) {
+        warn!("{warning}");
+    }
+}
+
+// the `text` argument or stdin when it is not set or is `-`
+fn read_text(text: Option<String>) -> eyre::Result<String> {
+    match text {
+        Some(text) if text != "-" => Ok(text),
+        _ => {
+            let mut text = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+                .wrap_err("Cannot read the text from stdin")?;
+            Ok(text)
+        }
+    }
+}
+
+/// Adds the entry of each of the `dois` and returns how many were found, added and skipped.
+///
+/// A DOI already in the [`Biblio`] is skipped without being searched for, and when `skip_similar`
+/// is set so is an entry that is likely the same work as an existing entry. A DOI that cannot be
+/// found, or that is missing required fields without `allow_incomplete`, is logged and not added.
+///
+/// The URL of the `feed` the DOIs were found in is recorded in the [`FEED_FIELD`] of each entry
+/// with the other provenance fields.
+fn add_dois(
+    biblio: &mut Biblio,
+    providers: &Providers,
+    dois: &[String],
+    options: &AddOptions,
+    skip_similar: bool,
+    feed: Option<&str>,
+) -> String {
+    let (mut added, mut skipped) = (0, 0);
+
+    for doi in dois {
+        if !biblio.find_by_field("doi", doi).is_empty() {
+            info!("An entry with the DOI '{doi}' is already in the bibliography");
+            skipped += 1;
+            continue;
+        }
+
+        let found = match providers.lookup(&Query::Doi(doi)) {
+            Ok(found) => found.map_err(|resolver| options.transliterate(resolver)),
+            Err(err) => {
+                warn!("Cannot find the entry of the DOI '{doi}': {err}");
+                continue;
+            }
+        };
+        let entry = match take_first_resolvable(found) {
+            Ok(entry) => options.convert(entry),
+            Err(resolver) if options.allow_incomplete => Ok(options.resolve_incomplete(resolver)),
+            Err(resolver) => Err(resolver),
+        };
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(resolver) => {
+                let missing = resolver.required_fields().collect::<Vec<_>>().join(", ");
+                warn!("The entry of the DOI '{doi}' is missing the required fields: {missing}");
+                continue;
+            }
+        };
+
+        if skip_similar {
+            let similar = biblio.similar_to_with(&entry, &options.similarity);
+            if !similar.is_empty() {
+                let cites = similar.iter().map(|e| e.cite()).collect::<Vec<_>>();
+                warn!(
+                    "The entry of the DOI '{doi}' is likely already in the bibliography as: {}",
+                    cites.join(", ")
+                );
+                skipped += 1;
+                continue;
+            }
+        }
+
+        // the entries of the same authors and year often generate the same cite key
+        if biblio.get(entry.cite()).is_some() {
+            let cite = biblio.unique_cite(entry.cite());
+            entry.set_cite(cite);
+        }
+
+        stamp(&mut entry, options);
+        if let Some(url) = feed.filter(|_| !options.no_provenance) {
+            entry.set_field(FEED_FIELD, url);
+        }
+        info!("Entry with cite '{}' added to bibliography", entry.cite());
+        biblio.insert(entry);
+        added += 1;
+    }
+
+    format!(
+        "Found {} DOIs: {added} added, {skipped} skipped, {} not found",
+        dois.len(),
+        dois.len() - added - skipped
+    )
+}
+
+/// Finds the entry of the article page at the `url`.
+///
+/// With `bibtex` the BibTeX of the citation export link of the page is used, or otherwise the
+/// entry of the DOI of the page, before the entry is built from the metadata of the page.
+fn search_page(
+    biblio: &Biblio,
+    providers: &Providers,
+    url: &str,
+    bibtex: bool,
+    transliterate: bool,
+) -> eyre::Result<Result<Biblio, BiblioResolver>> {
+    let html = seb::text_by_url(url).wrap_err_with(|| eyre!("Cannot fetch the page '{url}'"))?;
+    let page = Page::parse(&html);
+    if page.requires_login() {
+        return Err(eyre!(
+            "The page '{url}' requires signing in - use the DOI of the article with `seb add doi` \
+            or its PDF with `seb add pdf` instead"
+        ));
+    }
+
+    let doi = page.doi();
+    if let Some(doi) = &doi {
+        app::check_entry_field_duplication(biblio, "doi", doi)?;
+    }
+    if bibtex {
+        if let Some(link) = page.bibtex_link(url) {
+            info!("Fetching the BibTeX of the page from '{link}'");
+            match seb::entries_by_url::<BibTex>(&link) {
+                Ok(found) => return Ok(found),
+                Err(err) => warn!("Cannot fetch the BibTeX from '{link}': {err}"),
+            }
+        }
+        if let Some(doi) = &doi {
+            info!("Searching for the DOI '{doi}' of the page");
+            match providers.lookup(&Query::Doi(doi)) {
+                Ok(found) => return Ok(found),
+                Err(err) => warn!("Cannot find the entry of the DOI '{doi}': {err}"),
+            }
+        }
+        info!("No BibTeX found for the page - reading the page metadata instead");
+    }
+
+    let mut resolver = page
+        .resolver(url)
+        .ok_or_else(|| eyre!("No citation metadata found on the page '{url}'"))?;
+    resolver.set_transliterate(transliterate);
+    Ok(Biblio::try_resolve(vec![resolver]))
+}
+
+/// Returns the DOIs of the articles of the feed at the `url` that are published on or after the
+/// date the feed was last fetched, `since`, or of every article without a date to compare. In
+/// interactive mode the articles are selected from their titles.
+///
+/// An article without a DOI is searched for by its title and is skipped when no entry has the
+/// same title.
+fn feed_dois(url: &str, since: Option<NaiveDate>, interact: bool) -> eyre::Result<Vec<String>> {
+    let xml = seb::text_by_url(url).wrap_err_with(|| eyre!("Cannot fetch the feed '{url}'"))?;
+    let mut items = feed::items(&xml)
+        .into_iter()
+        .filter(|item| match (since, item.date) {
+            (Some(since), Some(date)) => date >= since,
+            _ => true,
+        })
+        .collect::<Vec<_>>();
+
+    if interact && !items.is_empty() {
+        let titles = items
+            .iter()
+            .map(|item| {
+                let title = item.title.as_deref().unwrap_or("(no title)");
+                item.doi
+                    .as_ref()
+                    .map_or_else(|| title.to_owned(), |doi| format!("{title} ({doi})"))
+            })
+            .collect::<Vec<_>>();
+        let selected = user_multi_select("Select the articles to add", &titles)?;
+        items = items
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| selected.contains(i))
+            .map(|(_, item)| item)
+            .collect();
+    }
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| item.doi.or_else(|| doi_by_title(item.title.as_deref()?)))
+        .collect())
+}
+
+// the DOI of the entry with the same title, as the title of a feed item is a complete title
+fn doi_by_title(title: &str) -> Option<String> {
+    let stubs = seb::entry_stubs_by_title(title)
+        .map_err(|err| warn!("Cannot find the entry of the article '{title}': {err}"))
+        .ok()?;
+    let doi = stubs
+        .into_iter()
+        .find(|(_, found)| normalize_title(found) == normalize_title(title))
+        .map(|(doi, _)| doi);
+    if doi.is_none() {
+        warn!("No entry has the title of the article '{title}' - skipping it");
+    }
+    doi
+}
 
+/// Returns a warning for each field value of the `entry` that looks like garbage metadata, such as
+/// a truncated title, so that the entry can be reconsidered before it is used.
+///
+/// These are heuristics so the entry is still added.
+fn quality_warnings(entry: &Entry, this_year: i32) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let cite = entry.cite();
+
+    let title = entry.title().trim().chars().count();
+    if title < MIN_TITLE_LEN {
+        warnings.push(format!(
+            "The title of '{cite}' is only {title} characters long, it may be truncated"
+        ));
+    }
+
+    // a braced name, such as `{World Health Organization}`, is a single name on purpose
+    for author in entry.authors() {
+        if author.given.is_none() && !author.family.starts_with('{') {
+            warnings.push(format!(
+                "The author '{}' of '{cite}' is a single name, it may be incomplete",
+                author.family
+            ));
+        }
+    }
+
+    if let Some(year) = entry
+        .year()
+        .filter(|year| !(MIN_YEAR..=this_year).contains(year))
+    {
+        warnings.push(format!(
+            "The year {year} of '{cite}' is not between {MIN_YEAR} and {this_year}"
+        ));
+    }
+
+    warnings
+}
+
+/// Inserts the `entry` and returns its cite key.
+///
+/// When `confirm_similar` is set and the [`Biblio`] has entries that are likely the same work, see
+/// [`Biblio::similar_to_with`] the `similarity`, a warning is logged and the entry is only
+/// inserted when `confirm_similar` returns `true`.
+fn insert<F>(
+    biblio: &mut Biblio,
+    entry: Entry,
+    similarity: &Similarity,
+    confirm_similar: Option<F>,
+) -> String
+where
+    F: FnOnce(&Entry, &[&Entry]) -> bool,
+{
+    let cite = entry.cite().to_owned();
+
+    if let Some(confirm) = confirm_similar {
+        let similar = biblio.similar_to_with(&entry, similarity);
+        if !similar.is_empty() {
+            let cites = similar.iter().map(|e| e.cite()).collect::<Vec<_>>();
+            warn!(
+                "'{cite}' is likely already in the bibliography as: {}",
+                cites.join(", ")
+            );
+            if !confirm(&entry, &similar) {
+                return format!("Entry '{cite}' was not added");
+            }
+        }
+    }
+
+    biblio.insert(entry);
+    info!("Entry with cite '{cite}' added to bibliography");
+    cite
+}
+
+fn search_by_title(
+    biblio: &Biblio,
+    providers: &Providers,
+    title: &str,
+    interact: &mut bool,
+) -> eyre::Result<Result<Biblio, BiblioResolver>> {
+    // We don't check duplication with title because it is unlikely to be a complete
+    // title or to match fully with an existing entry.
+    // Instead we will perform the search for entry stubs (doi, title) and once
+    // one is selected then perform the duplication check on the doi.
+    trace!("Searching parts of an entry by title..");
+    let mut entry_stubs = seb::entry_stubs_by_title(title)?;
+
+    let index = if *interact {
+        user_select_map("Confirm entry", &entry_stubs, |(_, title)| title.clone())?
+    } else {
+        trace!("Interact mode not enabled - taking first entry part");
+        0
+    };
+
+    *interact = false;
+
+    let doi = entry_stubs.remove(index).0;
+    app::check_entry_field_duplication(biblio, "doi", &doi)?;
+    providers
+        .lookup(&Query::Doi(&doi))
+        .wrap_err_with(|| eyre!("Cannot find the entry"))
+}
+
+fn contains_field(entry: &Entry, field_name: &str, value: &str) -> bool {
     entry
         .get_field(field_name)
         .map(|v| v.as_ref() == value)
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use seb::ast::{Entry, FieldQuery, DOI_ORIGINAL_FIELD};
+
+    use super::*;
+    use crate::fixture::manual;
+
+    fn entry(cite: &str, title: &str, author: &str) -> Entry {
+        manual(cite, &[("title", title), ("author", author)])
+    }
+
+    fn biblio() -> Biblio {
+        Biblio::new(vec![entry(
+            "knuth1984",
+            "Literate Programming",
+            "Knuth, Donald E.",
+        )])
+    }
+
+    #[test]
+    fn year_out_of_range_is_a_warning() {
+        let mut entry = entry("knuth", "Literate Programming", "Knuth, Donald E.");
+        assert!(quality_warnings(&entry, 2022).is_empty());
+
+        for year in ["1066", "2023"] {
+            entry.set_field("year", year);
+            assert_eq!(
+                vec![format!(
+                    "The year {year} of 'knuth' is not between 1500 and 2022"
+                )],
+                quality_warnings(&entry, 2022)
+            );
+        }
+    }
+
+    #[test]
+    fn single_token_author_is_a_warning() {
+        let entry = entry(
+            "knuth",
+            "Literate Programming",
+            "K and {World Health Organization} and Leslie Lamport",
+        );
+
+        assert_eq!(
+            vec!["The author 'K' of 'knuth' is a single name, it may be incomplete"],
+            quality_warnings(&entry, 2022)
+        );
+    }
+
+    #[test]
+    fn short_title_is_a_warning() {
+        let entry = entry("knuth", " Lit. ", "Knuth, Donald E.");
+
+        assert_eq!(
+            vec!["The title of 'knuth' is only 4 characters long, it may be truncated"],
+            quality_warnings(&entry, 2022)
+        );
+    }
+
+    #[test]
+    fn similar_entry_asks_before_adding() {
+        let mut biblio = biblio();
+        let mut asked = Vec::new();
+
+        let output = insert(
+            &mut biblio,
+            entry("literate", "Literate programming.", "Donald Knuth"),
+            &Similarity::default(),
+            Some(|_: &Entry, similar: &[&Entry]| {
+                asked.extend(similar.iter().map(|e| e.cite().to_owned()));
+                false
+            }),
+        );
+
+        assert_eq!("Entry 'literate' was not added", output);
+        assert_eq!(vec!["knuth1984"], asked);
+        assert!(biblio.get("literate").is_none());
+    }
+
+    #[test]
+    fn force_adds_similar_entry_without_asking() {
+        let mut biblio = biblio();
+
+        let output = insert(
+            &mut biblio,
+            entry("literate", "Literate programming.", "Donald Knuth"),
+            &Similarity::default(),
+            None::<fn(&Entry, &[&Entry]) -> bool>,
+        );
+
+        assert_eq!("literate", output);
+        assert!(biblio.get("literate").is_some());
+    }
+
+    #[test]
+    fn different_entry_is_added_without_asking() {
+        let mut biblio = biblio();
+
+        insert(
+            &mut biblio,
+            entry("tex", "The TeXbook", "Donald Knuth"),
+            &Similarity::default(),
+            Some(|_: &Entry, _: &[&Entry]| unreachable!("no similar entries")),
+        );
+
+        assert!(biblio.get("tex").is_some());
+    }
+
+    // Finds the entry built from each DOI starting with `10.1000/` by the function, which is
+    // missing fields when the function leaves out a field the entry type requires.
+    struct Found(fn(&str) -> Resolver);
+
+    impl seb::provider::Provider for Found {
+        fn name(&self) -> &'static str {
+            "found"
+        }
+
+        fn supports(&self, query: &Query<'_>) -> bool {
+            matches!(query, Query::Doi(doi) if doi.starts_with("10.1000/"))
+        }
+
+        fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, seb::Error> {
+            let Query::Doi(doi) = query else {
+                unreachable!("only DOIs are supported")
+            };
+            Ok(Biblio::try_resolve(vec![(self.0)(doi)]))
+        }
+    }
+
+    fn providers(found: fn(&str) -> Resolver) -> Providers {
+        let mut providers = Providers::new();
+        providers.register(Found(found));
+        providers
+    }
+
+    // A manual with a title by the DOI.
+    fn manual_of(doi: &str) -> Resolver {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "found");
+        resolver.title(if doi.ends_with('2') {
+            "The TeXbook"
+        } else {
+            "Concrete Mathematics"
+        });
+        resolver.set_field("doi", doi);
+        resolver
+    }
+
+    // A conference paper listed as an article, with the proceedings in the `venue` field.
+    fn paper_of(doi: &str, venue: &str) -> Resolver {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "paper");
+        resolver.set_field("author", "Lamport, Leslie");
+        resolver.title("Paxos Made Simple");
+        resolver.set_field(venue, "Proceedings of the Symposium");
+        resolver.set_field("year", "2001");
+        resolver.set_field("doi", doi);
+        resolver
+    }
+
+    // The options of the tests, which only add the entries found.
+    fn options() -> AddOptions {
+        AddOptions {
+            no_provenance: true,
+            force: false,
+            allow_incomplete: false,
+            collapse_whitespace: false,
+            kind: None,
+            similarity: Similarity::default(),
+            transliterate_keys: true,
+        }
+    }
+
+    #[test]
+    fn dois_in_text_are_added_once() {
+        let mut biblio = biblio();
+        biblio.insert(entry("found", "Found elsewhere", "Someone, Else"));
+        biblio
+            .get_mut("found")
+            .unwrap()
+            .set_field("doi", "10.1000/1");
+        let providers = providers(manual_of);
+        let options = options();
+
+        let dois = pdf::find_dois(
+            "Cited: doi:10.1000/1, 10.1000/2 and 10.1000/3; again 10.1000/2 and 10.9999/404.",
+        );
+        let output = add_dois(&mut biblio, &providers, &dois, &options, true, None);
+
+        assert_eq!("Found 4 DOIs: 2 added, 1 skipped, 1 not found", output);
+        assert_eq!("The TeXbook", &**biblio.get("founda").unwrap().title());
+        assert_eq!(
+            "Concrete Mathematics",
+            &**biblio.get("foundb").unwrap().title()
+        );
+    }
+
+    #[test]
+    fn added_dois_are_lowercased() {
+        let mut biblio = biblio();
+        let providers = providers(manual_of);
+        let options = options();
+
+        let dois = ["10.1000/ABC1".to_owned()];
+        let output = add_dois(&mut biblio, &providers, &dois, &options, true, None);
+        assert_eq!("Found 1 DOIs: 1 added, 0 skipped, 0 not found", output);
+
+        let entry = biblio.get("found").unwrap();
+        assert_eq!("10.1000/abc1", &**entry.get_field("doi").unwrap());
+        assert_eq!(
+            "10.1000/ABC1",
+            &**entry.get_field(DOI_ORIGINAL_FIELD).unwrap()
+        );
+
+        // the same DOI in uppercase is a duplicate
+        let output = add_dois(&mut biblio, &providers, &dois, &options, true, None);
+        assert_eq!("Found 1 DOIs: 0 added, 1 skipped, 0 not found", output);
+        assert!(app::check_entry_field_duplication(&biblio, "doi", "10.1000/ABC1").is_err());
+    }
+
+    #[test]
+    fn doi_is_added_as_the_entry_type_chosen() {
+        let mut biblio = biblio();
+        let providers = providers(|doi| paper_of(doi, "journal"));
+        let options = AddOptions {
+            kind: Some(EntryKind::InProceedings),
+            ..options()
+        };
+
+        let output = AddCommands::Doi {
+            doi: "10.1000/paxos".to_owned(),
+            cite: None,
+        }
+        .execute(
+            &mut biblio,
+            &providers,
+            false,
+            &options,
+            None::<fn(&Entry, &[&Entry]) -> bool>,
+        )
+        .unwrap();
+
+        assert_eq!("paper", output);
+        let entry = biblio.get("paper").unwrap();
+        assert_eq!(EntryKind::InProceedings, entry.kind());
+        assert_eq!(
+            "Proceedings of the Symposium",
+            &**entry.get_field("book_title").unwrap()
+        );
+        assert_eq!(None, entry.get_field("journal"));
+        assert_eq!("10.1000/paxos", &**entry.get_field("doi").unwrap());
+    }
+
+    #[test]
+    fn incomplete_doi_is_added_as_the_entry_type_chosen() {
+        let providers = providers(|doi| paper_of(doi, "book_title"));
+        let mut options = AddOptions {
+            allow_incomplete: true,
+            kind: Some(EntryKind::InProceedings),
+            ..options()
+        };
+        let dois = ["10.1000/paxos".to_owned()];
+
+        let mut papers = biblio();
+        add_dois(&mut papers, &providers, &dois, &options, false, None);
+        let entry = papers.get("paper").unwrap();
+        assert_eq!(EntryKind::InProceedings, entry.kind());
+        assert_eq!(None, entry.get_field(INCOMPLETE_FIELD));
+
+        options.kind = Some(EntryKind::Book);
+        let mut books = biblio();
+        add_dois(&mut books, &providers, &dois, &options, false, None);
+        let entry = books.get("paper").unwrap();
+        assert_eq!(EntryKind::Other("misc".into()), entry.kind());
+        assert!(entry
+            .get_field(INCOMPLETE_FIELD)
+            .unwrap()
+            .starts_with("book: "));
+    }
+}
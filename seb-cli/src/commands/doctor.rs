@@ -0,0 +1,314 @@
+use std::{
+    collections::HashSet,
+    io::Write,
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use seb::{
+    ast::KeyFormat,
+    file::FormatFile,
+    format::{BibTex, Reader},
+    provider::Providers,
+    ADS_TOKEN_VAR, CONTACT_EMAIL_VAR, IEEE_TOKEN_VAR,
+};
+
+use crate::config::{Config, CONFIG_FILE};
+
+use super::oa;
+
+/// How long to wait for a connection to a provider API.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a check turned out, only a failed check is critical.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, PartialEq)]
+struct Check {
+    status: Status,
+    message: String,
+}
+
+impl Check {
+    fn new<S: Into<String>>(status: Status, message: S) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks the config file, the bibliography `files`, the API tokens and email addresses and the
+/// network connection to each provider and writes the report to `out`, without changing any file.
+///
+/// When no `files` are given the bibliography file in the current directory is checked.
+///
+/// # Errors
+///
+/// An `Err` is returned when any of the critical checks fail, such as a bibliography file that
+/// cannot be parsed, or the report cannot be written.
+pub(crate) fn doctor<W: Write>(files: &[PathBuf], color: bool, mut out: W) -> eyre::Result<()> {
+    let (config_check, config) = check_config(Path::new(CONFIG_FILE));
+    let mut checks = vec![config_check];
+    checks.extend(check_files(files, &config.key_format()));
+    checks.extend(check_credentials(&config));
+    checks.extend(check_network(&Providers::default()));
+
+    writeln!(out, "{}", report(&checks, color))?;
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == Status::Fail)
+        .count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(eyre::eyre!(
+            "{failed} of the {} checks failed",
+            checks.len()
+        ))
+    }
+}
+
+fn check_config(path: &Path) -> (Check, Config) {
+    match Config::load(path) {
+        Ok(config) if path.exists() => (
+            Check::new(
+                Status::Pass,
+                format!("The {CONFIG_FILE} config file is valid"),
+            ),
+            config,
+        ),
+        Ok(config) => (
+            Check::new(
+                Status::Pass,
+                format!("No {CONFIG_FILE} config file - using the default settings"),
+            ),
+            config,
+        ),
+        Err(err) => (
+            Check::new(Status::Fail, chain(&err)),
+            // the other checks still use the default settings
+            Config::default(),
+        ),
+    }
+}
+
+fn check_files(files: &[PathBuf], key_format: &KeyFormat) -> Vec<Check> {
+    let opened = if files.is_empty() {
+        vec![FormatFile::<BibTex>::find(".")]
+    } else {
+        files.iter().map(FormatFile::<BibTex>::open).collect()
+    };
+
+    opened
+        .into_iter()
+        .map(|file| {
+            let mut file = match file {
+                Ok(file) => file,
+                Err(err) => {
+                    return Check::new(
+                        Status::Fail,
+                        format!("The bibliography file cannot be opened: {err}"),
+                    )
+                }
+            };
+            let path = file.path().display().to_string();
+            match file.read().and_then(|bibtex| bibtex.parse_with(key_format)) {
+                Ok(Ok(biblio)) => Check::new(
+                    Status::Pass,
+                    format!("{path} parses, entries: {}", biblio.entries().count()),
+                ),
+                Ok(Err(_)) => Check::new(
+                    Status::Warn,
+                    format!("{path} has entries missing required fields, see `seb check`"),
+                ),
+                Err(err) => Check::new(Status::Fail, format!("{path} cannot be parsed: {err}")),
+            }
+        })
+        .collect()
+}
+
+fn check_credentials(config: &Config) -> Vec<Check> {
+    let is_set = |var: &str| std::env::var(var).is_ok_and(|value| !value.trim().is_empty());
+
+    let ads = if is_set(ADS_TOKEN_VAR) {
+        Check::new(
+            Status::Pass,
+            format!("The ADS API token is set in {ADS_TOKEN_VAR}"),
+        )
+    } else {
+        Check::new(
+            Status::Warn,
+            format!("No ADS API token is set in {ADS_TOKEN_VAR} - `seb add ads` cannot be used"),
+        )
+    };
+    let ieee = if is_set(IEEE_TOKEN_VAR) {
+        Check::new(
+            Status::Pass,
+            format!("The IEEE Xplore API key is set in {IEEE_TOKEN_VAR}"),
+        )
+    } else {
+        Check::new(
+            Status::Warn,
+            format!(
+                "No IEEE Xplore API key is set in {IEEE_TOKEN_VAR} - `seb add standard` cannot \
+                be used"
+            ),
+        )
+    };
+    let unpaywall = if oa::email(config.unpaywall.email.as_deref()).is_some() {
+        Check::new(Status::Pass, "The Unpaywall email address is set")
+    } else {
+        Check::new(
+            Status::Warn,
+            format!(
+                "No Unpaywall email address is set in {CONFIG_FILE} or {} - `seb oa` and \
+                `seb enrich --oa` cannot be used",
+                oa::EMAIL_VAR
+            ),
+        )
+    };
+    let crossref = if config.crossref.email.is_some() || is_set(CONTACT_EMAIL_VAR) {
+        Check::new(Status::Pass, "The Crossref contact email address is set")
+    } else {
+        Check::new(
+            Status::Warn,
+            format!(
+                "No Crossref contact email address is set in {CONFIG_FILE} or \
+                {CONTACT_EMAIL_VAR} - Crossref requests use the slower public servers"
+            ),
+        )
+    };
+
+    vec![ads, ieee, unpaywall, crossref]
+}
+
+fn check_network(providers: &Providers) -> Vec<Check> {
+    let mut checked = HashSet::new();
+    providers
+        .iter()
+        .filter_map(|provider| Some((provider.name(), provider.host()?)))
+        .filter(|(_, host)| checked.insert(*host))
+        .map(|(name, host)| match connect(host) {
+            Ok(()) => Check::new(
+                Status::Pass,
+                format!("The {name} provider API at {host} is reachable"),
+            ),
+            Err(err) => Check::new(
+                Status::Fail,
+                format!("The {name} provider API at {host} is not reachable: {err}"),
+            ),
+        })
+        .collect()
+}
+
+fn connect(host: &str) -> std::io::Result<()> {
+    let addr = (host, 443).to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "the host has no address")
+    })?;
+    TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map(drop)
+}
+
+// Every error message in the chain of the `err`, such as `Invalid seb.toml file: missing field`.
+fn chain(err: &eyre::Report) -> String {
+    err.chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": ")
+}
+
+fn report(checks: &[Check], color: bool) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let (mark, code) = match check.status {
+                Status::Pass => ("ok", "32"),
+                Status::Warn => ("warn", "33"),
+                Status::Fail => ("fail", "31"),
+            };
+            if color {
+                format!("\x1b[{code}m[{mark}]\x1b[0m {}", check.message)
+            } else {
+                format!("[{mark}] {}", check.message)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{prelude::*, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn invalid_config_fails() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.child(CONFIG_FILE);
+
+        assert_eq!(Status::Pass, check_config(config.path()).0.status);
+
+        config.write_str("[format]\nkey = \"{month}\"").unwrap();
+        let (check, config) = check_config(config.path());
+        assert_eq!(Status::Fail, check.status);
+        assert!(check.message.starts_with("Invalid seb.toml file: "));
+        assert_eq!(Config::default(), config);
+    }
+
+    #[test]
+    fn bibliography_files_are_parsed() {
+        let dir = TempDir::new().unwrap();
+        let valid = dir.child("valid.bib");
+        valid.write_str("@manual{a, title = {A}}").unwrap();
+        let invalid = dir.child("invalid.bib");
+        invalid.write_str("@manual{a, title = {A}").unwrap();
+        let incomplete = dir.child("incomplete.bib");
+        incomplete.write_str("@book{a, title = {A}}").unwrap();
+
+        let statuses = check_files(
+            &[
+                valid.to_path_buf(),
+                invalid.to_path_buf(),
+                incomplete.to_path_buf(),
+                dir.child("missing.bib").to_path_buf(),
+            ],
+            &KeyFormat::default(),
+        )
+        .into_iter()
+        .map(|check| check.status)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![Status::Pass, Status::Fail, Status::Warn, Status::Fail],
+            statuses
+        );
+    }
+
+    #[test]
+    fn report_marks_each_check() {
+        let checks = [
+            Check::new(Status::Pass, "passed"),
+            Check::new(Status::Warn, "warned"),
+            Check::new(Status::Fail, "failed"),
+        ];
+
+        assert_eq!(
+            "[ok] passed\n[warn] warned\n[fail] failed",
+            report(&checks, false)
+        );
+        assert!(report(&checks, true).starts_with("\x1b[32m[ok]\x1b[0m passed\n"));
+    }
+
+    #[test]
+    fn providers_without_a_host_are_not_checked() {
+        assert!(check_network(&Providers::new()).is_empty());
+    }
+}
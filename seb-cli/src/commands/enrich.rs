@@ -0,0 +1,336 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use seb::{
+    ast::{
+        Biblio, Entry, EntryKind, FieldQuery, QuotedString, INCOMPLETE_FIELD, PROVENANCE_PREFIX,
+    },
+    provider::{Providers, Query},
+};
+
+use crate::filter::Filter;
+
+/// The number of lookups made at the same time.
+const WORKERS: usize = 4;
+
+/// The identifier used to look up an entry again.
+enum Id {
+    Doi(String),
+    Isbn(String),
+}
+
+impl Id {
+    fn of(entry: &Entry) -> Option<Self> {
+        entry
+            .get_field("doi")
+            .map(|doi| Self::Doi(doi.to_string()))
+            .or_else(|| {
+                entry
+                    .get_field("isbn")
+                    .map(|isbn| Self::Isbn(isbn.to_string()))
+            })
+    }
+
+    fn query(&self) -> Query<'_> {
+        match self {
+            Self::Doi(doi) => Query::Doi(doi),
+            Self::Isbn(isbn) => Query::Isbn(isbn),
+        }
+    }
+}
+
+/// Looks up each entry with a DOI or ISBN again and fills in the fields it is missing or that are
+/// empty, or replaces the existing fields when `overwrite` is set. Locked entries are skipped and at most
+/// `max` entries are changed.
+///
+/// Returns a summary of the fields filled for each entry.
+pub(super) fn enrich(
+    biblio: &mut Biblio,
+    providers: &Providers,
+    filter: Option<&Filter>,
+    overwrite: bool,
+    max: Option<usize>,
+) -> String {
+    let targets = biblio
+        .entries()
+        .filter(|entry| !entry.is_locked() && filter.is_none_or(|f| f.matches(entry)))
+        .filter_map(|entry| Some((entry.cite().to_owned(), Id::of(entry)?)))
+        .collect::<Vec<_>>();
+
+    let mut found = lookup_all(providers, &targets);
+    // the entries changed before the limit is reached do not depend on the order of the lookups
+    found.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let max = max.unwrap_or(usize::MAX);
+    let mut lines = Vec::new();
+    let mut left = 0;
+    for (cite, fields) in found {
+        if lines.len() == max {
+            left += usize::from(!changes(biblio, &cite, fields, overwrite).is_empty());
+            continue;
+        }
+        let names = fill(biblio, &cite, fields, overwrite);
+        if !names.is_empty() {
+            lines.push(format!("{cite}: {}", names.join(", ")));
+        }
+    }
+
+    lines.sort();
+    if left > 0 {
+        lines.push(super::limit_reached(max, left));
+    }
+    if lines.is_empty() {
+        return "No fields were filled".to_owned();
+    }
+    lines.join("\n")
+}
+
+/// Looks up the entry by its DOI and fills in the fields it is missing or that are empty, such as
+/// the title of an entry added with `--allow-incomplete`. Existing fields are never changed.
+///
+/// Returns the sorted names of the fields filled.
+pub(super) fn fill_from_doi(biblio: &mut Biblio, providers: &Providers, cite: &str) -> Vec<String> {
+    let Some(doi) = biblio.get(cite).and_then(|entry| entry.get_field("doi")) else {
+        return Vec::new();
+    };
+    let fields = lookup(providers, &Id::Doi(doi.to_string()));
+    fill(biblio, cite, fields, false)
+}
+
+// Sets the fields found on the entry and returns the sorted names of the fields that changed.
+fn fill(
+    biblio: &mut Biblio,
+    cite: &str,
+    fields: Vec<(String, QuotedString)>,
+    overwrite: bool,
+) -> Vec<String> {
+    let fields = changes(biblio, cite, fields, overwrite);
+    if fields.is_empty() {
+        return Vec::new();
+    }
+
+    let mut names = fields
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    if let Some(entry) = biblio.get_mut(cite) {
+        for (name, value) in fields {
+            entry.set_field(&name, value);
+        }
+    }
+    complete(biblio, cite);
+    names
+}
+
+// An entry added with `--allow-incomplete` is changed to the entry type it was meant to be, see
+// `INCOMPLETE_FIELD`, once it has every field that the type requires.
+fn complete(biblio: &mut Biblio, cite: &str) {
+    let Some(entry) = biblio.get(cite) else {
+        return;
+    };
+    let Some(incomplete) = entry.get_field(INCOMPLETE_FIELD) else {
+        return;
+    };
+    let kind = incomplete
+        .split_once(':')
+        .map_or(&**incomplete, |(kind, _)| kind);
+    let Ok(mut entry) = entry.convert(EntryKind::from(kind)) else {
+        return;
+    };
+    let required = entry.kind().required_fields();
+    if required
+        .iter()
+        .all(|name| entry.get_field(name).is_some_and(|value| !value.is_empty()))
+    {
+        entry.remove_field(INCOMPLETE_FIELD);
+        biblio.insert(entry);
+    }
+}
+
+// Returns the fields found that would change the entry.
+fn changes(
+    biblio: &Biblio,
+    cite: &str,
+    fields: Vec<(String, QuotedString)>,
+    overwrite: bool,
+) -> Vec<(String, QuotedString)> {
+    let Some(entry) = biblio.get(cite) else {
+        return Vec::new();
+    };
+    fields
+        .into_iter()
+        .filter(|(name, _)| overwrite || entry.get_field(name).is_none_or(|value| value.is_empty()))
+        .filter(|(name, value)| entry.get_field(name) != Some(value))
+        .collect()
+}
+
+// Looks up the targets concurrently and returns the fields found for each cite key.
+fn lookup_all(
+    providers: &Providers,
+    targets: &[(String, Id)],
+) -> Vec<(String, Vec<(String, QuotedString)>)> {
+    let next = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::with_capacity(targets.len()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..WORKERS.min(targets.len()) {
+            scope.spawn(|| {
+                while let Some((cite, id)) = targets.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let fields = lookup(providers, id);
+                    let mut found = found.lock().expect("No worker panics holding the lock");
+                    found.push((cite.clone(), fields));
+                    log::info!("[{}/{}] Looked up '{cite}'", found.len(), targets.len());
+                }
+            });
+        }
+    });
+
+    found
+        .into_inner()
+        .expect("No worker panics holding the lock")
+}
+
+fn lookup(providers: &Providers, id: &Id) -> Vec<(String, QuotedString)> {
+    let query = id.query();
+    match providers.lookup(&query) {
+        Ok(Ok(biblio)) => biblio
+            .into_entries()
+            .into_iter()
+            .next()
+            .map(|entry| {
+                entry
+                    .fields()
+                    .into_iter()
+                    .filter(|field| !field.name.starts_with(PROVENANCE_PREFIX))
+                    .map(|field| (field.name.to_string(), field.value.into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Ok(Err(_)) => {
+            log::warn!("The entry found by {query} is missing required fields - skipping");
+            Vec::new()
+        }
+        Err(err) => {
+            log::warn!("Cannot find the entry by {query}: {err}");
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seb::{
+        ast::{BiblioResolver, SOURCE_FIELD},
+        provider::Provider,
+        Error,
+    };
+
+    use super::*;
+    use crate::fixture::manual;
+
+    struct Fixed;
+
+    impl Provider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn supports(&self, query: &Query<'_>) -> bool {
+            matches!(query, Query::Doi(_))
+        }
+
+        fn lookup(&self, _: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+            Ok(Ok(Biblio::new(vec![manual(
+                "Found",
+                &[
+                    ("title", "Found title"),
+                    ("pages", "1--10"),
+                    ("doi", "10.1000/182"),
+                ],
+            )])))
+        }
+    }
+
+    fn providers() -> Providers {
+        let mut providers = Providers::new();
+        providers.register(Fixed);
+        providers
+    }
+
+    #[test]
+    fn only_missing_fields_are_filled() {
+        let mut biblio = Biblio::new(vec![
+            manual(
+                "Knuth",
+                &[("title", "Original title"), ("doi", "10.1000/182")],
+            ),
+            manual("NoDoi", &[("title", "No doi")]),
+        ]);
+
+        let report = enrich(&mut biblio, &providers(), None, false, None);
+
+        assert_eq!("Knuth: pages", report);
+        let entry = biblio.get("Knuth").unwrap();
+        assert_eq!("Original title", &*entry.title().to_string());
+        assert_eq!("1--10", &*entry.get_field("pages").unwrap().to_string());
+        assert!(entry.get_field(SOURCE_FIELD).is_none());
+        assert!(biblio.get("NoDoi").unwrap().get_field("pages").is_none());
+        assert!(biblio.dirty());
+    }
+
+    #[test]
+    fn overwrite_replaces_existing_fields() {
+        let mut biblio = Biblio::new(vec![manual(
+            "Knuth",
+            &[("title", "Original title"), ("doi", "10.1000/182")],
+        )]);
+
+        let report = enrich(&mut biblio, &providers(), None, true, None);
+
+        assert_eq!("Knuth: pages, title", report);
+        let entry = biblio.get("Knuth").unwrap();
+        assert_eq!("Found title", &*entry.title().to_string());
+    }
+
+    #[test]
+    fn enrich_stops_at_the_max_changes() {
+        let mut biblio = Biblio::new(vec![
+            manual("A", &[("doi", "10.1000/182")]),
+            manual("B", &[("doi", "10.1000/182")]),
+        ]);
+
+        let report = enrich(&mut biblio, &providers(), None, false, Some(1));
+
+        assert_eq!(
+            "A: pages\nStopped at the limit of 1 change(s) set by --max, 1 more were not made",
+            report
+        );
+        assert!(biblio.get("A").unwrap().get_field("pages").is_some());
+        assert!(biblio.get("B").unwrap().get_field("pages").is_none());
+
+        let report = enrich(&mut biblio, &providers(), None, false, Some(0));
+
+        assert_eq!(crate::commands::limit_reached(0, 1), report);
+        assert!(biblio.get("B").unwrap().get_field("pages").is_none());
+    }
+
+    #[test]
+    fn fill_from_doi_only_fills_missing_fields() {
+        let mut biblio = Biblio::new(vec![manual("Knuth", &[("title", "Original title")])]);
+        assert!(fill_from_doi(&mut biblio, &providers(), "Knuth").is_empty());
+
+        biblio
+            .get_mut("Knuth")
+            .unwrap()
+            .set_field("doi", "10.1000/182");
+        let filled = fill_from_doi(&mut biblio, &providers(), "Knuth");
+
+        assert_eq!(vec!["pages"], filled);
+        let entry = biblio.get("Knuth").unwrap();
+        assert_eq!("Original title", &*entry.title().to_string());
+    }
+}
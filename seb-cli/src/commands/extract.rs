@@ -0,0 +1,181 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use eyre::{eyre, Context};
+use seb::format::{BibTex, Format};
+
+/// Writes the entries cited in the LaTeX `.aux` file, and the entries they reference in a
+/// `crossref` field, as `BibTeX` to `out` or to stdout when `out` is not set.
+///
+/// A warning is logged for each cite key that no entry has. An `Err` is returned when `out` is the
+/// bibliography `file` the entries are extracted from, which would otherwise be overwritten.
+pub(super) fn extract(
+    bibtex: &BibTex,
+    aux: &Path,
+    out: Option<&Path>,
+    file: Option<&Path>,
+) -> eyre::Result<String> {
+    if let (Some(out), Some(file)) = (out, file) {
+        if is_same_file(out, file) {
+            return Err(eyre!(
+                "Cannot write the extracted entries to {}, which is the bibliography file they \
+                 are extracted from",
+                out.display()
+            ));
+        }
+    }
+
+    let cited = cited_keys(aux)?;
+    // `\nocite{*}` is written as `\citation{*}` and cites every entry
+    let keys = if cited.iter().any(|key| key == "*") {
+        bibtex.raw_entries().map(|entry| entry.key()).collect()
+    } else {
+        cited.iter().map(String::as_str).collect::<Vec<_>>()
+    };
+
+    let (extracted, missing) = bibtex.extract(&keys);
+    for key in missing {
+        log::warn!("The cite key '{key}' is cited but no entry has that cite key");
+    }
+
+    let count = extracted.raw_entries().count();
+    if let Some(out) = out {
+        fs::write(out, extracted.raw())
+            .wrap_err_with(|| eyre!("Cannot write {}", out.display()))?;
+        Ok(format!("{count} entries written to {}", out.display()))
+    } else {
+        std::io::stdout()
+            .lock()
+            .write_all(extracted.raw().as_bytes())?;
+        // the entries have already been written to stdout
+        Ok(String::new())
+    }
+}
+
+// Whether the paths are the same file, a path that does not exist is not the same as any file.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Reads the cite keys of the `\citation` commands in the `.aux` file in the order they are first
+/// cited, which includes the `.aux` files of the included files read with `\@input`.
+fn cited_keys(aux: &Path) -> eyre::Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut files = vec![aux.to_path_buf()];
+    let mut read = Vec::new();
+
+    while let Some(file) = files.pop() {
+        if read.contains(&file) {
+            continue;
+        }
+        let src =
+            fs::read_to_string(&file).wrap_err_with(|| eyre!("Cannot read {}", file.display()))?;
+
+        for key in arguments(&src, r"\citation").flat_map(|keys| keys.split(',')) {
+            let key = key.trim();
+            if !key.is_empty() && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_owned());
+            }
+        }
+
+        // the included `.aux` files are relative to the directory LaTeX was run in, which is the
+        // directory of the main `.aux` file
+        let dir = aux.parent().unwrap_or_else(|| Path::new(""));
+        let mut inputs = arguments(&src, r"\@input")
+            .map(|input| dir.join(input.trim()))
+            .collect::<Vec<PathBuf>>();
+        inputs.reverse();
+        files.extend(inputs);
+        read.push(file);
+    }
+
+    Ok(keys)
+}
+
+// Returns the argument in braces of each use of the `command`.
+fn arguments<'a>(src: &'a str, command: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    src.match_indices(command).filter_map(move |(i, _)| {
+        let rest = src[i + command.len()..].strip_prefix('{')?;
+        rest.find('}').map(|end| &rest[..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{
+        fixture::{FileWriteStr, PathChild},
+        TempDir,
+    };
+
+    use super::*;
+
+    #[test]
+    fn cited_entries_and_crossref_parent_are_written() {
+        let dir = TempDir::new().unwrap();
+        let aux = dir.child("paper.aux");
+        aux.write_str(
+            "\\relax\n\\citation{knuth}\n\\citation{paper,missing}\n\\@input{intro.aux}\n",
+        )
+        .unwrap();
+        dir.child("intro.aux")
+            .write_str("\\relax\n\\citation{knuth}\n")
+            .unwrap();
+        let out = dir.child("refs.bib");
+        let bibtex = BibTex::new(
+            "@book{knuth, author = {Donald Knuth}, title = {TAOCP}, publisher = {AW}, year = {1968}}\n\
+             @misc{uncited, title = {Uncited}}\n\
+             @inproceedings{paper, author = {Me}, title = {Paper}, crossref = {conf}}\n\
+             @proceedings{conf, title = {Conf}, year = {2020}}"
+                .to_owned(),
+        );
+
+        assert_eq!(
+            vec!["knuth", "paper", "missing"],
+            cited_keys(aux.path()).unwrap()
+        );
+
+        let output = extract(&bibtex, aux.path(), Some(out.path()), None).unwrap();
+
+        assert_eq!(
+            format!("3 entries written to {}", out.path().display()),
+            output
+        );
+        let written = BibTex::new(fs::read_to_string(out.path()).unwrap());
+        assert_eq!(
+            vec!["knuth", "paper", "conf"],
+            written
+                .raw_entries()
+                .map(|entry| entry.key())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cited_keys_match_ignoring_case_and_the_file_is_not_overwritten() {
+        let dir = TempDir::new().unwrap();
+        let aux = dir.child("paper.aux");
+        aux.write_str("\\citation{Knuth}\n").unwrap();
+        let file = dir.child("refs.bib");
+        file.write_str("@misc{knuth, title = {TAOCP}}").unwrap();
+        let bibtex = BibTex::new(fs::read_to_string(file.path()).unwrap());
+
+        assert!(extract(&bibtex, aux.path(), Some(file.path()), Some(file.path())).is_err());
+        assert_eq!(
+            "@misc{knuth, title = {TAOCP}}",
+            fs::read_to_string(file.path()).unwrap()
+        );
+
+        let out = dir.child("out.bib");
+        extract(&bibtex, aux.path(), Some(out.path()), Some(file.path())).unwrap();
+        assert_eq!(
+            "@misc{knuth, title = {TAOCP}}\n",
+            fs::read_to_string(out.path()).unwrap()
+        );
+    }
+}
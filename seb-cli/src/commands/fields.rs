@@ -0,0 +1,48 @@
+use seb::ast::EntryKind;
+
+/// Returns the required and optional fields of the entry type `name`, which is any name of the
+/// entry kind such as `@inproceedings` or `in proceedings`.
+pub(crate) fn fields(name: &str) -> String {
+    let kind = EntryKind::from(name);
+    // the fields are shown as they are written in `BibTeX`, such as `booktitle`
+    let list = |fields: &[&str]| match fields {
+        [] => "none".to_owned(),
+        fields => fields
+            .iter()
+            .map(|field| field.replace('_', ""))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    // the name of a known kind is its module name, such as `in_proceedings`
+    let name = kind.to_string().replace('_', " ");
+    let heading = match kind {
+        EntryKind::Other(_) => format!("{name} is a custom entry type"),
+        _ => name,
+    };
+    format!(
+        "{heading}\nRequired: {}\nOptional: {}",
+        list(kind.required_fields()),
+        list(kind.optional_fields())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn article_fields() {
+        assert_eq!(
+            "article\nRequired: author, title, journal, year\n\
+             Optional: volume, number, pages, month, note",
+            fields("@article")
+        );
+    }
+
+    #[test]
+    fn entry_type_aliases() {
+        assert!(fields("@inproceedings").contains("Required: author, title, booktitle, year"));
+        assert!(fields("@online").starts_with("online is a custom entry type\nRequired: title"));
+    }
+}
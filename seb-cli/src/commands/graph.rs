@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+
+use seb::ast::{Entry, FieldQuery};
+
+use super::related;
+
+/// The most characters of a title shown in the label of a node.
+const MAX_TITLE_CHARS: usize = 40;
+
+/// Composes the `entries` as a Graphviz DOT graph with a node for each entry, labelled with its
+/// cite key and short title.
+///
+/// An entry has an edge to the entry its `crossref` field references, given by the `crossrefs`
+/// of the raw bibliography, and to each entry in its `related` field, and a dashed edge without a
+/// direction joins entries that share an author. Only the edges between the `entries` are
+/// included and the nodes and edges are sorted so that the same entries always give the same
+/// graph.
+pub(super) fn dot<'a, I>(entries: I, crossrefs: &HashMap<String, String>) -> String
+where
+    I: IntoIterator<Item = &'a Entry>,
+{
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.cite());
+    let cites = entries
+        .iter()
+        .map(|entry| entry.cite())
+        .collect::<HashSet<_>>();
+
+    let mut lines = vec!["digraph seb {".to_owned()];
+    for entry in &entries {
+        lines.push(format!(
+            "    {} [label={}];",
+            quote(entry.cite()),
+            quote(&format!("{}\n{}", entry.cite(), short_title(entry)))
+        ));
+    }
+
+    for entry in &entries {
+        let crossref = crossrefs
+            .get(entry.cite())
+            .map(|parent| (parent.as_str(), "crossref"));
+        let related = entry
+            .related()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|related| (related, "related"));
+        for (other, label) in crossref.into_iter().chain(related) {
+            if cites.contains(other) {
+                lines.push(format!(
+                    "    {} -> {} [label={label}];",
+                    quote(entry.cite()),
+                    quote(other)
+                ));
+            }
+        }
+    }
+
+    let authors = entries
+        .iter()
+        .copied()
+        .map(related::authors)
+        .collect::<Vec<_>>();
+    for (i, entry) in entries.iter().enumerate() {
+        for (j, other) in entries.iter().enumerate().skip(i + 1) {
+            if !authors[i].is_disjoint(&authors[j]) {
+                lines.push(format!(
+                    "    {} -> {} [style=dashed, dir=none];",
+                    quote(entry.cite()),
+                    quote(other.cite())
+                ));
+            }
+        }
+    }
+
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
+
+// The title without the braces that protect its case, cut short at the last whole word.
+fn short_title(entry: &Entry) -> String {
+    let title = entry.title().replace(['{', '}'], "");
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title;
+    }
+    let cut = title.chars().take(MAX_TITLE_CHARS).collect::<String>();
+    let cut = cut
+        .rsplit_once(' ')
+        .map_or(cut.as_str(), |(words, _)| words);
+    format!("{}...", cut.trim_end())
+}
+
+// A DOT string is quoted so that any cite key or title is a valid ID.
+fn quote(id: &str) -> String {
+    format!(
+        "\"{}\"",
+        id.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use seb::{
+        ast::Biblio,
+        format::{BibTex, Format},
+    };
+
+    use crate::fixture::manual;
+
+    use super::*;
+
+    #[test]
+    fn crossref_is_an_edge_between_the_entries() {
+        let bibtex = BibTex::new(
+            "@inbook{chapter, chapter = {One}, title = {Chapter}, crossref = {book}}\n\
+             @book{book, author = {Me}, title = {Book}, publisher = {Pub}, year = {2020}}\n\
+             @manual{other, title = {Other}}"
+                .to_owned(),
+        );
+        let biblio = bibtex.parse().unwrap().unwrap();
+        let crossrefs = bibtex
+            .crossrefs()
+            .into_iter()
+            .map(|(cite, parent)| (cite.to_owned(), parent))
+            .collect();
+
+        assert_eq!(
+            "digraph seb {\n    \
+            \"book\" [label=\"book\\nBook\"];\n    \
+            \"chapter\" [label=\"chapter\\nChapter\"];\n    \
+            \"other\" [label=\"other\\nOther\"];\n    \
+            \"chapter\" -> \"book\" [label=crossref];\n    \
+            \"book\" -> \"chapter\" [style=dashed, dir=none];\n\
+            }",
+            dot(biblio.entries(), &crossrefs)
+        );
+        let without_parent = biblio.entries().filter(|entry| entry.cite() != "book");
+        assert!(!dot(without_parent, &crossrefs).contains("crossref"));
+    }
+
+    #[test]
+    fn related_and_shared_author_edges() {
+        let biblio = Biblio::new(vec![
+            manual("a", &[("author", "Donald E. Knuth"), ("related", "b, c")]),
+            manual("b", &[("author", "Knuth, D.")]),
+            manual("c", &[("author", "Leslie Lamport")]),
+        ]);
+
+        let graph = dot(biblio.entries(), &HashMap::new());
+
+        assert!(graph.contains("\n    \"a\" -> \"b\" [label=related];\n"));
+        assert!(graph.contains("\n    \"a\" -> \"c\" [label=related];\n"));
+        assert!(graph.contains("\n    \"a\" -> \"b\" [style=dashed, dir=none];\n"));
+        assert!(!graph.contains("\"c\" [style=dashed"));
+    }
+
+    #[test]
+    fn long_titles_are_cut_short_and_quotes_are_escaped() {
+        let title = "The {Art} of Computer Programming: Fundamental Algorithms";
+        let entry = manual("cite", &[("title", title)]);
+        assert_eq!("The Art of Computer Programming:...", short_title(&entry));
+        assert_eq!("\"say \\\"hi\\\"\"", quote("say \"hi\""));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use seb::ast::{Biblio, Entry, FieldQuery, List};
+
+use crate::filter::Filter;
+
+/// The most keywords suggested for an entry.
+const MAX_KEYWORDS: usize = 5;
+
+/// The most words in a keyword, longer phrases are too specific to be keywords.
+const MAX_WORDS: usize = 3;
+
+/// The words that separate the phrases of a text and are never part of a keyword.
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "as", "at", "be", "because", "been", "before", "being", "below", "between", "both",
+    "but", "by", "can", "could", "did", "do", "does", "doing", "down", "during", "each", "few",
+    "for", "from", "further", "had", "has", "have", "having", "he", "her", "here", "hers", "him",
+    "his", "how", "however", "i", "if", "in", "into", "is", "it", "its", "itself", "many", "may",
+    "me", "more", "most", "much", "must", "my", "new", "no", "nor", "not", "of", "off", "on",
+    "once", "one", "only", "or", "other", "our", "ours", "out", "over", "own", "paper", "same",
+    "she", "should", "show", "shows", "so", "some", "such", "than", "that", "the", "their",
+    "theirs", "them", "then", "there", "these", "they", "this", "those", "through", "thus", "to",
+    "too", "two", "under", "until", "up", "us", "use", "used", "uses", "using", "very", "was",
+    "we", "well", "were", "what", "when", "where", "which", "while", "who", "whom", "why", "will",
+    "with", "within", "without", "would", "you", "your",
+];
+
+/// Suggests keywords from the abstract of every unlocked entry that matches the `filter` and sets
+/// the `keywords` field of each entry when the keywords are confirmed. Entries that already have
+/// keywords are skipped unless `overwrite` is set and at most `max` entries are changed.
+///
+/// Returns the keywords added to each entry.
+pub(super) fn keywords_all<F>(
+    biblio: &mut Biblio,
+    filter: Option<&Filter>,
+    overwrite: bool,
+    max: Option<usize>,
+    confirm: F,
+) -> String
+where
+    F: Fn(&Entry, &[String]) -> bool,
+{
+    let mut suggested = biblio
+        .entries()
+        .filter(|entry| !entry.is_locked() && filter.is_none_or(|f| f.matches(entry)))
+        .filter(|entry| overwrite || entry.get_field("keywords").is_none_or(|k| k.is_empty()))
+        .filter_map(|entry| {
+            let keywords = extract(entry.get_field("abstract")?, MAX_KEYWORDS);
+            (!keywords.is_empty()).then(|| (entry.cite().to_owned(), keywords))
+        })
+        .collect::<Vec<_>>();
+    suggested.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let max = max.unwrap_or(usize::MAX);
+    let mut lines = Vec::new();
+    let mut left = 0;
+    for (cite, keywords) in suggested {
+        if lines.len() == max {
+            left += 1;
+            continue;
+        }
+        let Some(entry) = biblio.get_mut(&cite) else {
+            continue;
+        };
+        if !confirm(entry, &keywords) {
+            continue;
+        }
+        let keywords = List::Items(keywords);
+        entry.set_list("keywords", &keywords);
+        lines.push(format!("{cite}: {}", keywords.value()));
+    }
+
+    if left > 0 {
+        lines.push(super::limit_reached(max, left));
+    }
+    if lines.is_empty() {
+        return "No keywords were added".to_owned();
+    }
+    lines.join("\n")
+}
+
+/// Returns at most `max` keywords of the `text`, from the highest to the lowest scoring, using
+/// Rapid Automatic Keyword Extraction (RAKE).
+///
+/// The text is split into candidate phrases at punctuation and stop words. Each word is scored by
+/// the number of words in the phrases it is part of divided by the number of times it is used,
+/// and a phrase is scored by the sum of its word scores, so that a phrase of words used together
+/// scores higher than the same words used apart.
+pub(super) fn extract(text: &str, max: usize) -> Vec<String> {
+    let phrases = phrases(text);
+
+    let mut frequency = HashMap::<&str, u32>::new();
+    let mut degree = HashMap::<&str, u32>::new();
+    for phrase in &phrases {
+        let len = u32::try_from(phrase.len()).unwrap_or(u32::MAX);
+        for word in phrase {
+            *frequency.entry(word).or_default() += 1;
+            *degree.entry(word).or_default() += len;
+        }
+    }
+
+    let mut scored = Vec::<(String, f64)>::new();
+    for phrase in phrases.iter().filter(|phrase| phrase.len() <= MAX_WORDS) {
+        let keyword = phrase.join(" ");
+        if scored.iter().any(|(found, _)| *found == keyword) {
+            continue;
+        }
+        let score = phrase
+            .iter()
+            .map(|word| f64::from(degree[word.as_str()]) / f64::from(frequency[word.as_str()]))
+            .sum();
+        scored.push((keyword, score));
+    }
+    // the sort is stable so phrases with the same score stay in the order they are first used
+    scored.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    scored
+        .into_iter()
+        .take(max)
+        .map(|(keyword, _)| keyword)
+        .collect()
+}
+
+// Splits the text into the lowercase words of the candidate phrases.
+fn phrases(text: &str) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+    // braces only protect the case of LaTeX text and do not separate phrases
+    for part in text
+        .replace(['{', '}'], "")
+        .split(|c: char| !(c.is_alphanumeric() || c.is_whitespace() || c == '-' || c == '\''))
+    {
+        let mut phrase = Vec::new();
+        for word in part.split_whitespace() {
+            let word = word.trim_matches(|c| c == '-' || c == '\'').to_lowercase();
+            if is_stop_word(&word) {
+                if !phrase.is_empty() {
+                    phrases.push(std::mem::take(&mut phrase));
+                }
+            } else {
+                phrase.push(word);
+            }
+        }
+        if !phrase.is_empty() {
+            phrases.push(phrase);
+        }
+    }
+    phrases
+}
+
+// Numbers and single letters are never keywords.
+fn is_stop_word(word: &str) -> bool {
+    word.chars().count() < 2 || word.chars().all(|c| c.is_numeric()) || STOP_WORDS.contains(&word)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::manual;
+
+    use super::*;
+
+    #[test]
+    fn extract_keywords_from_abstract() {
+        let text =
+            "Compatibility of systems of linear constraints over the set of natural numbers. \
+            Criteria of compatibility of a system of linear Diophantine equations, strict \
+            inequations, and nonstrict inequations are considered. Upper bounds for components of \
+            a minimal set of solutions and algorithms of construction of minimal generating sets \
+            of solutions for all types of systems are given.";
+
+        assert_eq!(
+            vec![
+                "linear diophantine equations",
+                "minimal generating sets",
+                "linear constraints",
+                "natural numbers",
+                "strict inequations",
+            ],
+            extract(text, 5)
+        );
+    }
+
+    #[test]
+    fn keywords_skip_entries_with_keywords_unless_overwrite() {
+        let abstract_ = "Memory safety without garbage collection.";
+        let mut biblio = Biblio::new(vec![
+            manual("a", &[("abstract", abstract_)]),
+            manual("b", &[("abstract", abstract_), ("keywords", "rust")]),
+            manual("c", &[]),
+        ]);
+
+        let report = keywords_all(&mut biblio, None, false, None, |_, _| true);
+
+        assert_eq!("a: memory safety, garbage collection", report);
+        let entry = biblio.get("a").unwrap();
+        assert_eq!(
+            "memory safety, garbage collection",
+            &**entry.get_field("keywords").unwrap()
+        );
+        let entry = biblio.get("b").unwrap();
+        assert_eq!("rust", &**entry.get_field("keywords").unwrap());
+
+        let report = keywords_all(&mut biblio, None, true, None, |entry, _| {
+            entry.cite() == "b"
+        });
+
+        assert_eq!("b: memory safety, garbage collection", report);
+    }
+
+    #[test]
+    fn keywords_stop_at_the_max() {
+        let abstract_ = "Memory safety without garbage collection.";
+        let mut biblio = Biblio::new(vec![
+            manual("a", &[("abstract", abstract_)]),
+            manual("b", &[("abstract", abstract_)]),
+        ]);
+
+        let report = keywords_all(&mut biblio, None, false, Some(0), |_, _| true);
+
+        assert_eq!(crate::commands::limit_reached(0, 2), report);
+        assert!(biblio.get("a").unwrap().get_field("keywords").is_none());
+    }
+}
@@ -1,11 +1,53 @@
 mod add;
+mod doctor;
+mod enrich;
+mod extract;
+mod fields;
+mod graph;
+mod keywords;
+mod oa;
+mod related;
+mod retractions;
+mod split;
 
-use crate::interact;
-use add::AddCommands;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+};
 
-use seb::ast::Biblio;
+use crate::{
+    backup,
+    config::{Config, HistoryConfig},
+    diff::Diff,
+    file::FILE_FIELD,
+    filter::{Filter, YearRange},
+    history, interact,
+};
+use add::{AddCommands, AddOptions};
+pub(crate) use doctor::doctor;
+use related::{related, RelatedBy};
+use split::SplitBy;
 
-use clap::Subcommand;
+use seb::{
+    ast::{
+        Author, Biblio, Entry, EntryKind, FieldFilter, FieldQuery, KeyFormat, Metric, Role,
+        Similarity, WithFields, DEFAULT_SOURCE_PRIORITY, INCOMPLETE_FIELD, LOCKED_FIELD,
+        PROVENANCE_PREFIX, SOURCE_FIELD, URLDATE_FIELD,
+    },
+    file::FormatFile,
+    format::{
+        BibTex, BibTexConfig, FieldTransform, Format, InputEncoding, Reader, Transforms, Truncate,
+    },
+    normalize::{
+        collapse_page_range, collapse_quoted_whitespace, fix_quoted_mojibake, sentence_case_quoted,
+        VERBATIM_FIELDS,
+    },
+    provider::{Providers, Query},
+};
+
+use clap::{ArgEnum, Args, Subcommand};
 
 #[derive(Subcommand)]
 #[non_exhaustive]
@@ -13,12 +55,17 @@ pub enum Commands {
     /// Add an entry to the current bibliography file
     #[clap(arg_required_else_help = true)]
     Add {
+        #[clap(flatten)]
+        options: AddOptions,
+
         #[clap(subcommand)]
         command: AddCommands,
     },
 
     /// Check the local bibliography file that all the required fields are present for each entry
-    /// type.
+    /// type, that no cite key is used by more than one entry, that every crossref field
+    /// references an existing entry without forming a cycle and that every member of a `@set`
+    /// and every entry in a `related` field exists.
     ///
     /// This type of check is done before the `add`, `new`, `rm` commands but can be done
     /// explicitly using this command.
@@ -27,6 +74,82 @@ pub enum Commands {
     /// set using `-i` or `--interact`.
     Check,
 
+    /// Check whether any entry cites a retracted work
+    ///
+    /// The DOI of each entry is searched for in the retraction and expression of concern notices
+    /// recorded by Crossref, which include the notices collected by Retraction Watch. A warning
+    /// is shown for each entry found and entries without a DOI are skipped.
+    CheckRetractions {
+        /// Only check the entries that match the filter expression, see `seb list --help`.
+        #[clap(long = "where")]
+        filter: Option<Filter>,
+
+        /// Set the `x-retracted` field of each retracted entry to `true`.
+        #[clap(long)]
+        mark: bool,
+    },
+
+    /// Copy an entry to a new entry with a different cite key
+    ///
+    /// The copy has the same kind and fields as the entry, such as to add the published version
+    /// of a preprint and then change its DOI and year.
+    #[clap(arg_required_else_help = true)]
+    Copy {
+        /// The cite key of the entry to copy.
+        cite: String,
+
+        /// The cite key of the copy.
+        ///
+        /// When this is not set the cite key of the entry is used with the first suffix, such as
+        /// `a` or `b`, that no other entry uses.
+        new_key: Option<String>,
+    },
+
+    /// Remove entries that have the same DOI or arXiv ID as another entry.
+    ///
+    /// A DOI is authoritative so entries sharing the same DOI are the same work, as are a preprint
+    /// and its published version sharing the same arXiv ID. The published version is kept over a
+    /// preprint, with the arXiv ID of the preprint added to its note, and then the entry kept is
+    /// the one with the most preferred `source` field.
+    Dedup {
+        /// The sources of entries from most to least preferred.
+        ///
+        /// Entries added using `seb add` have the name of the provider as the source and entries
+        /// added using `seb new` have the `manual` source.
+        #[clap(long, multiple_values(true), default_values = DEFAULT_SOURCE_PRIORITY)]
+        priority: Vec<String>,
+
+        /// List the entries with similar titles, which are possibly duplicates, instead.
+        ///
+        /// Titles are compared ignoring case, braces, extra whitespace, trailing punctuation and
+        /// small typos. A title doesn't identify a single work so these entries are not removed.
+        #[clap(long, conflicts_with = "priority")]
+        titles: bool,
+
+        /// The lowest score, from 0.0 to 1.0, of titles that are similar, which overrides the
+        /// `threshold` in the `[dedup]` section of the config file.
+        ///
+        /// A higher threshold finds fewer possible duplicates. The default of 0.95 is one edit
+        /// for every 20 characters.
+        #[clap(long, requires = "titles", validator = threshold)]
+        threshold: Option<f64>,
+
+        /// How the similarity of titles is scored, which overrides the `metric` in the `[dedup]`
+        /// section of the config file.
+        ///
+        /// The jaro-winkler score is more forgiving of words missing from the end of a title.
+        #[clap(long, arg_enum, requires = "titles")]
+        metric: Option<TitleMetric>,
+
+        /// Remove at most this many entries, which guards against a mistake removing most of a
+        /// large bibliography.
+        #[clap(long, alias = "first", value_name = "N", conflicts_with = "titles")]
+        max: Option<usize>,
+
+        #[clap(skip)]
+        similarity: Similarity,
+    },
+
     /// Derive a new entry from an existing one.
     ///
     /// This can most commonly be used to make a new entry like a "book chapter" entry based on
@@ -70,6 +193,257 @@ pub enum Commands {
         fields: Option<Vec<String>>,
     },
 
+    /// Compare the entries in the bibliography file with another bibliography file
+    ///
+    /// Entries are matched by cite key and then by DOI, then entries only in one of the files and
+    /// entries with different fields are listed. Formatting differences are ignored.
+    #[clap(arg_required_else_help = true)]
+    Diff {
+        /// The bibliography file to compare with.
+        #[clap(parse(from_os_str))]
+        other: PathBuf,
+    },
+
+    /// Diagnose the environment and the bibliography file without changing anything
+    ///
+    /// Checks that the config file is valid, that the bibliography file exists and parses, that
+    /// the API tokens and email addresses are set and that the provider APIs are reachable. The
+    /// command fails when any of the critical checks fail, and the report can be pasted into a
+    /// bug report.
+    Doctor,
+
+    /// Set a field of an entry, replacing any existing value
+    ///
+    /// Setting the `doi` field also searches for the entry by the DOI and fills in the fields the
+    /// entry is missing, existing fields are never changed.
+    #[clap(arg_required_else_help = true)]
+    Edit {
+        /// The cite key of the entry to edit.
+        cite: String,
+
+        /// The name of the field to set.
+        field: String,
+
+        /// The value of the field.
+        value: String,
+
+        /// Do not fill in the missing fields when setting the `doi` field.
+        #[clap(long)]
+        no_fill: bool,
+    },
+
+    /// Fill in the missing fields of entries by searching for them again using their DOI or ISBN
+    ///
+    /// The fields that are missing or empty are filled in and existing fields are never changed
+    /// unless the `--overwrite` flag is used. An entry added with --allow-incomplete becomes the
+    /// entry type it was meant to be once it has the fields the type requires.
+    Enrich {
+        /// Only enrich the entries that match the filter expression, see `seb list --help`.
+        #[clap(long = "where")]
+        filter: Option<Filter>,
+
+        /// Replace the existing fields with the values found.
+        #[clap(long)]
+        overwrite: bool,
+
+        /// Also attach the URL of an open access PDF to each entry with a DOI, see `seb oa`.
+        #[clap(long)]
+        oa: bool,
+
+        /// Suggest keywords from the abstract of each entry without keywords instead, which are
+        /// added to the `keywords` field when confirmed.
+        ///
+        /// The keywords are the phrases of the abstract that score highest using Rapid Automatic
+        /// Keyword Extraction (RAKE) so no provider is searched. Entries that already have
+        /// keywords are only changed when the `--overwrite` flag is used.
+        #[clap(long, conflicts_with = "oa")]
+        keywords: bool,
+
+        /// Change at most this many entries, which guards against a filter matching more entries
+        /// than intended.
+        #[clap(long, alias = "first", value_name = "N", conflicts_with = "oa")]
+        max: Option<usize>,
+
+        /// The email address sent to the Unpaywall API, set from the config.
+        #[clap(skip)]
+        email: Option<String>,
+    },
+
+    /// Write only the entries cited in a LaTeX document to a new BibTeX file
+    ///
+    /// The cite keys are read from the `\citation` commands of the `.aux` file written by LaTeX
+    /// and the entries they reference in a `crossref` field are included. A warning is shown for
+    /// each cited key that no entry has.
+    #[clap(arg_required_else_help = true)]
+    Extract {
+        /// The `.aux` file written by LaTeX when compiling the document.
+        #[clap(parse(from_os_str))]
+        aux: PathBuf,
+
+        /// The file to write the entries to, the entries are written to stdout when not set.
+        ///
+        /// The file cannot be the bibliography file the entries are extracted from.
+        #[clap(short, long, parse(from_os_str))]
+        out: Option<PathBuf>,
+
+        /// The bibliography file the entries are extracted from.
+        #[clap(skip)]
+        file: Option<PathBuf>,
+    },
+
+    /// Export the entries in the bibliography file to stdout in another format
+    Export {
+        /// The format to export the entries as.
+        ///
+        /// jsonl: one JSON object per entry on its own line.
+        /// bibtex: the BibTeX of every entry.
+        /// dot: a Graphviz graph of the crossref, related and shared author links between entries.
+        #[clap(long, arg_enum, default_value = "jsonl", verbatim_doc_comment)]
+        format: ExportFormat,
+
+        #[clap(flatten)]
+        json: JsonStyle,
+
+        /// Leave out the provenance fields, such as `x-seb-source`, from the exported entries.
+        #[clap(long)]
+        no_provenance: bool,
+
+        /// Leave out these comma separated fields from the exported entries.
+        ///
+        /// A field ending with `*` leaves out every field starting with the rest of the name.
+        #[clap(long, use_value_delimiter = true, value_name = "FIELDS")]
+        exclude_fields: Vec<String>,
+
+        /// Only export these comma separated fields of the entries.
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            value_name = "FIELDS",
+            conflicts_with = "exclude-fields"
+        )]
+        only_fields: Option<Vec<String>>,
+
+        /// Cut these comma separated fields to at most a number of characters, such as
+        /// `abstract=500`, ending with `...`.
+        ///
+        /// Only the exported entries are cut, the bibliography file is not changed. Fields are
+        /// cut in the bibtex and jsonl formats.
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            value_name = "FIELD=N",
+            parse(try_from_str = truncate_limit)
+        )]
+        truncate: Vec<(String, usize)>,
+
+        /// Only export the entries that match the filter expression, see `seb list --help`.
+        #[clap(long = "where")]
+        filter: Option<Filter>,
+
+        #[clap(flatten)]
+        years: YearRange,
+
+        /// Pick the entries to export from a list of every entry.
+        ///
+        /// The entries that a picked entry references in a `crossref` field are also exported,
+        /// such as the proceedings of a paper in the proceedings.
+        #[clap(long)]
+        pick: bool,
+
+        /// The `crossref` parent of each entry, set from the raw bibliography.
+        #[clap(skip)]
+        crossrefs: HashMap<String, String>,
+    },
+
+    /// Print the required and optional fields of an entry type
+    ///
+    /// No bibliography file is needed. The required fields are the fields an entry needs to be
+    /// valid and the optional fields are the other fields the standard BibTeX styles use.
+    #[clap(arg_required_else_help = true)]
+    Fields {
+        /// The entry type, such as `@inproceedings`, `conference` or `in proceedings`.
+        ///
+        /// Both the BibTeX and BibLaTeX type names are accepted and any other type is a custom
+        /// entry type that only requires a title.
+        kind: String,
+    },
+
+    /// Write the bibliography file back in the canonical format without changing any entry
+    ///
+    /// The formatting options in the `[format.bibtex]` section of the config file are applied.
+    /// Formatting is idempotent so this can be used as a pre-commit formatter.
+    Format,
+
+    /// Print the history of the changes written to the bibliography file
+    ///
+    /// Each change is recorded with the time, the command and the cite keys of the entries that
+    /// were added, removed or changed, when the `log` of the `[history]` section in the config
+    /// file is `true`.
+    History,
+
+    /// Print the cite key of each entry in the bibliography file, one per line
+    ///
+    /// The cite keys are sorted. The entries are not fully parsed so this is fast enough to be used
+    /// for shell completion.
+    Keys,
+
+    /// Lock an entry so that it is skipped by commands that change many entries at once
+    ///
+    /// Locked entries are not changed by the dedup, enrich and normalize subcommands.
+    #[clap(arg_required_else_help = true)]
+    Lock {
+        /// The cite key of the entry to lock
+        cite: String,
+    },
+
+    /// List the entries in the bibliography file by cite key and title
+    List {
+        /// Only list the entries that match the filter expression.
+        ///
+        /// Fields are compared using `=`, `!=`, `>=`, `<=` or `contains` and comparisons can be
+        /// combined using `and`/`or`, for example:
+        ///
+        /// --where "year>=2020 and type=article"
+        ///
+        /// The `type` field is the kind of entry and the `cite` field is the cite key.
+        #[clap(long = "where", verbatim_doc_comment)]
+        filter: Option<Filter>,
+
+        /// Also list the authors of each entry, or the editors when an entry has no authors, and
+        /// the translators.
+        #[clap(long)]
+        names: bool,
+
+        /// Print only the number of entries that would be listed.
+        #[clap(long, conflicts_with = "names")]
+        count_only: bool,
+
+        #[clap(flatten)]
+        years: YearRange,
+    },
+
+    /// Search for an entry by an identifier and print it without changing the bibliography file
+    ///
+    /// No bibliography file is needed, which is useful to check the metadata of an identifier or
+    /// to use the providers from a script.
+    #[clap(arg_required_else_help = true)]
+    Lookup {
+        /// The type of identifier, such as `doi`, `isbn`, `rfc`, `bibcode` or `standard`.
+        ///
+        /// Other types are only found by the providers added for them.
+        kind: String,
+
+        /// The identifier to search for.
+        id: String,
+
+        /// The format to print the entries found as.
+        #[clap(long, arg_enum, default_value = "bibtex")]
+        format: ExportFormat,
+
+        #[clap(flatten)]
+        json: JsonStyle,
+    },
+
     /// Add a new entry manually
     ///
     /// This subcommand will assume interact flag is set even if not explicitly used.
@@ -109,81 +483,2018 @@ pub enum Commands {
         /// when unsure if the kind already requires that field.
         #[clap(long, multiple_values(true))]
         fields: Option<Vec<String>>,
+
+        /// Whether a generated cite key is transliterated to ASCII, from the config file.
+        #[clap(skip)]
+        transliterate_keys: bool,
+    },
+    /// Normalize the field values of every entry in the bibliography file
+    ///
+    /// A url field that links to the DOI of the entry is always removed, or moved to the doi field
+    /// of an entry without a DOI. The runs of whitespace in every field value are always collapsed
+    /// to single spaces, except inside braces, between paragraphs and in the verbatim fields, such
+    /// as url and file, and a page range of a single page, such as 1-1, is always collapsed to the
+    /// page.
+    Normalize {
+        /// Merge the variants of the same name, such as "J. Smith" and "John Smith", into the
+        /// most complete name.
+        ///
+        /// Names are only merged when the family names are the same and the given names agree,
+        /// and a name that could be either of two people, such as "J. Smith" with "John Smith"
+        /// and "Jane Smith", is left as it is. Each merge is confirmed unless --yes is used.
+        #[clap(long)]
+        authors: bool,
+
+        /// Store the arXiv ID of each entry, found in its eprint, url or doi field, in the eprint
+        /// field with the eprinttype arxiv.
+        #[clap(long)]
+        arxiv: bool,
+
+        /// Lowercase the DOIs, as they are case insensitive.
+        ///
+        /// A DOI that changes is kept as it was in the `doi-original` field.
+        #[clap(long)]
+        dois: bool,
+
+        /// Repair double encoded UTF-8 (mojibake), such as "Ã©" instead of "é".
+        ///
+        /// Only recognised double encoding patterns are repaired so correct text is left as it
+        /// is.
+        #[clap(long)]
+        fix_encoding: bool,
+
+        /// Regenerate the cite key of every entry using the key format.
+        ///
+        /// Entries with the same generated cite key are given the suffixes a, b, c and so on, in
+        /// the order of their old cite keys, and the crossref and related fields are updated to
+        /// the new cite keys. Locked entries keep their cite key.
+        #[clap(long)]
+        rekey: bool,
+
+        /// The template used to generate cite keys, which overrides the `key` in the
+        /// `[format]` section of the config file.
+        ///
+        /// The placeholders {author}, {year} and {title} are replaced with the last name of the
+        /// first author, the year and the first word of the title.
+        #[clap(long, requires = "rekey", value_name = "TEMPLATE")]
+        key_format: Option<KeyFormat>,
+
+        /// Convert the titles written in all caps to sentence case.
+        ///
+        /// Only a title with most of its letters in uppercase is converted. Known acronyms, such
+        /// as DNA, and the `acronyms` in the `[normalize]` section of the config file keep their
+        /// case, as do words with a digit and the parts of a title in braces.
+        #[clap(long)]
+        title_case: bool,
+
+        /// The acronyms kept by --title-case, set from the config.
+        #[clap(skip)]
+        acronyms: Vec<String>,
+    },
+
+    /// Attach the URL of an open access PDF of an entry using the Unpaywall API
+    ///
+    /// The DOI of the entry is searched for and the URL of the best open access PDF is stored in
+    /// the `oa_url` field. Unpaywall requires an email address with every request, which is read
+    /// from the `email` of the `[unpaywall]` section in the config file or the
+    /// `SEB_UNPAYWALL_EMAIL` environment variable.
+    #[clap(arg_required_else_help = true)]
+    Oa {
+        /// The cite key of the entry
+        cite: String,
+
+        /// The email address sent to the Unpaywall API, set from the config.
+        #[clap(skip)]
+        email: Option<String>,
+    },
+
+    /// List the entries related to an entry by the authors and keywords they share
+    ///
+    /// Every other entry is scored by the number of authors and keywords it has in common with
+    /// the entry, and the highest scoring entries are listed first.
+    #[clap(arg_required_else_help = true)]
+    Related {
+        /// The cite key of the entry to find related entries for
+        cite: String,
+
+        /// What the entries are compared by.
+        #[clap(long, arg_enum, default_value = "both")]
+        by: RelatedBy,
+
+        /// The maximum number of entries to list.
+        #[clap(long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Write the entries to a file for each type or year of entry
+    ///
+    /// The entries are kept in the bibliography file unless the `--move` flag is used. The
+    /// entries referenced in a `crossref` field are also written to the files of the entries that
+    /// reference them.
+    #[clap(arg_required_else_help = true)]
+    Split {
+        /// What the entries are grouped by, each group is written to its own file, such as
+        /// `article.bib` or `2021.bib`.
+        #[clap(long, arg_enum)]
+        by: SplitBy,
+
+        /// The directory the files are written to, existing files are never overwritten.
+        #[clap(long, parse(from_os_str))]
+        out_dir: PathBuf,
+
+        /// Remove the entries from the bibliography file once they are written.
+        #[clap(long = "move")]
+        move_entries: bool,
+
+        /// The style the files are written in, set from the config.
+        #[clap(skip)]
+        config: BibTexConfig,
+
+        /// The `crossref` parent of each entry, set from the raw bibliography.
+        #[clap(skip)]
+        crossrefs: HashMap<String, String>,
     },
+
+    /// Show statistics about the entries in the bibliography file
+    ///
+    /// The field coverage shows how many entries have each field, which helps to find where
+    /// the bibliography is missing information.
+    Stats {
+        /// List the cite keys of the entries that don't have this field.
+        #[clap(long, value_name = "FIELD")]
+        missing: Option<String>,
+    },
+
     /// Remove an entry from the bibliography file using the cite key
     #[clap(arg_required_else_help = true)]
     Rm {
         /// The cite key of the entry to remove
         cite: String,
     },
+
+    /// Unlock an entry that was locked using the lock subcommand
+    #[clap(arg_required_else_help = true)]
+    Unlock {
+        /// The cite key of the entry to unlock
+        cite: String,
+    },
+
+    /// Restore the bibliography file from the most recent backup.
+    ///
+    /// A backup is made before every change to the bibliography file and the number of backups
+    /// kept can be configured, undo can be used again to restore the backup before it.
+    Undo,
+}
+
+/// How the similarity of titles is scored, see [`Metric`].
+#[derive(Clone, Copy, ArgEnum)]
+pub enum TitleMetric {
+    Levenshtein,
+    JaroWinkler,
+}
+
+impl From<TitleMetric> for Metric {
+    fn from(metric: TitleMetric) -> Self {
+        match metric {
+            TitleMetric::Levenshtein => Metric::Levenshtein,
+            TitleMetric::JaroWinkler => Metric::JaroWinkler,
+        }
+    }
+}
+
+fn threshold(value: &str) -> Result<(), String> {
+    match value.parse::<f64>() {
+        Ok(threshold) if (0.0..=1.0).contains(&threshold) => Ok(()),
+        _ => Err(format!("'{value}' is not a number between 0.0 and 1.0")),
+    }
+}
+
+/// The formats supported by the `export` command.
+#[derive(Clone, Copy, ArgEnum)]
+pub enum ExportFormat {
+    Jsonl,
+    Bibtex,
+    Dot,
+}
+
+/// How the JSON documents are written, pretty-printed for reading them on a terminal and compact
+/// for piping them to another program.
+#[derive(Args, Clone, Copy, Debug, Default)]
+pub struct JsonStyle {
+    /// Print each JSON document indented over several lines, the default when stdout is a
+    /// terminal.
+    #[clap(long, conflicts_with = "compact")]
+    pretty: bool,
+
+    /// Print each JSON document on a single line, the default when stdout is not a terminal.
+    #[clap(long)]
+    compact: bool,
+}
+
+impl JsonStyle {
+    /// Returns `true` when the JSON documents are pretty-printed to stdout, which is a `terminal`
+    /// or not.
+    pub const fn is_pretty(self, terminal: bool) -> bool {
+        self.pretty || (!self.compact && terminal)
+    }
 }
 
 impl Commands {
+    /// Fills in the options that were not given on the command line from the config file.
+    pub fn configure(&mut self, config: &Config) {
+        match self {
+            Commands::Normalize {
+                rekey,
+                key_format,
+                acronyms,
+                ..
+            } => {
+                if *rekey {
+                    let format = key_format
+                        .take()
+                        .unwrap_or_else(|| config.format.key.clone());
+                    *key_format = Some(format.transliterate(config.format.transliterate_keys));
+                }
+                acronyms.clone_from(&config.normalize.acronyms);
+            }
+            Commands::Enrich { email, .. } | Commands::Oa { email, .. } => {
+                *email = oa::email(config.unpaywall.email.as_deref());
+            }
+            Commands::Split { config: style, .. } => style.clone_from(&config.format.bibtex),
+            Commands::Dedup {
+                threshold,
+                metric,
+                similarity,
+                ..
+            } => {
+                *similarity = Similarity {
+                    metric: metric.map_or(config.dedup.metric, Metric::from),
+                    threshold: threshold.unwrap_or(config.dedup.threshold),
+                };
+            }
+            Commands::Add { options, .. } => {
+                options.similarity = config.dedup;
+                options.transliterate_keys = config.format.transliterate_keys;
+            }
+            Commands::New {
+                transliterate_keys, ..
+            } => *transliterate_keys = config.format.transliterate_keys,
+            _ => {}
+        }
+    }
+
+    /// Sets the bibliography file at `path` on the commands that keep a record next to it, such as
+    /// the time each feed was last fetched, or that must not write over it.
+    pub fn set_file(&mut self, path: &Path) {
+        match self {
+            Commands::Add {
+                command: AddCommands::Feed { file, .. },
+                ..
+            }
+            | Commands::Extract { file, .. } => *file = Some(path.to_owned()),
+            _ => {}
+        }
+    }
+
+    /// Returns true when the command needs an existing bibliography file, so the file is not
+    /// created when it is missing.
+    pub const fn needs_existing_file(&self) -> bool {
+        matches!(self, Commands::Extract { .. })
+    }
+
+    /// Reads what the command needs from the raw bibliography before it is parsed, as parsing
+    /// inherits the fields of the `crossref` parent of an entry and leaves out the `crossref`
+    /// field. Called once for each file read.
+    pub fn read_raw(&mut self, bibtex: &BibTex) {
+        if let Commands::Export { crossrefs, .. } | Commands::Split { crossrefs, .. } = self {
+            crossrefs.extend(
+                bibtex
+                    .crossrefs()
+                    .into_iter()
+                    .map(|(cite, parent)| (cite.to_owned(), parent)),
+            );
+        }
+    }
+
+    /// Checks the raw bibliography before it is parsed, as parsing only keeps one entry for each
+    /// cite key.
+    pub fn audit(&self, bibtex: &BibTex) -> Result<(), Box<dyn std::error::Error>> {
+        if let Commands::Check = self {
+            let mut problems = bibtex
+                .duplicate_keys()
+                .into_iter()
+                .map(|(key, positions)| {
+                    let positions = positions
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("The cite key '{key}' is used by the entries at positions {positions}")
+                })
+                .collect::<Vec<_>>();
+
+            problems.extend(
+                bibtex
+                    .invalid_entries()
+                    .into_iter()
+                    .map(|key| format!("The entry '{key}' is not valid BibTeX")),
+            );
+            problems.extend(bibtex.dangling_crossrefs().into_iter().map(|(key, parent)| {
+                format!("The entry '{key}' has a crossref to '{parent}' but no entry has that cite key")
+            }));
+            problems.extend(bibtex.dangling_related().into_iter().map(|(key, related)| {
+                format!(
+                    "The entry '{key}' is related to '{related}' but no entry has that cite key"
+                )
+            }));
+            problems.extend(bibtex.missing_set_members().into_iter().map(|(key, member)| {
+                format!("The set '{key}' has the member '{member}' but no entry has that cite key")
+            }));
+            problems.extend(bibtex.crossref_cycles().into_iter().map(|cycle| {
+                format!(
+                    "The crossref fields of the entries form a cycle: {} -> {}",
+                    cycle.join(" -> "),
+                    cycle[0]
+                )
+            }));
+
+            if !problems.is_empty() {
+                return Err(eyre::eyre!("{}", problems.join("\n")).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` for the commands that only read the bibliography, which can be executed on
+    /// entries that are missing required fields.
+    pub const fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Commands::Diff { .. }
+                | Commands::Export { .. }
+                | Commands::Keys
+                | Commands::List { .. }
+                | Commands::Related { .. }
+                | Commands::Stats { .. }
+        )
+    }
+
+    /// Executes the commands that only need the raw bibliography, which avoids parsing and
+    /// resolving the whole bibliography.
+    ///
+    /// Returns `None` when the command needs the parsed bibliography, see [`Commands::execute`].
+    pub fn execute_raw(&self, bibtex: &BibTex) -> Option<eyre::Result<String>> {
+        match self {
+            Commands::Extract { aux, out, file } => Some(extract::extract(
+                bibtex,
+                aux,
+                out.as_deref(),
+                file.as_deref(),
+            )),
+            Commands::Keys => Some(Ok(keys(bibtex.raw_entries().map(|entry| entry.key())))),
+            _ => None,
+        }
+    }
+
+    /// Executes the commands that do not use a bibliography file, so no file is read, created or
+    /// written.
+    ///
+    /// Returns `None` when the command needs a bibliography file.
+    pub fn execute_without_file(&self, providers: &Providers) -> Option<eyre::Result<String>> {
+        match self {
+            Commands::Fields { kind } => Some(Ok(fields::fields(kind))),
+            Commands::Lookup {
+                kind,
+                id,
+                format,
+                json,
+            } => {
+                let stdout = std::io::stdout();
+                let pretty = json.is_pretty(stdout.is_terminal());
+                Some(
+                    lookup(providers, kind, id, *format, pretty, stdout.lock())
+                        // the entries have already been written to stdout
+                        .map(|()| String::new()),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Executes the commands that change the bibliography file itself rather than the entries.
+    ///
+    /// Returns `None` when the command needs the bibliography, see [`Commands::execute`].
+    pub fn execute_file(
+        &self,
+        path: &Path,
+        encoding: Option<InputEncoding>,
+        confirm: bool,
+        config: &HistoryConfig,
+    ) -> Option<eyre::Result<String>> {
+        match self {
+            Commands::History => Some(history::history(path)),
+            Commands::Undo => Some(undo(path, encoding, confirm, config)),
+            _ => None,
+        }
+    }
+
     pub fn execute(
         self,
         biblio: &mut Biblio,
+        providers: &Providers,
         interact: bool,
+        confirm: bool,
     ) -> Result<String, Box<dyn std::error::Error>> {
+        // without a prompt every removal is confirmed
+        let confirm_removal = |entry: &Entry| !confirm || interact::user_confirm_removal(entry);
+        let confirm_similar = |entry: &Entry, similar: &[&Entry]| {
+            !confirm || interact::user_confirm_similar(entry, similar)
+        };
+        let confirm_merge = |name: &Author, variants: &[Author]| {
+            !confirm || interact::user_confirm_merge(name, variants)
+        };
+        let confirm_keywords = |entry: &Entry, keywords: &[String]| {
+            !confirm || interact::user_confirm_keywords(entry, keywords)
+        };
+
         match self {
-            Commands::Add { command } => command.execute(biblio, interact),
+            Commands::Add { options, command } => {
+                let confirm_similar = (!options.force).then_some(confirm_similar);
+                command.execute(biblio, providers, interact, &options, confirm_similar)
+            }
             // trivially if the biblio is already resolved at this point then it was either
             // resolved interactively or was valid so a success message can be returned.
-            Commands::Check => Ok("All entries contain the required fields!".to_owned()),
+            Commands::Check => check(biblio),
+            Commands::CheckRetractions { filter, mark } => Ok(retractions::check_retractions(
+                biblio,
+                filter.as_ref(),
+                mark,
+                seb::retraction_by_doi,
+            )),
+            Commands::Copy { cite, new_key } => Ok(copy(biblio, &cite, new_key)?),
+            Commands::Dedup {
+                titles: true,
+                similarity,
+                ..
+            } => Ok(similar_titles(biblio, &similarity)),
+            Commands::Dedup { priority, max, .. } => {
+                Ok(dedup(biblio, &priority, max, confirm_removal))
+            }
             Commands::Derive {
                 entry,
                 kind,
                 cite,
                 fields,
+            } => derive(biblio, &entry, kind, cite, fields),
+            Commands::Diff { other } => diff(biblio, &other),
+            // only executed by `doctor` before the config and bibliography are read
+            Commands::Doctor => Err("Doctor checks the files without reading them".into()),
+            Commands::Edit {
+                cite,
+                field,
+                value,
+                no_fill,
+            } => Ok(edit(biblio, providers, &cite, &field, value, !no_fill)?),
+            Commands::Enrich {
+                filter,
+                overwrite,
+                keywords: true,
+                max,
+                ..
+            } => Ok(keywords::keywords_all(
+                biblio,
+                filter.as_ref(),
+                overwrite,
+                max,
+                confirm_keywords,
+            )),
+            Commands::Enrich {
+                filter,
+                overwrite,
+                oa,
+                max,
+                email,
+                ..
+            } => Ok(enrich(
+                biblio,
+                providers,
+                filter.as_ref(),
+                overwrite,
+                oa,
+                email,
+                max,
+            )?),
+            Commands::Export {
+                format,
+                json,
+                no_provenance,
+                exclude_fields,
+                only_fields,
+                truncate,
+                filter,
+                years,
+                pick,
+                crossrefs,
             } => {
-                let mut resolver = seb::ast::Entry::resolver_with_cite(kind, cite);
-
-                if let Some(entry) = biblio.get(&entry) {
-                    resolver.set_fields_from_entry(entry);
-                } else {
-                    return Err(eyre::eyre!("No entry found with the cite key of '{entry}'").into());
-                }
-
-                if let Some(fields) = fields {
-                    resolver.add_required_fields(fields);
+                if !truncate.is_empty() && matches!(format, ExportFormat::Dot) {
+                    return Err("Fields are not truncated in the dot format".into());
                 }
-
-                interact::user_resolve_entry(&mut resolver)?;
-                let derived_entry = resolver.resolve()?;
-                let cite = derived_entry.cite().to_owned();
-                biblio.insert(derived_entry);
-
-                log::info!(
-                    "Entry with cite '{cite}' derived from '{entry}' and added to bibliography"
-                );
-                Ok(cite)
+                let fields = export_fields(only_fields, exclude_fields, no_provenance);
+                let entries = listed(biblio, filter.as_ref(), &years).collect::<Vec<_>>();
+                let entries = if pick {
+                    pick_entries(biblio, &crossrefs, entries, |items| {
+                        interact::user_multi_select("Select the entries to export", items)
+                    })?
+                } else {
+                    entries
+                };
+                let truncate = truncate.into_iter().collect();
+                let stdout = std::io::stdout();
+                let pretty = json.is_pretty(stdout.is_terminal());
+                export(
+                    entries,
+                    &crossrefs,
+                    format,
+                    fields,
+                    truncate,
+                    pretty,
+                    stdout.lock(),
+                )?;
+                // the entries have already been written to stdout
+                Ok(String::new())
+            }
+            Commands::Format => {
+                biblio.touch();
+                Ok(String::new())
             }
-            Commands::New { kind, cite, fields } => {
+            // only executed by `execute_raw` as the entries are written as they are in the file
+            Commands::Extract { .. } => Err("Extract uses the raw bibliography".into()),
+            // normally executed by `execute_raw` without parsing
+            Commands::Keys => Ok(keys(biblio.entries().map(Entry::cite))),
+            Commands::Lock { cite } => Ok(lock(biblio, &cite, true)),
+            // only executed by `execute_without_file` as the bibliography is not used
+            Commands::Fields { .. } | Commands::Lookup { .. } => {
+                Err("Fields and lookup do not use the bibliography".into())
+            }
+            Commands::List {
+                filter,
+                count_only: true,
+                years,
+                ..
+            } => Ok(listed(biblio, filter.as_ref(), &years).count().to_string()),
+            Commands::List {
+                filter,
+                names,
+                years,
+                ..
+            } => Ok(list(biblio, filter.as_ref(), names, &years)),
+            Commands::New {
+                kind,
+                cite,
+                fields,
+                transliterate_keys,
+            } => {
                 let mut resolver = if let Some(cite) = cite {
                     seb::ast::Entry::resolver_with_cite(kind, cite)
                 } else {
                     seb::ast::Entry::resolver(kind)
                 };
+                resolver.set_transliterate(transliterate_keys);
 
                 if let Some(fields) = fields {
                     resolver.add_required_fields(fields);
                 }
 
                 interact::user_resolve_entry(&mut resolver)?;
-                let entry = resolver.resolve()?;
+                let mut entry = resolver.resolve()?;
+                entry.set_field(SOURCE_FIELD, "manual");
+                // an online resource can change so the date it was read is recorded
+                if matches!(entry.kind(), EntryKind::Other(kind) if kind == "online")
+                    && entry.get_field(URLDATE_FIELD).is_none()
+                {
+                    entry.set_field(
+                        URLDATE_FIELD,
+                        chrono::Local::now().format("%Y-%m-%d").to_string(),
+                    );
+                }
                 let cite = entry.cite().to_owned();
                 biblio.insert(entry);
 
                 log::info!("Entry with cite '{cite}' added to bibliography");
                 Ok(cite)
             }
-            Commands::Rm { cite } => {
-                log::trace!("Checking current bibliography for entry with this cite key..");
-                if biblio.remove(&cite) {
-                    Ok("Entry removed from bibliography".to_owned())
-                } else {
-                    Ok(format!("No entry found with the cite key of '{cite}'"))
+            Commands::Normalize {
+                authors,
+                arxiv,
+                dois,
+                fix_encoding,
+                rekey,
+                key_format,
+                title_case,
+                acronyms,
+            } => Ok(normalize(
+                biblio,
+                arxiv,
+                dois,
+                fix_encoding,
+                title_case.then_some(acronyms.as_slice()),
+                authors.then_some(confirm_merge),
+                rekey.then(|| key_format.unwrap_or_default()),
+            )),
+            Commands::Oa { cite, email } => Ok(oa::oa(biblio, &cite, &oa::required_email(email)?)?),
+            Commands::Related { cite, by, limit } => Ok(related(biblio, &cite, by, limit)?),
+            Commands::Split {
+                by,
+                out_dir,
+                move_entries,
+                config,
+                crossrefs,
+            } => Ok(split::split(
+                biblio,
+                &crossrefs,
+                by,
+                &out_dir,
+                move_entries,
+                &config,
+            )?),
+            Commands::Stats { missing } => Ok(match missing {
+                Some(field) => missing_field(biblio, &field),
+                None => field_coverage(biblio),
+            }),
+            Commands::Rm { cite } => Ok(rm(biblio, &cite, confirm_removal)),
+            Commands::Unlock { cite } => Ok(lock(biblio, &cite, false)),
+            // only executed by `execute_file` as the file is used without being parsed
+            Commands::History | Commands::Undo => Err("History and undo use the file".into()),
+        }
+    }
+}
+
+// The cite keys sorted, so that one file and several files list the keys in the same order.
+fn keys<'a>(cites: impl Iterator<Item = &'a str>) -> String {
+    let mut keys = cites.collect::<Vec<_>>();
+    keys.sort_unstable();
+    keys.join("\n")
+}
+
+/// The error for a `cite` key that no entry in the bibliography has.
+pub(super) fn no_entry(cite: &str) -> seb::Error {
+    seb::Error::new(
+        seb::ErrorKind::NoValue,
+        format!("No entry found with the cite key of '{cite}'"),
+    )
+    .with_hint("Run `seb keys` to list the cite keys of the bibliography")
+}
+
+fn copy(biblio: &mut Biblio, cite: &str, new_key: Option<String>) -> eyre::Result<String> {
+    let Some(entry) = biblio.get(cite) else {
+        return Err(no_entry(cite).into());
+    };
+
+    let new_key = new_key.unwrap_or_else(|| biblio.unique_cite(entry.cite()));
+    if biblio
+        .entries()
+        .any(|other| other.cite().eq_ignore_ascii_case(&new_key))
+    {
+        return Err(eyre::eyre!(
+            "An entry already exists with the cite key of '{new_key}'"
+        ));
+    }
+
+    let copy = entry.copy_with_cite(new_key.as_str());
+    biblio.insert(copy);
+    log::info!("Entry '{cite}' copied to '{new_key}'");
+    Ok(new_key)
+}
+
+fn derive(
+    biblio: &mut Biblio,
+    entry: &str,
+    kind: EntryKind<'static>,
+    cite: String,
+    fields: Option<Vec<String>>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut resolver = seb::ast::Entry::resolver_with_cite(kind, cite);
+
+    if let Some(entry) = biblio.get(entry) {
+        resolver.set_fields_from_entry(entry);
+    } else {
+        return Err(no_entry(entry).into());
+    }
+
+    if let Some(fields) = fields {
+        resolver.add_required_fields(fields);
+    }
+
+    interact::user_resolve_entry(&mut resolver)?;
+    let derived_entry = resolver.resolve()?;
+    let cite = derived_entry.cite().to_owned();
+    biblio.insert(derived_entry);
+
+    log::info!("Entry with cite '{cite}' derived from '{entry}' and added to bibliography");
+    Ok(cite)
+}
+
+fn diff(biblio: &Biblio, other: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = FormatFile::<BibTex>::open(other)?;
+    let other = file.read_ast()?.map_err(|resolver| {
+        eyre::eyre!("{} contains invalid entries:\n{resolver}", other.display())
+    })?;
+    Ok(Diff::new(biblio, &other).to_string())
+}
+
+fn normalize<F>(
+    biblio: &mut Biblio,
+    arxiv: bool,
+    dois: bool,
+    fix_encoding: bool,
+    title_case: Option<&[String]>,
+    merge_authors: Option<F>,
+    rekey: Option<KeyFormat>,
+) -> String
+where
+    F: Fn(&Author, &[Author]) -> bool,
+{
+    let mut changed = biblio.move_doi_urls()
+        + biblio.map_fields(|field, value| {
+            if VERBATIM_FIELDS.contains(&field) {
+                None
+            } else {
+                collapse_quoted_whitespace(value)
+            }
+        })
+        + biblio.map_fields(|field, value| match field {
+            "pages" => collapse_page_range(value).map(Into::into),
+            _ => None,
+        });
+    if arxiv {
+        changed += biblio.store_arxiv_ids();
+    }
+    if dois {
+        changed += biblio.lowercase_dois();
+    }
+    if fix_encoding {
+        changed += biblio.map_fields(|_, value| fix_quoted_mojibake(value));
+    }
+    // after the encoding is repaired so that the repaired letters are cased
+    if let Some(acronyms) = title_case {
+        changed += biblio.map_fields(|field, value| match field {
+            "title" => sentence_case_quoted(value, acronyms),
+            _ => None,
+        });
+    }
+
+    let mut lines = vec![format!("{changed} field(s) normalized")];
+    // the cite keys are generated from the merged names
+    if let Some(confirm) = merge_authors {
+        lines.extend(merge_author_variants(biblio, confirm));
+    }
+    if let Some(format) = rekey {
+        let renamed = biblio.rekey(&format);
+        lines.push(format!("{} cite key(s) changed", renamed.len()));
+        lines.extend(
+            renamed
+                .into_iter()
+                .map(|(old, new)| format!("{old} -> {new}")),
+        );
+    }
+    lines.join("\n")
+}
+
+// Replaces the variants of each name in the name fields of the unlocked entries with the most
+// complete name, see `Author::variants`, when the merge is confirmed.
+fn merge_author_variants<F>(biblio: &mut Biblio, confirm: F) -> Vec<String>
+where
+    F: Fn(&Author, &[Author]) -> bool,
+{
+    const ROLES: [Role; 3] = [Role::Author, Role::Editor, Role::Translator];
+
+    let names = biblio
+        .entries()
+        .filter(|entry| !entry.is_locked())
+        .flat_map(|entry| ROLES.into_iter().flat_map(|role| entry.names(role)))
+        .collect::<Vec<_>>();
+
+    let mut merged = HashMap::new();
+    let mut lines = Vec::new();
+    for group in Author::variants(&names) {
+        let (name, variants) = group.split_first().expect("A group has at least two names");
+        if confirm(name, variants) {
+            for variant in variants {
+                lines.push(format!("{variant} -> {name}"));
+                merged.insert(variant.clone(), name.clone());
+            }
+        }
+    }
+
+    // only the merged names are rewritten so the other names are kept as they are written
+    let changed = biblio.map_fields(|field, value| {
+        ROLES.iter().find(|role| role.field() == field)?;
+        let mut replaced = false;
+        let names = Author::split_list(value)
+            .into_iter()
+            .map(|name| {
+                let merged = Author::parse(&name).and_then(|name| merged.get(&name));
+                replaced |= merged.is_some();
+                merged.map_or(name, ToString::to_string)
+            })
+            .collect::<Vec<_>>();
+        replaced.then(|| names.join(" and ").into())
+    });
+    lines.insert(
+        0,
+        format!(
+            "{} name variant(s) merged in {changed} field(s)",
+            merged.len()
+        ),
+    );
+    lines
+}
+
+fn lock(biblio: &mut Biblio, cite: &str, locked: bool) -> String {
+    let Some(entry) = biblio.get_mut(cite) else {
+        return format!("No entry found with the cite key of '{cite}'");
+    };
+
+    if locked {
+        entry.set_field(LOCKED_FIELD, "true");
+        format!("Entry '{cite}' locked")
+    } else {
+        entry.remove_field(LOCKED_FIELD);
+        format!("Entry '{cite}' unlocked")
+    }
+}
+
+fn list(biblio: &Biblio, filter: Option<&Filter>, names: bool, years: &YearRange) -> String {
+    let mut entries = listed(biblio, filter, years)
+        .map(|entry| {
+            let mut line = format!("{}: {}", entry.cite(), entry.title().as_ref());
+            if names {
+                // the translators are credited after the authors, or the editors
+                let credits = entry
+                    .creators()
+                    .into_iter()
+                    .chain([(Role::Translator, entry.names(Role::Translator))])
+                    .filter(|(_, creators)| !creators.is_empty())
+                    .map(|(role, creators)| role.format(&creators))
+                    .collect::<Vec<_>>();
+                if !credits.is_empty() {
+                    line.push_str(" - ");
+                    line.push_str(&credits.join("; "));
                 }
             }
+            if let Some(file) = entry.get_field(FILE_FIELD) {
+                line.push_str(" (");
+                line.push_str(file);
+                line.push(')');
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort();
+    entries.join("\n")
+}
+
+// The entries that match the filter and are in the range of years.
+fn listed<'a>(
+    biblio: &'a Biblio,
+    filter: Option<&'a Filter>,
+    years: &'a YearRange,
+) -> impl Iterator<Item = &'a Entry> {
+    biblio
+        .entries()
+        .filter(move |entry| filter.is_none_or(|f| f.matches(entry)) && years.includes(entry))
+}
+
+fn edit(
+    biblio: &mut Biblio,
+    providers: &Providers,
+    cite: &str,
+    field: &str,
+    value: String,
+    fill: bool,
+) -> eyre::Result<String> {
+    let Some(entry) = biblio.get_mut(cite) else {
+        return Err(no_entry(cite).into());
+    };
+
+    let field = field.to_lowercase();
+    entry.set_field(&field, value);
+
+    if !fill || field != "doi" || entry.is_locked() {
+        return Ok(format!("Entry '{cite}' {field} set"));
+    }
+
+    let filled = enrich::fill_from_doi(biblio, providers, cite);
+    Ok(if filled.is_empty() {
+        format!("Entry '{cite}' doi set, no fields were filled")
+    } else {
+        format!("Entry '{cite}' doi set, filled: {}", filled.join(", "))
+    })
+}
+
+// The Unpaywall email is checked first so that nothing is looked up when the email is missing.
+fn enrich(
+    biblio: &mut Biblio,
+    providers: &Providers,
+    filter: Option<&Filter>,
+    overwrite: bool,
+    with_oa: bool,
+    email: Option<String>,
+    max: Option<usize>,
+) -> Result<String, seb::Error> {
+    let oa_email = with_oa.then(|| oa::required_email(email)).transpose()?;
+    let summary = enrich::enrich(biblio, providers, filter, overwrite, max);
+
+    Ok(match oa_email {
+        Some(email) => format!("{summary}\n{}", oa::oa_all(biblio, filter, &email)),
+        None => summary,
+    })
+}
+
+fn rm<F>(biblio: &mut Biblio, cite: &str, confirm: F) -> String
+where
+    F: FnOnce(&Entry) -> bool,
+{
+    log::trace!("Checking current bibliography for entry with this cite key..");
+    let entry = biblio
+        .entries()
+        .find(|entry| entry.cite().to_lowercase() == cite.to_lowercase());
+
+    match entry {
+        None => format!("No entry found with the cite key of '{cite}'"),
+        Some(entry) if !confirm(entry) => "Entry was not removed".to_owned(),
+        Some(_) => {
+            biblio.remove(cite);
+            "Entry removed from bibliography".to_owned()
+        }
+    }
+}
+
+fn dedup<F>(biblio: &mut Biblio, priority: &[String], max: Option<usize>, confirm: F) -> String
+where
+    F: Fn(&Entry) -> bool,
+{
+    let duplicates = biblio.doi_duplicates(priority);
+    if duplicates.is_empty() {
+        return "No entries share the same DOI or arXiv ID".to_owned();
+    }
+
+    let max = max.unwrap_or(usize::MAX);
+    let mut confirmed = Vec::new();
+    let mut left = 0;
+    for (entry, kept) in duplicates {
+        // no more removals are confirmed once the limit is reached
+        if confirmed.len() == max {
+            left += 1;
+        } else if confirm(entry) {
+            confirmed.push((entry.cite().to_owned(), kept.cite().to_owned()));
+        }
+    }
+    let mut removed = confirmed
+        .into_iter()
+        .filter_map(|(cite, kept)| biblio.remove_duplicate(&cite, &kept).map(|_| cite))
+        .collect::<Vec<_>>();
+
+    removed.sort();
+    let mut lines = Vec::new();
+    if !removed.is_empty() {
+        lines.push(format!("Removed duplicate entries: {}", removed.join(", ")));
+    }
+    if left > 0 {
+        lines.push(limit_reached(max, left));
+    }
+    if lines.is_empty() {
+        return "No duplicate entries were removed".to_owned();
+    }
+    lines.join("\n")
+}
+
+// Restores the most recent backup of the file at `path`, see `backup::undo`, and records the
+// change in the history when it is enabled.
+fn undo(
+    path: &Path,
+    encoding: Option<InputEncoding>,
+    confirm: bool,
+    config: &HistoryConfig,
+) -> eyre::Result<String> {
+    let before = config
+        .log
+        .then(|| history::before(path, encoding))
+        .flatten();
+    let mut restored = false;
+    let output = backup::undo(path, encoding, |summary| {
+        restored = !confirm || interact::user_confirm(&format!("{summary}\nRestore this backup?"));
+        restored
+    })?;
+    if let Some(before) = before.filter(|_| restored) {
+        let after = backup::read(path, encoding)?;
+        history::record(path, &before, &after, config.user.clone())?;
+    }
+    Ok(output)
+}
+
+/// The message shown when a bulk change stops at the `--max` limit of changes, with the number of
+/// changes that were `left`.
+pub(super) fn limit_reached(max: usize, left: usize) -> String {
+    format!("Stopped at the limit of {max} change(s) set by --max, {left} more were not made")
+}
+
+// the entries added with `--allow-incomplete` are still missing the required fields of their kind
+fn check(biblio: &Biblio) -> Result<String, Box<dyn std::error::Error>> {
+    let mut incomplete = biblio
+        .entries()
+        .filter_map(|entry| {
+            let value = entry.get_field(INCOMPLETE_FIELD)?;
+            let (kind, missing) = value.split_once(':').unwrap_or(("entry", value));
+            Some(format!(
+                "The entry '{}' was added as misc without the required {} fields: {}",
+                entry.cite(),
+                kind.trim(),
+                missing.trim()
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if incomplete.is_empty() {
+        Ok("All entries contain the required fields!".to_owned())
+    } else {
+        incomplete.sort();
+        Err(eyre::eyre!("{}", incomplete.join("\n")).into())
+    }
+}
+
+fn similar_titles(biblio: &Biblio, similarity: &Similarity) -> String {
+    let groups = biblio
+        .similar_titles_with(similarity)
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|entry| format!("{}: {}", entry.cite(), entry.title().as_ref()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>();
+
+    if groups.is_empty() {
+        "No entries have similar titles".to_owned()
+    } else {
+        groups.join("\n\n")
+    }
+}
+
+fn export_fields(
+    only: Option<Vec<String>>,
+    exclude: Vec<String>,
+    no_provenance: bool,
+) -> FieldFilter {
+    // the file an entry was read from is only in memory when reading several files
+    let fields = only
+        .map_or_else(|| FieldFilter::exclude(exclude), FieldFilter::only)
+        .and_exclude([FILE_FIELD]);
+    if no_provenance {
+        fields.and_exclude([format!("{PROVENANCE_PREFIX}*")])
+    } else {
+        fields
+    }
+}
+
+// A field and the most characters it is cut to, such as `abstract=500`.
+fn truncate_limit(limit: &str) -> Result<(String, usize), String> {
+    let (field, max) = limit
+        .split_once('=')
+        .ok_or_else(|| format!("'{limit}' is not FIELD=N, such as abstract=500"))?;
+    let max = max
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{max}' is not a number of characters"))?;
+    Ok((field.trim().to_owned(), max))
+}
+
+/// Returns the `entries` picked using `pick`, which is given the cite key and title of each entry
+/// sorted by the cite key, along with the entries that the picked entries reference in a
+/// `crossref` field, found in the `crossrefs` of the raw bibliography.
+fn pick_entries<'a, F>(
+    biblio: &'a Biblio,
+    crossrefs: &HashMap<String, String>,
+    mut entries: Vec<&'a Entry>,
+    pick: F,
+) -> eyre::Result<Vec<&'a Entry>>
+where
+    F: FnOnce(&[String]) -> eyre::Result<Vec<usize>>,
+{
+    entries.sort_by_key(|entry| entry.cite());
+    let items = entries
+        .iter()
+        .map(|entry| format!("{}: {}", entry.cite(), entry.title().as_ref()))
+        .collect::<Vec<_>>();
+
+    let mut picked = pick(&items)?
+        .into_iter()
+        .filter_map(|i| entries.get(i).copied())
+        .collect::<Vec<_>>();
+    // a parent can have a crossref of its own
+    let mut i = 0;
+    while let Some(&entry) = picked.get(i) {
+        let parent = crossrefs
+            .get(entry.cite())
+            .and_then(|parent| biblio.get(parent));
+        if let Some(parent) = parent {
+            if !picked.iter().any(|entry| entry.cite() == parent.cite()) {
+                picked.push(parent);
+            }
+        }
+        i += 1;
+    }
+    Ok(picked)
+}
+
+fn export<'a, I, W>(
+    entries: I,
+    crossrefs: &HashMap<String, String>,
+    format: ExportFormat,
+    fields: FieldFilter,
+    truncate: Truncate,
+    pretty: bool,
+    mut out: W,
+) -> Result<(), seb::Error>
+where
+    I: IntoIterator<Item = &'a Entry>,
+    W: Write,
+{
+    match format {
+        ExportFormat::Jsonl => {
+            let mut entries = entries
+                .into_iter()
+                .map(|entry| truncated(entry, &truncate))
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.cite().cmp(b.cite()));
+            let entries = entries.iter().map(|entry| WithFields(entry, &fields));
+            if pretty {
+                write_json_pretty(entries, out)
+            } else {
+                seb::format::write_json_lines(entries, out)
+            }
+        }
+        ExportFormat::Bibtex => {
+            let config = BibTexConfig {
+                fields,
+                transforms: Transforms::new().with(truncate),
+                ..BibTexConfig::default()
+            };
+            out.write_all(
+                BibTex::compose_entries_with(entries, &config)
+                    .raw()
+                    .as_bytes(),
+            )
+            .map_err(|err| seb::Error::wrap(seb::ErrorKind::IO, err))
+        }
+        ExportFormat::Dot => writeln!(out, "{}", graph::dot(entries, crossrefs))
+            .map_err(|err| seb::Error::wrap(seb::ErrorKind::IO, err)),
+    }
+}
+
+// The `entry` with the fields cut by `truncate`, which is only copied when a field is cut.
+fn truncated<'a>(entry: &'a Entry, truncate: &Truncate) -> Cow<'a, Entry> {
+    let cut = entry
+        .fields()
+        .into_iter()
+        .filter_map(|mut field| {
+            truncate.transform(entry, &mut field);
+            match field.value {
+                Cow::Owned(value) => Some((field.name.into_owned(), value)),
+                Cow::Borrowed(_) => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    if cut.is_empty() {
+        return Cow::Borrowed(entry);
+    }
+    let mut entry = entry.copy_with_cite(entry.cite());
+    for (name, value) in cut {
+        entry.set_field(&name, value);
+    }
+    Cow::Owned(entry)
+}
+
+// Writes each entry as an indented JSON document on its own lines, which is easier to read than
+// JSON lines but is no longer one document per line.
+fn write_json_pretty<I, W>(entries: I, mut out: W) -> Result<(), seb::Error>
+where
+    I: IntoIterator,
+    I::Item: serde::Serialize,
+    W: Write,
+{
+    for entry in entries {
+        serde_json::to_writer_pretty(&mut out, &entry)
+            .map_err(|err| seb::Error::wrap(seb::ErrorKind::IO, err))?;
+        out.write_all(b"\n")
+            .map_err(|err| seb::Error::wrap(seb::ErrorKind::IO, err))?;
+    }
+    out.flush()
+        .map_err(|err| seb::Error::wrap(seb::ErrorKind::IO, err))
+}
+
+fn lookup<W: Write>(
+    providers: &Providers,
+    kind: &str,
+    id: &str,
+    format: ExportFormat,
+    pretty: bool,
+    out: W,
+) -> eyre::Result<()> {
+    let query = match kind.to_lowercase().as_str() {
+        "doi" => Query::Doi(id),
+        "isbn" => Query::Isbn(id),
+        "rfc" | "ietf" => Query::Rfc(
+            id.trim()
+                .parse()
+                .map_err(|_| eyre::eyre!("The RFC number '{id}' is not a number"))?,
+        ),
+        "bibcode" | "ads" => Query::Bibcode(id),
+        "standard" | "ieee" => Query::Standard(id),
+        _ => Query::Other { kind, id },
+    };
+
+    let biblio = providers.lookup(&query)?.unwrap_or_else(|resolver| {
+        log::warn!("The entry found by {query} is missing required fields - they are left empty");
+        resolver.into_partial()
+    });
+    Ok(export(
+        biblio.entries(),
+        &HashMap::new(),
+        format,
+        FieldFilter::default(),
+        Truncate::new(),
+        pretty,
+        out,
+    )?)
+}
+
+fn field_coverage(biblio: &Biblio) -> String {
+    let total = biblio.entries().count();
+    if total == 0 {
+        return "0 entries".to_owned();
+    }
+
+    let mut counts = HashMap::new();
+
+    for entry in biblio.entries() {
+        // the required fields of incomplete entries are empty and the file field is only in memory
+        for field in entry
+            .fields()
+            .into_iter()
+            .filter(|f| !f.value.is_empty() && f.name != FILE_FIELD)
+        {
+            *counts.entry(field.name.to_string()).or_insert(0_usize) += 1;
+        }
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    // most common fields first and then by name
+    counts.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+
+    let width = counts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut lines = vec![format!("{total} entries")];
+    lines.extend(counts.into_iter().map(|(name, count)| {
+        let percent = count * 100 / total;
+        format!("{name:<width$}  {count:>5}/{total}  {percent:>3}%")
+    }));
+    lines.join("\n")
+}
+
+fn missing_field(biblio: &Biblio, field: &str) -> String {
+    let mut cites = biblio
+        .entries()
+        .filter(|entry| entry.get_field(field).is_none_or(|value| value.is_empty()))
+        .map(Entry::cite)
+        .collect::<Vec<_>>();
+    cites.sort_unstable();
+    cites.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{fixture::FileWriteStr, prelude::PathChild, TempDir};
+
+    use super::*;
+    use crate::fixture::manual;
+
+    fn biblio() -> Biblio {
+        Biblio::new(vec![
+            manual("a", &[("doi", "10.1000/182"), ("year", "2020")]),
+            manual("b", &[("year", "2021")]),
+            manual("c", &[]),
+        ])
+    }
+
+    #[test]
+    fn field_coverage_is_sorted_by_count() {
+        assert_eq!(
+            "3 entries\ntitle      3/3  100%\nyear       2/3   66%\ndoi        1/3   33%",
+            field_coverage(&biblio())
+        );
+    }
+
+    #[test]
+    fn missing_field_lists_cite_keys() {
+        assert_eq!("b\nc", missing_field(&biblio(), "doi"));
+    }
+
+    #[test]
+    fn normalize_leaves_locked_entry_unchanged() {
+        let mut biblio = BibTex::new(
+            "@manual{locked, title = {CafÃ©}, x-seb-locked = {true}}\n\
+             @manual{unlocked, title = {CafÃ©}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        let compose = |biblio: &Biblio| {
+            BibTex::compose_entry_with(biblio.get("locked").unwrap(), &BibTexConfig::default())
+        };
+        let before = compose(&biblio);
+
+        Commands::Normalize {
+            authors: false,
+            arxiv: false,
+            dois: false,
+            fix_encoding: true,
+            rekey: false,
+            key_format: None,
+            title_case: false,
+            acronyms: Vec::new(),
         }
+        .execute(&mut biblio, &Providers::new(), false, false)
+        .unwrap();
+
+        assert_eq!(before, compose(&biblio));
+        assert_eq!("Café", &**biblio.get("unlocked").unwrap().title());
+    }
+
+    #[test]
+    fn normalize_merges_author_variants() {
+        let mut biblio = BibTex::new(
+            "@manual{one, title = {One}, author = {J. Smith and Leslie Lamport}}\n\
+             @manual{two, title = {Two}, author = {John A. Smith}}\n\
+             @manual{three, title = {Three}, editor = {Smith, J. A.}}\n\
+             @manual{locked, title = {Locked}, author = {J. Smith}, x-seb-locked = {true}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let output = Commands::Normalize {
+            authors: true,
+            arxiv: false,
+            dois: false,
+            fix_encoding: false,
+            rekey: false,
+            key_format: None,
+            title_case: false,
+            acronyms: Vec::new(),
+        }
+        .execute(&mut biblio, &Providers::new(), false, false)
+        .unwrap();
+
+        assert_eq!(
+            "0 field(s) normalized\n\
+             2 name variant(s) merged in 2 field(s)\n\
+             Smith, J. A. -> Smith, John A.\n\
+             Smith, J. -> Smith, John A.",
+            output
+        );
+        let names = |cite: &str, role: Role| Author::join(&biblio.get(cite).unwrap().names(role));
+        // the names that are not merged are kept as they are written
+        let fields = biblio.get("one").unwrap().fields();
+        let author = fields.iter().find(|field| field.name == "author").unwrap();
+        assert_eq!("Smith, John A. and Leslie Lamport", &**author.value);
+        assert_eq!("Smith, John A.", names("three", Role::Editor));
+        assert_eq!("Smith, J.", names("locked", Role::Author));
+
+        let declined = normalize(
+            &mut biblio,
+            false,
+            false,
+            false,
+            None,
+            Some(|_: &Author, _: &[Author]| false),
+            None,
+        );
+        assert!(declined.ends_with("0 name variant(s) merged in 0 field(s)"));
+    }
+
+    #[test]
+    fn normalize_title_case_keeps_acronyms() {
+        let mut biblio = BibTex::new(
+            "@manual{caps, title = {MACHINE LEARNING FOR DNA: A {CRISPR} STUDY}}
+             @manual{custom, title = {THE QUIC PROTOCOL}}
+             @manual{mixed, title = {Machine Learning for DNA}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let output = Commands::Normalize {
+            authors: false,
+            arxiv: false,
+            dois: false,
+            fix_encoding: false,
+            rekey: false,
+            key_format: None,
+            title_case: true,
+            acronyms: vec!["QUIC".to_owned()],
+        }
+        .execute(&mut biblio, &Providers::new(), false, false)
+        .unwrap();
+
+        assert_eq!("2 field(s) normalized", output);
+        let title = |cite: &str| biblio.get(cite).unwrap().title().to_string();
+        assert_eq!("Machine learning for DNA: A CRISPR study", title("caps"));
+        assert_eq!("The QUIC protocol", title("custom"));
+        assert_eq!("Machine Learning for DNA", title("mixed"));
+    }
+
+    #[test]
+    fn list_incomplete_entries() {
+        let resolver = BibTex::new(
+            "@manual{manual, title = {Manual}}\n\
+             @article{incomplete, title = {Incomplete}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .expect_err("The article is missing required fields");
+
+        let biblio = resolver.into_partial();
+
+        assert_eq!(
+            "incomplete: Incomplete\nmanual: Manual",
+            list(&biblio, None, false, &YearRange::default())
+        );
+        assert_eq!("incomplete\nmanual", missing_field(&biblio, "journal"));
+    }
+
+    #[test]
+    fn list_names_falls_back_to_editors_and_credits_translators() {
+        let biblio = BibTex::new(
+            "@proceedings{edited, editor = {Knuth, Donald and Leslie Lamport}, title = {Edited},\
+             year = {2000}}\n\
+             @manual{manual, author = {Alan Turing}, title = {Manual}}\n\
+             @manual{translated, author = {Tolstoy, Leo}, translator = {Constance Garnett},\
+             title = {Translated}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            "edited: Edited - Donald Knuth and Leslie Lamport (eds.)\n\
+             manual: Manual - Alan Turing\n\
+             translated: Translated - Leo Tolstoy; Constance Garnett (trans.)",
+            list(&biblio, None, true, &YearRange::default())
+        );
+    }
+
+    #[test]
+    fn copy_is_an_independent_entry_with_a_new_key() {
+        let mut biblio = biblio();
+
+        assert_eq!("aa", copy(&mut biblio, "a", None).unwrap());
+        assert!(biblio.dirty());
+
+        biblio.get_mut("aa").unwrap().set_field("year", "2022");
+        let (original, copied) = (biblio.get("a").unwrap(), biblio.get("aa").unwrap());
+        assert_eq!(original.title(), copied.title());
+        assert_eq!("2020", &**original.get_field("year").unwrap());
+        assert_eq!("2022", &**copied.get_field("year").unwrap());
+
+        assert_eq!(
+            "preprint",
+            copy(&mut biblio, "a", Some("preprint".to_owned())).unwrap()
+        );
+        assert!(copy(&mut biblio, "a", Some("B".to_owned())).is_err());
+        assert!(copy(&mut biblio, "missing", None).is_err());
+    }
+
+    #[test]
+    fn check_reports_entries_added_incomplete() {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "paper");
+        resolver.title("Paper");
+        resolver.author("Me");
+        let incomplete = resolver.resolve_incomplete();
+
+        // the incomplete entry is written and read back without missing fields
+        let mut biblio = BibTex::new(BibTex::compose_entry(&incomplete))
+            .parse()
+            .unwrap()
+            .expect("The misc entry only requires a title");
+
+        let err = Commands::Check
+            .execute(&mut biblio, &Providers::default(), false, false)
+            .unwrap_err();
+
+        assert_eq!(
+            "The entry 'paper' was added as misc without the required article fields: journal, year",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn check_reports_dangling_crossref() {
+        let bibtex = BibTex::new(
+            "@inproceedings{paper, author = {Me}, title = {Paper}, booktitle = {Conf}, \
+             year = {2020}, crossref = {conf2020}}"
+                .to_owned(),
+        );
+
+        let err = Commands::Check.audit(&bibtex).unwrap_err();
+
+        assert_eq!(
+            "The entry 'paper' has a crossref to 'conf2020' but no entry has that cite key",
+            err.to_string()
+        );
+        assert!(Commands::List {
+            filter: None,
+            names: false,
+            count_only: false,
+            years: YearRange::default(),
+        }
+        .audit(&bibtex)
+        .is_ok());
+    }
+
+    #[test]
+    fn check_reports_dangling_related() {
+        let bibtex = BibTex::new(
+            "@misc{translation, title = {Translation}, related = {original, missing}, \
+             relatedtype = {translationof}}\n\
+             @misc{original, title = {Original}}"
+                .to_owned(),
+        );
+
+        let err = Commands::Check.audit(&bibtex).unwrap_err();
+
+        assert_eq!(
+            "The entry 'translation' is related to 'missing' but no entry has that cite key",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn check_reports_missing_set_member() {
+        let bibtex = BibTex::new("@set{set, entryset = {a, b}}\n@misc{a, title = {A}}".to_owned());
+
+        let err = Commands::Check.audit(&bibtex).unwrap_err();
+
+        assert_eq!(
+            "The set 'set' has the member 'b' but no entry has that cite key",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        let config = BibTexConfig {
+            indent: 2,
+            ..BibTexConfig::default()
+        };
+        let format = |raw: String| {
+            let mut biblio = BibTex::new(raw).parse().unwrap().unwrap();
+            Commands::Format
+                .execute(&mut biblio, &Providers::new(), false, false)
+                .unwrap();
+            assert!(biblio.dirty());
+            BibTex::compose_with(&biblio, &config).raw()
+        };
+
+        let once = format(
+            "@manual{b,title=\"Second\",year={2021},url={u},note={n},doi={d}}\n\
+             @manual{d, title = {Fourth}}\n\
+             @manual{c, title = {Third}}\n\
+             @article{a, year = 2020, title = {First}, author = {Knuth, Donald}, journal = {J}}"
+                .to_owned(),
+        );
+        let twice = format(once.clone());
+
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn declined_removal_leaves_biblio_unchanged() {
+        let mut biblio = biblio();
+        let before = biblio.entries().count();
+
+        assert_eq!("Entry was not removed", rm(&mut biblio, "a", |_| false));
+        assert_eq!(before, biblio.entries().count());
+        assert!(!biblio.dirty());
+
+        assert_eq!(
+            "Entry removed from bibliography",
+            rm(&mut biblio, "A", |_| true)
+        );
+        assert!(biblio.get("a").is_none());
+    }
+
+    #[test]
+    fn dedup_only_removes_confirmed_duplicates() {
+        let doi = [("doi", "10.1000/182")];
+        let mut biblio = Biblio::new(vec![manual("a", &doi), manual("d", &doi)]);
+
+        let priority = [String::from("manual")];
+        assert_eq!(
+            "No duplicate entries were removed",
+            dedup(&mut biblio, &priority, None, |_| false)
+        );
+        assert!(biblio.get("d").is_some());
+        assert!(!biblio.dirty());
+
+        assert_eq!(
+            "Removed duplicate entries: d",
+            dedup(&mut biblio, &priority, None, |entry| entry.cite() == "d")
+        );
+        assert!(biblio.get("d").is_none());
+    }
+
+    #[test]
+    fn dedup_stops_at_the_max_removals() {
+        let doi = [("doi", "10.1000/182")];
+        let mut biblio = Biblio::new(vec![
+            manual("a", &doi),
+            manual("b", &doi),
+            manual("c", &doi),
+        ]);
+
+        let priority = [String::from("manual")];
+        let summary = dedup(&mut biblio, &priority, Some(0), |_| true);
+        assert_eq!(limit_reached(0, 2), summary);
+        assert_eq!(3, biblio.entries().count());
+
+        let summary = dedup(&mut biblio, &priority, Some(1), |_| true);
+        assert!(summary
+            .ends_with("Stopped at the limit of 1 change(s) set by --max, 1 more were not made"));
+        assert_eq!(2, biblio.entries().count());
+
+        // the last duplicate is removed without reaching the limit
+        let summary = dedup(&mut biblio, &priority, Some(1), |_| true);
+        assert!(!summary.contains("limit"), "{summary}");
+        assert_eq!(1, biblio.entries().count());
+    }
+
+    #[test]
+    fn similar_titles_depend_on_the_threshold() {
+        let biblio = Biblio::new(vec![
+            manual("a", &[("title", "Quicksort: a fast sorting scheme")]),
+            manual("b", &[("title", "Quicksort: a fast sorting schema")]),
+        ]);
+        let similarity = |threshold| Similarity {
+            metric: Metric::Levenshtein,
+            threshold,
+        };
+
+        assert_eq!(
+            "a: Quicksort: a fast sorting scheme\nb: Quicksort: a fast sorting schema",
+            similar_titles(&biblio, &similarity(0.9))
+        );
+        assert_eq!(
+            "No entries have similar titles",
+            similar_titles(&biblio, &similarity(0.99))
+        );
+    }
+
+    #[test]
+    fn threshold_is_between_zero_and_one() {
+        assert!(threshold("0").is_ok());
+        assert!(threshold("0.9").is_ok());
+        assert!(threshold("1.5").is_err());
+        assert!(threshold("high").is_err());
+    }
+
+    #[test]
+    fn lock_and_unlock_toggle_locked_field() {
+        let mut biblio = biblio();
+
+        lock(&mut biblio, "a", true);
+        assert!(biblio.get("a").unwrap().is_locked());
+
+        lock(&mut biblio, "a", false);
+        assert!(biblio.get("a").unwrap().get_field(LOCKED_FIELD).is_none());
+    }
+
+    // Finds a manual entry for the DOI `10.1000/182`.
+    struct Found;
+
+    impl seb::provider::Provider for Found {
+        fn name(&self) -> &'static str {
+            "found"
+        }
+
+        fn supports(&self, query: &Query<'_>) -> bool {
+            matches!(query, Query::Doi("10.1000/182"))
+        }
+
+        fn lookup(
+            &self,
+            _: &Query<'_>,
+        ) -> Result<Result<Biblio, seb::ast::BiblioResolver>, seb::Error> {
+            Ok(Ok(Biblio::new(vec![manual(
+                "found",
+                &[("title", "Found title"), ("year", "1999")],
+            )])))
+        }
+    }
+
+    fn found() -> Providers {
+        let mut providers = Providers::new();
+        providers.register(Found);
+        providers
+    }
+
+    #[test]
+    fn edit_doi_fills_missing_title() {
+        let providers = found();
+        let mut biblio = BibTex::new(
+            "@misc{a, title = {}, year = {2020}, x-seb-incomplete = {manual: title}}".to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let report = edit(
+            &mut biblio,
+            &providers,
+            "a",
+            "DOI",
+            "10.1000/182".to_owned(),
+            true,
+        );
+
+        assert_eq!("Entry 'a' doi set, filled: title", report.unwrap());
+        let entry = biblio.get("a").unwrap();
+        assert_eq!("Found title", &**entry.title());
+        // the entry has the fields of the entry type it was meant to be
+        assert_eq!(EntryKind::Manual, entry.kind());
+        assert!(entry.get_field(INCOMPLETE_FIELD).is_none());
+        assert_eq!("2020", &**entry.get_field("year").unwrap());
+        assert_eq!("10.1000/182", &**entry.get_field("doi").unwrap());
+    }
+
+    #[test]
+    fn lookup_prints_the_entry_without_a_bibliography() {
+        let mut out = Vec::new();
+        lookup(
+            &found(),
+            "DOI",
+            "10.1000/182",
+            ExportFormat::Bibtex,
+            false,
+            &mut out,
+        )
+        .unwrap();
+
+        let printed = BibTex::new(String::from_utf8(out).unwrap());
+        assert_eq!(
+            vec!["found"],
+            printed
+                .raw_entries()
+                .map(|entry| entry.key())
+                .collect::<Vec<_>>()
+        );
+        assert!(lookup(
+            &found(),
+            "doi",
+            "10.1000/404",
+            ExportFormat::Jsonl,
+            false,
+            Vec::new()
+        )
+        .is_err());
+        assert!(lookup(
+            &found(),
+            "rfc",
+            "one",
+            ExportFormat::Jsonl,
+            false,
+            Vec::new()
+        )
+        .is_err());
+
+        // handled before a bibliography file is opened, so no file is ever created or written
+        let command = Commands::Lookup {
+            kind: "doi".to_owned(),
+            id: "10.1000/404".to_owned(),
+            format: ExportFormat::Jsonl,
+            json: JsonStyle::default(),
+        };
+        assert!(command.execute_without_file(&found()).unwrap().is_err());
+        assert!(Commands::Keys.execute_without_file(&found()).is_none());
+    }
+
+    #[test]
+    fn keys_are_sorted_whether_or_not_the_entries_are_parsed() {
+        let src = "@manual{b, title = {B}}\n@manual{a, title = {A}}";
+        let bibtex = BibTex::new(src.to_owned());
+        let mut biblio = BibTex::new(src.to_owned()).parse().unwrap().unwrap();
+
+        assert_eq!(
+            "a\nb",
+            Commands::Keys.execute_raw(&bibtex).unwrap().unwrap()
+        );
+        assert_eq!(
+            "a\nb",
+            Commands::Keys
+                .execute(&mut biblio, &found(), false, false)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn compact_json_has_no_newlines_within_a_document() {
+        let biblio = BibTex::new(
+            "@manual{one, title = {One}, author = {Me}}\n@manual{two, title = {Two}}".to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        let export_json = |pretty: bool| {
+            let mut out = Vec::new();
+            let fields = FieldFilter::default();
+            let truncate = Truncate::new();
+            export(
+                biblio.entries(),
+                &HashMap::new(),
+                ExportFormat::Jsonl,
+                fields,
+                truncate,
+                pretty,
+                &mut out,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let compact = export_json(false);
+        let lines = compact.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with('{') && line.ends_with('}')));
+
+        let pretty = export_json(true);
+        assert!(pretty.lines().count() > 2);
+        assert!(pretty.contains("\n  \"cite\": \"one\""));
+
+        let style = |pretty, compact| JsonStyle { pretty, compact };
+        assert!(style(false, false).is_pretty(true));
+        assert!(!style(false, false).is_pretty(false));
+        assert!(style(true, false).is_pretty(false));
+        assert!(!style(false, true).is_pretty(true));
+    }
+
+    #[test]
+    fn exported_fields_are_truncated() {
+        let biblio =
+            BibTex::new("@manual{one, title = {One}, abstract = {A long abstract}}".into())
+                .parse()
+                .unwrap()
+                .unwrap();
+        let truncate = truncate_limit("abstract = 6").unwrap();
+        assert_eq!(("abstract".to_owned(), 6), truncate);
+        assert!(truncate_limit("abstract").is_err());
+        assert!(truncate_limit("abstract=long").is_err());
+
+        let mut out = Vec::new();
+        let (fields, truncate) = (FieldFilter::default(), [truncate].into_iter().collect());
+        export(
+            biblio.entries(),
+            &HashMap::new(),
+            ExportFormat::Bibtex,
+            fields,
+            truncate,
+            false,
+            &mut out,
+        )
+        .unwrap();
+
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("abstract = {A long...}"));
+        let entry = biblio.get("one").unwrap();
+        assert_eq!("A long abstract", &**entry.get_field("abstract").unwrap());
+
+        let mut out = Vec::new();
+        let truncate = [("abstract".to_owned(), 6)].into_iter().collect();
+        export(
+            biblio.entries(),
+            &HashMap::new(),
+            ExportFormat::Jsonl,
+            FieldFilter::default(),
+            truncate,
+            false,
+            &mut out,
+        )
+        .unwrap();
+
+        let json = serde_json::from_slice::<serde_json::Value>(&out).unwrap();
+        assert_eq!("A long...", json["abstract"]);
+    }
+
+    #[test]
+    fn picked_entries_are_exported_with_their_crossref_parents() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("refs.bib");
+        file.write_str(
+            "@inproceedings{paper, author = {Me}, title = {Paper}, crossref = {conf}}\n\
+             @proceedings{conf, title = {Conference}, year = {2020}}\n\
+             @manual{manual, title = {Manual}}\n\
+             @manual{other, title = {Other}}\n",
+        )
+        .unwrap();
+        let bibtex = FormatFile::<BibTex>::open(file.path())
+            .unwrap()
+            .read()
+            .unwrap();
+        let biblio = bibtex.parse().unwrap().unwrap();
+        // parsing leaves out the crossref field so the parent is only in the raw bibliography
+        assert!(biblio.get("paper").unwrap().get_field("crossref").is_none());
+        let mut command = Commands::Export {
+            format: ExportFormat::Jsonl,
+            json: JsonStyle::default(),
+            no_provenance: false,
+            exclude_fields: Vec::new(),
+            only_fields: None,
+            truncate: Vec::new(),
+            filter: None,
+            years: YearRange::default(),
+            pick: true,
+            crossrefs: HashMap::new(),
+        };
+        command.read_raw(&bibtex);
+        let Commands::Export { crossrefs, .. } = command else {
+            unreachable!()
+        };
+
+        let picked = pick_entries(&biblio, &crossrefs, biblio.entries().collect(), |items| {
+            assert_eq!("conf: Conference", items[0]);
+            Ok(items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.starts_with("manual:") || item.starts_with("paper:"))
+                .map(|(i, _)| i)
+                .collect())
+        })
+        .unwrap();
+
+        let mut out = Vec::new();
+        let (fields, truncate) = (FieldFilter::default(), Truncate::new());
+        export(
+            picked,
+            &crossrefs,
+            ExportFormat::Jsonl,
+            fields,
+            truncate,
+            false,
+            &mut out,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let cites = out
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["cite"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["conf", "manual", "paper"], cites);
+    }
+
+    #[test]
+    fn export_is_filtered_by_the_where_expression() {
+        let biblio = Biblio::new(vec![
+            manual("old", &[("year", "2019"), ("publisher", "Press")]),
+            manual("new", &[("year", "2021"), ("publisher", "Press")]),
+            manual("other", &[("year", "2021"), ("publisher", "Other")]),
+        ]);
+        let filter = "year>=2020 and publisher=Press".parse::<Filter>().unwrap();
+        let entries = listed(&biblio, Some(&filter), &YearRange::default());
+
+        let mut out = Vec::new();
+        let (fields, truncate) = (FieldFilter::default(), Truncate::new());
+        export(
+            entries,
+            &HashMap::new(),
+            ExportFormat::Jsonl,
+            fields,
+            truncate,
+            false,
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(1, out.lines().count());
+        assert!(out.contains(r#""cite":"new""#));
     }
 }
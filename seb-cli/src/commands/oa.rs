@@ -0,0 +1,96 @@
+use seb::{
+    ast::{Biblio, FieldQuery},
+    OA_URL_FIELD,
+};
+
+use crate::filter::Filter;
+
+/// The environment variable that contains the email address sent to the Unpaywall API, which is
+/// used when the `[unpaywall]` section of the config doesn't set one.
+pub(super) const EMAIL_VAR: &str = "SEB_UNPAYWALL_EMAIL";
+
+/// Returns the email address sent to the Unpaywall API from the config or the [`EMAIL_VAR`]
+/// environment variable.
+pub(super) fn email(config: Option<&str>) -> Option<String> {
+    config
+        .map(str::to_owned)
+        .or_else(|| std::env::var(EMAIL_VAR).ok())
+        .filter(|email| !email.trim().is_empty())
+}
+
+/// The Unpaywall API terms require an email address with every request.
+pub(super) fn required_email(email: Option<String>) -> Result<String, seb::Error> {
+    email.ok_or_else(|| {
+        seb::Error::new(
+            seb::ErrorKind::NoValue,
+            format!(
+                "The Unpaywall API requires an email address - set the email of the [unpaywall] \
+                section in seb.toml or the {EMAIL_VAR} environment variable"
+            ),
+        )
+    })
+}
+
+/// Attaches the URL of the open access PDF of the entry with the `cite` key.
+pub(super) fn oa(biblio: &mut Biblio, cite: &str, email: &str) -> Result<String, seb::Error> {
+    let Some(entry) = biblio.get(cite) else {
+        return Err(super::no_entry(cite));
+    };
+    if entry.get_field("doi").is_none() {
+        return Ok(format!(
+            "'{cite}' has no DOI to search for an open access PDF"
+        ));
+    }
+
+    Ok(attach(biblio, cite, email)?
+        .unwrap_or_else(|| format!("No open access PDF found for '{cite}'")))
+}
+
+/// Attaches the URL of the open access PDF of every unlocked entry with a DOI that matches the
+/// `filter` and returns the URLs found for each entry.
+///
+/// An entry that cannot be searched for is skipped so that the other entries are still searched.
+pub(super) fn oa_all(biblio: &mut Biblio, filter: Option<&Filter>, email: &str) -> String {
+    let mut cites = biblio
+        .entries()
+        .filter(|entry| !entry.is_locked() && filter.is_none_or(|f| f.matches(entry)))
+        .filter(|entry| entry.get_field("doi").is_some())
+        .map(|entry| entry.cite().to_owned())
+        .collect::<Vec<_>>();
+    cites.sort();
+
+    let lines = cites
+        .iter()
+        .filter_map(|cite| {
+            attach(biblio, cite, email)
+                .map_err(|err| {
+                    log::warn!("Cannot search for an open access PDF of '{cite}': {err}");
+                })
+                .ok()
+                .flatten()
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        "No open access PDFs were found".to_owned()
+    } else {
+        lines.join("\n")
+    }
+}
+
+// Returns the line for the entry when an open access PDF is found.
+fn attach(biblio: &mut Biblio, cite: &str, email: &str) -> Result<Option<String>, seb::Error> {
+    let Some(entry) = biblio.get(cite) else {
+        return Ok(None);
+    };
+    let Some(url) = seb::oa_url(entry, email)? else {
+        return Ok(None);
+    };
+    // the entry is only changed, and the file written, when the URL is new
+    if entry.get_field(OA_URL_FIELD).map(|old| &**old) != Some(url.as_str()) {
+        if let Some(entry) = biblio.get_mut(cite) {
+            entry.set_field(OA_URL_FIELD, url.as_str());
+        }
+    }
+    Ok(Some(format!("{cite}: {url}")))
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use clap::ArgEnum;
+use seb::ast::{Biblio, Entry};
+
+/// What entries are compared by to find related entries.
+#[derive(Clone, Copy, ArgEnum)]
+pub enum RelatedBy {
+    Authors,
+    Keywords,
+    Both,
+}
+
+/// Scores every other entry by the number of authors and keywords it shares with the entry with
+/// the `cite` key and returns up to `limit` of the highest scoring entries, one per line.
+///
+/// Entries that share nothing are not included and entries with the same score are sorted by cite
+/// key.
+pub(super) fn related(
+    biblio: &Biblio,
+    cite: &str,
+    by: RelatedBy,
+    limit: usize,
+) -> Result<String, seb::Error> {
+    let Some(entry) = biblio.get(cite) else {
+        return Err(super::no_entry(cite));
+    };
+
+    let authors = authors(entry);
+    let keywords = keywords(entry);
+
+    let mut scored = biblio
+        .entries()
+        .filter(|other| other.cite() != entry.cite())
+        .filter_map(|other| {
+            let score = match by {
+                RelatedBy::Authors => authors.intersection(&self::authors(other)).count(),
+                RelatedBy::Keywords => keywords.intersection(&self::keywords(other)).count(),
+                RelatedBy::Both => {
+                    authors.intersection(&self::authors(other)).count()
+                        + keywords.intersection(&self::keywords(other)).count()
+                }
+            };
+            (score > 0).then_some((score, other))
+        })
+        .collect::<Vec<_>>();
+
+    if scored.is_empty() {
+        return Ok(format!("No entries are related to '{cite}'"));
+    }
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.cmp(a_score).then_with(|| a.cite().cmp(b.cite()))
+    });
+
+    Ok(scored
+        .into_iter()
+        .take(limit)
+        .map(|(score, other)| format!("{score:>3}  {}: {}", other.cite(), other.title().as_ref()))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+// Names are compared by the last name and the first initial so that "Donald E. Knuth" and
+// "Knuth, D." are the same author.
+pub(super) fn authors(entry: &Entry) -> HashSet<String> {
+    entry
+        .authors()
+        .into_iter()
+        .filter_map(|author| {
+            let last = author
+                .family
+                .trim_matches(|c| c == '{' || c == '}')
+                .to_lowercase();
+            let initial = author
+                .given
+                .and_then(|given| given.chars().find(|c| c.is_alphabetic()));
+            (!last.is_empty()).then(|| match initial {
+                Some(initial) => format!("{last}, {}", initial.to_lowercase()),
+                None => last,
+            })
+        })
+        .collect()
+}
+
+// Keywords are compared ignoring case.
+fn keywords(entry: &Entry) -> HashSet<String> {
+    entry
+        .list("keywords")
+        .map(|keywords| keywords.items())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|keyword| keyword.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::manual;
+
+    fn biblio() -> Biblio {
+        Biblio::new(vec![
+            manual(
+                "target",
+                &[
+                    ("author", "Donald E. Knuth and Leslie Lamport"),
+                    ("keywords", "typesetting, TeX"),
+                ],
+            ),
+            manual(
+                "both",
+                &[
+                    ("author", "Knuth, D. and Lamport, Leslie"),
+                    ("keywords", "tex"),
+                ],
+            ),
+            manual(
+                "keyword",
+                &[
+                    ("author", "Edsger Dijkstra"),
+                    ("keywords", "Typesetting; TeX"),
+                ],
+            ),
+            manual("author", &[("author", "D. Knuth")]),
+            manual(
+                "unrelated",
+                &[("author", "Alan Turing"), ("keywords", "computability")],
+            ),
+        ])
+    }
+
+    #[test]
+    fn related_by_authors() {
+        assert_eq!(
+            "  2  both: Title\n  1  author: Title",
+            related(&biblio(), "target", RelatedBy::Authors, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn related_by_keywords() {
+        assert_eq!(
+            "  2  keyword: Title\n  1  both: Title",
+            related(&biblio(), "target", RelatedBy::Keywords, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn related_by_both_is_limited() {
+        assert_eq!(
+            "  3  both: Title\n  2  keyword: Title",
+            related(&biblio(), "target", RelatedBy::Both, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn related_to_missing_entry_is_an_error() {
+        assert!(related(&biblio(), "missing", RelatedBy::Both, 10).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use seb::{
+    ast::{Biblio, Entry, FieldQuery},
+    Retraction,
+};
+
+use crate::filter::Filter;
+
+/// The field set to `true` on a retracted entry with `--mark`.
+pub(super) const RETRACTED_FIELD: &str = "x-retracted";
+
+/// The number of searches made at the same time.
+const WORKERS: usize = 4;
+
+/// Searches for the retraction notices of every entry with a DOI that matches the `filter` using
+/// `search` and warns about each entry that has been retracted or has an expression of concern.
+///
+/// With `mark` the [`RETRACTED_FIELD`] of each unlocked retracted entry is set to `true`. An entry
+/// that cannot be searched for is logged and skipped so that the other entries are still checked.
+///
+/// Returns the notice found for each entry.
+pub(super) fn check_retractions<F>(
+    biblio: &mut Biblio,
+    filter: Option<&Filter>,
+    mark: bool,
+    search: F,
+) -> String
+where
+    F: Fn(&str) -> Result<Option<Retraction>, seb::Error> + Sync,
+{
+    let targets = biblio
+        .entries()
+        .filter(|entry| filter.is_none_or(|f| f.matches(entry)))
+        .filter_map(|entry| {
+            let doi = entry.get_field("doi")?.trim().to_owned();
+            Some((entry.cite().to_owned(), doi))
+        })
+        .collect::<Vec<_>>();
+
+    let mut found = search_all(&targets, search);
+    found.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut lines = Vec::new();
+    for (cite, retraction) in found {
+        match retraction {
+            Retraction::Retracted => log::warn!("'{cite}' has been retracted"),
+            Retraction::ExpressionOfConcern => {
+                log::warn!("'{cite}' has an expression of concern");
+            }
+        }
+        lines.push(format!("{cite}: {retraction}"));
+        if mark && retraction == Retraction::Retracted {
+            if biblio.get(&cite).is_some_and(Entry::is_locked) {
+                log::info!("'{cite}' is locked so is not marked as retracted");
+            } else if let Some(entry) = biblio.get_mut(&cite) {
+                entry.set_field(RETRACTED_FIELD, "true");
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        format!(
+            "No retracted entries found in {} entries with a DOI",
+            targets.len()
+        )
+    } else {
+        lines.join("\n")
+    }
+}
+
+// Searches the DOIs of the targets concurrently and returns the notice found for each cite key.
+fn search_all<F>(targets: &[(String, String)], search: F) -> Vec<(String, Retraction)>
+where
+    F: Fn(&str) -> Result<Option<Retraction>, seb::Error> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let checked = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..WORKERS.min(targets.len()) {
+            scope.spawn(|| {
+                while let Some((cite, doi)) = targets.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    match search(doi) {
+                        Ok(Some(retraction)) => found
+                            .lock()
+                            .expect("No worker panics holding the lock")
+                            .push((cite.clone(), retraction)),
+                        Ok(None) => {}
+                        Err(err) => log::warn!("Cannot check whether '{cite}' is retracted: {err}"),
+                    }
+                    let checked = checked.fetch_add(1, Ordering::Relaxed) + 1;
+                    log::info!("[{checked}/{}] Checked '{cite}'", targets.len());
+                }
+            });
+        }
+    });
+
+    found
+        .into_inner()
+        .expect("No worker panics holding the lock")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::manual;
+
+    use super::*;
+
+    // Only `10.1000/retracted` is retracted and `10.1000/concern` has an expression of concern.
+    fn search(doi: &str) -> Result<Option<Retraction>, seb::Error> {
+        match doi {
+            "10.1000/retracted" => Ok(Some(Retraction::Retracted)),
+            "10.1000/concern" => Ok(Some(Retraction::ExpressionOfConcern)),
+            "10.1000/offline" => Err(seb::Error::new(seb::ErrorKind::IO, "Network error")),
+            _ => Ok(None),
+        }
+    }
+
+    #[test]
+    fn retracted_doi_is_flagged_and_marked() {
+        let mut biblio = Biblio::new(vec![
+            manual("a", &[("doi", "10.1000/retracted")]),
+            manual("b", &[("doi", "10.1000/concern")]),
+            manual("c", &[("doi", "10.1000/fine")]),
+            manual("d", &[("doi", "10.1000/offline")]),
+            manual("e", &[]),
+        ]);
+
+        let report = check_retractions(&mut biblio, None, true, search);
+
+        assert_eq!("a: retracted\nb: expression of concern", report);
+        let retracted = biblio.get("a").unwrap();
+        assert_eq!("true", &**retracted.get_field(RETRACTED_FIELD).unwrap());
+        for cite in ["b", "c", "d", "e"] {
+            assert!(biblio
+                .get(cite)
+                .unwrap()
+                .get_field(RETRACTED_FIELD)
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn entries_are_only_marked_with_mark() {
+        let mut biblio = Biblio::new(vec![manual("a", &[("doi", "10.1000/retracted")])]);
+
+        let report = check_retractions(&mut biblio, None, false, search);
+
+        assert_eq!("a: retracted", report);
+        assert!(biblio
+            .get("a")
+            .unwrap()
+            .get_field(RETRACTED_FIELD)
+            .is_none());
+        assert!(!biblio.dirty());
+
+        let mut biblio = Biblio::new(vec![manual("c", &[("doi", "10.1000/fine")])]);
+        assert_eq!(
+            "No retracted entries found in 1 entries with a DOI",
+            check_retractions(&mut biblio, None, true, search)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use clap::ArgEnum;
+use eyre::{eyre, Context};
+use seb::{
+    ast::{Biblio, Entry, FieldQuery},
+    format::{BibTex, BibTexConfig, Format},
+};
+
+/// What the entries are grouped by when splitting a bibliography into files.
+#[derive(Clone, Copy, ArgEnum)]
+pub enum SplitBy {
+    Type,
+    Year,
+}
+
+/// Writes each group of entries to its own file in `out_dir`, such as `article.bib` or
+/// `2021.bib`, and returns the number of entries written to each file.
+///
+/// The entries referenced in a `crossref` field, given by the `crossrefs` of the raw bibliography,
+/// are also written to the file of each group that references them so that no file has a
+/// dangling `crossref`. The entries are removed from the
+/// bibliography when `move_entries` is set. Existing files are never overwritten.
+pub(super) fn split(
+    biblio: &mut Biblio,
+    crossrefs: &HashMap<String, String>,
+    by: SplitBy,
+    out_dir: &Path,
+    move_entries: bool,
+    config: &BibTexConfig,
+) -> eyre::Result<String> {
+    let mut groups = BTreeMap::<_, Vec<&Entry>>::new();
+    for entry in biblio.entries() {
+        groups.entry(group(entry, by)).or_default().push(entry);
+    }
+
+    for entries in groups.values_mut() {
+        let mut cites = entries
+            .iter()
+            .map(|entry| entry.cite())
+            .collect::<HashSet<_>>();
+        let mut i = 0;
+        while let Some(entry) = entries.get(i).copied() {
+            let parent = crossrefs
+                .get(entry.cite())
+                .and_then(|parent| biblio.get(parent));
+            if let Some(parent) = parent.filter(|parent| cites.insert(parent.cite())) {
+                entries.push(parent);
+            }
+            i += 1;
+        }
+    }
+
+    let paths = groups
+        .keys()
+        .map(|name| out_dir.join(format!("{name}.bib")))
+        .collect::<Vec<_>>();
+    if let Some(path) = paths.iter().find(|path| path.exists()) {
+        return Err(eyre!("The file {} already exists", path.display()));
+    }
+
+    fs::create_dir_all(out_dir)
+        .wrap_err_with(|| eyre!("Cannot create the {} directory", out_dir.display()))?;
+    let mut lines = Vec::with_capacity(groups.len());
+    for (entries, path) in groups.values().zip(&paths) {
+        let bibtex = BibTex::compose_entries_with(entries.iter().copied(), config);
+        fs::write(path, bibtex.raw()).wrap_err_with(|| eyre!("Cannot write {}", path.display()))?;
+        lines.push(format!("{}: {} entries", path.display(), entries.len()));
+    }
+
+    if move_entries {
+        let cites = biblio
+            .entries()
+            .map(|entry| entry.cite().to_owned())
+            .collect::<Vec<_>>();
+        for cite in cites {
+            biblio.remove(&cite);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+// The name of the file for the group of the entry, which only uses characters that are valid in a
+// file name on every platform.
+fn group(entry: &Entry, by: SplitBy) -> String {
+    match by {
+        SplitBy::Type => entry
+            .kind()
+            .to_string()
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase(),
+        SplitBy::Year => entry
+            .year()
+            .map_or_else(|| "undated".to_owned(), |year| year.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{fixture::PathChild, TempDir};
+
+    use super::*;
+
+    fn biblio() -> (Biblio, HashMap<String, String>) {
+        let bibtex = BibTex::new(
+            "@article{a, author = {Me}, title = {A}, journal = {J}, year = {2020}}\n\
+             @article{b, author = {Me}, title = {B}, journal = {J}, year = {2021}}\n\
+             @inproceedings{paper, author = {Me}, title = {Paper}, crossref = {conf}}\n\
+             @proceedings{conf, title = {Conf}, year = {2021}}\n\
+             @manual{m, title = {M}}"
+                .to_owned(),
+        );
+        // parsing inherits the fields of a crossref and leaves out the field
+        let crossrefs = bibtex
+            .crossrefs()
+            .into_iter()
+            .map(|(cite, parent)| (cite.to_owned(), parent))
+            .collect();
+        (bibtex.parse().unwrap().unwrap(), crossrefs)
+    }
+
+    fn keys(path: &Path) -> Vec<String> {
+        let mut keys = BibTex::new(fs::read_to_string(path).unwrap())
+            .raw_entries()
+            .map(|entry| entry.key().to_owned())
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn split_by_type_writes_a_file_per_kind() {
+        let dir = TempDir::new().unwrap();
+        let (mut biblio, crossrefs) = biblio();
+
+        let report = split(
+            &mut biblio,
+            &crossrefs,
+            SplitBy::Type,
+            dir.path(),
+            false,
+            &BibTexConfig::default(),
+        )
+        .unwrap();
+
+        let mut files = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|file| file.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            vec![
+                "article.bib",
+                "inproceedings.bib",
+                "manual.bib",
+                "proceedings.bib"
+            ],
+            files
+        );
+        assert_eq!(vec!["a", "b"], keys(dir.child("article.bib").path()));
+        // the proceedings is kept with the paper that references it
+        assert_eq!(
+            vec!["conf", "paper"],
+            keys(dir.child("inproceedings.bib").path())
+        );
+        assert!(report.contains("inproceedings.bib: 2 entries"));
+        assert!(!biblio.dirty());
+
+        let err = split(
+            &mut biblio,
+            &crossrefs,
+            SplitBy::Type,
+            dir.path(),
+            true,
+            &BibTexConfig::default(),
+        );
+        assert!(err.unwrap_err().to_string().contains("already exists"));
+        assert_eq!(5, biblio.entries().count());
+    }
+
+    #[test]
+    fn moved_entries_are_removed() {
+        let dir = TempDir::new().unwrap();
+        let (mut biblio, crossrefs) = biblio();
+
+        split(
+            &mut biblio,
+            &crossrefs,
+            SplitBy::Year,
+            dir.path(),
+            true,
+            &BibTexConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(vec!["a"], keys(dir.child("2020.bib").path()));
+        assert_eq!(vec!["m"], keys(dir.child("undated.bib").path()));
+        assert_eq!(0, biblio.entries().count());
+        assert!(biblio.dirty());
+    }
+}
@@ -0,0 +1,491 @@
+use std::{collections::HashMap, io::ErrorKind, path::Path};
+
+use eyre::{eyre, Context};
+use seb::{
+    ast::{KeyFormat, Similarity},
+    format::{BibTex, BibTexConfig, Dialect, FieldCase, MonthStyle, OutputEncoding, QuoteStyle},
+    HttpOptions, RateLimits,
+};
+use serde::Deserialize;
+
+/// The name of the config file read from the current directory.
+pub const CONFIG_FILE: &str = "seb.toml";
+
+/// The settings read from the [`CONFIG_FILE`], every setting is optional.
+///
+/// ```toml
+/// [format]
+/// key = "{author}{year}"
+/// transliterate-keys = true
+/// sidecar = ["abstract"]
+///
+/// [format.bibtex]
+/// indent = 2
+/// field-case = "lower"
+/// quote-style = "quotes"
+/// trailing-comma = false
+/// sort-fields = true
+/// month-style = "macro"
+/// dialect = "bibtex"
+/// output-encoding = "utf8"
+/// annotate = false
+/// urldate-in-note = false
+///
+/// [providers]
+/// doi = ["crossref"]
+///
+/// [backup]
+/// keep = 5
+///
+/// [history]
+/// log = true
+/// user = "alice"
+///
+/// [unpaywall]
+/// email = "me@example.com"
+///
+/// [crossref]
+/// email = "me@example.com"
+///
+/// [normalize]
+/// acronyms = ["CRISPR", "QUIC"]
+///
+/// [dedup]
+/// metric = "jaro-winkler"
+/// threshold = 0.9
+///
+/// [rate-limits]
+/// default = 5.0
+///
+/// [rate-limits.hosts]
+/// "api.crossref.org" = 2.5
+///
+/// [http]
+/// proxy = "http://proxy.example.com:8080"
+///
+/// [http.headers]
+/// "X-Institution" = "campus"
+/// ```
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub format: FormatConfig,
+    /// The names of the providers consulted for each kind of identifier, in order.
+    pub providers: HashMap<String, Vec<String>>,
+    pub backup: BackupConfig,
+    pub history: HistoryConfig,
+    pub unpaywall: UnpaywallConfig,
+    pub crossref: CrossRefConfig,
+    pub normalize: NormalizeConfig,
+    /// How titles are compared when looking for entries that are possibly duplicates.
+    pub dedup: Similarity,
+    /// The requests per second sent to the provider APIs.
+    #[serde(rename = "rate-limits")]
+    pub rate_limits: RateLimits,
+    /// The proxy and extra headers of the requests sent to the provider APIs.
+    pub http: HttpOptions,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    pub bibtex: BibTexConfig,
+    /// The template used to generate cite keys when rekeying the bibliography and for the entries
+    /// read without a cite key.
+    pub key: KeyFormat,
+    /// Whether names and titles in non-Latin scripts are transliterated to ASCII in cite keys.
+    pub transliterate_keys: bool,
+    /// The fields, such as `abstract`, stored in a JSON sidecar file next to the bibliography file
+    /// instead of in it.
+    pub sidecar: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// The number of backups kept for each bibliography file, zero disables the backups.
+    pub keep: usize,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Whether a line is appended to the history file for every change written to the
+    /// bibliography file.
+    pub log: bool,
+    /// The user recorded with each change.
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct UnpaywallConfig {
+    /// The email address sent with every request to the Unpaywall API, as required by its terms.
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CrossRefConfig {
+    /// The contact email address sent with every request to the Crossref API, which takes
+    /// precedence over the `SEB_CONTACT_EMAIL` environment variable.
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NormalizeConfig {
+    /// The acronyms kept as they are written here when titles are converted to sentence case, on
+    /// top of the acronyms that are always kept, such as `DNA`.
+    pub acronyms: Vec<String>,
+}
+
+/// The start of the `@comment` block of a bibliography file that holds the [`FileSettings`].
+pub const FILE_SETTINGS_PREFIX: &str = "seb:";
+
+/// The settings of a single bibliography file, read from a `@comment` block that starts with
+/// [`FILE_SETTINGS_PREFIX`] so that they are shared by everyone who edits the file.
+///
+/// The settings take precedence over the config file and the command line options take precedence
+/// over the settings. Only the settings for how the file is written can be set, with the names
+/// of the `[format]` and `[format.bibtex]` sections of the config file:
+///
+/// ```bibtex
+/// @comment{seb:
+///     key-format = "{author}{year}"
+///     transliterate-keys = false
+///     indent = 2
+///     field-case = "lower"
+///     quote-style = "quotes"
+///     trailing-comma = false
+///     sort-fields = true
+///     month-style = "macro"
+///     dialect = "bibtex"
+///     output-encoding = "utf8"
+///     annotate = false
+///     urldate-in-note = false
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FileSettings {
+    pub key_format: Option<KeyFormat>,
+    pub transliterate_keys: Option<bool>,
+    pub indent: Option<usize>,
+    pub field_case: Option<FieldCase>,
+    pub quote_style: Option<QuoteStyle>,
+    pub trailing_comma: Option<bool>,
+    pub sort_fields: Option<bool>,
+    pub month_style: Option<MonthStyle>,
+    pub dialect: Option<Dialect>,
+    pub output_encoding: Option<OutputEncoding>,
+    pub annotate: Option<bool>,
+    pub urldate_in_note: Option<bool>,
+}
+
+impl FileSettings {
+    /// Reads the settings from the first `@comment` block of the `bibtex` that starts with
+    /// [`FILE_SETTINGS_PREFIX`], or `None` when there is no such block.
+    pub fn read(bibtex: &BibTex) -> eyre::Result<Option<Self>> {
+        bibtex
+            .comments()
+            .iter()
+            .find_map(|comment| comment.trim_start().strip_prefix(FILE_SETTINGS_PREFIX))
+            .map(|settings| {
+                toml::from_str(settings).wrap_err_with(|| {
+                    eyre!("Invalid settings in the @comment{{{FILE_SETTINGS_PREFIX} ...}} block")
+                })
+            })
+            .transpose()
+    }
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            bibtex: BibTexConfig::default(),
+            key: KeyFormat::default(),
+            transliterate_keys: true,
+            sidecar: Vec::new(),
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self { keep: 5 }
+    }
+}
+
+impl Config {
+    /// The key format used to generate cite keys, which transliterates the keys when the config
+    /// says so.
+    pub fn key_format(&self) -> KeyFormat {
+        self.format
+            .key
+            .clone()
+            .transliterate(self.format.transliterate_keys)
+    }
+
+    /// Applies the `settings` of the bibliography file, which take precedence over the config
+    /// file.
+    pub fn apply(&mut self, settings: FileSettings) {
+        let format = &mut self.format;
+        if let Some(key) = settings.key_format {
+            format.key = key;
+        }
+        format.transliterate_keys = settings
+            .transliterate_keys
+            .unwrap_or(format.transliterate_keys);
+
+        let bibtex = &mut format.bibtex;
+        bibtex.indent = settings.indent.unwrap_or(bibtex.indent);
+        bibtex.field_case = settings.field_case.unwrap_or(bibtex.field_case);
+        bibtex.quote_style = settings.quote_style.unwrap_or(bibtex.quote_style);
+        bibtex.trailing_comma = settings.trailing_comma.unwrap_or(bibtex.trailing_comma);
+        bibtex.sort_fields = settings.sort_fields.unwrap_or(bibtex.sort_fields);
+        bibtex.month_style = settings.month_style.unwrap_or(bibtex.month_style);
+        bibtex.dialect = settings.dialect.unwrap_or(bibtex.dialect);
+        bibtex.output_encoding = settings.output_encoding.unwrap_or(bibtex.output_encoding);
+        bibtex.annotate = settings.annotate.unwrap_or(bibtex.annotate);
+        bibtex.urldate_in_note = settings.urldate_in_note.unwrap_or(bibtex.urldate_in_note);
+    }
+
+    /// Reads the config from the file at `path` or the default config if the file doesn't exist.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(config) => config.parse(),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                log::trace!(
+                    "No {} file found - using the default config",
+                    path.display()
+                );
+                Ok(Self::default())
+            }
+            Err(err) => Err(err).wrap_err_with(|| eyre!("Cannot read {}", path.display())),
+        }
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).wrap_err_with(|| eyre!("Invalid {CONFIG_FILE} file"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seb::format::Format;
+
+    use super::*;
+
+    #[test]
+    fn empty_config_is_default() {
+        assert_eq!(Config::default(), "".parse().unwrap());
+    }
+
+    #[test]
+    fn bibtex_format_section() {
+        let config: Config = "
+            [format.bibtex]
+            indent = 2
+            field-case = \"upper\"
+            quote-style = \"quotes\"
+            "
+        .parse()
+        .unwrap();
+
+        let expected = BibTexConfig {
+            indent: 2,
+            field_case: FieldCase::Upper,
+            quote_style: QuoteStyle::Quotes,
+            ..BibTexConfig::default()
+        };
+        assert_eq!(expected, config.format.bibtex);
+    }
+
+    #[test]
+    fn key_format() {
+        let config: Config = "[format]\nkey = \"{author}_{title}\"".parse().unwrap();
+
+        assert_eq!(
+            "{author}_{title}".parse::<KeyFormat>().unwrap(),
+            config.format.key
+        );
+        assert!("[format]\nkey = \"{month}\"".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn transliterate_keys() {
+        assert!(Config::default().format.transliterate_keys);
+
+        let config: Config = "[format]\ntransliterate-keys = false".parse().unwrap();
+        assert!(!config.format.transliterate_keys);
+    }
+
+    #[test]
+    fn sidecar_fields() {
+        assert!(Config::default().format.sidecar.is_empty());
+
+        let config: Config = "[format]\nsidecar = [\"abstract\"]".parse().unwrap();
+        assert_eq!(vec!["abstract".to_owned()], config.format.sidecar);
+    }
+
+    #[test]
+    fn normalize_acronyms() {
+        assert!(Config::default().normalize.acronyms.is_empty());
+
+        let config: Config = "[normalize]\nacronyms = [\"CRISPR\"]".parse().unwrap();
+        assert_eq!(vec!["CRISPR".to_owned()], config.normalize.acronyms);
+    }
+
+    #[test]
+    fn history_section() {
+        assert!(!Config::default().history.log);
+
+        let config: Config = "[history]\nlog = true\nuser = \"alice\"".parse().unwrap();
+        assert!(config.history.log);
+        assert_eq!(Some("alice".to_owned()), config.history.user);
+    }
+
+    #[test]
+    fn provider_order() {
+        let config: Config = "[providers]\ndoi = [\"crossref\", \"other\"]"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            HashMap::from([(
+                "doi".to_owned(),
+                vec!["crossref".to_owned(), "other".to_owned()]
+            )]),
+            config.providers
+        );
+    }
+
+    #[test]
+    fn backups_kept() {
+        assert_eq!(5, Config::default().backup.keep);
+        assert_eq!(
+            0,
+            "[backup]\nkeep = 0".parse::<Config>().unwrap().backup.keep
+        );
+    }
+
+    #[test]
+    fn crossref_email() {
+        assert_eq!(None, Config::default().crossref.email);
+        assert_eq!(
+            Some("me@example.com".to_owned()),
+            "[crossref]\nemail = \"me@example.com\""
+                .parse::<Config>()
+                .unwrap()
+                .crossref
+                .email
+        );
+    }
+
+    #[test]
+    fn dedup_similarity() {
+        use seb::ast::Metric;
+
+        assert_eq!(Similarity::default(), Config::default().dedup);
+        assert_eq!(
+            Similarity {
+                metric: Metric::JaroWinkler,
+                threshold: 0.9,
+            },
+            "[dedup]\nmetric = \"jaro-winkler\"\nthreshold = 0.9"
+                .parse::<Config>()
+                .unwrap()
+                .dedup
+        );
+        assert!("[dedup]\nthreshold = 1.5".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn rate_limits() {
+        assert_eq!(RateLimits::default(), Config::default().rate_limits);
+
+        let config: Config =
+            "[rate-limits]\ndefault = 2.0\n[rate-limits.hosts]\n\"example.com\" = 0.5"
+                .parse()
+                .unwrap();
+        assert!((config.rate_limits.default - 2.0).abs() < f64::EPSILON);
+        assert!((config.rate_limits.per_second("example.com") - 0.5).abs() < f64::EPSILON);
+        assert!("[rate-limits]\ndefault = 0".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn http_options() {
+        assert_eq!(HttpOptions::default(), Config::default().http);
+
+        let config: Config = "[http]\nproxy = \"http://proxy.example.com:8080\"\n\
+            [http.headers]\n\"X-Institution\" = \"campus\""
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Some("http://proxy.example.com:8080"),
+            config.http.proxy.as_deref()
+        );
+        assert_eq!("campus", config.http.headers["X-Institution"]);
+    }
+
+    #[test]
+    fn invalid_value_is_an_error() {
+        assert!("[format.bibtex]\nquote-style = \"single\""
+            .parse::<Config>()
+            .is_err());
+    }
+
+    #[test]
+    fn file_settings_change_the_key_format() {
+        let mut config: Config = "
+            [format]
+            key = \"{author}{year}\"
+
+            [format.bibtex]
+            indent = 2
+            "
+        .parse()
+        .unwrap();
+        let bibtex = BibTex::new(
+            "@comment{jabref-meta: databaseType:bibtex;}\n\
+            @comment{seb: key-format = \"{author}_{title}\"}\n\
+            @manual{cite, title = {Title}}\n"
+                .to_owned(),
+        );
+
+        config.apply(FileSettings::read(&bibtex).unwrap().unwrap());
+
+        assert_eq!(
+            "{author}_{title}".parse::<KeyFormat>().unwrap(),
+            config.format.key
+        );
+        // the settings not in the file are still read from the config file
+        assert_eq!(2, config.format.bibtex.indent);
+    }
+
+    #[test]
+    fn file_settings_are_optional_and_validated() {
+        let bibtex = BibTex::new("@comment{other}\n@manual{cite, title = {Title}}\n".to_owned());
+        assert_eq!(None, FileSettings::read(&bibtex).unwrap());
+
+        let bibtex =
+            BibTex::new("@comment{seb:\n    indent = 2\n    sort-fields = true\n}".to_owned());
+        let expected = FileSettings {
+            indent: Some(2),
+            sort_fields: Some(true),
+            ..FileSettings::default()
+        };
+        assert_eq!(Some(expected), FileSettings::read(&bibtex).unwrap());
+
+        // only the settings for how the file is written can be set
+        let bibtex = BibTex::new("@comment{seb: [backup]\nkeep = 0}".to_owned());
+        assert!(FileSettings::read(&bibtex).is_err());
+        let bibtex = BibTex::new("@comment{seb: key-format = \"{month}\"}".to_owned());
+        assert!(FileSettings::read(&bibtex).is_err());
+    }
+}
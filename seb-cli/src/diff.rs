@@ -0,0 +1,207 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use seb::ast::{normalize_doi, Biblio, Entry, FieldQuery};
+
+use crate::file::FILE_FIELD;
+
+/// The structural differences between two bibliographies.
+///
+/// Entries are matched by the cite key first and then by the DOI, so formatting and the order of
+/// entries or fields are ignored.
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    /// Cite keys of the entries only in the first bibliography.
+    pub removed: Vec<String>,
+    /// Cite keys of the entries only in the second bibliography.
+    pub added: Vec<String>,
+    /// Entries in both bibliographies with different fields.
+    pub changed: Vec<Change>,
+}
+
+/// An entry in both bibliographies with different fields.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub cite: String,
+    /// The cite key in the second bibliography when the entries were matched by DOI.
+    pub other_cite: Option<String>,
+    /// The names of the fields that are different or only in one of the entries.
+    pub fields: Vec<String>,
+}
+
+impl Diff {
+    pub fn new(biblio: &Biblio, other: &Biblio) -> Self {
+        let mut diff = Self::default();
+        let mut matched = HashSet::new();
+
+        // the entries are matched in the order of their cite keys so that the same entries are
+        // matched by DOI every time
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.cite().cmp(b.cite()));
+        for entry in entries {
+            let other_entry = other.get(entry.cite()).or_else(|| {
+                let doi = normalize_doi(entry.get_field("doi")?);
+                // an entry is only matched once, by its cite key or by the DOI of one entry
+                other.entries().find(|e| {
+                    biblio.get(e.cite()).is_none()
+                        && !matched.contains(e.cite())
+                        && e.get_field("doi").is_some_and(|d| normalize_doi(d) == doi)
+                })
+            });
+
+            let Some(other_entry) = other_entry else {
+                diff.removed.push(entry.cite().to_owned());
+                continue;
+            };
+            matched.insert(other_entry.cite());
+
+            let fields = changed_fields(entry, other_entry);
+            if !fields.is_empty() {
+                diff.changed.push(Change {
+                    cite: entry.cite().to_owned(),
+                    other_cite: (entry.cite() != other_entry.cite())
+                        .then(|| other_entry.cite().to_owned()),
+                    fields,
+                });
+            }
+        }
+
+        diff.added = other
+            .entries()
+            .map(Entry::cite)
+            .filter(|cite| !matched.contains(cite))
+            .map(ToOwned::to_owned)
+            .collect();
+
+        diff.removed.sort();
+        diff.added.sort();
+        diff.changed.sort_by(|a, b| a.cite.cmp(&b.cite));
+        diff
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn changed_fields(entry: &Entry, other: &Entry) -> Vec<String> {
+    let values = |entry: &Entry| {
+        entry
+            .fields()
+            .into_iter()
+            // the file an entry was read from is only in memory when reading several files
+            .filter(|field| field.name != FILE_FIELD)
+            .map(|field| (field.name.to_string(), field.value().to_owned()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (fields, other_fields) = (values(entry), values(other));
+
+    let mut changed = Vec::new();
+    if entry.kind() != other.kind() {
+        changed.push("type".to_owned());
+    }
+    changed.extend(
+        fields
+            .keys()
+            .chain(
+                other_fields
+                    .keys()
+                    .filter(|name| !fields.contains_key(*name)),
+            )
+            .filter(|name| fields.get(*name) != other_fields.get(*name))
+            .cloned(),
+    );
+    changed.sort();
+    changed
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No differences");
+        }
+
+        let mut lines = Vec::new();
+        lines.extend(self.removed.iter().map(|cite| format!("- {cite}")));
+        lines.extend(self.added.iter().map(|cite| format!("+ {cite}")));
+        lines.extend(self.changed.iter().map(|change| {
+            let cite = match &change.other_cite {
+                Some(other) => format!("{} -> {other} (matched by doi)", change.cite),
+                None => change.cite.clone(),
+            };
+            format!("~ {cite}: {}", change.fields.join(", "))
+        }));
+
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::manual;
+
+    #[test]
+    fn entries_only_in_one_bibliography() {
+        let biblio = Biblio::new(vec![manual("both", &[]), manual("removed", &[])]);
+        let other = Biblio::new(vec![manual("both", &[]), manual("added", &[])]);
+
+        let diff = Diff::new(&biblio, &other);
+
+        assert_eq!(vec!["removed"], diff.removed);
+        assert_eq!(vec!["added"], diff.added);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn changed_fields_are_reported() {
+        let biblio = Biblio::new(vec![manual("cite", &[("year", "2020"), ("note", "a")])]);
+        let other = Biblio::new(vec![manual("cite", &[("year", "2021"), ("url", "b")])]);
+
+        let diff = Diff::new(&biblio, &other);
+
+        assert_eq!("~ cite: note, url, year", diff.to_string());
+    }
+
+    #[test]
+    fn entries_matched_by_doi_when_cite_differs() {
+        let biblio = Biblio::new(vec![manual("old", &[("doi", "10.1000/182")])]);
+        let other = Biblio::new(vec![manual(
+            "new",
+            &[("doi", "https://doi.org/10.1000/182"), ("year", "2020")],
+        )]);
+
+        let diff = Diff::new(&biblio, &other);
+
+        assert_eq!("~ old -> new (matched by doi): doi, year", diff.to_string());
+    }
+
+    #[test]
+    fn entry_is_matched_by_doi_once() {
+        let biblio = Biblio::new(vec![
+            manual("first", &[("doi", "10.1000/182")]),
+            manual("second", &[("doi", "10.1000/182")]),
+        ]);
+        let other = Biblio::new(vec![manual(
+            "new",
+            &[("doi", "10.1000/182"), ("year", "2020")],
+        )]);
+
+        let diff = Diff::new(&biblio, &other);
+
+        assert_eq!(
+            "- second\n~ first -> new (matched by doi): year",
+            diff.to_string()
+        );
+    }
+
+    #[test]
+    fn identical_bibliographies_have_no_differences() {
+        let biblio = Biblio::new(vec![manual("cite", &[("year", "2020")])]);
+        let other = Biblio::new(vec![manual("cite", &[("year", "2020")])]);
+
+        assert_eq!("No differences", Diff::new(&biblio, &other).to_string());
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDate};
+use eyre::{eyre, Context};
+use log::warn;
+
+use crate::{
+    html::{self, unescape},
+    pdf::find_dois,
+};
+
+/// The provenance field that records the URL of the feed an entry was added from.
+pub const FEED_FIELD: &str = "x-seb-feed";
+
+/// The file, next to the bibliography file, that records the time each feed was last fetched as a
+/// JSON object keyed by the URL of the feed.
+pub const FEEDS_FILE: &str = ".seb-feeds";
+
+/// An article in an RSS or Atom feed, such as the table of contents of a journal.
+#[derive(Debug, Default, PartialEq)]
+pub struct FeedItem {
+    /// The title of the article.
+    pub title: Option<String>,
+    /// The DOI of the article, if one was found.
+    pub doi: Option<String>,
+    /// The date the article was published or last updated.
+    pub date: Option<NaiveDate>,
+}
+
+/// Returns the `<item>` elements of an RSS feed or the `<entry>` elements of an Atom feed.
+///
+/// An item that is not closed, or that has neither a DOI nor a title, is skipped with a warning.
+pub fn items(feed: &str) -> Vec<FeedItem> {
+    let mut items = Vec::new();
+    let mut rest = feed;
+    let mut position = 0;
+
+    while let Some((tag, start)) = ["item", "entry"]
+        .into_iter()
+        .filter_map(|tag| Some((tag, open_tag(rest, tag)?)))
+        .min_by_key(|(_, start)| *start)
+    {
+        position += 1;
+        rest = &rest[start..];
+        let close = format!("</{tag}>");
+        let Some(end) = rest.find(&close) else {
+            warn!("Item {position} of the feed is not closed - skipping it");
+            break;
+        };
+
+        let body = &rest[..end];
+        rest = &rest[end + close.len()..];
+        if let Some(item) = item(body) {
+            items.push(item);
+        } else {
+            warn!("Item {position} of the feed has no DOI or title - skipping it");
+        }
+    }
+    items
+}
+
+/// Returns the date the feed at the `url` was last fetched for the bibliography file at `path`,
+/// see [`FEEDS_FILE`], or `None` when it has not been fetched.
+pub fn last_fetched(path: &Path, url: &str) -> eyre::Result<Option<NaiveDate>> {
+    Ok(read_fetched(path)?
+        .get(url)
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|time| time.date_naive()))
+}
+
+/// Records that the feed at the `url` was fetched now for the bibliography file at `path`, see
+/// [`FEEDS_FILE`].
+pub fn record_fetch(path: &Path, url: &str) -> eyre::Result<()> {
+    let mut fetched = read_fetched(path)?;
+    fetched.insert(url.to_owned(), chrono::Local::now().to_rfc3339());
+    let feeds = feeds_path(path);
+    let json = serde_json::to_string_pretty(&fetched).wrap_err("Cannot write the feed times")?;
+    fs::write(&feeds, json).wrap_err_with(|| eyre!("Cannot write {}", feeds.display()))
+}
+
+fn read_fetched(path: &Path) -> eyre::Result<BTreeMap<String, String>> {
+    let feeds = feeds_path(path);
+    match fs::read_to_string(&feeds) {
+        Ok(json) => serde_json::from_str(&json)
+            .wrap_err_with(|| eyre!("{} is not a record of the feeds fetched", feeds.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).wrap_err_with(|| eyre!("Cannot read {}", feeds.display())),
+    }
+}
+
+fn feeds_path(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(FEEDS_FILE)
+}
+
+fn item(body: &str) -> Option<FeedItem> {
+    // the DOI of the article itself, the description may cite the DOIs of other articles
+    let doi = ["prism:doi", "dc:identifier", "link", "guid", "id"]
+        .into_iter()
+        .filter_map(|name| element(body, name).or_else(|| attribute(body, name, "href")))
+        .find_map(|text| find_dois(&text).into_iter().next());
+    let title = element(body, "title")
+        .map(|title| text(&title))
+        .filter(|title| !title.is_empty());
+    let date = [
+        "updated",
+        "published",
+        "pubDate",
+        "dc:date",
+        "prism:publicationDate",
+    ]
+    .into_iter()
+    .filter_map(|name| element(body, name))
+    .find_map(|date| parse_date(date.trim()));
+
+    (doi.is_some() || title.is_some()).then_some(FeedItem { title, doi, date })
+}
+
+// the position of the opening tag of the element `name`, which is not the start of another element
+// with a longer name, such as `<items>`
+fn open_tag(xml: &str, name: &str) -> Option<usize> {
+    let tag = format!("<{name}");
+    xml.match_indices(&tag).map(|(i, _)| i).find(|i| {
+        xml[i + tag.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c == '>' || c == '/' || c.is_whitespace())
+    })
+}
+
+// the content of the first element `name` in the `xml`
+fn element(xml: &str, name: &str) -> Option<String> {
+    let start = open_tag(xml, name)?;
+    let rest = &xml[start..];
+    let open_end = rest.find('>')?;
+    if rest[..open_end].ends_with('/') {
+        // an empty element, such as an Atom `<link href="..."/>`
+        return None;
+    }
+    let content = &rest[open_end + 1..];
+    let end = content.find(&format!("</{name}>"))?;
+    Some(content[..end].to_owned())
+}
+
+// the value of the `attribute` of the first element `name` in the `xml`, such as the `href` of an
+// Atom `<link href="..."/>`
+fn attribute(xml: &str, name: &str, attribute: &str) -> Option<String> {
+    let start = open_tag(xml, name)?;
+    let tag = &xml[start..start + xml[start..].find('>')?];
+    let (_, value) = tag.split_once(&format!(" {attribute}="))?;
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(unescape(&value[..value.find(quote)?]))
+}
+
+// the text of an element, without the CDATA markers, HTML tags or entities
+fn text(content: &str) -> String {
+    let content = content.trim();
+    let content = content
+        .strip_prefix("<![CDATA[")
+        .and_then(|content| content.strip_suffix("]]>"))
+        .map_or_else(|| unescape(content), ToOwned::to_owned);
+
+    // the HTML tags of a title, such as `<i>`, are escaped in the XML
+    html::text(&content)
+}
+
+// RSS dates are RFC 2822 dates and Atom dates are RFC 3339 dates, some feeds only have the date
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .map(|date| date.date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn rss_and_atom_items() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:prism="http://prismstandard.org/namespaces/basic/2.0/">
+            <channel>
+                <title>Journal of Algorithms</title>
+                <items>ignored</items>
+                <item>
+                    <title><![CDATA[QuickXsort: A Fast Sorting Scheme]]></title>
+                    <link>https://example.com/article/1</link>
+                    <description>Cites 10.1000/cited</description>
+                    <prism:doi>10.1007/s00453-019-00634-0</prism:doi>
+                    <pubDate>Tue, 10 Sep 2019 04:00:00 GMT</pubDate>
+                </item>
+                <item>
+                    <title>Cited Elsewhere</title>
+                    <description>Cites 10.1000/cited</description>
+                    <guid>https://doi.org/10.1000/guid</guid>
+                </item>
+                <item><description>Cites 10.1000/cited</description></item>
+                <item>
+                    <title>Sorting &lt;i&gt;in place&lt;/i&gt;</title>
+                    <pubDate>not a date</pubDate>
+                </item>
+                <item><title>Not closed</title>
+            </channel>
+            </rss>"#;
+
+        assert_eq!(
+            vec![
+                FeedItem {
+                    title: Some("QuickXsort: A Fast Sorting Scheme".to_owned()),
+                    doi: Some("10.1007/s00453-019-00634-0".to_owned()),
+                    date: NaiveDate::from_ymd_opt(2019, 9, 10),
+                },
+                FeedItem {
+                    title: Some("Cited Elsewhere".to_owned()),
+                    doi: Some("10.1000/guid".to_owned()),
+                    date: None,
+                },
+                FeedItem {
+                    title: Some("Sorting in place".to_owned()),
+                    doi: None,
+                    date: None,
+                },
+            ],
+            items(rss)
+        );
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+            <entry>
+                <title type="html">Algorithm Engineering</title>
+                <link href="https://doi.org/10.1007/978-3-319-49487-6_1"/>
+                <updated>2019-01-02T10:00:00Z</updated>
+            </entry>
+        </feed>"#;
+
+        assert_eq!(
+            vec![FeedItem {
+                title: Some("Algorithm Engineering".to_owned()),
+                doi: Some("10.1007/978-3-319-49487-6_1".to_owned()),
+                date: NaiveDate::from_ymd_opt(2019, 1, 2),
+            }],
+            items(atom)
+        );
+    }
+    #[test]
+    fn fetches_are_recorded_next_to_the_bibliography() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let path = dir.path().join("refs.bib");
+        let url = "https://example.com/toc.rss";
+        assert_eq!(None, last_fetched(&path, url).unwrap());
+
+        record_fetch(&path, url).unwrap();
+        record_fetch(&path, "https://example.com/other.rss").unwrap();
+
+        let today = chrono::Local::now().date_naive();
+        assert_eq!(Some(today), last_fetched(&path, url).unwrap());
+        assert!(dir.path().join(FEEDS_FILE).exists());
+    }
+}
@@ -1,6 +1,80 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use seb::{file::FormatFile, format::Format};
+use seb::{
+    ast::{Biblio, FieldQuery, KeyFormat},
+    file::{FormatFile, Sidecar},
+    format::{BibTex, Format, InputEncoding, Reader},
+};
+
+/// The field that records the file an entry was read from when more than one file is used.
+///
+/// The field is only set in memory as the files are never written when more than one is used.
+pub const FILE_FIELD: &str = "x-seb-file";
+
+/// Returns `true` when the `path` of a `--file` is an http(s) URL, such as the raw URL of a
+/// bibliography shared on GitHub, which is downloaded and can only be read.
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.starts_with("http://") || path.starts_with("https://"))
+}
+
+/// Returns the `files` followed by the files matching each of the glob `patterns`, such as
+/// `refs/**/*.bib`, in the order of their paths. A file is only included once.
+///
+/// # Errors
+///
+/// An `Err` is returned when a pattern is invalid or doesn't match any file.
+pub fn with_globs(mut files: Vec<PathBuf>, patterns: &[String]) -> eyre::Result<Vec<PathBuf>> {
+    for pattern in patterns {
+        let mut matches = glob::glob(pattern)
+            .map_err(|err| eyre::eyre!("The glob '{pattern}' is invalid: {err}"))?
+            .filter_map(|path| {
+                path.map_err(|err| log::warn!("Cannot read a match of the glob '{pattern}': {err}"))
+                    .ok()
+            })
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            return Err(eyre::eyre!("The glob '{pattern}' doesn't match any file"));
+        }
+        matches.sort();
+        for path in matches {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Returns the file changed by a command that writes the bibliography, which is the `into` file
+/// or the only file given.
+///
+/// # Errors
+///
+/// An `Err` is returned when more than one file is given without `into`, or when the file is a
+/// URL as a URL cannot be written.
+pub fn writable(files: Vec<PathBuf>, into: Option<PathBuf>) -> eyre::Result<Option<PathBuf>> {
+    let file = match (into, files.len()) {
+        (Some(into), _) => Some(into),
+        (None, 0 | 1) => files.into_iter().next(),
+        (None, _) => {
+            return Err(eyre::eyre!(
+                "More than one file was given, by --file or --glob, but this command changes a \
+                single file, use --into to choose the file"
+            ))
+        }
+    };
+
+    match file {
+        Some(url) if is_url(&url) => Err(eyre::eyre!(
+            "The file '{}' is a URL, which can only be read, use --into to choose a local file to \
+            change",
+            url.display()
+        )),
+        file => Ok(file),
+    }
+}
 
 #[allow(clippy::module_name_repetitions)]
 pub fn open_or_create_format_file<F: Format>(
@@ -18,8 +92,217 @@ pub fn open_or_create_format_file<F: Format>(
         })?
     } else {
         log::trace!("Searching current directory for any {} files", F::name());
-        FormatFile::find(".")?
+        FormatFile::find(".")
+            .map_err(|err| err.with_hint("Use `--file` to choose the bibliography file"))?
     };
 
     Ok(file)
 }
+
+/// The hint for a bibliography file that cannot be parsed.
+pub const PARSE_HINT: &str = "Run `seb check` to find the entries that are not valid BibTeX";
+
+/// Reads the entries of every file into a single [`Biblio`] and records the file that each entry
+/// was read from in the [`FILE_FIELD`].
+///
+/// Only the commands that read the bibliography use more than one file so the entries missing
+/// required fields are kept with the missing fields left empty. When more than one file has an
+/// entry with the same cite key the entry in the last file is used. An entry without a cite key is
+/// given one generated with the `key_format`. Every file is read in the `encoding`, or the detected
+/// encoding of each file when it is `None`.
+///
+/// A path that is a URL, see [`is_url`], is downloaded instead of read. Each raw file is given to
+/// `read_raw` before it is parsed.
+pub fn read_union<R>(
+    paths: &[PathBuf],
+    key_format: &KeyFormat,
+    encoding: Option<InputEncoding>,
+    read_raw: R,
+) -> Result<Biblio, Box<dyn std::error::Error>>
+where
+    R: FnMut(&BibTex),
+{
+    read_union_with(paths, key_format, encoding, read_raw, seb::text_by_url)
+}
+
+// `read_union` with the function used to download the URLs
+fn read_union_with<R, D>(
+    paths: &[PathBuf],
+    key_format: &KeyFormat,
+    encoding: Option<InputEncoding>,
+    mut read_raw: R,
+    download: D,
+) -> Result<Biblio, Box<dyn std::error::Error>>
+where
+    R: FnMut(&BibTex),
+    D: Fn(&str) -> Result<String, seb::Error>,
+{
+    let mut union = Biblio::default();
+
+    for path in paths {
+        let bibtex = if is_url(path) {
+            let url = path.display().to_string();
+            log::trace!("downloading {url} as a {} file", BibTex::name());
+            BibTex::new(download(&url)?)
+        } else {
+            log::trace!("reading {} as a {} file", path.display(), BibTex::name());
+            FormatFile::<BibTex>::open(path)?
+                .with_encoding(encoding)
+                .read()?
+        };
+        read_raw(&bibtex);
+        let biblio = bibtex
+            .parse_with(key_format)
+            .map_err(|err| err.with_hint(PARSE_HINT))?;
+        let mut biblio = biblio.unwrap_or_else(|resolver| {
+            log::warn!(
+                "Some entries in {} are missing required fields - the missing fields are left empty",
+                path.display()
+            );
+            resolver.into_partial()
+        });
+        if !is_url(path) {
+            Sidecar::new(path, Vec::new()).load(&mut biblio)?;
+        }
+
+        let file = path.display().to_string();
+        for mut entry in biblio.into_entries() {
+            if let Some(other) = union
+                .get(entry.cite())
+                .and_then(|e| e.get_field(FILE_FIELD))
+            {
+                log::warn!(
+                    "The cite key '{}' is used in {} and {file} - using the entry in {file}",
+                    entry.cite(),
+                    &**other
+                );
+            }
+            entry.set_field(FILE_FIELD, file.as_str());
+            union.insert(entry);
+        }
+    }
+
+    Ok(union)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{
+        fixture::{FileWriteStr, PathChild, PathCreateDir},
+        TempDir,
+    };
+
+    use super::*;
+    use crate::filter::Filter;
+
+    const URL: &str = "https://raw.githubusercontent.com/example/refs/main/refs.bib";
+
+    #[test]
+    fn glob_matches_files_in_subdirectories() {
+        let dir = TempDir::new().unwrap();
+        dir.child("refs/sorting").create_dir_all().unwrap();
+        dir.child("refs/rust.bib")
+            .write_str("@manual{a, title = {Rust in Action}}\n")
+            .unwrap();
+        dir.child("refs/sorting/sorting.bib")
+            .write_str("@manual{b, title = {QuickXsort}}\n")
+            .unwrap();
+        dir.child("refs/notes.txt")
+            .write_str("Not a bibliography")
+            .unwrap();
+
+        let pattern = format!("{}/refs/**/*.bib", dir.path().display());
+        let files = with_globs(Vec::new(), &[pattern.clone(), pattern]).unwrap();
+        assert_eq!(2, files.len());
+
+        let biblio = read_union(&files, &KeyFormat::default(), None, |_| ()).unwrap();
+        let file = |cite| {
+            let file = PathBuf::from(&**biblio.get(cite).unwrap().get_field(FILE_FIELD).unwrap());
+            file.file_name().unwrap().to_owned()
+        };
+        assert_eq!("rust.bib", file("a"));
+        assert_eq!("sorting.bib", file("b"));
+
+        let missing = format!("{}/missing/*.bib", dir.path().display());
+        assert!(with_globs(Vec::new(), &[missing]).is_err());
+    }
+
+    #[test]
+    fn url_source_is_downloaded() {
+        let download = |url: &str| {
+            assert_eq!(URL, url);
+            Ok("@manual{a, title = {Rust in Action}}\n@misc{b, title = {Other}}\n".to_owned())
+        };
+        let biblio = read_union_with(
+            &[PathBuf::from(URL)],
+            &KeyFormat::default(),
+            None,
+            |_| (),
+            download,
+        )
+        .unwrap();
+
+        let mut cites = biblio
+            .entries()
+            .map(seb::ast::Entry::cite)
+            .collect::<Vec<_>>();
+        cites.sort_unstable();
+        assert_eq!(vec!["a", "b"], cites);
+        assert_eq!(
+            URL,
+            &**biblio.get("a").unwrap().get_field(FILE_FIELD).unwrap()
+        );
+    }
+
+    #[test]
+    fn url_source_cannot_be_changed() {
+        // commands that change the file, such as `rm`, need the file to write
+        let err = writable(vec![PathBuf::from(URL)], None).unwrap_err();
+        assert!(err.to_string().contains("can only be read"));
+
+        let into = PathBuf::from("local.bib");
+        assert_eq!(
+            Some(into.clone()),
+            writable(vec![PathBuf::from(URL)], Some(into)).unwrap()
+        );
+        assert!(writable(vec![PathBuf::from("refs.bib")], None).is_ok());
+        assert!(!is_url(Path::new("http.bib")));
+    }
+
+    #[test]
+    fn search_across_two_files() {
+        let dir = TempDir::new().unwrap();
+        let first = dir.child("first.bib");
+        first
+            .write_str("@manual{a, title = {Rust in Action}}\n@manual{b, title = {Other}}\n")
+            .unwrap();
+        let second = dir.child("second.bib");
+        second
+            .write_str("@manual{c, title = {Programming Rust}}\n")
+            .unwrap();
+
+        let biblio = read_union(
+            &[first.to_path_buf(), second.to_path_buf()],
+            &KeyFormat::default(),
+            None,
+            |_| (),
+        )
+        .unwrap();
+        let filter: Filter = "title contains rust".parse().unwrap();
+
+        let mut found = biblio
+            .entries()
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| {
+                let file = PathBuf::from(&**entry.get_field(FILE_FIELD).unwrap());
+                (entry.cite(), file.file_name().unwrap().to_owned())
+            })
+            .collect::<Vec<_>>();
+        found.sort();
+
+        assert_eq!(
+            vec![("a", "first.bib".into()), ("c", "second.bib".into())],
+            found
+        );
+    }
+}
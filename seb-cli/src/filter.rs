@@ -0,0 +1,355 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use clap::Args;
+use seb::ast::{Entry, FieldQuery};
+
+/// Limits the entries to those published in a range of years, both years are inclusive.
+#[derive(Args, Debug, Default)]
+pub struct YearRange {
+    /// Only include the entries published in or after this year.
+    #[clap(long, value_name = "YEAR")]
+    since: Option<i32>,
+
+    /// Only include the entries published in or before this year.
+    #[clap(long, value_name = "YEAR")]
+    until: Option<i32>,
+
+    /// Include the entries without a year when using --since or --until.
+    #[clap(long)]
+    include_undated: bool,
+}
+
+impl YearRange {
+    /// Returns `true` when the entry was published in this range of years.
+    ///
+    /// Every entry is included when neither `since` or `until` are set.
+    pub fn includes(&self, entry: &Entry) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        entry.year().map_or(self.include_undated, |year| {
+            self.since.is_none_or(|since| year >= since)
+                && self.until.is_none_or(|until| year <= until)
+        })
+    }
+}
+
+/// A filter expression used to select entries in a bibliography.
+///
+/// The expression is made up of field comparisons which can be combined using `and`/`or`, where
+/// `and` binds tighter than `or`:
+///
+/// ```text
+/// year>=2020 and type=article or author contains "Knuth"
+/// ```
+///
+/// The following comparison operators are supported: `=`, `!=`, `>=`, `<=` and `contains`.
+///
+/// The `type` field compares against the entry kind and the `cite` field compares against the
+/// citation key, the `author` field compares against the `editor` field when an entry has no
+/// authors, all other field names are compared to the entry field with the same name.
+#[derive(Debug, PartialEq)]
+pub enum Filter {
+    Compare {
+        field: String,
+        op: Op,
+        value: String,
+    },
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Eq,
+    NotEq,
+    GreaterEq,
+    LessEq,
+    Contains,
+}
+
+impl Filter {
+    /// Returns `true` when the entry satisfies this filter expression.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Filter::Compare { field, op, value } => match field_value(entry, field) {
+                Some(actual) => compare(&actual, *op, value),
+                // a missing field can only ever be not equal to a value
+                None => *op == Op::NotEq,
+            },
+            Filter::And(lhs, rhs) => lhs.matches(entry) && rhs.matches(entry),
+            Filter::Or(lhs, rhs) => lhs.matches(entry) || rhs.matches(entry),
+        }
+    }
+}
+
+fn field_value(entry: &Entry, field: &str) -> Option<String> {
+    match field {
+        // remove separators so that "in proceedings", "in_proceedings" and "inproceedings" match
+        "type" | "kind" => Some(normalize_kind(&entry.kind().to_string())),
+        "cite" | "key" => Some(entry.cite().to_owned()),
+        // an edited volume has no authors so the editors are compared instead
+        "author" => entry
+            .get_field("author")
+            .or_else(|| entry.get_field("editor"))
+            .map(|qs| qs.to_string()),
+        name => entry.get_field(name).map(|qs| qs.to_string()),
+    }
+}
+
+fn normalize_kind(kind: &str) -> String {
+    kind.chars()
+        .filter(|c| !matches!(c, ' ' | '_'))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn compare(actual: &str, op: Op, expected: &str) -> bool {
+    let actual = actual.to_lowercase();
+    let expected = expected.to_lowercase();
+
+    // numeric values, like the year, should be compared as numbers and not lexicographically
+    let ordering = match (actual.trim().parse::<i64>(), expected.trim().parse::<i64>()) {
+        (Ok(actual), Ok(expected)) => actual.cmp(&expected),
+        _ => actual.cmp(&expected),
+    };
+
+    match op {
+        Op::Eq => ordering == Ordering::Equal,
+        Op::NotEq => ordering != Ordering::Equal,
+        Op::GreaterEq => ordering != Ordering::Less,
+        Op::LessEq => ordering != Ordering::Greater,
+        Op::Contains => actual.contains(&expected),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(format!("Unterminated quote in '{s}'")),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            '=' => tokens.push(Token::Op(Op::Eq)),
+            '!' | '>' | '<' => {
+                if chars.next_if_eq(&'=').is_none() {
+                    return Err(format!("Expected '=' after '{c}' in '{s}'"));
+                }
+                tokens.push(Token::Op(match c {
+                    '!' => Op::NotEq,
+                    '>' => Op::GreaterEq,
+                    _ => Op::LessEq,
+                }));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars
+                    .next_if(|c| !c.is_whitespace() && !matches!(c, '"' | '=' | '!' | '>' | '<'))
+                {
+                    word.push(c);
+                }
+
+                if word.eq_ignore_ascii_case("contains") {
+                    tokens.push(Token::Op(Op::Contains));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn next_keyword(&mut self, keyword: &str) -> bool {
+        self.tokens
+            .next_if(|t| matches!(t, Token::Word(w) if w.eq_ignore_ascii_case(keyword)))
+            .is_some()
+    }
+
+    fn or_expr(&mut self) -> Result<Filter, String> {
+        let mut lhs = self.and_expr()?;
+        while self.next_keyword("or") {
+            lhs = Filter::Or(Box::new(lhs), Box::new(self.and_expr()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Filter, String> {
+        let mut lhs = self.comparison()?;
+        while self.next_keyword("and") {
+            lhs = Filter::And(Box::new(lhs), Box::new(self.comparison()?));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Filter, String> {
+        let field = match self.tokens.next() {
+            Some(Token::Word(field)) => field.to_lowercase(),
+            Some(token) => return Err(format!("Expected a field name but found {token:?}")),
+            None => return Err("Expected a field name but the expression ended".to_owned()),
+        };
+
+        let Some(Token::Op(op)) = self.tokens.next() else {
+            return Err(format!("Expected a comparison operator after '{field}'"));
+        };
+
+        let Some(Token::Word(value) | Token::Quoted(value)) = self.tokens.next() else {
+            return Err(format!("Expected a value to compare with '{field}'"));
+        };
+
+        Ok(Filter::Compare { field, op, value })
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+        };
+
+        let filter = parser.or_expr()?;
+
+        if let Some(token) = parser.tokens.next() {
+            Err(format!("Unexpected {token:?} in '{s}'"))
+        } else {
+            Ok(filter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use seb::ast::{Article, Entry};
+
+    use super::*;
+    use crate::fixture;
+
+    fn article(cite: &str, year: &str) -> Entry {
+        Entry::Article(Article {
+            cite: cite.to_owned(),
+            author: "Donald Knuth".into(),
+            title: "An article".into(),
+            journal: "Journal".into(),
+            year: year.into(),
+            optional: HashMap::default(),
+        })
+    }
+
+    fn manual(cite: &str) -> Entry {
+        fixture::manual(cite, &[("title", "A manual"), ("year", "2021")])
+    }
+
+    #[test]
+    fn numeric_year_comparison_is_not_lexicographic() {
+        let filter: Filter = "year>=2020".parse().unwrap();
+
+        assert!(filter.matches(&article("new", "2021")));
+        assert!(filter.matches(&article("boundary", "2020")));
+        assert!(!filter.matches(&article("old", "999")));
+    }
+
+    #[test]
+    fn compound_and_expression() {
+        let filter: Filter = "year>=2020 and type=article".parse().unwrap();
+
+        assert!(filter.matches(&article("new", "2021")));
+        assert!(!filter.matches(&article("old", "2019")));
+        assert!(!filter.matches(&manual("manual")));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter: Filter = "type = manual or year <= 2000 and author contains \"knuth\""
+            .parse()
+            .unwrap();
+
+        assert!(filter.matches(&manual("manual")));
+        assert!(filter.matches(&article("old", "1968")));
+        assert!(!filter.matches(&article("new", "2021")));
+    }
+
+    #[test]
+    fn author_falls_back_to_editor() {
+        let filter: Filter = "author contains lamport".parse().unwrap();
+        let mut edited = manual("edited");
+        edited.set_field("editor", "Leslie Lamport");
+
+        assert!(filter.matches(&edited));
+        edited.set_field("author", "Donald Knuth");
+        assert!(!filter.matches(&edited));
+    }
+
+    #[test]
+    fn missing_field_only_matches_not_equal() {
+        let eq: Filter = "doi=10.1000/182".parse().unwrap();
+        let not_eq: Filter = "doi!=10.1000/182".parse().unwrap();
+
+        assert!(!eq.matches(&manual("manual")));
+        assert!(not_eq.matches(&manual("manual")));
+    }
+
+    #[test]
+    fn year_range_boundaries_are_inclusive() {
+        let range = YearRange {
+            since: Some(2020),
+            until: Some(2021),
+            include_undated: false,
+        };
+
+        assert!(!range.includes(&article("before", "2019")));
+        assert!(range.includes(&article("since", "2020")));
+        assert!(range.includes(&article("until", "2021")));
+        assert!(!range.includes(&article("after", "2022")));
+    }
+
+    #[test]
+    fn year_range_undated_entries() {
+        let undated = fixture::manual("undated", &[("title", "A manual")]);
+        let since = |include_undated| YearRange {
+            since: Some(2020),
+            until: None,
+            include_undated,
+        };
+
+        assert!(YearRange::default().includes(&undated));
+        assert!(!since(false).includes(&undated));
+        assert!(since(true).includes(&undated));
+        assert!(since(false).includes(&manual("manual")));
+    }
+
+    #[test]
+    fn invalid_expressions_are_errors() {
+        assert!("year".parse::<Filter>().is_err());
+        assert!("year >".parse::<Filter>().is_err());
+        assert!("year = 2020 and".parse::<Filter>().is_err());
+        assert!("title = \"unterminated".parse::<Filter>().is_err());
+    }
+}
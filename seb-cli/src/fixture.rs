@@ -0,0 +1,21 @@
+//! The entries shared by the tests of the commands.
+
+use seb::ast::{Entry, Manual};
+
+/// Returns a manual with the `cite` key and the `optional` fields, where a `title` field is used
+/// as the title of the manual instead of "Title".
+pub fn manual(cite: &str, optional: &[(&str, &str)]) -> Entry {
+    let title = optional
+        .iter()
+        .find(|(name, _)| *name == "title")
+        .map_or("Title", |(_, title)| title);
+    Entry::Manual(Manual {
+        cite: cite.to_owned(),
+        title: title.into(),
+        optional: optional
+            .iter()
+            .filter(|(name, _)| *name != "title")
+            .map(|(name, value)| ((*name).to_owned(), (*value).into()))
+            .collect(),
+    })
+}
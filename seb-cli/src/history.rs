@@ -0,0 +1,223 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use eyre::{eyre, Context};
+use log::warn;
+use seb::{ast::Biblio, format::InputEncoding};
+use serde::{Deserialize, Serialize};
+
+use crate::{backup, diff::Diff};
+
+/// The file, next to the bibliography file, that a line is appended to for every change written
+/// to the bibliography file.
+pub const HISTORY_FILE: &str = ".seb-history";
+
+/// A change written to a bibliography file by a single command, which is a JSON line of the
+/// history file.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Record {
+    /// The time the change was written, in RFC 3339.
+    pub time: String,
+    /// The user that made the change, when one is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// The arguments of the command that made the change, such as `add doi 10.1000/182`.
+    pub command: String,
+    /// The name of the bibliography file that was changed.
+    pub file: String,
+    /// Cite keys of the entries that were added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Cite keys of the entries that were removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// Cite keys of the entries with changed fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<String>,
+}
+
+impl Record {
+    /// Creates the record of the `command` that changed the `file` from `before` to `after`.
+    pub fn new(
+        command: String,
+        file: &Path,
+        before: &Biblio,
+        after: &Biblio,
+        user: Option<String>,
+    ) -> Self {
+        let diff = Diff::new(before, after);
+        Self {
+            time: chrono::Local::now().to_rfc3339(),
+            user,
+            command,
+            file: file_name(file),
+            added: diff.added,
+            removed: diff.removed,
+            changed: diff.changed.into_iter().map(|change| change.cite).collect(),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.time)?;
+        if let Some(user) = &self.user {
+            write!(f, " {user}")?;
+        }
+        write!(f, ": seb {}", self.command)?;
+
+        let changes = [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ]
+        .into_iter()
+        .filter(|(_, cites)| !cites.is_empty())
+        .map(|(kind, cites)| format!("{kind} {}", cites.join(", ")))
+        .collect::<Vec<_>>();
+        if changes.is_empty() {
+            f.write_str(" - no entries changed")
+        } else {
+            write!(f, " - {}", changes.join("; "))
+        }
+    }
+}
+
+/// Returns the arguments the command was run with, which are recorded in the history.
+pub fn command_line() -> String {
+    std::env::args().skip(1).collect::<Vec<_>>().join(" ")
+}
+
+/// Reads the bibliography file at `path` in the `encoding` before it is changed, so that the
+/// entries changed can be recorded in the history.
+///
+/// A file that cannot be read is only a warning that the change is not recorded, as the change
+/// itself can still be made.
+pub fn before(path: &Path, encoding: Option<InputEncoding>) -> Option<Biblio> {
+    backup::read(path, encoding)
+        .map_err(|err| warn!("{err:#} - the change is not recorded in the history"))
+        .ok()
+}
+
+/// Appends the record of the command that changed the bibliography file at `path` from `before`
+/// to `after`, see [`Record::new`], to the history file.
+pub fn record(
+    path: &Path,
+    before: &Biblio,
+    after: &Biblio,
+    user: Option<String>,
+) -> eyre::Result<()> {
+    append(
+        path,
+        &Record::new(command_line(), path, before, after, user),
+    )
+}
+
+/// Appends the `record` as a line of the history file next to the bibliography file at `path`.
+pub fn append(path: &Path, record: &Record) -> eyre::Result<()> {
+    let history = history_path(path);
+    let line = serde_json::to_string(record).wrap_err("Cannot write the history record")?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history)
+        .and_then(|mut file| writeln!(file, "{line}"))
+        .wrap_err_with(|| eyre!("Cannot append to {}", history.display()))
+}
+
+/// Returns the history of the bibliography file at `path`, oldest first, with a line for each
+/// change written to it.
+///
+/// Lines of the history file that cannot be read are skipped with a warning.
+pub fn history(path: &Path) -> eyre::Result<String> {
+    let history = history_path(path);
+    let content = match fs::read_to_string(&history) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Ok(format!("No history of {} to show", path.display()))
+        }
+        Err(err) => return Err(err).wrap_err_with(|| eyre!("Cannot read {}", history.display())),
+    };
+
+    let file = file_name(path);
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_str::<Record>(line) {
+            Ok(record) => Some(record),
+            Err(err) => {
+                warn!(
+                    "Line {} of {} is not a history record: {err}",
+                    i + 1,
+                    history.display()
+                );
+                None
+            }
+        })
+        .filter(|record| record.file == file)
+        .map(|record| record.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn history_path(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(HISTORY_FILE)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{fixture::PathChild, TempDir};
+    use seb::format::{BibTex, Format};
+
+    use super::*;
+
+    #[test]
+    fn add_appends_one_history_line() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let path = dir.child("refs.bib");
+        let parse = |bibtex: &str| BibTex::new(bibtex.to_owned()).parse().unwrap().unwrap();
+
+        let before = parse("@misc{old, title = {Old}}");
+        let mut after = parse("@misc{old, title = {Old}}");
+        let added = parse("@misc{new, title = {New}}")
+            .into_entries()
+            .pop()
+            .unwrap();
+        after.insert(added);
+
+        let record = Record::new(
+            "add doi 10.1000/182".to_owned(),
+            path.path(),
+            &before,
+            &after,
+            Some("alice".to_owned()),
+        );
+        append(path.path(), &record).unwrap();
+
+        let content = fs::read_to_string(dir.child(HISTORY_FILE).path()).unwrap();
+        assert_eq!(1, content.lines().count());
+
+        let line = serde_json::from_str::<Record>(content.trim()).unwrap();
+        assert_eq!(vec!["new".to_owned()], line.added);
+        assert!(line.removed.is_empty() && line.changed.is_empty());
+        assert_eq!("refs.bib", line.file);
+
+        let shown = history(path.path()).unwrap();
+        assert!(shown.ends_with(" alice: seb add doi 10.1000/182 - added new"));
+        assert!(history(dir.child("other.bib").path()).unwrap().is_empty());
+    }
+}
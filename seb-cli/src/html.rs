@@ -0,0 +1,130 @@
+//! The text of HTML and XML content, shared by the article pages and the feeds.
+
+/// The names of the entities of the Latin-1 characters from U+00A0 to U+00FF, in order.
+const LATIN_1: [&str; 96] = [
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect", "uml", "copy", "ordf",
+    "laquo", "not", "shy", "reg", "macr", "deg", "plusmn", "sup2", "sup3", "acute", "micro",
+    "para", "middot", "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34", "iquest",
+    "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil", "Egrave", "Eacute",
+    "Ecirc", "Euml", "Igrave", "Iacute", "Icirc", "Iuml", "ETH", "Ntilde", "Ograve", "Oacute",
+    "Ocirc", "Otilde", "Ouml", "times", "Oslash", "Ugrave", "Uacute", "Ucirc", "Uuml", "Yacute",
+    "THORN", "szlig", "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil",
+    "egrave", "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml", "eth", "ntilde",
+    "ograve", "oacute", "ocirc", "otilde", "ouml", "divide", "oslash", "ugrave", "uacute", "ucirc",
+    "uuml", "yacute", "thorn", "yuml",
+];
+
+/// The other named entities that are common in titles and names.
+const NAMED: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("OElig", 'Œ'),
+    ("oelig", 'œ'),
+    ("Scaron", 'Š'),
+    ("scaron", 'š'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("hellip", '…'),
+];
+
+/// The longest entity, without the `&` and `;`, that is decoded.
+const MAX_ENTITY_LEN: usize = 8;
+
+/// Returns the text of the `content` without its tags, with the whitespace collapsed and the
+/// entities decoded, see [`unescape`].
+pub fn text(content: &str) -> String {
+    let mut text = String::with_capacity(content.len());
+    let mut in_tag = false;
+    for c in content.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Decodes the named entities, such as `&szlig;`, and the decimal and hexadecimal character
+/// references, such as `&#223;` and `&#xDF;`, of the `text`.
+///
+/// The text is decoded in a single pass so that an escaped entity, such as `&amp;lt;`, is decoded
+/// once. An unknown entity is left as it is.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .split_once(';')
+            .filter(|(entity, _)| entity.len() <= MAX_ENTITY_LEN)
+            .and_then(|(entity, after)| Some((entity_char(entity)?, after)));
+        if let Some((c, after)) = decoded {
+            unescaped.push(c);
+            rest = after;
+        } else {
+            unescaped.push('&');
+            rest = &rest[1..];
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+// The character of an entity without its `&` and `;`.
+fn entity_char(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    if let Some(i) = LATIN_1.iter().position(|name| *name == entity) {
+        return char::from_u32(0xA0 + u32::try_from(i).ok()?);
+    }
+    NAMED
+        .iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, c)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_and_numeric_entities_are_decoded() {
+        assert_eq!("Weiß, Armin", unescape("Wei&szlig;, Armin"));
+        assert_eq!("Gödel & Escher", unescape("G&#246;del &amp; Escher"));
+        assert_eq!("Gödel – Bach", unescape("G&#xF6;del &ndash; Bach"));
+        assert_eq!("Ça 'va'", unescape("&Ccedil;a &apos;va&#39;"));
+    }
+
+    #[test]
+    fn entities_are_decoded_once_and_unknown_entities_are_kept() {
+        assert_eq!("&lt;i&gt;", unescape("&amp;lt;i&amp;gt;"));
+        assert_eq!(
+            "R&D &unknown; &#xZZ; & more",
+            unescape("R&D &unknown; &#xZZ; & more")
+        );
+        assert_eq!("a & b;", unescape("a & b;"));
+    }
+
+    #[test]
+    fn text_has_no_tags() {
+        assert_eq!(
+            "Download citation <now>",
+            text("<span>Download</span>\n  citation &lt;now&gt;")
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use dialoguer::Input;
 use eyre::{eyre, Context, Result};
-use seb::ast::{Biblio, BiblioResolver, Entry, QuotedString, Resolver as EntryResolver};
+use seb::ast::{
+    Author, Biblio, BiblioResolver, Entry, EntryKind, QuotedString, Resolver as EntryResolver,
+};
 
 pub fn user_select<S: ToString>(prompt: &str, items: &[S]) -> Result<usize> {
     let selection = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
@@ -17,6 +19,18 @@ pub fn user_select<S: ToString>(prompt: &str, items: &[S]) -> Result<usize> {
     }
 }
 
+/// Asks the user to select any of the `items`, which are all selected to begin with, and returns
+/// the indices of the items selected.
+pub fn user_multi_select<S: ToString>(prompt: &str, items: &[S]) -> Result<Vec<usize>> {
+    dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(&vec![true; items.len()])
+        .interact_opt()
+        .wrap_err_with(|| eyre!("User selection cancelled"))?
+        .ok_or_else(|| eyre!("No selection made - cancelling operation"))
+}
+
 pub fn user_select_map<T, F>(prompt: &str, items: &[T], f: F) -> Result<usize>
 where
     F: Fn(&T) -> String,
@@ -34,6 +48,55 @@ fn entries_titles(entries: &[Entry]) -> Vec<String> {
     entries.iter().map(|e| e.title().to_string()).collect()
 }
 
+/// Asks the user a yes or no question, a cancelled prompt is the same as answering no.
+pub fn user_confirm(prompt: &str) -> bool {
+    dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact_opt()
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+pub fn user_confirm_removal(entry: &Entry) -> bool {
+    user_confirm(&format!(
+        "Remove entry '{}' ({})?",
+        entry.cite(),
+        entry.title().as_ref()
+    ))
+}
+
+pub fn user_confirm_merge(name: &Author, variants: &[Author]) -> bool {
+    let variants = variants
+        .iter()
+        .map(|variant| format!("'{variant}'"))
+        .collect::<Vec<_>>();
+    user_confirm(&format!("Merge {} into '{name}'?", variants.join(", ")))
+}
+
+pub fn user_confirm_similar(entry: &Entry, similar: &[&Entry]) -> bool {
+    let similar = similar
+        .iter()
+        .map(|e| format!("'{}' ({})", e.cite(), e.title().as_ref()))
+        .collect::<Vec<_>>();
+    user_confirm(&format!(
+        "'{}' ({}) is similar to {}, add it anyway?",
+        entry.cite(),
+        entry.title().as_ref(),
+        similar.join(", ")
+    ))
+}
+
+pub fn user_confirm_keywords(entry: &Entry, keywords: &[String]) -> bool {
+    user_confirm(&format!(
+        "Add the keywords '{}' to '{}' ({})?",
+        keywords.join(", "),
+        entry.cite(),
+        entry.title().as_ref()
+    ))
+}
+
 pub fn user_input(prompt: String) -> Result<String> {
     Input::new()
         .with_prompt(prompt)
@@ -68,9 +131,119 @@ pub fn user_resolve_entry(resolver: &mut EntryResolver) -> eyre::Result<()> {
     println!("Missing required fields for {kind} entry:");
 
     while let Some(field_entry) = resolver.next_required_entry() {
-        let input = user_input(format!("Enter value for the {} field", field_entry.key()))?;
+        let input = user_input_field(field_entry.key())?;
         field_entry.insert(QuotedString::new(input));
     }
 
     Ok(())
 }
+
+/// Builds a new entry by asking the user for the kind of entry, the required fields and then any
+/// optional fields.
+///
+/// Cancelling any prompt returns an error before the entry is built.
+pub fn user_build_entry(cite: Option<String>, transliterate: bool) -> eyre::Result<Entry> {
+    let kinds = EntryKind::known();
+    let mut items = kinds.iter().map(ToString::to_string).collect::<Vec<_>>();
+    items.push("other".to_owned());
+
+    let kind = match kinds.get(user_select("Select the kind of entry", &items)?) {
+        Some(kind) => kind.clone(),
+        None => EntryKind::from(user_input("Enter the kind of entry".to_owned())?.trim()),
+    };
+
+    // the same optional fields as `seb fields` shows for the kind
+    let prompt = match kind.optional_fields() {
+        [] => "Enter the name of an optional field, or nothing to finish".to_owned(),
+        optional => format!(
+            "Enter the name of an optional field, such as {}, or nothing to finish",
+            optional.join(", ")
+        ),
+    };
+    let mut resolver = match cite {
+        Some(cite) => Entry::resolver_with_cite(kind, cite),
+        None => Entry::resolver(kind),
+    };
+    resolver.set_transliterate(transliterate);
+    user_resolve_entry(&mut resolver)?;
+
+    loop {
+        let name = Input::<String>::new()
+            .with_prompt(&prompt)
+            .allow_empty(true)
+            .validate_with(|name: &String| validate_field_name(name))
+            .interact_text()
+            .wrap_err_with(|| eyre!("User input cancelled"))?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            break;
+        }
+        let value = user_input_field(name)?;
+        resolver.set_field(name, QuotedString::new(value));
+    }
+
+    // every required field has been set by the user
+    resolver
+        .resolve()
+        .map_err(|resolver| eyre!("Cannot build the entry:\n{resolver}"))
+}
+
+fn user_input_field(name: &str) -> Result<String> {
+    Input::new()
+        .with_prompt(format!("Enter value for the {name} field"))
+        .validate_with(|value: &String| validate_field_value(name, value))
+        .interact_text()
+        .wrap_err_with(|| eyre!("User input cancelled"))
+}
+
+fn validate_field_name(name: &str) -> std::result::Result<(), String> {
+    match name
+        .trim()
+        .chars()
+        .find(|c| c.is_whitespace() || matches!(c, '{' | '}' | '=' | ',' | '"' | '#'))
+    {
+        Some(c) => Err(format!("A field name cannot contain '{c}'")),
+        None => Ok(()),
+    }
+}
+
+fn validate_field_value(name: &str, value: &str) -> std::result::Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        Err(format!("The {name} field cannot be empty"))
+    } else if name == "year" && !value.chars().all(|c| c.is_ascii_digit()) {
+        Err(format!(
+            "The year should only contain digits but found '{value}'"
+        ))
+    } else if value.matches('{').count() != value.matches('}').count() {
+        Err("The braces in the value are not balanced".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_names_cannot_contain_separators() {
+        assert!(validate_field_name("note").is_ok());
+        assert!(
+            validate_field_name("").is_ok(),
+            "An empty name finishes the prompt"
+        );
+        assert!(validate_field_name("my note").is_err());
+        assert!(validate_field_name("note=").is_err());
+    }
+
+    #[test]
+    fn field_values_are_validated() {
+        assert!(validate_field_value("title", "{LaTeX} guide").is_ok());
+        assert!(validate_field_value("title", "  ").is_err());
+        assert!(validate_field_value("title", "{LaTeX guide").is_err());
+        assert!(validate_field_value("year", "2021").is_ok());
+        assert!(validate_field_value("year", "in press").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{IsTerminal, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use stderrlog::{ColorChoice, StdErrLog};
+
+/// The environment variable used for the log file when the `--log-file` option is not used.
+pub const LOG_FILE_ENV: &str = "SEB_LOG_FILE";
+
+/// A logger that writes to stderr at the chosen verbosity and, optionally, writes every record
+/// to a log file regardless of that verbosity.
+struct Logger {
+    stderr: StdErrLog,
+    file: Option<Mutex<File>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.file.is_some() || self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        self.stderr.log(record);
+
+        if let Some(mut file) = self.file.as_ref().and_then(|file| file.lock().ok()) {
+            // failing to write a log line should never stop the program
+            let _ = writeln!(
+                file,
+                "{} {:<5} {}: {}",
+                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Some(mut file) = self.file.as_ref().and_then(|file| file.lock().ok()) {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Sets up the global logger to write to stderr at the `verbosity` level and to append every
+/// record to the `log_file`, when given.
+///
+/// A log file that cannot be opened is reported as a warning and only stderr is used.
+pub fn init(verbosity: usize, log_file: Option<&Path>) -> Result<(), SetLoggerError> {
+    let mut stderr = stderrlog::new();
+    stderr
+        .verbosity(verbosity)
+        .color(if std::io::stderr().is_terminal() {
+            ColorChoice::Auto
+        } else {
+            ColorChoice::Never
+        });
+
+    let file = log_file.map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| (path, err))
+    });
+
+    let (file, file_err) = match file {
+        Some(Ok(file)) => (Some(Mutex::new(file)), None),
+        Some(Err(err)) => (None, Some(err)),
+        None => (None, None),
+    };
+
+    log::set_max_level(if file.is_some() {
+        LevelFilter::Trace
+    } else {
+        level_filter(verbosity)
+    });
+    log::set_boxed_logger(Box::new(Logger { stderr, file }))?;
+
+    if let Some((path, err)) = file_err {
+        log::warn!("Cannot open the log file '{}': {err}", path.display());
+    }
+    Ok(())
+}
+
+// matches the verbosity levels used by stderrlog
+const fn level_filter(verbosity: usize) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::Error,
+        1 => LevelFilter::Warn,
+        2 => LevelFilter::Info,
+        3 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
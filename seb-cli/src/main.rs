@@ -8,80 +8,348 @@
 )]
 #![allow(clippy::as_conversions, clippy::mod_module_files)]
 
-use std::{error, path::PathBuf, process};
+use std::{
+    error,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process,
+};
 
 mod app;
+mod backup;
 mod commands;
+mod config;
+mod diff;
+mod feed;
 mod file;
+mod filter;
+#[cfg(test)]
+mod fixture;
+mod history;
+mod html;
 mod interact;
+mod logger;
+mod page;
+mod pdf;
 
 use commands::Commands;
+use config::FileSettings;
 use interact::user_resolve_biblio_resolver;
 
-use seb::format::{BibTex, Reader, Writer};
+use seb::{
+    ast::{Biblio, BiblioResolver},
+    file::{FormatFile, Sidecar},
+    format::{BibTex, InputEncoding, OutputEncoding, Reader, Writer},
+    normalize::{latex_to_unicode_quoted, unicode_to_latex},
+    provider::Providers,
+    ErrorKind,
+};
 
-use clap::{Args, Parser};
-use log::trace;
+use clap::{ArgEnum, Args, Parser};
+use eyre::WrapErr;
+use log::{trace, warn};
 
 fn main() {
     if let Err(err) = try_main() {
         eprintln!("{}", err);
-        process::exit(2);
+        if let Some(hint) = hint(err.as_ref()) {
+            eprintln!("\nhint: {hint}");
+        }
+        process::exit(exit_code(err.as_ref()));
     }
 }
 
+/// Returns the exit code for the category of the error.
+///
+/// | Code | Category                                                   |
+/// |------|------------------------------------------------------------|
+/// | 1    | Any other error                                            |
+/// | 2    | Invalid command line usage, reported by clap               |
+/// | 3    | No value found, such as no entry matching a DOI            |
+/// | 4    | IO error, such as a network failure or unreadable file     |
+/// | 5    | Parse error, such as invalid BibTeX or a response payload  |
+///
+/// The first [`seb::Error`] in the chain of error sources decides the category.
+fn exit_code(err: &(dyn error::Error + 'static)) -> i32 {
+    let kind = std::iter::successors(Some(err), |err| err.source()).find_map(|err| {
+        if err.is::<BiblioResolver>() {
+            Some(ErrorKind::Deserialize)
+        } else {
+            err.downcast_ref::<seb::Error>().map(seb::Error::kind)
+        }
+    });
+
+    match kind {
+        Some(ErrorKind::NoValue) => 3,
+        Some(ErrorKind::IO) => 4,
+        Some(ErrorKind::Deserialize) => 5,
+        None => 1,
+    }
+}
+
+/// Returns the suggestion of how to recover from the error, which is the hint of the first
+/// [`seb::Error`] in the chain of error sources that has one, see [`seb::Error::with_hint`].
+fn hint(err: &(dyn error::Error + 'static)) -> Option<&str> {
+    std::iter::successors(Some(err), |err| err.source())
+        .filter_map(|err| err.downcast_ref::<seb::Error>())
+        .find_map(seb::Error::hint)
+}
+
 fn try_main() -> Result<(), Box<dyn error::Error>> {
     let Cli {
-        command,
+        mut command,
         global_opts:
             GlobalOpts {
                 file,
+                glob,
+                into,
                 interact,
+                yes,
+                no_verify,
                 verbosity,
                 quiet,
+                output_encoding,
+                annotate_output,
+                encoding,
+                strict,
+                log_file,
             },
     } = Cli::parse();
+    let log_file = log_file.or_else(|| std::env::var_os(logger::LOG_FILE_ENV).map(PathBuf::from));
+    setup_errlog(verbosity as usize, quiet, log_file.as_deref())?;
 
-    setup_errlog(verbosity as usize, quiet)?;
+    let encoding = encoding.map(InputEncoding::from);
+    let file = file::with_globs(file, &glob)?;
 
     // `quiet` and `interact` cannot be set at the same time
-    let interact = quiet ^ interact;
+    let interact = !quiet && interact;
 
     if interact {
         trace!("Interact mode enabled");
     }
 
-    let mut file = file::open_or_create_format_file::<BibTex>(file)?;
-    let biblio = file.read_ast()?;
+    // the config file is one of the things checked so it is not loaded first
+    if matches!(command, Commands::Doctor) {
+        let stdout = std::io::stdout();
+        return Ok(commands::doctor(
+            &file,
+            stdout.is_terminal(),
+            stdout.lock(),
+        )?);
+    }
+
+    let mut config = config::Config::load(Path::new(config::CONFIG_FILE))?;
+    let providers = providers(&config)?;
+
+    if let Some(output) = command.execute_without_file(&providers) {
+        print(quiet, &output?);
+        return Ok(());
+    }
+
+    // read-only commands use the entries of every file and are the only commands that can use a URL
+    let union = file.len() > 1 || file.iter().any(|file| file::is_url(file));
+    if union && into.is_none() && command.is_read_only() {
+        configure(&mut command, &mut config, output_encoding, annotate_output);
+        let mut biblio = file::read_union(&file, &config.key_format(), encoding, |bibtex| {
+            command.read_raw(bibtex);
+        })?;
+        print(
+            quiet,
+            &command.execute(&mut biblio, &providers, false, false)?,
+        );
+        return Ok(());
+    }
+
+    let file = file::writable(file, into)?;
+    if let Some(path) = file.as_deref() {
+        if command.needs_existing_file() && !path.exists() {
+            return Err(eyre::eyre!("The file '{}' does not exist", path.display()).into());
+        }
+    }
+    let mut file = file::open_or_create_format_file::<BibTex>(file)?.with_encoding(encoding);
+    let confirm = !yes && std::io::stdin().is_terminal();
+
+    if let Some(output) =
+        command.execute_file(file.path(), file.encoding(), confirm, &config.history)
+    {
+        print(quiet, &output?);
+        return Ok(());
+    }
+
+    let bibtex = file.read()?;
 
+    // the settings of the file take precedence over the config file but not the options
+    if let Some(settings) = FileSettings::read(&bibtex)? {
+        trace!("Using the settings of the {} file", file.path().display());
+        config.apply(settings);
+    }
+    configure(&mut command, &mut config, output_encoding, annotate_output);
+    command.set_file(file.path());
+    // an entry without a cite key is given one generated with the configured key format
+    let key_format = config.key_format();
+
+    if let Some(output) = command.execute_raw(&bibtex) {
+        print(quiet, &output?);
+        return Ok(());
+    }
+
+    command.audit(&bibtex)?;
+    command.read_raw(&bibtex);
+    let biblio = bibtex
+        .parse_with(&key_format)
+        .map_err(|err| err.with_hint(file::PARSE_HINT))?;
+
+    // read-only commands can show the entries that are missing required fields
+    let partial = !interact && command.is_read_only() && biblio.is_err();
     let mut biblio = match biblio {
         Err(resolver) if interact => user_resolve_biblio_resolver(resolver)?,
+        Err(mut resolver) if partial => {
+            // the entries are named as an export would otherwise include them without a trace
+            for entry in resolver.unresolved() {
+                let missing = entry.required_fields().collect::<Vec<_>>().join(", ");
+                warn!(
+                    "The entry '{}' is missing {missing} - the missing fields are left empty",
+                    entry.cite()
+                );
+            }
+            resolver.into_partial()
+        }
         res => res?,
     };
+    // the fields stored in the sidecar file are available to every command
+    let sidecar = Sidecar::new(file.path(), config.format.sidecar.clone());
+    sidecar.load(&mut biblio)?;
+    // the LaTeX commands of a file written in ascii are only read as the characters when the file
+    // is switched to utf8, so that a file is otherwise written back as it was read
+    if matches!(output_encoding, Some(Encoding::Utf8)) && !command.is_read_only() {
+        biblio.map_fields(|_, value| latex_to_unicode_quoted(value));
+    }
 
-    let command_res = command.execute(&mut biblio, interact);
+    let command_res = command.execute(&mut biblio, &providers, interact, confirm);
 
-    if biblio.dirty() {
-        trace!("Updating the bibliography file..");
-        file.write_ast(biblio)?;
-        trace!("Done!");
+    if biblio.dirty() && !partial {
+        if strict && config.format.bibtex.output_encoding == OutputEncoding::Ascii {
+            check_ascii(&biblio)?;
+        }
+        write(&mut file, &mut biblio, &sidecar, &config, !no_verify)?;
     }
 
-    let message = command_res?;
-    println!("{message}");
+    print(quiet, &command_res?);
     Ok(())
 }
 
-fn setup_errlog(verbosity: usize, quiet: bool) -> Result<(), Box<dyn error::Error>> {
-    // if quiet then ignore verbosity but still show errors
-    let verbosity = if quiet {
-        dbg!("quiet flag used but dbg! and error will still be shown");
-        1
+/// Writes the bibliography file, and its sidecar file, after backing it up and records the change
+/// in the history when it is enabled.
+fn write(
+    file: &mut FormatFile<BibTex>,
+    biblio: &mut Biblio,
+    sidecar: &Sidecar,
+    config: &config::Config,
+    verify: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    // the file is read before it is written to find the entries changed for the history
+    let before = config
+        .history
+        .log
+        .then(|| history::before(file.path(), file.encoding()))
+        .flatten();
+    let backup = backup::backup(file.path(), config.backup.keep)?;
+    trace!("Updating the bibliography file..");
+    sidecar.store(biblio)?;
+    file.write(BibTex::compose_with(biblio, &config.format.bibtex))?;
+    if verify {
+        trace!("Verifying the bibliography file..");
+        backup::verify(file.path(), backup.as_deref(), file.encoding())?;
+    }
+    if let Some(before) = before {
+        history::record(file.path(), &before, biblio, config.history.user.clone())?;
+    }
+    trace!("Done!");
+    Ok(())
+}
+
+/// Applies the options that override the config for a single command and then fills in the
+/// options of the `command` that were not given from the config.
+fn configure(
+    command: &mut Commands,
+    config: &mut config::Config,
+    output_encoding: Option<Encoding>,
+    annotate_output: bool,
+) {
+    if let Some(encoding) = output_encoding {
+        config.format.bibtex.output_encoding = encoding.into();
+    }
+    config.format.bibtex.annotate |= annotate_output;
+    command.configure(config);
+}
+
+/// Returns the providers in the order set by the `config` and sets how the provider APIs are sent
+/// requests.
+fn providers(config: &config::Config) -> eyre::Result<Providers> {
+    seb::set_rate_limits(config.rate_limits.clone());
+    // the config file takes precedence over the environment variables
+    seb::set_http_options(&config.http.clone().or_env())
+        .wrap_err("Invalid proxy or headers in the [http] options")?;
+
+    // the config file takes precedence over the environment variable
+    if let Some(email) = &config.crossref.email {
+        seb::set_contact_email(email);
+    }
+
+    let mut providers = Providers::default();
+    for (kind, order) in &config.providers {
+        providers.set_order(kind, order);
+    }
+    Ok(providers)
+}
+
+/// Returns an error listing the cite keys that are not ASCII and the characters that have no
+/// LaTeX command, which cannot be written exactly with the ascii output encoding.
+fn check_ascii(biblio: &Biblio) -> eyre::Result<()> {
+    let mut problems = Vec::new();
+    for entry in biblio.entries() {
+        if !entry.cite().is_ascii() {
+            problems.push(format!("The cite key '{}' is not ASCII", entry.cite()));
+        }
+        for field in entry.fields() {
+            let unknown =
+                unicode_to_latex(&field.value).map_or_else(Vec::new, |(_, unknown)| unknown);
+            for c in unknown {
+                problems.push(format!(
+                    "The character '{c}' in the {} field of '{}' has no LaTeX command",
+                    field.name,
+                    entry.cite()
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
     } else {
-        verbosity + 2
-    };
+        Err(eyre::eyre!("{}", problems.join("\n")))
+    }
+}
+
+fn print(quiet: bool, output: &str) {
+    if !quiet && !output.is_empty() {
+        println!("{output}");
+    }
+}
+
+fn setup_errlog(
+    verbosity: usize,
+    quiet: bool,
+    log_file: Option<&Path>,
+) -> Result<(), Box<dyn error::Error>> {
+    // if quiet then ignore verbosity but still show errors
+    let verbosity = if quiet { 0 } else { verbosity + 2 };
 
-    stderrlog::new().verbosity(verbosity).init()?;
+    logger::init(verbosity, log_file)?;
+
+    if quiet {
+        trace!("Quiet mode enabled");
+    }
     Ok(())
 }
 
@@ -97,16 +365,56 @@ struct Cli {
     global_opts: GlobalOpts,
 }
 
+#[allow(clippy::struct_excessive_bools)] // each flag is a separate command line option
 #[derive(Debug, Args)]
 struct GlobalOpts {
     /// The name of the file
-    #[clap(short, long, parse(from_os_str), global = true)]
-    file: Option<PathBuf>,
+    ///
+    /// This option can be used more than once so that the commands that only read the
+    /// bibliography, such as list and stats, use the entries of every file. An http(s) URL is
+    /// downloaded and can only be used by these commands.
+    #[clap(
+        short,
+        long,
+        parse(from_os_str),
+        global = true,
+        multiple_occurrences = true
+    )]
+    file: Vec<PathBuf>,
+
+    /// Use every file matching the glob pattern, such as 'refs/**/*.bib', like a --file.
+    ///
+    /// This option can be used more than once and the patterns are quoted so that seb expands
+    /// them rather than the shell.
+    #[clap(
+        long,
+        global = true,
+        multiple_occurrences = true,
+        value_name = "PATTERN"
+    )]
+    glob: Vec<String>,
+
+    /// The file changed when more than one --file is used.
+    #[clap(long, parse(from_os_str), global = true, value_name = "PATH")]
+    into: Option<PathBuf>,
 
     /// Enables interactive mode, which allows for dynamically resolving invalid entries.
     #[clap(short, long, global = true)]
     interact: bool,
 
+    /// Removes entries without asking for confirmation.
+    ///
+    /// The confirmation is also skipped when stdin is not a terminal.
+    #[clap(short, long, alias = "confirm", global = true)]
+    yes: bool,
+
+    /// Skips re-reading the file after it is written.
+    ///
+    /// By default the file is parsed again after every write and restored from the most recent
+    /// backup when it no longer parses.
+    #[clap(long, global = true)]
+    no_verify: bool,
+
     /// How chatty the program is when performing commands
     ///
     /// The number of times this flag is used will increase how chatty
@@ -114,7 +422,73 @@ struct GlobalOpts {
     #[clap(short, long, parse(from_occurrences), global = true)]
     verbosity: u8,
 
-    /// Prevents the program from writing to stdout, errors will still be printed to stderr.
+    /// Prevents the program from writing to stdout, only errors will be printed to stderr.
     #[clap(short, long, global = true)]
     quiet: bool,
+
+    /// The characters written to the bibliography file, which overrides the `output-encoding` in
+    /// the `[format.bibtex]` section of the config file.
+    ///
+    /// With ascii every other character is written as its LaTeX command, such as {\"{i}} for ï,
+    /// and with utf8 the LaTeX commands for accented letters and symbols are written as the
+    /// characters.
+    #[clap(long, arg_enum, global = true, value_name = "ENCODING")]
+    output_encoding: Option<Encoding>,
+
+    /// Follows each entry with comments explaining the content seb generated, such as a cite key,
+    /// which overrides the `annotate` option in the `[format.bibtex]` section of the config file.
+    #[clap(long, global = true)]
+    annotate_output: bool,
+
+    /// The encoding the bibliography file is read in, which is detected when this is not set.
+    ///
+    /// A file that is not UTF-8, such as a Latin-1 file with accented names, is transcoded to
+    /// UTF-8 as it is read and is written as UTF-8.
+    #[clap(long, arg_enum, global = true, value_name = "ENCODING")]
+    encoding: Option<SourceEncoding>,
+
+    /// Fails, instead of warning, when a character has no LaTeX command or a cite key is not
+    /// ASCII and the output encoding is ascii.
+    #[clap(long, global = true)]
+    strict: bool,
+
+    /// Append the full log, at every verbosity level, to this file.
+    ///
+    /// The `SEB_LOG_FILE` environment variable is used when this option is not set.
+    #[clap(long, parse(from_os_str), global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+}
+
+/// The characters written to the bibliography file, see [`OutputEncoding`].
+#[derive(Clone, Copy, Debug, ArgEnum)]
+enum Encoding {
+    Utf8,
+    Ascii,
+}
+
+impl From<Encoding> for OutputEncoding {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Utf8 => Self::Utf8,
+            Encoding::Ascii => Self::Ascii,
+        }
+    }
+}
+
+/// The characters of the bibliography file when it is read, see [`InputEncoding`].
+#[derive(Clone, Copy, Debug, ArgEnum)]
+enum SourceEncoding {
+    Utf8,
+    Latin1,
+    Windows1252,
+}
+
+impl From<SourceEncoding> for InputEncoding {
+    fn from(encoding: SourceEncoding) -> Self {
+        match encoding {
+            SourceEncoding::Utf8 => Self::Utf8,
+            SourceEncoding::Latin1 => Self::Latin1,
+            SourceEncoding::Windows1252 => Self::Windows1252,
+        }
+    }
 }
@@ -0,0 +1,335 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use seb::ast::{Entry, EntryKind, FieldQuery, Resolver};
+
+use crate::{
+    html::{text, unescape},
+    pdf::find_dois,
+};
+
+static META: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<meta\s([^>]*)>").expect("meta pattern is valid"));
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<a\s([^>]*)>(.*?)</a>").expect("link pattern is valid"));
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)([\w:.-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#)
+        .expect("attribute pattern is valid")
+});
+static PASSWORD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<input\s[^>]*type\s*=\s*["']?password"#).expect("password pattern is valid")
+});
+
+/// The citation metadata and links of an article page, such as the page of a paper on a
+/// publisher's website.
+///
+/// Publishers add `citation_*` meta tags, which Google Scholar reads, and Dublin Core `dc.*`
+/// meta tags to their article pages.
+#[derive(Debug, Default)]
+pub struct Page {
+    // the lowercase names and the values of the meta tags in the order they are found
+    meta: Vec<(String, String)>,
+    // the `href` and text of each link
+    links: Vec<(String, String)>,
+    password: bool,
+}
+
+impl Page {
+    /// Reads the meta tags and links of the `html`.
+    pub fn parse(html: &str) -> Self {
+        let meta = META
+            .captures_iter(html)
+            .filter_map(|tag| {
+                let attributes = attributes(&tag[1]);
+                let name = attribute(&attributes, "name")
+                    .or_else(|| attribute(&attributes, "property"))?;
+                let content = attribute(&attributes, "content")?;
+                Some((name.to_lowercase(), content.to_owned()))
+            })
+            .collect();
+        let links = LINK
+            .captures_iter(html)
+            .filter_map(|link| {
+                let href = attribute(&attributes(&link[1]), "href")?.to_owned();
+                Some((href, text(&link[2])))
+            })
+            .collect();
+
+        Self {
+            meta,
+            links,
+            password: PASSWORD.is_match(html),
+        }
+    }
+
+    /// The URL of the link to export the citation of the page as `BibTeX`, resolved against the
+    /// `url` of the page.
+    pub fn bibtex_link(&self, url: &str) -> Option<String> {
+        self.links
+            .iter()
+            .find(|(href, text)| {
+                let href = href.to_lowercase();
+                !href.starts_with("javascript:")
+                    && !href.starts_with('#')
+                    && (href.contains("bibtex")
+                        || href.contains("format=bib")
+                        || href
+                            .split(['?', '#'])
+                            .next()
+                            .and_then(|path| path.rsplit_once('.'))
+                            .is_some_and(|(_, extension)| extension == "bib")
+                        || text.to_lowercase().contains("bibtex"))
+            })
+            .map(|(href, _)| resolve_url(url, href))
+    }
+
+    /// The DOI of the article from the meta tags of the page.
+    ///
+    /// The rest of the page is not searched as it often has the DOIs of the references.
+    pub fn doi(&self) -> Option<String> {
+        ["citation_doi", "dc.identifier", "prism.doi"]
+            .into_iter()
+            .flat_map(|name| self.values(name))
+            .find_map(|value| find_dois(value).into_iter().next())
+    }
+
+    /// Returns `true` when the page asks for a password and has no citation metadata, such as
+    /// the sign in page a login-walled article redirects to.
+    pub fn requires_login(&self) -> bool {
+        self.password && self.title().is_none()
+    }
+
+    /// Builds the entry of the article from the meta tags of the page, with the `url` of the page
+    /// in the `url` field, or [`None`] when the page has no title.
+    ///
+    /// The entry is an `article` when the page names a journal, an `inproceedings` when it names a
+    /// conference and otherwise a `misc` entry.
+    pub fn resolver(&self, url: &str) -> Option<Resolver> {
+        let title = self.title()?;
+        let journal = self.value("citation_journal_title");
+        let conference = self
+            .value("citation_conference_title")
+            .or_else(|| self.value("citation_inbook_title"));
+
+        let kind = match (journal, conference) {
+            (Some(_), _) => EntryKind::Article,
+            (None, Some(_)) => EntryKind::InProceedings,
+            (None, None) => EntryKind::Other("misc".into()),
+        };
+        let mut resolver = Entry::resolver(kind);
+        resolver.title(title);
+
+        let authors = ["citation_author", "dc.creator"]
+            .into_iter()
+            .map(|name| self.values(name).collect::<Vec<_>>())
+            .find(|authors| !authors.is_empty());
+        if let Some(authors) = authors {
+            resolver.author(authors.join(" and "));
+        }
+        if let Some(journal) = journal {
+            resolver.journal(journal);
+        }
+        if let Some(conference) = conference {
+            resolver.set_field("booktitle", conference);
+        }
+        let year = [
+            "citation_publication_date",
+            "citation_date",
+            "citation_online_date",
+            "citation_year",
+            "dc.date",
+        ]
+        .into_iter()
+        .filter_map(|name| self.value(name))
+        .find_map(|date| {
+            let year = date
+                .split(|c: char| !c.is_ascii_digit())
+                .find(|part| part.len() == 4)?;
+            Some(year.to_owned())
+        });
+        if let Some(year) = year {
+            resolver.year(year);
+        }
+        for (name, field) in [
+            ("citation_volume", "volume"),
+            ("citation_issue", "number"),
+            ("citation_publisher", "publisher"),
+            ("dc.publisher", "publisher"),
+            ("citation_isbn", "isbn"),
+            ("citation_issn", "issn"),
+        ] {
+            if let Some(value) = self.value(name) {
+                if resolver.get_field(field).is_none() {
+                    resolver.set_field(field, value);
+                }
+            }
+        }
+        match (
+            self.value("citation_firstpage"),
+            self.value("citation_lastpage"),
+        ) {
+            (Some(first), Some(last)) => resolver.set_field("pages", format!("{first}--{last}")),
+            (Some(first), None) => resolver.set_field("pages", first),
+            _ => {}
+        }
+        if let Some(doi) = self.doi() {
+            resolver.set_field("doi", doi);
+        }
+        resolver.set_field("url", url);
+
+        Some(resolver)
+    }
+
+    fn title(&self) -> Option<&str> {
+        ["citation_title", "dc.title", "og:title"]
+            .into_iter()
+            .find_map(|name| self.value(name))
+    }
+
+    fn value<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        self.values(name).next()
+    }
+
+    fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.meta
+            .iter()
+            .filter(move |(meta, _)| meta == name)
+            .map(|(_, value)| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+// the names and unescaped values of the attributes of a tag
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    ATTRIBUTE
+        .captures_iter(tag)
+        .map(|attribute| {
+            let value = (2..=4)
+                .find_map(|i| attribute.get(i))
+                .map_or("", |value| value.as_str());
+            (attribute[1].to_lowercase(), unescape(value))
+        })
+        .collect()
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(attribute, _)| attribute == name)
+        .map(|(_, value)| value.as_str())
+}
+
+// the `href` of a link, which can be relative to the page `url`, as an absolute URL
+fn resolve_url(url: &str, href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return href.to_owned();
+    }
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    if let Some(href) = href.strip_prefix("//") {
+        return format!("{scheme}://{href}");
+    }
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    if href.starts_with('/') {
+        return format!("{scheme}://{host}{href}");
+    }
+    // relative to the directory of the page
+    let path = rest.split(['?', '#']).next().unwrap_or(rest);
+    let directory = path.rfind('/').map_or(path, |end| &path[..end]);
+    format!("{scheme}://{directory}/{href}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<html><head>
+        <meta name="citation_title" content="QuickXsort: A Fast Sorting Scheme in Theory and Practice">
+        <meta name="citation_author" content="Edelkamp, Stefan">
+        <meta name="citation_author" content="Wei&szlig;, Armin">
+        <meta name="citation_journal_title" content="Algorithmica">
+        <meta name="citation_publication_date" content="2019/09/10">
+        <meta name="citation_volume" content="82">
+        <meta name="citation_firstpage" content="509">
+        <meta name="citation_lastpage" content="588">
+        <meta name="citation_doi" content="10.1007/s00453-019-00634-0">
+        <meta property='og:title' content='Ignored'>
+        </head><body>
+        <a href="/login">Sign in</a>
+        <a href='/citation/export?format=bib&amp;id=1'><span>Download</span> citation</a>
+        <p>Cites doi:10.1000/cited</p>
+        </body></html>"#;
+
+    #[test]
+    fn citation_meta_tags_build_an_article() {
+        let page = Page::parse(ARTICLE);
+        let entry = page
+            .resolver("https://example.com/article/1")
+            .expect("page has a title")
+            .resolve()
+            .expect("article has every required field");
+
+        assert_eq!(EntryKind::Article, entry.kind());
+        assert_eq!(
+            "QuickXsort: A Fast Sorting Scheme in Theory and Practice",
+            &**entry.title()
+        );
+        assert_eq!(
+            "Edelkamp, Stefan and Weiß, Armin",
+            &**entry.get_field("author").unwrap()
+        );
+        assert_eq!("2019", &**entry.get_field("year").unwrap());
+        assert_eq!("509--588", &**entry.get_field("pages").unwrap());
+        assert_eq!(
+            "10.1007/s00453-019-00634-0",
+            &**entry.get_field("doi").unwrap()
+        );
+        assert_eq!(
+            "https://example.com/article/1",
+            &**entry.get_field("url").unwrap()
+        );
+    }
+
+    #[test]
+    fn bibtex_link_is_resolved_against_the_page() {
+        let page = Page::parse(ARTICLE);
+        assert_eq!(
+            Some("https://example.com/citation/export?format=bib&id=1".to_owned()),
+            page.bibtex_link("https://example.com/article/1")
+        );
+
+        let page = Page::parse(r#"<a href="cite.bib">BibTeX</a><a href="x">Other</a>"#);
+        assert_eq!(
+            Some("https://example.com/article/cite.bib".to_owned()),
+            page.bibtex_link("https://example.com/article/1?view=full")
+        );
+
+        assert_eq!(
+            None,
+            Page::parse("<a href=\"/pdf\">PDF</a>").bibtex_link("x")
+        );
+    }
+
+    #[test]
+    fn doi_is_only_read_from_meta_tags() {
+        assert_eq!(
+            Some("10.1007/s00453-019-00634-0".to_owned()),
+            Page::parse(ARTICLE).doi()
+        );
+        assert_eq!(
+            Some("10.1000/182".to_owned()),
+            Page::parse(r#"<meta name="DC.Identifier" content="doi:10.1000/182">"#).doi()
+        );
+        assert_eq!(None, Page::parse("<p>Cites 10.1000/cited</p>").doi());
+    }
+
+    #[test]
+    fn sign_in_page_requires_login() {
+        let sign_in = r#"<form><input name="user"><input type="password" name="pw"></form>"#;
+        assert!(Page::parse(sign_in).requires_login());
+        assert!(Page::parse(sign_in).resolver("x").is_none());
+
+        // article pages often have a sign in form as well as the metadata
+        let article = format!("{ARTICLE}{sign_in}");
+        assert!(!Page::parse(&article).requires_login());
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::HashSet, fs, path::Path, sync::LazyLock};
+
+use eyre::{eyre, Context, Result};
+use regex::bytes::Regex;
+use seb::ast::normalize_doi;
+
+/// The number of content streams scanned for a DOI, which covers the first pages of most PDFs.
+const MAX_STREAMS: usize = 32;
+
+/// The DOI pattern recommended by Crossref, which matches almost every DOI in use.
+static DOI: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"10\.\d{4,9}/[-._;()/:A-Za-z0-9]+").expect("DOI pattern is valid")
+});
+
+/// The identifying information found in a PDF file.
+#[derive(Debug, Default, PartialEq)]
+pub struct PdfInfo {
+    /// The DOI of the document, if one was found.
+    pub doi: Option<String>,
+    /// The title of the document, if one was found.
+    pub title: Option<String>,
+}
+
+/// Reads the DOI and title of the PDF file at the `path`.
+///
+/// The DOI is taken from the XMP or document metadata first and then from the text of the
+/// first pages.
+pub fn read(path: &Path) -> Result<PdfInfo> {
+    let bytes = fs::read(path).wrap_err_with(|| eyre!("Cannot read '{}'", path.display()))?;
+    parse(&bytes).wrap_err_with(|| eyre!("Cannot read '{}' as a PDF", path.display()))
+}
+
+fn parse(bytes: &[u8]) -> Result<PdfInfo> {
+    if !bytes.starts_with(b"%PDF-") {
+        return Err(eyre!("The file is not a PDF"));
+    }
+    if find(bytes, b"/Encrypt").is_some() {
+        return Err(eyre!("The PDF is encrypted"));
+    }
+
+    let xmp = between(bytes, b"<x:xmpmeta", b"</x:xmpmeta>");
+    let doi = xmp
+        .and_then(find_doi)
+        .or_else(|| info_string(bytes, b"/doi").and_then(|doi| find_doi(doi.as_bytes())))
+        .or_else(|| {
+            streams(bytes)
+                .take(MAX_STREAMS)
+                .find_map(|text| find_doi(&text))
+        });
+
+    let title = xmp
+        .and_then(|xmp| between(xmp, b"<dc:title>", b"</dc:title>"))
+        .and_then(xmp_text)
+        .or_else(|| info_string(bytes, b"/Title"))
+        .filter(|title| !title.trim().is_empty());
+
+    Ok(PdfInfo { doi, title })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn between<'a>(bytes: &'a [u8], start: &[u8], end: &[u8]) -> Option<&'a [u8]> {
+    let from = find(bytes, start)? + start.len();
+    let to = find(&bytes[from..], end)?;
+    Some(&bytes[from..from + to])
+}
+
+fn find_doi(text: &[u8]) -> Option<String> {
+    DOI.find(text).map(|m| trim_doi(m.as_bytes()))
+}
+
+/// Returns every DOI in the `text`, such as the references of a paper, in the order they are
+/// first found and without the DOIs that only differ by case.
+pub fn find_dois(text: &str) -> Vec<String> {
+    let mut found = HashSet::new();
+    DOI.find_iter(text.as_bytes())
+        .map(|m| trim_doi(m.as_bytes()))
+        .filter(|doi| found.insert(normalize_doi(doi)))
+        .collect()
+}
+
+// the punctuation around a DOI in a sentence is matched by the pattern
+fn trim_doi(doi: &[u8]) -> String {
+    String::from_utf8_lossy(doi)
+        .trim_end_matches(['.', ',', ';', ':', ')'])
+        .to_owned()
+}
+
+// the text of the first `rdf:li` element, such as `<rdf:li xml:lang="x-default">Title</rdf:li>`
+fn xmp_text(xml: &[u8]) -> Option<String> {
+    let li = between(xml, b"<rdf:li", b"</rdf:li>")?;
+    let text = &li[find(li, b">")? + 1..];
+    Some(
+        String::from_utf8_lossy(text)
+            .replace("&amp;", "&")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .trim()
+            .to_owned(),
+    )
+}
+
+// a string value in the document information dictionary, such as `/Title (Title)` or the
+// hexadecimal `/Title <FEFF0054>`
+fn info_string(bytes: &[u8], key: &[u8]) -> Option<String> {
+    let start = find(bytes, key)? + key.len();
+    let rest = &bytes[start..];
+    let open = rest.iter().position(|b| !b.is_ascii_whitespace())?;
+    let value = match rest[open] {
+        b'(' => literal_string(&rest[open..])?.0,
+        b'<' => hex_string(&rest[open + 1..])?,
+        _ => return None,
+    };
+    Some(text_string(&value))
+}
+
+// the digits of a hexadecimal string after the `<`, where a missing last digit is 0
+fn hex_string(bytes: &[u8]) -> Option<Vec<u8>> {
+    let end = bytes.iter().position(|b| *b == b'>')?;
+    let digits = bytes[..end]
+        .iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| char::from(*b).to_digit(16))
+        .collect::<Option<Vec<_>>>()?;
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] * 16 + pair.get(1).copied().unwrap_or(0))
+            .map(|byte| u8::try_from(byte).expect("Two hexadecimal digits are a byte"))
+            .collect(),
+    )
+}
+
+// a text string is UTF-16BE when it starts with the byte order mark, otherwise it is read as
+// UTF-8, which covers the ASCII of PDFDocEncoding
+fn text_string(value: &[u8]) -> String {
+    match value.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => {
+            let units = utf16
+                .chunks(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        None => String::from_utf8_lossy(value).into_owned(),
+    }
+}
+
+// parses a literal string starting at the `(` and returns the bytes and the length read
+fn literal_string(bytes: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut value = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'\\' => {
+                i += 1;
+                match bytes.get(i)? {
+                    b'n' => value.push(b'\n'),
+                    b'r' => value.push(b'\r'),
+                    b't' => value.push(b'\t'),
+                    &escaped => value.push(escaped),
+                }
+            }
+            b'(' => {
+                if depth > 0 {
+                    value.push(b);
+                }
+                depth += 1;
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((value, i + 1));
+                }
+                value.push(b);
+            }
+            _ => value.push(b),
+        }
+        i += 1;
+    }
+    None
+}
+
+// the text shown in each content stream, decompressing the streams that use `/FlateDecode`
+fn streams(bytes: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let mut rest = bytes;
+    std::iter::from_fn(move || loop {
+        let start = find(rest, b"stream")?;
+        let dict = &rest[..start];
+        let mut data_start = start + b"stream".len();
+        if rest[data_start..].starts_with(b"\r\n") {
+            data_start += 2;
+        } else if rest[data_start..].starts_with(b"\n") {
+            data_start += 1;
+        }
+        let len = find(&rest[data_start..], b"endstream")?;
+        let data = &rest[data_start..data_start + len];
+        rest = &rest[data_start + len + b"endstream".len()..];
+
+        // only the dictionary of this stream, after the last object before it
+        let dict = find_last(dict, b"obj").map_or(dict, |i| &dict[i..]);
+        let content = if find(dict, b"/FlateDecode").is_some() {
+            match miniz_oxide::inflate::decompress_to_vec_zlib(data) {
+                Ok(content) => content,
+                Err(_) => continue,
+            }
+        } else if find(dict, b"/Filter").is_some() {
+            // other filters are used for images and fonts rather than text
+            continue;
+        } else {
+            data.to_vec()
+        };
+        return Some(shown_text(&content));
+    })
+}
+
+fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+// joins the literal strings shown by the text operators, the strings in a `TJ` array are joined
+// without a space as they are parts of the same text
+fn shown_text(content: &[u8]) -> Vec<u8> {
+    let mut text = Vec::new();
+    let mut in_array = false;
+    let mut i = 0;
+
+    while let Some(&b) = content.get(i) {
+        match b {
+            b'[' => in_array = true,
+            b']' => {
+                in_array = false;
+                text.push(b' ');
+            }
+            b'(' => {
+                let Some((value, len)) = literal_string(&content[i..]) else {
+                    break;
+                };
+                text.extend(value);
+                if !in_array {
+                    text.push(b' ');
+                }
+                i += len;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doi_from_xmp_metadata() {
+        let pdf = b"%PDF-1.7\n1 0 obj\n<< /Type /Metadata /Subtype /XML >>\nstream\n\
+            <x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><dc:title><rdf:Alt>\
+            <rdf:li xml:lang=\"x-default\">QuickXsort &amp; more</rdf:li></rdf:Alt></dc:title>\
+            <prism:doi>10.1007/s00453-019-00634-0</prism:doi></x:xmpmeta>\nendstream\nendobj\n%%EOF";
+
+        assert_eq!(
+            PdfInfo {
+                doi: Some("10.1007/s00453-019-00634-0".to_owned()),
+                title: Some("QuickXsort & more".to_owned()),
+            },
+            parse(pdf).unwrap()
+        );
+    }
+
+    #[test]
+    fn doi_from_page_text() {
+        let pdf = b"%PDF-1.4\n4 0 obj\n<< /Length 60 >>\nstream\nBT (An article) Tj \
+            [(https://doi.org/10.1000/) -20 (182.)] TJ ET\nendstream\nendobj\n\
+            5 0 obj\n<< /Title (An article) >>\nendobj\n%%EOF";
+
+        assert_eq!(
+            PdfInfo {
+                doi: Some("10.1000/182".to_owned()),
+                title: Some("An article".to_owned()),
+            },
+            parse(pdf).unwrap()
+        );
+    }
+
+    #[test]
+    fn utf16_info_strings_are_decoded() {
+        let pdf = b"%PDF-1.4\n5 0 obj\n<< /Title (\xFE\xFF\x00G\x00\xF6\x00d\x00e\x00l) \
+            /doi <FEFF 0031 0030 002E 0031 0030 0030 0030 002F 0031 0038 0032> >>\nendobj\n%%EOF";
+
+        assert_eq!(
+            PdfInfo {
+                doi: Some("10.1000/182".to_owned()),
+                title: Some("G\u{f6}del".to_owned()),
+            },
+            parse(pdf).unwrap()
+        );
+    }
+
+    #[test]
+    fn dois_in_text() {
+        let text = "See doi:10.1000/182, (https://doi.org/10.1007/s00453-019-00634-0) and \
+            10.1000/ABC.\nAgain 10.1000/182; 10.1000/abc and 10.12/short.";
+
+        assert_eq!(
+            vec!["10.1000/182", "10.1007/s00453-019-00634-0", "10.1000/ABC"],
+            find_dois(text)
+        );
+        assert!(find_dois("No identifiers here").is_empty());
+    }
+
+    #[test]
+    fn title_without_doi() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<< /Title (A \\(draft\\) report) >>\nendobj\n%%EOF";
+
+        assert_eq!(
+            PdfInfo {
+                doi: None,
+                title: Some("A (draft) report".to_owned()),
+            },
+            parse(pdf).unwrap()
+        );
+    }
+
+    #[test]
+    fn encrypted_and_invalid_files_are_errors() {
+        let encrypted = b"%PDF-1.4\ntrailer\n<< /Root 1 0 R /Encrypt 2 0 R >>\n%%EOF";
+
+        assert!(parse(encrypted).is_err());
+        assert!(parse(b"not a pdf").is_err());
+    }
+}
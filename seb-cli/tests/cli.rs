@@ -1,4 +1,5 @@
 use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
 use std::process::Command;
 
 // We check the --help output in order to confirm that the clap cli is setup correctly.
@@ -18,3 +19,303 @@ fn check_clap_cli_help() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn quiet_flag_writes_nothing_to_stdout_or_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let file = dir.child("refs.bib");
+    file.write_str("@manual{cite, title = {Title}}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--quiet", "--file", "refs.bib", "list"]);
+    cmd.assert().success().stdout("").stderr("");
+
+    // the log file cannot be opened which is only a warning
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path()).args([
+        "--quiet",
+        "--file",
+        "refs.bib",
+        "--log-file",
+        "missing/seb.log",
+        "rm",
+        "cite",
+    ]);
+    cmd.assert().success().stdout("").stderr("");
+
+    Ok(())
+}
+
+#[test]
+fn quiet_flag_still_writes_errors_to_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let file = dir.child("refs.bib");
+    // an article requires more than a title
+    file.write_str("@article{cite, title = {Title}}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--quiet", "--file", "refs.bib", "rm", "cite"]);
+    let output = cmd.output()?;
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn list_file_with_incomplete_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let file = dir.child("refs.bib");
+    // an article requires more than a title
+    let content = "@article{incomplete, title = {Incomplete}}\n@manual{manual, title = {Manual}}\n";
+    file.write_str(content)?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "refs.bib", "list"]);
+    cmd.assert()
+        .success()
+        .stdout("incomplete: Incomplete\nmanual: Manual\n");
+
+    // an export names each entry that is missing fields
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "refs.bib", "export", "--format", "bibtex"]);
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("'incomplete' is missing"), "{stderr}");
+    assert!(!stderr.contains("'manual'"), "{stderr}");
+
+    // the file is left unchanged
+    file.assert(content);
+
+    Ok(())
+}
+
+#[test]
+fn parse_error_and_not_found_have_different_exit_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let invalid = dir.child("invalid.bib");
+    invalid.write_str("this is not BibTeX")?;
+    let valid = dir.child("valid.bib");
+    valid.write_str("@manual{cite, title = {Title}}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "invalid.bib", "list"]);
+    cmd.assert().code(5);
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path()).args([
+        "--file",
+        "valid.bib",
+        "derive",
+        "missing",
+        "manual",
+        "derived",
+    ]);
+    cmd.assert().code(3);
+
+    Ok(())
+}
+
+#[test]
+fn list_across_two_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("one.bib")
+        .write_str("@manual{a, title = {Rust in Action}}\n@manual{b, title = {Other}}\n")?;
+    dir.child("two.bib")
+        .write_str("@manual{c, title = {Programming Rust}}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path()).args([
+        "--file",
+        "one.bib",
+        "--file",
+        "two.bib",
+        "list",
+        "--where",
+        "title contains rust",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout("a: Rust in Action (one.bib)\nc: Programming Rust (two.bib)\n");
+
+    // changing the bibliography needs a single file
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "one.bib", "--file", "two.bib", "rm", "a"]);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn export_across_two_files_has_no_file_field() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("one.bib")
+        .write_str("@manual{a, title = {Rust in Action}}\n")?;
+    dir.child("two.bib")
+        .write_str("@manual{c, title = {Programming Rust}}\n")?;
+
+    for format in ["bibtex", "jsonl"] {
+        let mut cmd = Command::cargo_bin("seb")?;
+        cmd.current_dir(dir.path()).args([
+            "--file", "one.bib", "--file", "two.bib", "export", "--format", format,
+        ]);
+        let output = cmd.output()?;
+        assert!(output.status.success());
+
+        let exported = String::from_utf8(output.stdout)?;
+        assert!(exported.contains("Programming Rust"), "{exported}");
+        assert!(!exported.contains("x-seb-file"), "{exported}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_count_only_prints_the_number_of_matches() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("refs.bib").write_str(
+        "@manual{a, title = {Rust in Action}}\n@manual{b, title = {Other}}\n\
+         @manual{c, title = {Programming Rust}}\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path()).args([
+        "--file",
+        "refs.bib",
+        "list",
+        "--where",
+        "title contains rust",
+        "--count-only",
+    ]);
+    cmd.assert().success().stdout("2\n");
+
+    Ok(())
+}
+
+#[test]
+fn parse_error_suggests_check_command() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("invalid.bib").write_str("this is not BibTeX")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "invalid.bib", "list"]);
+    let output = cmd.output()?;
+
+    assert_eq!(Some(5), output.status.code());
+    assert!(String::from_utf8(output.stderr)?
+        .ends_with("\nhint: Run `seb check` to find the entries that are not valid BibTeX\n"));
+
+    Ok(())
+}
+
+#[test]
+fn missing_cite_key_suggests_keys_command() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("refs.bib")
+        .write_str("@manual{a, title = {A}}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "refs.bib", "copy", "missing"]);
+    let output = cmd.output()?;
+
+    assert_eq!(Some(3), output.status.code());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("No entry found with the cite key of 'missing'"));
+    assert!(stderr.ends_with("\nhint: Run `seb keys` to list the cite keys of the bibliography\n"));
+
+    Ok(())
+}
+
+#[test]
+fn latex_escapes_are_kept_unless_switching_to_utf8() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let file = dir.child("refs.bib");
+    file.write_str(
+        "@manual{muller, title = {M{\\\"u}ller and J{\\'e}r{\\^o}me}}\n\
+         @manual{other, title = {Other}}\n",
+    )?;
+    let format = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("seb")?;
+        cmd.current_dir(dir.path())
+            .args(["--file", "refs.bib"])
+            .args(args)
+            .arg("format");
+        cmd.assert().success();
+        Ok(std::fs::read_to_string(file.path())?)
+    };
+
+    let formatted = format(&[])?;
+    assert!(formatted.contains("M{\\\"u}ller"), "{formatted}");
+    // formatting the formatted file changes nothing
+    assert_eq!(formatted, format(&[])?);
+
+    let utf8 = format(&["--output-encoding", "utf8"])?;
+    assert!(utf8.contains("Müller and Jérôme"), "{utf8}");
+
+    Ok(())
+}
+
+#[test]
+fn history_records_changes_and_undo() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("seb.toml").write_str("[history]\nlog = true\n")?;
+    dir.child("refs.bib")
+        .write_str("@manual{a, title = {A}}\n")?;
+    let seb = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("seb")?;
+        cmd.current_dir(dir.path())
+            .args(["--file", "refs.bib"])
+            .args(args);
+        let output = cmd.output()?;
+        assert!(output.status.success(), "{args:?}");
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    seb(&["copy", "a", "b"])?;
+    seb(&["undo"])?;
+
+    let history = seb(&["history"])?;
+    let lines = history.lines().collect::<Vec<_>>();
+    assert_eq!(2, lines.len(), "{history}");
+    assert!(lines[0].ends_with(": seb --file refs.bib copy a b - added b"));
+    assert!(lines[1].ends_with(": seb --file refs.bib undo - removed b"));
+
+    Ok(())
+}
+
+#[test]
+fn extract_never_creates_or_overwrites_the_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("paper.aux").write_str("\\citation{a}\n")?;
+
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "missing.bib", "extract", "paper.aux"]);
+    cmd.assert().failure();
+    assert!(!dir.child("missing.bib").path().exists());
+
+    dir.child("refs.bib")
+        .write_str("@manual{a, title = {A}}\n@manual{b, title = {B}}\n")?;
+    let mut cmd = Command::cargo_bin("seb")?;
+    cmd.current_dir(dir.path())
+        .args(["--file", "refs.bib"])
+        .args(["extract", "paper.aux", "--out", "refs.bib"]);
+    cmd.assert().failure();
+    assert_eq!(
+        "@manual{a, title = {A}}\n@manual{b, title = {B}}\n",
+        std::fs::read_to_string(dir.child("refs.bib").path())?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Compares looking up entries by DOI with [`Biblio::find_by_field`], which uses the index, against
+//! scanning every entry with [`Biblio::contains_field`].
+//!
+//! Run with `cargo bench --bench find_by_field`.
+
+use std::{collections::HashMap, hint::black_box, time::Instant};
+
+use seb::ast::{Biblio, Entry, Manual};
+
+const ENTRIES: usize = 10_000;
+const LOOKUPS: usize = 1_000;
+
+fn biblio() -> Biblio {
+    Biblio::new(
+        (0..ENTRIES)
+            .map(|i| {
+                Entry::Manual(Manual {
+                    cite: format!("cite{i}"),
+                    title: format!("Title {i}").into(),
+                    optional: HashMap::from([("doi".to_owned(), format!("10.1000/{i}").into())]),
+                })
+            })
+            .collect(),
+    )
+}
+
+fn time<F: FnMut(&str) -> bool>(name: &str, mut f: F) {
+    let dois = (0..LOOKUPS)
+        .map(|i| format!("10.1000/{}", i * (ENTRIES / LOOKUPS)))
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    for doi in &dois {
+        assert!(black_box(f(doi)));
+    }
+    let elapsed = start.elapsed();
+
+    println!("{name}: {LOOKUPS} lookups in {elapsed:?}");
+}
+
+fn main() {
+    let biblio = biblio();
+
+    time("scan", |doi| biblio.contains_field("doi", |f| &**f == doi));
+    time("index", |doi| !biblio.find_by_field("doi", doi).is_empty());
+}
@@ -0,0 +1,61 @@
+//! Compares finding one entry of a large bibliography file by parsing the whole file, by parsing
+//! only the entry with [`BibTex::parse_entry`] and by streaming the file with
+//! [`FormatFile::find_entry`], which never reads the whole file into memory.
+//!
+//! Run with `cargo bench --bench find_entry --features file`.
+
+use std::{fmt::Write, hint::black_box, time::Instant};
+
+use seb::{
+    file::FormatFile,
+    format::{BibTex, Reader},
+};
+
+const ENTRIES: usize = 100_000;
+
+fn bibliography() -> String {
+    (0..ENTRIES).fold(String::new(), |mut bibtex, i| {
+        writeln!(
+            bibtex,
+            "@article{{cite{i},\n  author = {{Author {i}}},\n  title = {{Title {i}}},\n  \
+             journal = {{Journal}},\n  year = {{2000}},\n  abstract = {{{}}}\n}}\n",
+            "A long abstract. ".repeat(20)
+        )
+        .expect("writing to a String cannot fail");
+        bibtex
+    })
+}
+
+fn time<F: FnMut(&mut FormatFile<BibTex>) -> bool>(name: &str, path: &str, mut f: F) {
+    let mut file = FormatFile::<BibTex>::open(path).expect("the bibliography file was written");
+
+    let start = Instant::now();
+    assert!(black_box(f(&mut file)));
+    let elapsed = start.elapsed();
+
+    println!("{name}: {elapsed:?}");
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("seb-find-entry-bench.bib");
+    std::fs::write(&path, bibliography()).expect("the temp directory is writable");
+    let path = path.to_str().expect("the temp directory is UTF-8");
+    let first = "cite0";
+    let last = format!("cite{}", ENTRIES - 1);
+
+    time("parse", path, |file| {
+        let biblio = file.read_ast().unwrap().unwrap();
+        biblio.get(&last).is_some()
+    });
+    time("parse_entry (last)", path, |file| {
+        file.read().unwrap().parse_entry(&last).unwrap().is_some()
+    });
+    time("find_entry (first)", path, |file| {
+        file.find_entry(first).unwrap().is_some()
+    });
+    time("find_entry (last)", path, |file| {
+        file.find_entry(&last).unwrap().is_some()
+    });
+
+    let _ = std::fs::remove_file(path);
+}
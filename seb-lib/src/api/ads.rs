@@ -0,0 +1,203 @@
+use log::{info, trace};
+use serde::Deserialize;
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, Resolver},
+    Error, ErrorKind,
+};
+
+use super::Client;
+
+const ADS_URL: &str = "https://api.adsabs.harvard.edu/v1/search/query";
+const ADS_FIELDS: &str = "bibcode,title,author,pub,year,volume,page,doi";
+
+/// The environment variable that contains the ADS API token.
+pub const ADS_TOKEN_VAR: &str = "SEB_ADS_TOKEN";
+
+pub(crate) fn get_entries_by_bibcode<C: Client>(
+    bibcode: &str,
+    token: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    info!("Searching for bibcode '{bibcode}' using the ADS API");
+    // bibcodes can contain '&', such as in the A&A journal abbreviation
+    let url = format!(
+        "{ADS_URL}?q=bibcode:{}&fl={ADS_FIELDS}",
+        bibcode.replace('&', "%26").replace('+', "%2B")
+    );
+
+    let client = C::default();
+    let authorization = format!("Bearer {token}");
+    let AdsModel { response } =
+        client.get_json_with_headers(&url, &[("Authorization", &authorization)])?;
+
+    trace!("Request was successful");
+
+    let doc = response.docs.into_iter().next().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NoValue,
+            format!("No entries found with a bibcode of {bibcode}"),
+        )
+    })?;
+
+    Ok(Biblio::try_resolve(vec![Resolver::from(doc)]))
+}
+
+/// Reads the ADS API token from the [`ADS_TOKEN_VAR`] environment variable.
+pub(crate) fn token() -> Result<String, Error> {
+    std::env::var(ADS_TOKEN_VAR)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .ok_or_else(|| {
+            Error::new(ErrorKind::NoValue, "The ADS API requires a token").with_hint(format!(
+                "Create a token at https://ui.adsabs.harvard.edu/user/settings/token and set it \
+                in the {ADS_TOKEN_VAR} environment variable"
+            ))
+        })
+}
+
+#[derive(Deserialize)]
+struct AdsModel {
+    response: Response,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    docs: Vec<Doc>,
+}
+
+#[derive(Deserialize)]
+struct Doc {
+    bibcode: String,
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<String>,
+    #[serde(rename = "pub")]
+    journal: Option<String>,
+    year: Option<String>,
+    volume: Option<String>,
+    #[serde(default)]
+    page: Vec<String>,
+    #[serde(default)]
+    doi: Vec<String>,
+}
+
+impl From<Doc> for Resolver {
+    fn from(doc: Doc) -> Self {
+        let Doc {
+            bibcode,
+            title,
+            author,
+            journal,
+            year,
+            volume,
+            page,
+            doi,
+        } = doc;
+
+        let mut resolver = ast::Entry::resolver_with_cite(ast::EntryKind::Article, &bibcode);
+
+        // missing required fields are left for the user to resolve
+        if let Some(title) = title.into_iter().next() {
+            resolver.title(title);
+        }
+        if !author.is_empty() {
+            resolver.author(author.join(" and "));
+        }
+        if let Some(journal) = journal {
+            resolver.journal(journal);
+        }
+        if let Some(year) = year {
+            resolver.year(year);
+        }
+        if let Some(volume) = volume {
+            resolver.set_field("volume", volume);
+        }
+        if let Some(page) = page.into_iter().next() {
+            resolver.pages(page);
+        }
+        if let Some(doi) = doi.into_iter().next() {
+            resolver.set_field("doi", doi);
+        }
+        resolver.set_field("bibcode", bibcode);
+
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient},
+        ast::FieldQuery,
+        ErrorKind,
+    };
+
+    impl_text_producer! {
+        ArticleProducer => Ok(
+            r#"{
+                "response": {
+                    "numFound": 1,
+                    "docs": [{
+                        "bibcode": "2019A&A...623A..72S",
+                        "title": ["A test article"],
+                        "author": ["Smith, J.", "Jones, A."],
+                        "pub": "Astronomy and Astrophysics",
+                        "year": "2019",
+                        "volume": "623",
+                        "page": ["A72"],
+                        "doi": ["10.1051/0004-6361/201834371"]
+                    }]
+                }
+            }"#.to_owned()
+        ),
+        NotFoundProducer => Ok(r#"{"response": {"numFound": 0, "docs": []}}"#.to_owned()),
+    }
+
+    #[test]
+    fn url_format_is_correct() {
+        assert!(
+            super::get_entries_by_bibcode::<MockClient<ArticleProducer>>(
+                "2019A&A...623A..72S",
+                "token"
+            )
+            .is_ok()
+        );
+        assert_url!("https://api.adsabs.harvard.edu/v1/search/query?q=bibcode:2019A%26A...623A..72S&fl=bibcode,title,author,pub,year,volume,page,doi");
+    }
+
+    #[test]
+    fn doc_maps_to_article_with_bibcode() {
+        let biblio = super::get_entries_by_bibcode::<MockClient<ArticleProducer>>(
+            "2019A&A...623A..72S",
+            "token",
+        )
+        .expect("ArticleProducer produces valid json")
+        .expect("All required article fields are present");
+
+        let entry = biblio
+            .get("2019A&A...623A..72S")
+            .expect("bibcode is the cite");
+        assert_eq!(
+            "Smith, J. and Jones, A.",
+            &**entry.get_field("author").unwrap()
+        );
+        assert_eq!(
+            "Astronomy and Astrophysics",
+            &**entry.get_field("journal").unwrap()
+        );
+        assert_eq!(
+            "2019A&A...623A..72S",
+            &**entry.get_field("bibcode").unwrap()
+        );
+        assert_eq!("A72", &**entry.get_field("pages").unwrap());
+    }
+
+    #[test]
+    fn unknown_bibcode_is_no_value_error() {
+        let err = super::get_entries_by_bibcode::<MockClient<NotFoundProducer>>("unknown", "token")
+            .expect_err("No docs are returned");
+
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+}
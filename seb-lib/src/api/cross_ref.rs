@@ -1,20 +1,87 @@
+use std::sync::{Mutex, Once};
+
+use log::{info, warn};
 use serde::Deserialize;
 
 use crate::{
-    api::format_api,
-    ast::{Biblio, BiblioResolver},
+    api::{encode, format_api},
+    ast::{normalize_doi, Biblio, BiblioResolver},
     format::BibTex,
     Error, ErrorKind,
 };
 
 use super::Client;
 
+/// The environment variable that contains the contact email address sent to the Crossref API.
+///
+/// Crossref serves the requests that include a contact email address from its faster "polite"
+/// pool of servers.
+pub const CONTACT_EMAIL_VAR: &str = "SEB_CONTACT_EMAIL";
+
+static SUGGEST_EMAIL: Once = Once::new();
+
+static CONTACT_EMAIL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the contact email address that every following request to the Crossref API is sent with,
+/// which is read from the [`CONTACT_EMAIL_VAR`] environment variable until this is called.
+pub fn set_contact_email(email: &str) {
+    *CONTACT_EMAIL
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(email.to_owned());
+}
+
+/// Returns the contact email address set by [`set_contact_email`], or else the one in the
+/// [`CONTACT_EMAIL_VAR`] environment variable.
+///
+/// A suggestion to set the email address is logged once when it is not set.
+pub(crate) fn contact_email() -> Option<String> {
+    let email = CONTACT_EMAIL
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+        .or_else(|| std::env::var(CONTACT_EMAIL_VAR).ok())
+        .filter(|email| !email.trim().is_empty());
+    if email.is_none() {
+        SUGGEST_EMAIL.call_once(|| {
+            info!(
+                "Set a contact email address in the {CONTACT_EMAIL_VAR} environment variable to \
+                use the faster Crossref API servers"
+            );
+        });
+    }
+    email
+}
+
+// Adds the encoded email address as the `mailto` query parameter to the `url`, which may already
+// have a query string.
+fn with_mailto(url: String, mailto: Option<&str>) -> String {
+    match mailto.map(encode) {
+        Some(email) if url.contains('?') => format!("{url}&mailto={email}"),
+        Some(email) => format!("{url}?mailto={email}"),
+        None => url,
+    }
+}
+
+// The `User-Agent` Crossref asks clients to send, which includes the contact email address.
+fn user_agent(mailto: Option<&str>) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    mailto.map_or_else(
+        || format!("seb/{version} (https://github.com/mc1098/seb)"),
+        |email| format!("seb/{version} (https://github.com/mc1098/seb; mailto:{email})"),
+    )
+}
+
 #[inline]
 pub(crate) fn get_entries_by_doi<C: Client>(
     doi: &str,
+    mailto: Option<&str>,
 ) -> Result<Result<Biblio, BiblioResolver>, Error> {
-    let url = format!("https://api.crossref.org/works/{doi}/transform/application/x-bibtex");
-    format_api::get_entry_by_url::<C, BibTex>(&url)
+    let url = with_mailto(
+        format!("https://api.crossref.org/works/{doi}/transform/application/x-bibtex"),
+        mailto,
+    );
+    let user_agent = user_agent(mailto);
+    format_api::get_entry_by_url_with_headers::<C, BibTex>(&url, &[("User-Agent", &user_agent)])
 }
 
 #[derive(Deserialize)]
@@ -30,13 +97,16 @@ struct Message {
 #[derive(Deserialize)]
 struct EntryStub {
     #[serde(rename = "DOI")]
-    doi: String,
+    doi: Option<String>,
+    #[serde(default)]
     title: Vec<String>,
 }
 
 impl EntryStub {
-    fn into_tuple(mut self) -> (String, String) {
-        (self.doi, self.title.remove(0))
+    // A stub without a DOI cannot be used to find the entry and is skipped.
+    fn into_tuple(self) -> Option<(String, String)> {
+        let title = self.title.into_iter().next().unwrap_or_default();
+        self.doi.map(|doi| (doi, title))
     }
 }
 
@@ -46,11 +116,16 @@ impl EntryStub {
 /// entry - the DOI can be used later with `get_entries_by_doi` function.
 pub(crate) fn get_entry_stubs_by_title<C: Client>(
     title: &str,
+    mailto: Option<&str>,
 ) -> Result<Vec<(String, String)>, Error> {
-    let url = format!("https://api.crossref.org/works?query.title={title}&select=DOI,title");
+    let url = with_mailto(
+        format!("https://api.crossref.org/works?query.title={title}&select=DOI,title"),
+        mailto,
+    );
     let client = C::default();
 
-    let query_result: QueryResult = client.get_json(&url)?;
+    let query_result: QueryResult =
+        client.get_json_with_headers(&url, &[("User-Agent", &user_agent(mailto))])?;
     let items = query_result.message.items;
     // check for empty array of items
     if items.is_empty() {
@@ -59,10 +134,105 @@ pub(crate) fn get_entry_stubs_by_title<C: Client>(
             format!("No entries found with a title of {title}"),
         ))
     } else {
-        Ok(items.into_iter().map(EntryStub::into_tuple).collect())
+        let count = items.len();
+        let stubs: Vec<_> = items
+            .into_iter()
+            .filter_map(EntryStub::into_tuple)
+            .collect();
+        if stubs.len() < count {
+            warn!(
+                "{} of the Crossref results for '{title}' have no DOI and are skipped",
+                count - stubs.len()
+            );
+        }
+        Ok(stubs)
     }
 }
 
+/// An editorial notice that a work should not be relied on, which Crossref records as an update
+/// to the work, including the notices collected by Retraction Watch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Retraction {
+    /// An expression of concern about the work, which may be followed by a retraction.
+    ExpressionOfConcern,
+    /// The work has been retracted, withdrawn or removed, in whole or in part.
+    Retracted,
+}
+
+impl Retraction {
+    // the Crossref update types, any other update, such as a correction, is not a retraction
+    fn from_update(kind: &str) -> Option<Self> {
+        match kind {
+            "retraction" | "partial_retraction" | "withdrawal" | "removal" => Some(Self::Retracted),
+            "expression_of_concern" => Some(Self::ExpressionOfConcern),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Retraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ExpressionOfConcern => f.write_str("expression of concern"),
+            Self::Retracted => f.write_str("retracted"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UpdateResult {
+    message: UpdateMessage,
+}
+
+#[derive(Deserialize)]
+struct UpdateMessage {
+    items: Vec<Notice>,
+}
+
+#[derive(Deserialize)]
+struct Notice {
+    #[serde(rename = "update-to", default)]
+    update_to: Vec<Update>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    #[serde(rename = "DOI")]
+    doi: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Searches for the notices that update the work with the `doi` and returns the most serious
+/// [`Retraction`], or `None` when the work has not been retracted.
+///
+/// The `doi` is normalized, see [`normalize_doi`], so that a DOI written as a link is found.
+pub(crate) fn get_retraction<C: Client>(
+    doi: &str,
+    mailto: Option<&str>,
+) -> Result<Option<Retraction>, Error> {
+    let doi = normalize_doi(doi);
+    let url = with_mailto(
+        format!(
+            "https://api.crossref.org/works?filter=updates:{}&select=DOI,update-to",
+            encode(&doi)
+        ),
+        mailto,
+    );
+    let result: UpdateResult =
+        C::default().get_json_with_headers(&url, &[("User-Agent", &user_agent(mailto))])?;
+
+    Ok(result
+        .message
+        .items
+        .into_iter()
+        .flat_map(|notice| notice.update_to)
+        // a notice can update several works
+        .filter(|update| normalize_doi(&update.doi) == doi)
+        .filter_map(|update| Retraction::from_update(&update.kind))
+        .max())
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -70,16 +240,48 @@ mod test {
         ErrorKind,
     };
 
-    use super::QueryResult;
+    use super::{QueryResult, Retraction};
 
     const ENTRY_STUB_JSON: &str = include_str!("../../tests/data/crossref_entry_stub.json");
 
     #[test]
     fn by_doi_url_format_is_correct() {
-        assert!(super::get_entries_by_doi::<MockClient>("balloons").is_err());
+        assert!(super::get_entries_by_doi::<MockClient>("balloons", None).is_err());
         assert_url!("https://api.crossref.org/works/balloons/transform/application/x-bibtex");
     }
 
+    #[test]
+    fn contact_email_is_sent_as_mailto() {
+        assert!(
+            super::get_entries_by_doi::<MockClient>("balloons", Some("me@example.com")).is_err()
+        );
+        assert_url!(
+            "https://api.crossref.org/works/balloons/transform/application/x-bibtex\
+            ?mailto=me%40example.com"
+        );
+
+        assert!(
+            super::get_entry_stubs_by_title::<MockClient>("test", Some("me@example.com")).is_err()
+        );
+        assert_url!(
+            "https://api.crossref.org/works?query.title=test&select=DOI,title\
+            &mailto=me%40example.com"
+        );
+
+        // an address with query characters cannot add parameters
+        assert!(super::get_entries_by_doi::<MockClient>("balloons", Some("a+b&c@x.org")).is_err());
+        assert_url!(
+            "https://api.crossref.org/works/balloons/transform/application/x-bibtex\
+            ?mailto=a%2Bb%26c%40x.org"
+        );
+    }
+
+    #[test]
+    fn user_agent_includes_contact_email() {
+        assert!(super::user_agent(Some("me@example.com")).ends_with("; mailto:me@example.com)"));
+        assert!(!super::user_agent(None).contains("mailto"));
+    }
+
     #[test]
     fn json_can_be_deserialized_to_query_result() {
         let qr: QueryResult = serde_json::from_str(ENTRY_STUB_JSON).unwrap();
@@ -99,7 +301,7 @@ mod test {
 
     #[test]
     fn valid_json_produces_resolved_biblio() {
-        let res = super::get_entry_stubs_by_title::<MockClient<ValidJsonProducer>>("test")
+        let res = super::get_entry_stubs_by_title::<MockClient<ValidJsonProducer>>("test", None)
             .expect("ValidJsonProducer always produces a valid json String to be deserialized");
 
         assert_eq!(20, res.len());
@@ -109,14 +311,58 @@ mod test {
 
     #[test]
     fn by_title_url_format_is_correct() {
-        assert!(super::get_entry_stubs_by_title::<EmptyItemClient>("My test title").is_err());
+        assert!(super::get_entry_stubs_by_title::<EmptyItemClient>("My test title", None).is_err());
         // Not expecting percent encoding here, the str to URL conversion will do this.
         assert_url!("https://api.crossref.org/works?query.title=My test title&select=DOI,title");
     }
 
+    impl_text_producer! {
+        RetractionProducer => Ok(
+            r#"{
+                "message": {
+                    "items": [
+                        {
+                            "DOI": "10.1000/notice",
+                            "update-to": [
+                                {"DOI": "10.1000/OTHER", "type": "retraction"},
+                                {"DOI": "10.1000/PAPER", "type": "expression_of_concern"},
+                                {"DOI": "10.1000/PAPER", "type": "retraction"}
+                            ]
+                        },
+                        {"DOI": "10.1000/correction", "update-to": [
+                            {"DOI": "10.1000/fixed", "type": "correction"}
+                        ]}
+                    ]
+                }
+            }"#.to_owned()
+        ),
+    }
+
+    #[test]
+    fn retraction_notice_of_the_doi_is_found() {
+        type RetractionClient = MockClient<RetractionProducer>;
+
+        assert_eq!(
+            Some(Retraction::Retracted),
+            super::get_retraction::<RetractionClient>("https://doi.org/10.1000/Paper", None)
+                .unwrap()
+        );
+        assert_url!(
+            "https://api.crossref.org/works?filter=updates:10.1000%2Fpaper&select=DOI,update-to"
+        );
+        assert_eq!(
+            None,
+            super::get_retraction::<RetractionClient>("10.1000/fixed", None).unwrap()
+        );
+        assert_eq!(
+            None,
+            super::get_retraction::<EmptyItemClient>("10.1000/paper", None).unwrap()
+        );
+    }
+
     #[test]
     fn empty_item_returns_no_value_error() {
-        let res = super::get_entry_stubs_by_title::<EmptyItemClient>("test")
+        let res = super::get_entry_stubs_by_title::<EmptyItemClient>("test", None)
             .expect_err("EmptyItemProducer returns an Err");
 
         assert_eq!(ErrorKind::NoValue, res.kind());
@@ -9,21 +9,30 @@ use super::Client;
 pub(crate) fn get_entry_by_url<C: Client, F: Format>(
     url: &str,
 ) -> Result<Result<Biblio, BiblioResolver>, Error> {
-    let client = C::default();
-
-    client
-        .get_text(url)
-        .and_then(|text| {
-            if text.is_empty() {
-                Err(Error::new(
-                    ErrorKind::NoValue,
-                    "Request did not find any results",
-                ))
-            } else {
-                Ok(F::new(text))
-            }
-        })
-        .and_then(Format::parse)
+    parse_response::<F>(C::default().get_text(url))
+}
+
+pub(crate) fn get_entry_by_url_with_headers<C: Client, F: Format>(
+    url: &str,
+    headers: &[(&str, &str)],
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    parse_response::<F>(C::default().get_text_with_headers(url, headers))
+}
+
+fn parse_response<F: Format>(
+    text: Result<String, Error>,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    text.and_then(|text| {
+        if text.is_empty() {
+            Err(Error::new(
+                ErrorKind::NoValue,
+                "Request did not find any results",
+            ))
+        } else {
+            Ok(F::new(text))
+        }
+    })
+    .and_then(Format::parse)
 }
 
 #[cfg(test)]
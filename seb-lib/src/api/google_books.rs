@@ -1,4 +1,4 @@
-use log::{info, trace};
+use log::{info, trace, warn};
 use serde::Deserialize;
 
 use crate::{
@@ -42,6 +42,8 @@ pub(crate) fn get_book_info<C: Client>(isbn: String) -> Result<Book, Error> {
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(Debug))]
 struct GoogleModel {
+    // the API leaves out the items when no books are found
+    #[serde(default)]
     items: Vec<Item>,
 }
 
@@ -50,7 +52,7 @@ struct GoogleModel {
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(Debug))]
 struct Item {
-    #[serde(rename = "volumeInfo")]
+    #[serde(rename = "volumeInfo", default)]
     volume_info: VolumeInfo,
 }
 
@@ -61,14 +63,17 @@ pub(crate) struct Book {
 }
 
 /// Volume information from the Google Book API
-#[derive(Deserialize)]
+///
+/// Every field is optional so that a field missing from the response leaves a partial entry.
+#[derive(Default, Deserialize)]
 #[cfg_attr(test, derive(Debug))]
+#[serde(default)]
 struct VolumeInfo {
     authors: Vec<String>,
-    title: String,
-    publisher: String,
+    title: Option<String>,
+    publisher: Option<String>,
     #[serde(rename = "publishedDate")]
-    published_date: String,
+    published_date: Option<String>,
 }
 
 impl Item {
@@ -91,7 +96,7 @@ impl TryFrom<Book> for Resolver {
             isbn,
             volume_info:
                 VolumeInfo {
-                    mut authors,
+                    authors,
                     title,
                     publisher,
                     published_date,
@@ -99,36 +104,58 @@ impl TryFrom<Book> for Resolver {
         } = book;
 
         let mut resolver = ast::Entry::resolver(ast::EntryKind::Book);
+        let mut missing = Vec::new();
+
+        if let Some(published_date) = published_date {
+            // date_parts = Year-Month-Day, where Day is not often used.
+            let mut date_parts = published_date.split('-');
+
+            // a date in another format is left out rather than risk an invalid date in the entry
+            if let Some(year) = date_parts.next().filter(|s| s.parse::<u16>().is_ok()) {
+                resolver.year(year.to_owned());
+
+                if let Some(month) = date_parts.next().filter(|s| s.parse::<u16>().is_ok()) {
+                    resolver.set_field("month", month);
+                }
+            } else {
+                warn!(
+                    "The Google Books response for ISBN '{isbn}' has the publishedDate \
+                     '{published_date}' in an unexpected format - the entry is partial"
+                );
+            }
+        } else {
+            missing.push("publishedDate");
+        }
 
-        // date_parts = Year-Month-Day, where Day is not often used.
-        let mut date_parts = published_date.split('-');
-
-        let year = date_parts
-            .next()
-            .filter(|s| s.parse::<u16>().is_ok())
-            .ok_or_else(|| {
-                Error::new(
-                    ErrorKind::Deserialize,
-                    "Date format was different then expected - aborting to avoid invalid dates in entry"
-                )
-            })?
-            .to_owned();
-
-        resolver.year(year);
-
-        if let Some(month) = date_parts.next().filter(|s| s.parse::<u16>().is_ok()) {
-            resolver.set_field("month", month);
+        if let Some(title) = title {
+            resolver.title(title);
+        } else {
+            missing.push("title");
         }
 
-        resolver.title(title);
+        let authors = authors
+            .iter()
+            .filter_map(|author| ast::Author::parse(author))
+            .collect::<Vec<_>>();
 
-        authors.retain(|author| !author.is_empty());
+        if authors.is_empty() {
+            missing.push("authors");
+        } else {
+            resolver.author(ast::Author::join(&authors));
+        }
 
-        if !authors.is_empty() {
-            resolver.author(authors.join(","));
+        if let Some(publisher) = publisher {
+            resolver.publisher(publisher);
+        } else {
+            missing.push("publisher");
         }
 
-        resolver.publisher(publisher);
+        if !missing.is_empty() {
+            warn!(
+                "The Google Books response for ISBN '{isbn}' has no {} - the entry is partial",
+                missing.join(", ")
+            );
+        }
         resolver.set_field("isbn", isbn);
 
         Ok(resolver)
@@ -153,6 +180,19 @@ mod tests {
                 "items": []
             }"#.to_owned()
         ),
+        NoItemsProducer => Ok(r#"{"kind": "books#volumes", "totalItems": 0}"#.to_owned()),
+        NoPublisherProducer => Ok(
+            r#"{
+                "items": [{
+                    "volumeInfo": {
+                        "title": "Code Complete",
+                        "authors": ["Steve McConnell"],
+                        "publishedDate": "2004"
+                    }
+                }]
+            }"#.to_owned()
+        ),
+        ChangedSchemaProducer => Ok(r#"{"items": {"volumeInfo": []}}"#.to_owned()),
     }
 
     #[test]
@@ -165,6 +205,37 @@ mod tests {
         drop(err.unwrap());
     }
 
+    #[test]
+    fn no_items_field_returns_err_no_value() {
+        let err = super::get_entries_by_isbn::<MockClient<NoItemsProducer>>("0735619670")
+            .expect_err("a response without items has no books");
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+
+    #[test]
+    fn missing_field_produces_partial_entry() {
+        let mut resolver =
+            super::get_entries_by_isbn::<MockClient<NoPublisherProducer>>("0735619670")
+                .expect("a missing field is not an error")
+                .expect_err("the publisher is required so the entry is partial");
+
+        let entry = resolver.unresolved().next().expect("one partial entry");
+        assert_eq!("Code Complete", &**entry.get_field("title").unwrap());
+        assert_eq!("2004", &**entry.get_field("year").unwrap());
+        assert_eq!("0735619670", &**entry.get_field("isbn").unwrap());
+        assert!(entry.get_field("publisher").is_none());
+    }
+
+    #[test]
+    fn changed_schema_returns_deserialize_error() {
+        let err = super::get_entries_by_isbn::<MockClient<ChangedSchemaProducer>>("0735619670")
+            .expect_err("items is not a list");
+        assert_eq!(ErrorKind::Deserialize, err.kind());
+        assert!(err
+            .to_string()
+            .contains("https://www.googleapis.com/books/v1/volumes"));
+    }
+
     #[test]
     fn isbn_10_url_is_format_is_correct() {
         assert!(super::get_entries_by_isbn::<MockClient<ValidJsonProducer>>("0735619670").is_ok());
@@ -197,27 +268,24 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(
-        expected = "Date format was different then expected - aborting to avoid invalid dates in entry"
-    )]
-    fn invalid_date_format_returns_deserialize_error() {
+    fn invalid_date_format_produces_partial_entry() {
         let ignore = "Ignore".to_owned();
         let item = Item {
             volume_info: VolumeInfo {
                 authors: vec![ignore.clone()],
-                title: ignore.clone(),
-                publisher: ignore.clone(),
-                published_date: "2022@apples".to_owned(),
+                title: Some(ignore.clone()),
+                publisher: Some(ignore.clone()),
+                published_date: Some("2022@apples".to_owned()),
             },
         };
 
         let book = item.build(ignore);
-        let err = Resolver::try_from(book);
-
-        let kind = err.as_ref().map(|_| ()).map_err(Error::kind);
+        let resolver = Resolver::try_from(book).expect("an invalid date is not an error");
 
-        assert_eq!(Err(ErrorKind::Deserialize), kind);
-        drop(err.unwrap());
+        assert!(resolver.get_field("year").is_none());
+        assert!(resolver.get_field("month").is_none());
+        assert_eq!("Ignore", &**resolver.get_field("title").unwrap());
+        assert!(resolver.resolve().is_err());
     }
 
     #[test]
@@ -225,9 +293,9 @@ mod tests {
         let item = Item {
             volume_info: VolumeInfo {
                 authors: vec!["Ignore".to_owned()],
-                title: "Ignore".to_owned(),
-                publisher: "Ignore".to_owned(),
-                published_date: "2002-09-01".to_owned(),
+                title: Some("Ignore".to_owned()),
+                publisher: Some("Ignore".to_owned()),
+                published_date: Some("2002-09-01".to_owned()),
             },
         };
 
@@ -260,8 +328,11 @@ mod tests {
         // ISBN is not in the response so will be the default value until changed.
         assert_eq!(isbn, book.isbn);
         assert_eq!("Steve McConnell", book.volume_info.authors[0]);
-        assert_eq!("Code Complete", book.volume_info.title);
-        assert_eq!("DV-Professional", book.volume_info.publisher);
-        assert_eq!("2004", book.volume_info.published_date);
+        assert_eq!(Some("Code Complete"), book.volume_info.title.as_deref());
+        assert_eq!(
+            Some("DV-Professional"),
+            book.volume_info.publisher.as_deref()
+        );
+        assert_eq!(Some("2004"), book.volume_info.published_date.as_deref());
     }
 }
@@ -0,0 +1,255 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use log::warn;
+use reqwest::{
+    blocking,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Proxy,
+};
+use serde::Deserialize;
+
+use crate::{Error, ErrorKind};
+
+/// The environment variable of the URL of the proxy that every request to a provider API is sent
+/// through.
+pub const PROXY_VAR: &str = "SEB_PROXY";
+
+/// The environment variable of the extra headers sent with every request to a provider API, as
+/// `Name: value` lines.
+pub const HEADERS_VAR: &str = "SEB_HEADERS";
+
+/// How the requests to the provider APIs are sent, such as through the proxy of an institution
+/// and with the headers that it requires for access.
+///
+/// Without a `proxy` the requests are sent through the proxy in the standard `HTTPS_PROXY`,
+/// `HTTP_PROXY` and `ALL_PROXY` environment variables, if any, except to the hosts in `NO_PROXY`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use seb::HttpOptions;
+///
+/// let options = HttpOptions {
+///     proxy: Some("http://proxy.example.com:8080".to_owned()),
+///     headers: HashMap::from([("X-Institution".to_owned(), "campus".to_owned())]),
+/// };
+///
+/// assert!(seb::set_http_options(&options).is_ok());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HttpOptions {
+    /// The URL of the proxy that every request is sent through, such as
+    /// `http://proxy.example.com:8080`.
+    pub proxy: Option<String>,
+    /// The extra headers sent with every request, by name.
+    pub headers: HashMap<String, String>,
+}
+
+impl HttpOptions {
+    /// Fills in the options that are not set from the [`PROXY_VAR`] and [`HEADERS_VAR`]
+    /// environment variables, so that the options already set take precedence.
+    #[must_use]
+    pub fn or_env(mut self) -> Self {
+        if self.proxy.is_none() {
+            self.proxy = std::env::var(PROXY_VAR)
+                .ok()
+                .filter(|proxy| !proxy.trim().is_empty());
+        }
+        if let Ok(headers) = std::env::var(HEADERS_VAR) {
+            for (name, value) in parse_headers(&headers) {
+                self.headers.entry(name).or_insert(value);
+            }
+        }
+        self
+    }
+}
+
+// The `Name: value` lines of the headers, skipping the lines without a name.
+fn parse_headers(headers: &str) -> Vec<(String, String)> {
+    headers
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
+
+static CLIENT: Mutex<Option<HttpClient>> = Mutex::new(None);
+
+/// Sets the proxy and headers that every following request to a provider API is sent with, which
+/// are read from the environment variables, see [`HttpOptions::or_env`], until this is called.
+///
+/// # Errors
+///
+/// An `Err` is returned when the proxy is not a valid URL or a header is not a valid HTTP header,
+/// and the requests are sent as they were before.
+pub fn set_http_options(options: &HttpOptions) -> Result<(), Error> {
+    let client = HttpClient::new(options)?;
+    *CLIENT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(client);
+    Ok(())
+}
+
+/// The client that sends the requests to the provider APIs with the [`HttpOptions`].
+#[derive(Clone)]
+pub(crate) struct HttpClient(pub(super) blocking::Client);
+
+impl HttpClient {
+    pub(crate) fn new(options: &HttpOptions) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &options.headers {
+            let header = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                Error::wrap_with(
+                    ErrorKind::Deserialize,
+                    e,
+                    format!("Invalid header name '{name}'"),
+                )
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                Error::wrap_with(
+                    ErrorKind::Deserialize,
+                    e,
+                    format!("Invalid value of the '{name}' header"),
+                )
+            })?;
+            headers.insert(header, value);
+        }
+
+        let mut builder = blocking::Client::builder().default_headers(headers);
+        if let Some(proxy) = &options.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| {
+                Error::wrap_with(
+                    ErrorKind::Deserialize,
+                    e,
+                    format!("Invalid proxy URL '{proxy}'"),
+                )
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        builder
+            .build()
+            .map(Self)
+            .map_err(|e| Error::wrap(ErrorKind::IO, e))
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        CLIENT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get_or_insert_with(|| {
+                Self::new(&HttpOptions::default().or_env()).unwrap_or_else(|err| {
+                    warn!("Ignoring the {PROXY_VAR} and {HEADERS_VAR} variables: {err}");
+                    Self(blocking::Client::new())
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+    use crate::api::Client;
+
+    const OK: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+    // Accepts a single request and sends the `response`, returning the request line and headers.
+    fn serve_once(listener: TcpListener, response: &'static [u8]) -> JoinHandle<String> {
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            stream.write_all(response).unwrap();
+            request
+        })
+    }
+
+    #[test]
+    fn configured_headers_are_sent_through_the_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let options = HttpOptions {
+            proxy: Some(format!("http://{}", listener.local_addr().unwrap())),
+            headers: HashMap::from([("X-Institution".to_owned(), "campus".to_owned())]),
+        };
+        let proxy = serve_once(listener, OK);
+
+        let client = HttpClient::new(&options).unwrap();
+
+        assert_eq!(
+            "ok",
+            client.get_text("http://api.example.com/works").unwrap()
+        );
+        let request = proxy.join().unwrap().to_lowercase();
+        assert!(request.starts_with("get http://api.example.com/works http/1.1\r\n"));
+        assert!(request.contains("\r\nx-institution: campus\r\n"));
+    }
+
+    #[test]
+    fn error_status_is_an_error_without_the_query() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/search?apikey=secret",
+            listener.local_addr().unwrap()
+        );
+        let server = serve_once(
+            listener,
+            b"HTTP/1.1 403 Forbidden\r\nContent-Length: 18\r\nConnection: close\r\n\r\n\
+            Developer Inactive",
+        );
+
+        let client = HttpClient::new(&HttpOptions::default()).unwrap();
+        let err = client.get_json::<serde_json::Value>(&url).unwrap_err();
+
+        server.join().unwrap();
+        assert_eq!(ErrorKind::IO, err.kind());
+        assert!(!format!("{err:?}").contains("secret"));
+    }
+
+    #[test]
+    fn invalid_options_are_errors() {
+        let header = HttpOptions {
+            proxy: None,
+            headers: HashMap::from([("Bad Name".to_owned(), "value".to_owned())]),
+        };
+        assert!(HttpClient::new(&header).is_err());
+
+        let proxy = HttpOptions {
+            proxy: Some("not a url".to_owned()),
+            headers: HashMap::new(),
+        };
+        assert!(HttpClient::new(&proxy).is_err());
+    }
+
+    #[test]
+    fn headers_are_read_one_per_line() {
+        assert_eq!(
+            vec![
+                ("X-Institution".to_owned(), "campus".to_owned()),
+                ("Cookie".to_owned(), "a=1; b=2".to_owned()),
+            ],
+            parse_headers("X-Institution: campus\n\n: empty\nCookie: a=1; b=2\n")
+        );
+    }
+}
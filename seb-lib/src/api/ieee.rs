@@ -0,0 +1,237 @@
+use log::{info, trace};
+use serde::Deserialize;
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, Resolver},
+    Error, ErrorKind,
+};
+
+use super::Client;
+
+const IEEE_URL: &str = "https://ieeexploreapi.ieee.org/api/v1/search/articles";
+
+/// The environment variable that contains the IEEE Xplore API key.
+pub const IEEE_TOKEN_VAR: &str = "SEB_IEEE_TOKEN";
+
+/// The kind of entry used for standards, which biblatex styles such as biblatex-ieee support.
+pub const STANDARD_KIND: &str = "standard";
+
+/// Splits a standard, such as `IEEE Std 754-2019` or `ISO 8601`, into the organization that
+/// published it and its number.
+///
+/// Returns [`None`] when the standard has no number after the organization.
+pub(crate) fn split_standard(standard: &str) -> Option<(&str, &str)> {
+    let (organization, number) = standard.trim().split_once(char::is_whitespace)?;
+    let number = number.trim_start();
+    // IEEE standards are commonly cited as `IEEE Std 754-2019`
+    let number = ["Std.", "Std"]
+        .iter()
+        .find_map(|std| {
+            number
+                .get(..std.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(std))
+                .map(|_| number[std.len()..].trim_start())
+        })
+        .unwrap_or(number);
+
+    (!number.is_empty()).then_some((organization, number))
+}
+
+pub(crate) fn get_entries_by_standard<C: Client>(
+    number: &str,
+    token: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    info!("Searching for the standard '{number}' using the IEEE Xplore API");
+    let url = format!(
+        "{IEEE_URL}?apikey={}&content_type=Standards&querytext={}",
+        super::encode(token),
+        super::encode(number)
+    );
+
+    let client = C::default();
+    let IeeeModel { articles } = client.get_json(&url)?;
+
+    trace!("Request was successful");
+
+    // without a year the latest revision of the standard is used
+    let article = articles
+        .into_iter()
+        .filter(|article| {
+            article.standard_number.as_deref().is_some_and(|found| {
+                found.eq_ignore_ascii_case(number)
+                    || found
+                        .get(..number.len())
+                        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(number))
+                        && found[number.len()..].starts_with('-')
+            })
+        })
+        .max_by(|a, b| a.publication_year.cmp(&b.publication_year))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NoValue,
+                format!("No IEEE standard found with a number of {number}"),
+            )
+        })?;
+
+    Ok(Biblio::try_resolve(vec![Resolver::from(article)]))
+}
+
+/// Reads the IEEE Xplore API key from the [`IEEE_TOKEN_VAR`] environment variable.
+pub(crate) fn token() -> Result<String, Error> {
+    std::env::var(IEEE_TOKEN_VAR)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .ok_or_else(|| {
+            Error::new(ErrorKind::NoValue, "The IEEE Xplore API requires a key").with_hint(format!(
+                "Register for a key at https://developer.ieee.org and set it in the \
+                {IEEE_TOKEN_VAR} environment variable"
+            ))
+        })
+}
+
+#[derive(Deserialize)]
+struct IeeeModel {
+    #[serde(default)]
+    articles: Vec<Article>,
+}
+
+#[derive(Deserialize)]
+struct Article {
+    standard_number: Option<String>,
+    title: Option<String>,
+    publisher: Option<String>,
+    publication_year: Option<u16>,
+    doi: Option<String>,
+}
+
+impl From<Article> for Resolver {
+    fn from(article: Article) -> Self {
+        let Article {
+            standard_number,
+            title,
+            publisher,
+            publication_year,
+            doi,
+        } = article;
+        let number = standard_number.unwrap_or_default();
+
+        let mut resolver = ast::Entry::resolver_with_cite(
+            ast::EntryKind::Other(STANDARD_KIND.into()),
+            format!("IEEE{number}"),
+        );
+
+        // missing required fields are left for the user to resolve
+        if let Some(title) = title {
+            resolver.title(title);
+        }
+        resolver.set_field("organization", "IEEE");
+        resolver.set_field("number", number);
+        if let Some(year) = publication_year {
+            resolver.year(year.to_string());
+        }
+        if let Some(publisher) = publisher {
+            resolver.set_field("publisher", publisher);
+        }
+        if let Some(doi) = doi {
+            resolver.set_field("doi", doi);
+        }
+
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient},
+        ast::FieldQuery,
+        ErrorKind,
+    };
+
+    impl_text_producer! {
+        StandardProducer => Ok(
+            r#"{
+                "total_records": 2,
+                "articles": [{
+                    "standard_number": "754-2008",
+                    "title": "IEEE Standard for Floating-Point Arithmetic",
+                    "publisher": "IEEE",
+                    "publication_year": 2008,
+                    "doi": "10.1109/IEEESTD.2008.4610935"
+                }, {
+                    "standard_number": "754-2019",
+                    "title": "IEEE Standard for Floating-Point Arithmetic",
+                    "publisher": "IEEE",
+                    "publication_year": 2019,
+                    "doi": "10.1109/IEEESTD.2019.8766229"
+                }, {
+                    "standard_number": "7540-2021",
+                    "title": "Another standard",
+                    "publication_year": 2021
+                }]
+            }"#.to_owned()
+        ),
+        NotFoundProducer => Ok(r#"{"total_records": 0}"#.to_owned()),
+    }
+
+    #[test]
+    fn split_standard_into_organization_and_number() {
+        assert_eq!(
+            Some(("IEEE", "754-2019")),
+            super::split_standard("IEEE Std 754-2019")
+        );
+        assert_eq!(
+            Some(("IEEE", "802.11")),
+            super::split_standard("IEEE 802.11")
+        );
+        assert_eq!(Some(("ISO", "8601")), super::split_standard(" ISO 8601"));
+        assert_eq!(
+            Some(("ISO/IEC", "27001")),
+            super::split_standard("ISO/IEC 27001")
+        );
+        assert_eq!(None, super::split_standard("IEEE"));
+        assert_eq!(None, super::split_standard("IEEE Std"));
+    }
+
+    #[test]
+    fn url_format_is_correct() {
+        assert!(
+            super::get_entries_by_standard::<MockClient<StandardProducer>>("754", "key").is_ok()
+        );
+        assert_url!("https://ieeexploreapi.ieee.org/api/v1/search/articles?apikey=key&content_type=Standards&querytext=754");
+
+        let _ = super::get_entries_by_standard::<MockClient<StandardProducer>>("C37.118 & 1", "k");
+        assert_url!("https://ieeexploreapi.ieee.org/api/v1/search/articles?apikey=k&content_type=Standards&querytext=C37.118%20%26%201");
+    }
+
+    #[test]
+    fn latest_revision_is_used_without_a_year() {
+        let biblio = super::get_entries_by_standard::<MockClient<StandardProducer>>("754", "key")
+            .expect("StandardProducer produces valid json")
+            .expect("All required standard fields are present");
+
+        let entry = biblio.get("IEEE754-2019").expect("number is in the cite");
+        assert_eq!("standard", entry.kind().to_string());
+        assert_eq!("754-2019", &**entry.get_field("number").unwrap());
+        assert_eq!("IEEE", &**entry.get_field("organization").unwrap());
+        assert_eq!("2019", &**entry.get_field("year").unwrap());
+    }
+
+    #[test]
+    fn revision_with_a_year_is_used() {
+        let biblio =
+            super::get_entries_by_standard::<MockClient<StandardProducer>>("754-2008", "key")
+                .expect("StandardProducer produces valid json")
+                .expect("All required standard fields are present");
+
+        assert!(biblio.get("IEEE754-2008").is_some());
+    }
+
+    #[test]
+    fn unknown_standard_is_no_value_error() {
+        let err = super::get_entries_by_standard::<MockClient<NotFoundProducer>>("754", "key")
+            .expect_err("No articles are returned");
+
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+}
@@ -1,26 +1,44 @@
+use log::debug;
 use serde::de::DeserializeOwned;
 
+pub(crate) mod ads;
 pub(crate) mod cross_ref;
 pub(crate) mod format_api;
 pub(crate) mod google_books;
+pub(crate) mod http;
+pub(crate) mod ieee;
 pub(crate) mod ietf;
+pub(crate) mod rate_limit;
+pub(crate) mod unpaywall;
 
 pub trait Client
 where
     Self: Default,
 {
     fn get_text(&self, url: &str) -> Result<String, Error>;
+    fn get_text_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> Result<String, Error>;
     fn get_json<T>(&self, url: &str) -> Result<T, Error>
     where
         T: DeserializeOwned;
+    fn get_json_with_headers<T>(&self, url: &str, headers: &[(&str, &str)]) -> Result<T, Error>
+    where
+        T: DeserializeOwned;
 }
 
-impl Client for reqwest::blocking::Client {
+impl Client for http::HttpClient {
     fn get_text(&self, url: &str) -> Result<String, Error> {
-        let resp = self
-            .get(url)
+        self.get_text_with_headers(url, &[])
+    }
+
+    fn get_text_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> Result<String, Error> {
+        rate_limit::wait(url);
+        let resp = headers
+            .iter()
+            .fold(self.0.get(url), |req, (name, value)| {
+                req.header(*name, *value)
+            })
             .send()
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+            .map_err(io_error)?;
         let text = resp
             .text()
             .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
@@ -36,11 +54,87 @@ impl Client for reqwest::blocking::Client {
     where
         T: DeserializeOwned,
     {
-        self.get(url)
+        rate_limit::wait(url);
+        let text = self
+            .0
+            .get(url)
             .send()
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))
-            .and_then(|r| r.json().map_err(|e| Error::wrap(ErrorKind::Deserialize, e)))
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .and_then(reqwest::blocking::Response::text)
+            .map_err(io_error)?;
+        parse_json(url, &text)
+    }
+
+    fn get_json_with_headers<T>(&self, url: &str, headers: &[(&str, &str)]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        rate_limit::wait(url);
+        headers
+            .iter()
+            .fold(self.0.get(url), |req, (name, value)| {
+                req.header(*name, *value)
+            })
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .and_then(reqwest::blocking::Response::text)
+            .map_err(io_error)
+            .and_then(|text| parse_json(url, &text))
+    }
+}
+
+// The query of the URL of a request may contain the contact email address or an API key, so it is
+// left out of the errors, which are shown to the user and logged.
+fn io_error(mut e: reqwest::Error) -> Error {
+    if let Some(url) = e.url_mut() {
+        url.set_query(None);
     }
+    Error::wrap(ErrorKind::IO, e)
+        .with_hint("Check the network connection, the API of the provider may be unreachable")
+}
+
+// The URL without its query, see `io_error`.
+fn without_query(url: &str) -> &str {
+    url.split('?').next().unwrap_or(url)
+}
+
+/// Percent-encodes the `value` of a query parameter of a URL, so that a value with characters
+/// such as `&`, `#` or a space is sent as it is.
+///
+/// Only the unreserved characters of RFC 3986 are left as they are.
+pub(crate) fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Deserializes the JSON `response` of the `url`.
+///
+/// The models of the responses only require the fields that an entry cannot be found without, so
+/// that a field that is missing or renamed leaves a partial entry, but when the response still
+/// cannot be deserialized it is logged at debug level so that the change can be reported.
+pub(crate) fn parse_json<T>(url: &str, response: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_str(response).map_err(|e| {
+        let url = without_query(url);
+        debug!("Unexpected response from {url}:\n{response}");
+        Error::wrap_with(
+            ErrorKind::Deserialize,
+            e,
+            format!(
+                "The response from {url} is not in the expected format - the response is \
+                logged at debug level"
+            ),
+        )
+    })
 }
 
 #[cfg(test)]
@@ -101,14 +195,23 @@ mod test {
             P::produce()
         }
 
+        fn get_text_with_headers(&self, url: &str, _: &[(&str, &str)]) -> Result<String, Error> {
+            self.get_text(url)
+        }
+
         fn get_json<T>(&self, url: &str) -> Result<T, Error>
         where
             T: DeserializeOwned,
         {
             URL_SINK.with(|sink| *sink.borrow_mut() = Some(url.to_owned()));
-            P::produce().and_then(|json| {
-                serde_json::from_str(&json).map_err(|e| Error::wrap(ErrorKind::Deserialize, e))
-            })
+            P::produce().and_then(|json| parse_json(url, &json))
+        }
+
+        fn get_json_with_headers<T>(&self, url: &str, _: &[(&str, &str)]) -> Result<T, Error>
+        where
+            T: DeserializeOwned,
+        {
+            self.get_json(url)
         }
     }
 
@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use serde::{Deserialize, Deserializer};
+
+/// The number of requests per second sent to each host, which every request to a provider API
+/// waits for so that many lookups, such as enriching a whole bibliography, are not throttled.
+///
+/// The [`Default`] limits are the rates the APIs ask clients to stay under, where they publish
+/// one, and `default` for every other host. The limits of the `hosts` read from a config file are
+/// added to the default limits so only the hosts that change need to be set.
+///
+/// # Examples
+///
+/// ```
+/// use seb::RateLimits;
+///
+/// let limits = RateLimits::default();
+///
+/// assert_eq!(1.0, limits.per_second("www.googleapis.com"));
+/// assert_eq!(limits.default, limits.per_second("example.com"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RateLimits {
+    /// The requests per second sent to a host without its own limit.
+    #[serde(deserialize_with = "rate")]
+    pub default: f64,
+    /// The requests per second sent to each host, such as `api.crossref.org`.
+    #[serde(deserialize_with = "host_rates")]
+    pub hosts: HashMap<String, f64>,
+}
+
+// the hosts of the provider APIs and their rate limits
+const HOST_LIMITS: [(&str, f64); 5] = [
+    // the limit of the Crossref "polite" pool
+    ("api.crossref.org", 10.0),
+    // the default quota of 100 requests every 100 seconds
+    ("www.googleapis.com", 1.0),
+    ("api.adsabs.harvard.edu", 1.0),
+    ("api.unpaywall.org", 10.0),
+    ("datatracker.ietf.org", 2.0),
+];
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            default: 5.0,
+            hosts: HOST_LIMITS
+                .iter()
+                .map(|(host, rate)| ((*host).to_owned(), *rate))
+                .collect(),
+        }
+    }
+}
+
+impl RateLimits {
+    /// Returns the number of requests per second sent to the `host`.
+    #[must_use]
+    pub fn per_second(&self, host: &str) -> f64 {
+        self.hosts.get(host).copied().unwrap_or(self.default)
+    }
+
+    // the least time between two requests to the `host`
+    fn interval(&self, host: &str) -> Duration {
+        Duration::from_secs_f64(1.0 / self.per_second(host))
+    }
+}
+
+fn rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let rate = f64::deserialize(deserializer)?;
+    if rate.is_finite() && rate > 0.0 {
+        Ok(rate)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "The rate limit {rate} is not a positive number of requests per second"
+        )))
+    }
+}
+
+fn host_rates<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, f64>, D::Error> {
+    let mut hosts = RateLimits::default().hosts;
+    for (host, rate) in HashMap::<String, f64>::deserialize(deserializer)? {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(serde::de::Error::custom(format!(
+                "The rate limit {rate} of {host} is not a positive number of requests per second"
+            )));
+        }
+        hosts.insert(host, rate);
+    }
+    Ok(hosts)
+}
+
+static LIMITER: Mutex<Option<Limiter>> = Mutex::new(None);
+
+/// Sets the rate limits that every following request to a provider API waits for, which are the
+/// [`Default`] limits until this is called.
+pub fn set_rate_limits(limits: RateLimits) {
+    let mut limiter = LIMITER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    *limiter = Some(Limiter::new(limits, SystemClock));
+}
+
+/// Waits until a request to the host of the `url` keeps to its rate limit.
+///
+/// The time of the request is reserved before waiting so the requests of other threads to the
+/// same host are spaced after it, and the requests to other hosts don't wait.
+pub(crate) fn wait(url: &str) {
+    let Some(host) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+    else {
+        return;
+    };
+
+    let delay = LIMITER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get_or_insert_with(|| Limiter::new(RateLimits::default(), SystemClock))
+        .delay(&host);
+    if !delay.is_zero() {
+        debug!("Waiting {delay:?} before the next request to {host}");
+        std::thread::sleep(delay);
+    }
+}
+
+// The source of the current time, which is replaced in tests.
+pub(crate) trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// The time that the next request to each host can be sent.
+pub(crate) struct Limiter<C: Clock = SystemClock> {
+    limits: RateLimits,
+    next: HashMap<String, Instant>,
+    clock: C,
+}
+
+impl<C: Clock> Limiter<C> {
+    fn new(limits: RateLimits, clock: C) -> Self {
+        Self {
+            limits,
+            next: HashMap::new(),
+            clock,
+        }
+    }
+
+    // Reserves the next time a request to the `host` can be sent and returns how long to wait
+    // until then.
+    fn delay(&mut self, host: &str) -> Duration {
+        let now = self.clock.now();
+        let interval = self.limits.interval(host);
+        let next = self.next.entry(host.to_owned()).or_insert(now);
+        let at = (*next).max(now);
+        *next = at + interval;
+        at - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    // A clock that only moves when it is advanced.
+    #[derive(Clone)]
+    struct MockClock {
+        start: Instant,
+        elapsed: Rc<Cell<Duration>>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                elapsed: Rc::new(Cell::new(Duration::ZERO)),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.elapsed.set(self.elapsed.get() + duration);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+    }
+
+    fn limits() -> RateLimits {
+        RateLimits {
+            default: 2.0,
+            hosts: HashMap::from([("slow.example.com".to_owned(), 0.5)]),
+        }
+    }
+
+    #[test]
+    fn requests_are_spaced_by_the_rate() {
+        let clock = MockClock::new();
+        let mut limiter = Limiter::new(limits(), clock.clone());
+
+        assert_eq!(Duration::ZERO, limiter.delay("example.com"));
+        assert_eq!(Duration::from_millis(500), limiter.delay("example.com"));
+        assert_eq!(Duration::from_secs(1), limiter.delay("example.com"));
+
+        // the reserved requests have been sent so the next is spaced from the last of them
+        clock.advance(Duration::from_millis(1200));
+        assert_eq!(Duration::from_millis(300), limiter.delay("example.com"));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(Duration::ZERO, limiter.delay("example.com"));
+    }
+
+    #[test]
+    fn hosts_are_limited_separately() {
+        let clock = MockClock::new();
+        let mut limiter = Limiter::new(limits(), clock);
+
+        assert_eq!(Duration::ZERO, limiter.delay("example.com"));
+        assert_eq!(Duration::ZERO, limiter.delay("slow.example.com"));
+        assert_eq!(Duration::from_secs(2), limiter.delay("slow.example.com"));
+        assert_eq!(Duration::from_millis(500), limiter.delay("example.com"));
+    }
+
+    #[test]
+    fn configured_hosts_are_added_to_the_defaults() {
+        let limits: RateLimits =
+            serde_json::from_str(r#"{"hosts": {"api.crossref.org": 2.5}}"#).unwrap();
+
+        assert!((limits.per_second("api.crossref.org") - 2.5).abs() < f64::EPSILON);
+        assert!((limits.per_second("www.googleapis.com") - 1.0).abs() < f64::EPSILON);
+        assert!(serde_json::from_str::<RateLimits>(r#"{"default": 0}"#).is_err());
+        assert!(serde_json::from_str::<RateLimits>(r#"{"hosts": {"a.org": -1}}"#).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use log::{info, trace};
+use serde::Deserialize;
+
+use crate::{
+    ast::{normalize_doi, Entry, FieldQuery},
+    Error,
+};
+
+use super::{encode, Client};
+
+const UNPAYWALL_URL: &str = "https://api.unpaywall.org/v2";
+
+/// The field that the URL of the best open access PDF of an entry is stored in.
+pub(crate) const OA_URL_FIELD: &str = "oa_url";
+
+/// Looks up the DOI of the `entry` using the Unpaywall API and returns the URL of the best open
+/// access PDF.
+///
+/// Returns `None` when the entry has no DOI or no open access PDF was found.
+pub(crate) fn oa_url<C: Client>(entry: &Entry, email: &str) -> Result<Option<String>, Error> {
+    let Some(doi) = entry.get_field("doi").map(|doi| normalize_doi(doi)) else {
+        trace!("'{}' has no DOI - skipping", entry.cite());
+        return Ok(None);
+    };
+
+    info!("Searching for an open access PDF of '{doi}' using the Unpaywall API");
+    // the DOI is the path of the URL so only the parts between its slashes are encoded
+    let path = doi.split('/').map(encode).collect::<Vec<_>>().join("/");
+    let url = format!("{UNPAYWALL_URL}/{path}?email={}", encode(email));
+    let model: UnpaywallModel = C::default().get_json(&url)?;
+
+    Ok(model
+        .best_oa_location
+        .and_then(|location| location.url_for_pdf))
+}
+
+/// Stores the URL of the best open access PDF of the `entry`, see [`oa_url`], in the
+/// [`OA_URL_FIELD`]. The field is only set when the URL is different.
+///
+/// Returns `false` when the entry has no DOI or no open access PDF was found.
+pub(crate) fn attach_oa_url<C: Client>(entry: &mut Entry, email: &str) -> Result<bool, Error> {
+    let Some(pdf) = oa_url::<C>(entry, email)? else {
+        return Ok(false);
+    };
+    if entry.get_field(OA_URL_FIELD).map(|url| &**url) != Some(pdf.as_str()) {
+        entry.set_field(OA_URL_FIELD, pdf);
+    }
+    Ok(true)
+}
+
+#[derive(Deserialize)]
+struct UnpaywallModel {
+    best_oa_location: Option<Location>,
+}
+
+#[derive(Deserialize)]
+struct Location {
+    url_for_pdf: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient, NetworkErrorProducer},
+        ast::{Entry, EntryKind, FieldQuery},
+    };
+
+    use super::*;
+
+    impl_text_producer! {
+        OpenAccessProducer => Ok(
+            r#"{
+                "doi": "10.1038/nature12373",
+                "is_oa": true,
+                "best_oa_location": {
+                    "url": "https://europepmc.org/articles/pmc4221854",
+                    "url_for_pdf": "https://europepmc.org/articles/pmc4221854?pdf=render"
+                }
+            }"#.to_owned()
+        ),
+        ClosedAccessProducer => Ok(
+            r#"{"doi": "10.1038/nature12373", "is_oa": false, "best_oa_location": null}"#.to_owned()
+        ),
+    }
+
+    fn entry(doi: Option<&str>) -> Entry {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+        resolver.title("Title");
+        if let Some(doi) = doi {
+            resolver.set_field("doi", doi);
+        }
+        resolver.resolve().unwrap()
+    }
+
+    #[test]
+    fn open_access_pdf_populates_oa_url() {
+        let mut entry = entry(Some("10.1038/nature12373"));
+
+        assert!(
+            attach_oa_url::<MockClient<OpenAccessProducer>>(&mut entry, "me+seb@example.com")
+                .unwrap()
+        );
+        assert_url!(
+            "https://api.unpaywall.org/v2/10.1038/nature12373?email=me%2Bseb%40example.com"
+        );
+        assert_eq!(
+            "https://europepmc.org/articles/pmc4221854?pdf=render",
+            &**entry.get_field(OA_URL_FIELD).unwrap()
+        );
+    }
+
+    #[test]
+    fn doi_is_normalized_and_encoded_in_the_url() {
+        let entry = entry(Some("https://doi.org/10.1002/(SICI)#x?y"));
+
+        oa_url::<MockClient<ClosedAccessProducer>>(&entry, "me@example.com").unwrap();
+
+        assert_url!(
+            "https://api.unpaywall.org/v2/10.1002/%28sici%29%23x%3Fy?email=me%40example.com"
+        );
+    }
+
+    #[test]
+    fn closed_access_leaves_entry_unchanged() {
+        let mut entry = entry(Some("10.1038/nature12373"));
+
+        assert!(
+            !attach_oa_url::<MockClient<ClosedAccessProducer>>(&mut entry, "me@example.com")
+                .unwrap()
+        );
+        assert!(entry.get_field(OA_URL_FIELD).is_none());
+    }
+
+    #[test]
+    fn entry_without_doi_is_skipped() {
+        // the network error is never produced as no request is made
+        assert!(!attach_oa_url::<MockClient<NetworkErrorProducer>>(
+            &mut entry(None),
+            "me@example.com"
+        )
+        .unwrap());
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::HashMap, fmt};
+
+/// The name of a single author, which is the structured form of the names in the `author` field.
+///
+/// The `author` field is a list of names separated by `and`, as in `BibTeX`, where each name is
+/// either "First Last" or "Last, First". Every format reads and writes the names of the field
+/// using [`Author::parse_list`] and [`Author::join`] so that the same authors are kept whichever
+/// format an entry is read from or written to.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::Author;
+///
+/// let authors = Author::parse_list("Donald E. Knuth and Lamport, Leslie");
+///
+/// assert_eq!(Author::new("Knuth", Some("Donald E.")), authors[0]);
+/// assert_eq!(Author::new("Lamport", Some("Leslie")), authors[1]);
+/// assert_eq!("Knuth, Donald E. and Lamport, Leslie", Author::join(&authors));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Author {
+    /// The family, or last, name.
+    pub family: String,
+    /// The given, or first, names when the name has them.
+    pub given: Option<String>,
+}
+
+impl Author {
+    /// Creates an author from the family name and the optional given names.
+    #[must_use]
+    pub fn new<S: Into<String>>(family: S, given: Option<&str>) -> Self {
+        Self {
+            family: family.into(),
+            given: given.map(str::to_owned),
+        }
+    }
+
+    /// Parses a single name written as "Last, First" or "First Last".
+    ///
+    /// A name wrapped in braces, such as `{World Health Organization}`, is kept whole as the
+    /// family name. Returns `None` when the name is empty.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        if let Some((family, given)) = rsplit_once_outside_braces(name, ',') {
+            let given = given.trim();
+            return Some(Self::new(
+                family.trim(),
+                (!given.is_empty()).then_some(given),
+            ));
+        }
+
+        match rsplit_once_outside_braces(name, ' ') {
+            Some((given, family)) => Some(Self::new(family, Some(given.trim()))),
+            None => Some(Self::new(name, None)),
+        }
+    }
+
+    /// Parses every name in a list of names separated by `and`.
+    #[must_use]
+    pub fn parse_list(names: &str) -> Vec<Self> {
+        Self::split_list(names)
+            .iter()
+            .filter_map(|name| Self::parse(name))
+            .collect()
+    }
+
+    /// Splits a list of names separated by `and` into the names as they are written, without
+    /// parsing them, so that a name can be replaced without rewriting the others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::Author;
+    ///
+    /// assert_eq!(
+    ///     vec!["Leslie Lamport", "{Barnes and Noble}"],
+    ///     Author::split_list("Leslie Lamport and {Barnes and Noble}")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn split_list(names: &str) -> Vec<String> {
+        let mut split = Vec::new();
+        let mut name = Vec::new();
+        // `and` only separates names outside of braces, such as in `{Barnes and Noble}`
+        for word in words_outside_braces(names) {
+            if word.eq_ignore_ascii_case("and") {
+                split.push(name.join(" "));
+                name.clear();
+            } else {
+                name.push(word);
+            }
+        }
+        split.push(name.join(" "));
+        split.retain(|name| !name.is_empty());
+        split
+    }
+
+    /// Joins the names as "Last, First" separated by `and`, which is the value of the `author`
+    /// field.
+    #[must_use]
+    pub fn join(authors: &[Self]) -> String {
+        authors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    /// Returns `true` when both names can be the same person written differently, such as
+    /// "J. Smith", "John Smith" and "Smith, J.".
+    ///
+    /// The family names must be the same, ignoring case and braces, and the given names must
+    /// agree name by name, where an initial agrees with a name starting with it and a missing
+    /// middle name agrees with any. A name without given names is only a variant of itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::Author;
+    ///
+    /// let john = Author::new("Smith", Some("John"));
+    ///
+    /// assert!(john.is_variant_of(&Author::new("Smith", Some("J."))));
+    /// assert!(john.is_variant_of(&Author::new("Smith", Some("John A."))));
+    /// assert!(!john.is_variant_of(&Author::new("Smith", Some("Jane"))));
+    /// assert!(!john.is_variant_of(&Author::new("Smith", None)));
+    /// ```
+    #[must_use]
+    pub fn is_variant_of(&self, other: &Self) -> bool {
+        if family_key(&self.family) != family_key(&other.family) {
+            return false;
+        }
+        let (Some(given), Some(other_given)) = (&self.given, &other.given) else {
+            return self.given == other.given;
+        };
+
+        given_names(given)
+            .zip(given_names(other_given))
+            .all(|(name, other)| {
+                let (name, other) = (name.to_lowercase(), other.to_lowercase());
+                name == other
+                    || (name.chars().count() == 1 && other.starts_with(&name))
+                    || (other.chars().count() == 1 && name.starts_with(&other))
+            })
+    }
+
+    /// Groups the distinct names that are variants of each other, see [`Author::is_variant_of`],
+    /// with the most complete name of each group first, followed by the variants.
+    ///
+    /// A group is only made when every name in it is a variant of every other name, so that a
+    /// name that could be either of two people, such as "J. Smith" with "John Smith" and
+    /// "Jane Smith", is never grouped. The most complete name is the one with the longest given
+    /// names and then the one used the most.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::Author;
+    ///
+    /// let names = Author::parse_list("J. Smith and John Smith and Smith, J. and Leslie Lamport");
+    ///
+    /// assert_eq!(
+    ///     vec![vec![Author::new("Smith", Some("John")), Author::new("Smith", Some("J."))]],
+    ///     Author::variants(&names)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn variants(names: &[Self]) -> Vec<Vec<Self>> {
+        let mut uses = HashMap::<&Self, usize>::new();
+        for name in names {
+            *uses.entry(name).or_default() += 1;
+        }
+
+        let mut families = HashMap::<String, Vec<&Self>>::new();
+        for name in uses.keys() {
+            families
+                .entry(family_key(&name.family))
+                .or_default()
+                .push(name);
+        }
+
+        let mut groups = Vec::new();
+        for names in families.into_values().filter(|names| names.len() > 1) {
+            for mut group in connected_variants(&names) {
+                let all_variants = group
+                    .iter()
+                    .enumerate()
+                    .all(|(i, name)| group[i + 1..].iter().all(|other| name.is_variant_of(other)));
+                if group.len() < 2 || !all_variants {
+                    continue;
+                }
+
+                group.sort_by(|a, b| {
+                    completeness(b)
+                        .cmp(&completeness(a))
+                        .then(uses[b].cmp(&uses[a]))
+                        .then_with(|| a.to_string().cmp(&b.to_string()))
+                });
+                groups.push(group.into_iter().cloned().collect::<Vec<_>>());
+            }
+        }
+        groups.sort_by(|a, b| a[0].to_string().cmp(&b[0].to_string()));
+        groups
+    }
+}
+
+// the names that are connected by a chain of variants, such as "John Smith", "J. Smith" and
+// "Jane Smith", which are only grouped when they are all variants of each other
+fn connected_variants<'a>(names: &[&'a Author]) -> Vec<Vec<&'a Author>> {
+    let mut groups: Vec<Vec<&Author>> = Vec::new();
+    for &name in names {
+        let (connected, mut rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|group| group.iter().any(|other| name.is_variant_of(other)));
+        let mut group = connected.into_iter().flatten().collect::<Vec<_>>();
+        group.push(name);
+        rest.push(group);
+        groups = rest;
+    }
+    groups
+}
+
+fn family_key(family: &str) -> String {
+    family.replace(['{', '}'], "").to_lowercase()
+}
+
+fn given_names(given: &str) -> impl Iterator<Item = &str> {
+    given
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .filter(|name| !name.is_empty())
+}
+
+fn completeness(author: &Author) -> usize {
+    author.given.as_deref().map_or(0, |given| {
+        given_names(given).map(|name| name.chars().count()).sum()
+    })
+}
+
+/// The role of the names in a name field, which is the field the names are read from.
+///
+/// Edited volumes often have editors but no authors and translations credit the translators as
+/// well as the authors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// The names in the `author` field.
+    Author,
+    /// The names in the `editor` field.
+    Editor,
+    /// The names in the `translator` field.
+    Translator,
+}
+
+impl Role {
+    /// Returns the name of the field that holds the names with this role.
+    #[must_use]
+    pub const fn field(self) -> &'static str {
+        match self {
+            Self::Author => "author",
+            Self::Editor => "editor",
+            Self::Translator => "translator",
+        }
+    }
+
+    /// Formats the names for a reference list as "First Last", separated by commas and `and`,
+    /// followed by the role when the names are not the authors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Author, Role};
+    ///
+    /// let editors = Author::parse_list("Knuth, Donald E. and Leslie Lamport");
+    ///
+    /// assert_eq!(
+    ///     "Donald E. Knuth and Leslie Lamport (eds.)",
+    ///     Role::Editor.format(&editors)
+    /// );
+    /// assert_eq!("Donald E. Knuth (trans.)", Role::Translator.format(&editors[..1]));
+    /// ```
+    #[must_use]
+    pub fn format(self, names: &[Author]) -> String {
+        let names = names
+            .iter()
+            .map(|name| {
+                let family = name.family.replace(['{', '}'], "");
+                match &name.given {
+                    Some(given) => format!("{given} {family}"),
+                    None => family,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut formatted = match names.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+            None => return String::new(),
+        };
+        match (self, names.len()) {
+            (Self::Author, _) => {}
+            (Self::Editor, 1) => formatted.push_str(" (ed.)"),
+            (Self::Editor, _) => formatted.push_str(" (eds.)"),
+            (Self::Translator, _) => formatted.push_str(" (trans.)"),
+        }
+        formatted
+    }
+}
+
+impl fmt::Display for Author {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.given {
+            Some(given) => write!(f, "{}, {given}", self.family),
+            None => f.write_str(&self.family),
+        }
+    }
+}
+
+// Returns the index of each character of the value that is not inside braces.
+fn outside_braces(value: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut depth = 0_usize;
+    value.char_indices().filter(move |(_, c)| match c {
+        '{' => {
+            depth += 1;
+            false
+        }
+        '}' => {
+            depth = depth.saturating_sub(1);
+            false
+        }
+        _ => depth == 0,
+    })
+}
+
+fn rsplit_once_outside_braces(value: &str, separator: char) -> Option<(&str, &str)> {
+    let (i, _) = outside_braces(value)
+        .filter(|(_, c)| *c == separator)
+        .last()?;
+    Some((&value[..i], &value[i + separator.len_utf8()..]))
+}
+
+fn words_outside_braces(value: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for (i, c) in outside_braces(value).filter(|(_, c)| c.is_whitespace()) {
+        words.push(&value[start..i]);
+        start = i + c.len_utf8();
+    }
+    words.push(&value[start..]);
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_last_and_last_first_are_the_same_author() {
+        assert_eq!(
+            Author::parse("Donald E. Knuth"),
+            Author::parse("Knuth, Donald E.")
+        );
+    }
+
+    #[test]
+    fn braced_names_are_kept_whole() {
+        assert_eq!(
+            vec![
+                Author::new("{Barnes and Noble}", None),
+                Author::new("Turing", Some("Alan")),
+            ],
+            Author::parse_list("{Barnes and Noble} AND Alan Turing")
+        );
+    }
+
+    #[test]
+    fn suffix_stays_with_the_family_name() {
+        let author = Author::parse("King, Jr., Martin Luther").unwrap();
+
+        assert_eq!(Author::new("King, Jr.", Some("Martin Luther")), author);
+        assert_eq!("King, Jr., Martin Luther", author.to_string());
+    }
+
+    #[test]
+    fn edited_book_lists_the_editors() {
+        let editors = Author::parse_list(
+            "Knuth, Donald E. and Leslie Lamport and {World Health Organization}",
+        );
+
+        assert_eq!(
+            "Donald E. Knuth, Leslie Lamport and World Health Organization (eds.)",
+            Role::Editor.format(&editors)
+        );
+        assert_eq!("Leslie Lamport (ed.)", Role::Editor.format(&editors[1..2]));
+        assert_eq!("Leslie Lamport", Role::Author.format(&editors[1..2]));
+        assert!(Role::Editor.format(&[]).is_empty());
+    }
+
+    #[test]
+    fn empty_names_are_skipped() {
+        assert!(Author::parse_list("  ").is_empty());
+        assert_eq!(1, Author::parse_list("Knuth and ").len());
+    }
+
+    #[test]
+    fn three_variants_of_one_name_are_grouped() {
+        let names = Author::parse_list(
+            "J. Smith and John A. Smith and Smith, J. A. and Smith, John A. and Jane Doe and Doe, J.",
+        );
+
+        assert_eq!(
+            vec![
+                vec![
+                    Author::new("Doe", Some("Jane")),
+                    Author::new("Doe", Some("J."))
+                ],
+                vec![
+                    Author::new("Smith", Some("John A.")),
+                    Author::new("Smith", Some("J. A.")),
+                    Author::new("Smith", Some("J.")),
+                ],
+            ],
+            Author::variants(&names)
+        );
+    }
+
+    #[test]
+    fn name_that_could_be_two_people_is_not_grouped() {
+        let names = Author::parse_list("J. Smith and John Smith and Jane Smith and Smith");
+
+        assert!(Author::variants(&names).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::ast::{Entry, FieldQuery};
+
+use super::normalize_doi;
+
+/// A lazily built index of the normalized DOI, ISBN and cite key values of the entries in a
+/// [`Biblio`][super::Biblio].
+///
+/// The index is built on the first lookup and is cleared by [`Self::invalidate`], which must be
+/// called whenever the entries are changed.
+#[derive(Debug, Default)]
+pub(super) struct LazyIndex(OnceLock<Index>);
+
+#[derive(Debug, Default)]
+struct Index {
+    doi: HashMap<String, Vec<String>>,
+    isbn: HashMap<String, Vec<String>>,
+    cite: HashMap<String, String>,
+}
+
+impl LazyIndex {
+    pub(super) fn invalidate(&mut self) {
+        self.0.take();
+    }
+
+    /// Returns the cite keys of the entries with the `name` field matching the `value`, or `None`
+    /// when the field is not indexed.
+    pub(super) fn cites(
+        &self,
+        entries: &HashMap<String, Entry>,
+        name: &str,
+        value: &str,
+    ) -> Option<&[String]> {
+        let index = || self.0.get_or_init(|| Index::new(entries));
+        let cites = match name.to_lowercase().as_str() {
+            "doi" => index().doi.get(&normalize_doi(value)).map(Vec::as_slice),
+            "isbn" => index().isbn.get(&normalize_isbn(value)).map(Vec::as_slice),
+            // the `key` field is a BibTeX field of its own, used for sorting, not the cite key
+            "cite" => index()
+                .cite
+                .get(&value.to_lowercase())
+                .map(std::slice::from_ref),
+            _ => return None,
+        };
+        Some(cites.unwrap_or_default())
+    }
+}
+
+impl Index {
+    fn new(entries: &HashMap<String, Entry>) -> Self {
+        log::trace!("building the field index for {} entries", entries.len());
+        let mut index = Self::default();
+        for entry in entries.values() {
+            let cite = entry.cite().to_owned();
+            if let Some(doi) = entry.get_field("doi") {
+                index
+                    .doi
+                    .entry(normalize_doi(doi))
+                    .or_default()
+                    .push(cite.clone());
+            }
+            if let Some(isbn) = entry.get_field("isbn") {
+                index
+                    .isbn
+                    .entry(normalize_isbn(isbn))
+                    .or_default()
+                    .push(cite.clone());
+            }
+            index.cite.insert(cite.to_lowercase(), cite);
+        }
+        index
+    }
+}
+
+// The index is a cache of the entries so it is never part of the equality of two bibliographies.
+impl PartialEq for LazyIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Clone for LazyIndex {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// ISBNs are written with or without hyphens and spaces and the check digit can be a lower case x.
+fn normalize_isbn(isbn: &str) -> String {
+    isbn.chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .collect::<String>()
+        .to_uppercase()
+}
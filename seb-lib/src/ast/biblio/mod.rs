@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+mod index;
 mod resolver;
 
+use index::LazyIndex;
+
 pub use resolver::BiblioResolver;
 
-use super::{Entry, FieldQuery, QuotedString, Resolver};
+use super::{Entry, FieldQuery, KeyFormat, QuotedString, Resolver, Similarity};
 
 /// An intermediate representation of a bibliography which is not tied to a specific end format.
 #[derive(Debug, Default, PartialEq)]
@@ -12,6 +15,43 @@ use super::{Entry, FieldQuery, QuotedString, Resolver};
 pub struct Biblio {
     dirty: bool,
     entries: HashMap<String, Entry>,
+    index: LazyIndex,
+    blocks: Blocks,
+}
+
+/// The top-level blocks of a bibliography that are not entries, such as the `@preamble` and
+/// `@comment` blocks of `BibTeX`.
+///
+/// The blocks are kept as they were read so that they are written back when the bibliography is
+/// composed to the same format.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blocks {
+    /// The contents of each preamble, in the order they were read.
+    pub preambles: Vec<String>,
+    /// The contents of each comment, in the order they were read, along with where it is written
+    /// back.
+    pub comments: Vec<(CommentPlacement, String)>,
+}
+
+/// Where a comment of the [`Blocks`] is written back, as the entries are not written in the order
+/// they were read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommentPlacement {
+    /// Before the entries, for a comment read before every entry.
+    Start,
+    /// Just before the entry with this cite key, for a comment read between two entries.
+    Before(String),
+    /// After the entries, for a comment read after every entry or before an entry that has since
+    /// been removed.
+    End,
+}
+
+impl Blocks {
+    /// Returns `true` when there are no preambles or comments.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.preambles.is_empty() && self.comments.is_empty()
+    }
 }
 
 impl Biblio {
@@ -24,6 +64,8 @@ impl Biblio {
                 .into_iter()
                 .map(|e| (e.cite().to_owned(), e))
                 .collect(),
+            index: LazyIndex::default(),
+            blocks: Blocks::default(),
         }
     }
 
@@ -35,14 +77,35 @@ impl Biblio {
     /// Returns [`Err(BiblioResolver)`] if one of the entry resolvers fail, this allows resolving
     /// the resolvers and retrying the resolve.
     pub fn try_resolve(resolvers: Vec<Resolver>) -> Result<Self, BiblioResolver> {
+        Self::try_resolve_with_blocks(resolvers, Blocks::default())
+    }
+
+    /// Attempts to resolve all of the entry resolvers, see [`Biblio::try_resolve`], and keeps the
+    /// `blocks` that are not entries in the [`Biblio`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err(BiblioResolver)`] if one of the entry resolvers fail, the `blocks` are kept
+    /// by the [`BiblioResolver`].
+    pub fn try_resolve_with_blocks(
+        resolvers: Vec<Resolver>,
+        blocks: Blocks,
+    ) -> Result<Self, BiblioResolver> {
         BiblioResolver {
             failed: false,
             resolvers,
             entries: Vec::new(),
+            blocks,
         }
         .resolve()
     }
 
+    /// Returns the top-level blocks that are not entries.
+    #[must_use]
+    pub const fn blocks(&self) -> &Blocks {
+        &self.blocks
+    }
+
     /// Checks and resets the `dirty` flag.
     ///
     /// The `dirty` flag will return true when this instance has been edited since it was created.
@@ -54,9 +117,17 @@ impl Biblio {
         dirty
     }
 
+    /// Sets the `dirty` flag without changing any entry.
+    ///
+    /// This is used to write a [`Biblio`] back in its canonical form when nothing has changed.
+    pub const fn touch(&mut self) {
+        self.dirty = true;
+    }
+
     /// Insert a new [`Entry`].
     pub fn insert(&mut self, entry: Entry) {
         self.dirty = true;
+        self.index.invalidate();
         self.entries.insert(entry.cite().to_owned(), entry);
     }
 
@@ -69,10 +140,44 @@ impl Biblio {
             check
         });
 
-        self.dirty |= removed;
+        if removed {
+            self.dirty = true;
+            self.index.invalidate();
+        }
         removed
     }
 
+    /// Returns the `cite` key followed by the first suffix, `a`, `b`, ..., `z`, `aa` and so on,
+    /// that no entry uses, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "knuth1984a");
+    /// resolver.title("Title");
+    /// let biblio = Biblio::new(vec![resolver.resolve().unwrap()]);
+    ///
+    /// assert_eq!("knuth1984b", biblio.unique_cite("knuth1984"));
+    /// ```
+    #[must_use]
+    pub fn unique_cite(&self, cite: &str) -> String {
+        let used = self
+            .entries
+            .keys()
+            .map(|key| key.to_lowercase())
+            .collect::<HashSet<_>>();
+        let mut i = 0;
+        loop {
+            let candidate = format!("{cite}{}", suffix(i));
+            if !used.contains(&candidate.to_lowercase()) {
+                return candidate;
+            }
+            i += 1;
+        }
+    }
+
     /// Return a reference to a slice of entries.
     pub fn entries(&self) -> impl Iterator<Item = &Entry> {
         self.entries.values()
@@ -108,11 +213,981 @@ impl Biblio {
             .any(|e| e.get_field(key).map(&predicate).unwrap_or_default())
     }
 
+    /// Returns the entries with a `name` field that matches the `value`.
+    ///
+    /// The `doi`, `isbn` and `cite` fields are matched by their normalized value using an index
+    /// that is built on the first lookup and rebuilt after this [`Biblio`] is changed, so repeated
+    /// lookups don't need to check every entry. All other fields, including the `BibTeX` `key`
+    /// field, must be equal to the `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    /// resolver.title("Title");
+    /// resolver.set_field("doi", "10.1000/182");
+    /// let biblio = Biblio::new(vec![resolver.resolve().unwrap()]);
+    ///
+    /// assert_eq!(1, biblio.find_by_field("doi", "https://doi.org/10.1000/182").len());
+    /// assert!(biblio.find_by_field("doi", "10.1000/183").is_empty());
+    /// ```
+    #[must_use]
+    pub fn find_by_field(&self, name: &str, value: &str) -> Vec<&Entry> {
+        match self.index.cites(&self.entries, name, value) {
+            Some(cites) => cites
+                .iter()
+                .filter_map(|cite| self.entries.get(cite))
+                .collect(),
+            None => self
+                .entries
+                .values()
+                .filter(|e| e.get_field(name).is_some_and(|f| &**f == value))
+                .collect(),
+        }
+    }
+
     /// Returns the reference to the `Entry` corresponding to the cite key value.
     #[must_use]
     pub fn get(&self, key: &str) -> Option<&Entry> {
         self.entries.get(key)
     }
+
+    /// Returns a mutable reference to the `Entry` corresponding to the cite key value.
+    ///
+    /// This [`Biblio`] is marked as dirty when the entry is found, as it is assumed that the entry
+    /// will be edited. The cite key should not be changed using the mutable reference, instead
+    /// [`Self::remove`] and [`Self::insert`] the entry.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Entry> {
+        let entry = self.entries.get_mut(key);
+        if entry.is_some() {
+            self.dirty = true;
+            self.index.invalidate();
+        }
+        entry
+    }
+
+    /// Replaces the value of each field in every entry with the value returned by `f`, when `f`
+    /// returns `Some`, and returns the number of fields that were changed.
+    ///
+    /// `f` is called with the name and value of each field. [Locked][Entry::is_locked] entries
+    /// are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind, FieldQuery};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    /// resolver.title("  Title ");
+    /// let mut biblio = Biblio::new(vec![resolver.resolve().unwrap()]);
+    ///
+    /// let changed = biblio.map_fields(|_, value| {
+    ///     (value.trim() != &**value).then(|| value.trim().into())
+    /// });
+    ///
+    /// assert_eq!(1, changed);
+    /// assert_eq!("Title", &**biblio.get("cite").unwrap().title());
+    /// ```
+    pub fn map_fields<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&str, &QuotedString) -> Option<QuotedString>,
+    {
+        let mut changed = 0;
+
+        for entry in self.entries.values_mut().filter(|entry| !entry.is_locked()) {
+            let updates = entry
+                .fields()
+                .into_iter()
+                .filter_map(|field| Some((field.name.to_string(), f(&field.name, &field.value)?)))
+                .collect::<Vec<_>>();
+
+            for (name, value) in updates {
+                entry.set_field(&name, value);
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            self.dirty = true;
+            self.index.invalidate();
+        }
+        changed
+    }
+
+    /// Lowercases the DOI of every entry, see [`lowercase_doi`], and returns the number of DOIs
+    /// that were changed. [Locked][Entry::is_locked] entries are skipped.
+    pub fn lowercase_dois(&mut self) -> usize {
+        let mut changed = 0;
+        for entry in self.entries.values_mut().filter(|entry| !entry.is_locked()) {
+            changed += usize::from(lowercase_doi(entry));
+        }
+
+        if changed > 0 {
+            self.dirty = true;
+            self.index.invalidate();
+        }
+        changed
+    }
+
+    /// Moves the DOI in the `url` field of every entry to its `doi` field, see [`move_doi_url`],
+    /// and returns the number of entries that were changed. [Locked][Entry::is_locked] entries
+    /// are skipped.
+    pub fn move_doi_urls(&mut self) -> usize {
+        let mut changed = 0;
+        for entry in self.entries.values_mut().filter(|entry| !entry.is_locked()) {
+            changed += usize::from(move_doi_url(entry));
+        }
+
+        if changed > 0 {
+            self.dirty = true;
+            self.index.invalidate();
+        }
+        changed
+    }
+
+    /// Stores the arXiv ID of every entry in its `eprint` field, see [`store_arxiv_id`], and
+    /// returns the number of entries that were changed. [Locked][Entry::is_locked] entries are
+    /// skipped.
+    pub fn store_arxiv_ids(&mut self) -> usize {
+        let mut changed = 0;
+        for entry in self.entries.values_mut().filter(|entry| !entry.is_locked()) {
+            changed += usize::from(store_arxiv_id(entry));
+        }
+
+        if changed > 0 {
+            self.dirty = true;
+            self.index.invalidate();
+        }
+        changed
+    }
+
+    /// Returns the entries to change without marking this [`Biblio`] as dirty, for changes that
+    /// are not written to the bibliography, such as moving fields to a sidecar file.
+    pub(crate) fn entries_mut_untracked(&mut self) -> impl Iterator<Item = &mut Entry> {
+        self.index.invalidate();
+        self.entries.values_mut()
+    }
+
+    /// Sets the field on every entry without marking this [`Biblio`] as dirty.
+    pub(crate) fn set_field_all(&mut self, name: &str, value: &str) {
+        self.index.invalidate();
+        self.entries
+            .values_mut()
+            .for_each(|entry| entry.set_field(name, value));
+    }
+
+    /// Regenerates the cite key of every entry using the `format` and returns the old and new
+    /// cite keys of the entries that changed, sorted by the old cite key.
+    ///
+    /// Entries that generate the same cite key are given the suffixes `a`, `b`, `c` and so on in
+    /// the order of their old cite keys, so the same bibliography is always given the same cite
+    /// keys. [Locked][Entry::is_locked] entries keep their cite key.
+    /// The `crossref` and [`RELATED_FIELD`] fields that reference a changed cite key, ignoring
+    /// case, are updated to the new cite key.
+    ///
+    /// All of the new cite keys are found before any entry is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind, KeyFormat};
+    ///
+    /// let entries = ["first", "second"].into_iter().map(|cite| {
+    ///     let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, cite);
+    ///     resolver.title("Title");
+    ///     resolver.set_field("author", "Donald Knuth");
+    ///     resolver.set_field("year", "1968");
+    ///     resolver.resolve().unwrap()
+    /// });
+    /// let mut biblio = Biblio::new(entries.collect());
+    ///
+    /// let changed = biblio.rekey(&KeyFormat::default());
+    ///
+    /// assert_eq!(("first".to_owned(), "Knuth1968a".to_owned()), changed[0]);
+    /// assert_eq!(("second".to_owned(), "Knuth1968b".to_owned()), changed[1]);
+    /// ```
+    pub fn rekey(&mut self, format: &KeyFormat) -> Vec<(String, String)> {
+        let mut groups: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for entry in self.entries.values().filter(|entry| !entry.is_locked()) {
+            groups
+                .entry(format.key(entry))
+                .or_default()
+                .push(entry.cite());
+        }
+
+        let mut used = self
+            .entries
+            .values()
+            .filter(|entry| entry.is_locked())
+            .map(|entry| entry.cite().to_owned())
+            .collect::<HashSet<_>>();
+        let mut renames = HashMap::new();
+
+        for (key, mut cites) in groups {
+            // the cites are in the order of the map so they are sorted to be suffixed the same
+            cites.sort_unstable();
+            if cites.len() == 1 && !used.contains(&key) {
+                used.insert(key.clone());
+                renames.insert(cites[0].to_owned(), key);
+                continue;
+            }
+
+            let mut i = 0;
+            for cite in cites {
+                let new = loop {
+                    let candidate = format!("{key}{}", suffix(i));
+                    i += 1;
+                    if !used.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                used.insert(new.clone());
+                renames.insert(cite.to_owned(), new);
+            }
+        }
+        renames.retain(|old, new| old != new);
+
+        if renames.is_empty() {
+            return Vec::new();
+        }
+
+        // cite keys are referenced ignoring case, as they are matched by BibTeX
+        let references = renames
+            .iter()
+            .map(|(old, new)| (old.to_lowercase(), new.as_str()))
+            .collect::<HashMap<_, _>>();
+        let renamed = |cite: &str| references.get(&cite.to_lowercase()).copied();
+
+        self.entries = std::mem::take(&mut self.entries)
+            .into_values()
+            .map(|mut entry| {
+                if let Some(new) = renames.get(entry.cite()) {
+                    entry.set_cite(new.clone());
+                    if entry.get_field(KEY_FIELD).is_some() {
+                        entry.set_field(KEY_FIELD, format.placeholders());
+                    }
+                }
+                let parent = entry
+                    .get_field("crossref")
+                    .map(|parent| parent.trim().to_owned());
+                if let Some(new) = parent.and_then(|parent| renamed(&parent)) {
+                    entry.set_field("crossref", new);
+                }
+                let related = entry
+                    .related()
+                    .filter(|related| related.iter().any(|cite| renamed(cite).is_some()))
+                    .map(|related| {
+                        related
+                            .into_iter()
+                            .map(|cite| renamed(cite).unwrap_or(cite))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    });
+                if let Some(related) = related {
+                    entry.set_field(RELATED_FIELD, related);
+                }
+                let members = entry
+                    .entry_set()
+                    .filter(|members| members.iter().any(|cite| renamed(cite).is_some()))
+                    .map(|members| {
+                        members
+                            .into_iter()
+                            .map(|cite| renamed(cite).unwrap_or(cite))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    });
+                if let Some(members) = members {
+                    entry.set_field(ENTRYSET_FIELD, members);
+                }
+                (entry.cite().to_owned(), entry)
+            })
+            .collect();
+        self.dirty = true;
+        self.index.invalidate();
+
+        let mut renames = renames.into_iter().collect::<Vec<_>>();
+        renames.sort();
+        renames
+    }
+
+    /// Removes the entries that share the same DOI or arXiv ID as another entry and returns the
+    /// entries removed.
+    ///
+    /// A DOI is authoritative so entries with the same normalized DOI are the same work, and so
+    /// are a preprint and its published version with the same [arXiv ID][arxiv_id]. For each set
+    /// of duplicates the published version, with a DOI that is not an arXiv DOI, is kept over a
+    /// preprint and then the entry kept is the one whose [`SOURCE_FIELD`] value appears first in
+    /// the `priority` list, entries without a source or with a source not in the list are the
+    /// least preferred. Ties are broken by keeping the entry with the lowest cite key.
+    ///
+    /// [Locked][Entry::is_locked] entries are never removed and are kept in preference to the
+    /// other entries with the same DOI. A removed preprint is noted on the published version kept,
+    /// see [`Self::remove_duplicate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind, DEFAULT_SOURCE_PRIORITY, SOURCE_FIELD};
+    ///
+    /// let entries = [("a", "manual"), ("b", "crossref")].into_iter().map(|(cite, source)| {
+    ///     let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, cite);
+    ///     resolver.title("Title");
+    ///     resolver.set_field("doi", "10.1000/182");
+    ///     resolver.set_field(SOURCE_FIELD, source);
+    ///     resolver.resolve().unwrap()
+    /// });
+    /// let mut biblio = Biblio::new(entries.collect());
+    ///
+    /// let removed = biblio.dedup_by_doi(DEFAULT_SOURCE_PRIORITY);
+    ///
+    /// assert_eq!("a", removed[0].cite());
+    /// assert!(biblio.get("b").is_some());
+    /// ```
+    pub fn dedup_by_doi<S: AsRef<str>>(&mut self, priority: &[S]) -> Vec<Entry> {
+        let duplicates = self
+            .doi_duplicates(priority)
+            .into_iter()
+            .map(|(entry, kept)| (entry.cite().to_owned(), kept.cite().to_owned()))
+            .collect::<Vec<_>>();
+
+        duplicates
+            .iter()
+            .filter_map(|(cite, kept)| self.remove_duplicate(cite, kept))
+            .collect()
+    }
+
+    /// Returns the entries that [`Self::dedup_by_doi`] would remove without removing them, each
+    /// paired with the entry that is kept in its place.
+    ///
+    /// This allows each removal to be checked first, the entries can then be removed using
+    /// [`Self::remove_duplicate`].
+    #[must_use]
+    pub fn doi_duplicates<S: AsRef<str>>(&self, priority: &[S]) -> Vec<(&Entry, &Entry)> {
+        let rank = |entry: &Entry| {
+            entry
+                .get_field(SOURCE_FIELD)
+                .and_then(|source| {
+                    priority
+                        .iter()
+                        .position(|p| p.as_ref().eq_ignore_ascii_case(source.trim()))
+                })
+                .unwrap_or(priority.len())
+        };
+
+        let preference = |entry: &Entry| (!entry.is_locked(), !is_published(entry), rank(entry));
+
+        // the entries that share a DOI or an arXiv ID, directly or through another entry, are
+        // the same work
+        let entries = self.entries.values().collect::<Vec<_>>();
+        let mut work = (0..entries.len()).collect::<Vec<_>>();
+        let mut first = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let doi = entry
+                .get_field("doi")
+                .map(|doi| format!("doi:{}", normalize_doi(doi)));
+            let arxiv = arxiv_id(entry).map(|id| format!("arxiv:{id}"));
+            for id in doi.into_iter().chain(arxiv) {
+                let other = *first.entry(id).or_insert(i);
+                let (a, b) = (root(&mut work, i), root(&mut work, other));
+                work[a] = b;
+            }
+        }
+
+        let mut kept: HashMap<usize, &Entry> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            kept.entry(root(&mut work, i))
+                .and_modify(|current| {
+                    if (preference(entry), entry.cite()) < (preference(current), current.cite()) {
+                        *current = entry;
+                    }
+                })
+                .or_insert(entry);
+        }
+
+        entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let kept = kept[&root(&mut work, i)];
+                (!entry.is_locked() && kept.cite() != entry.cite()).then_some((*entry, kept))
+            })
+            .collect()
+    }
+
+    /// Removes the `duplicate` entry, found by [`Self::doi_duplicates`], that the `kept` entry is
+    /// kept in place of and returns it, or `None` when there is no `duplicate` entry.
+    ///
+    /// When the duplicate is a preprint of the published version kept, its arXiv ID is added to
+    /// the `note` of the kept entry, such as `Preprint: arXiv:2106.01345`, so that the preprint
+    /// is still cited, unless the kept entry is [locked][Entry::is_locked] or already mentions it.
+    pub fn remove_duplicate(&mut self, duplicate: &str, kept: &str) -> Option<Entry> {
+        let removed = self.entries.remove(duplicate)?;
+        self.dirty = true;
+        self.index.invalidate();
+
+        let preprint = arxiv_id(&removed).filter(|_| !is_published(&removed));
+        let kept = self
+            .entries
+            .get_mut(kept)
+            .filter(|kept| !kept.is_locked() && is_published(kept));
+        if let Some((id, kept)) = preprint.zip(kept) {
+            let preprint = format!("{PREPRINT_NOTE}arXiv:{id}");
+            match kept.get_field("note").map(|note| note.to_string()) {
+                Some(note) if note.contains(&id) => {}
+                Some(note) => kept.set_field("note", format!("{note}; {preprint}")),
+                None => kept.set_field("note", preprint),
+            }
+        }
+        Some(removed)
+    }
+
+    /// Returns the groups of entries with similar titles, which are possibly duplicates.
+    ///
+    /// Titles are compared after [normalization][normalize_title] and are similar when the
+    /// [Levenshtein distance][L] between them is at most one edit for every 20 characters, so
+    /// small differences such as a typo are also found. Unlike a DOI a title doesn't identify a
+    /// single work so the entries are not removed. See [`Biblio::similar_titles_with`] to compare
+    /// the titles in another way.
+    ///
+    /// Each group is sorted by cite key and the groups are sorted by their first cite key.
+    ///
+    /// [L]: https://en.wikipedia.org/wiki/Levenshtein_distance
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind};
+    ///
+    /// let entries = [("a", "Foo Bar."), ("b", "{Foo} bar"), ("c", "Baz")].into_iter().map(
+    ///     |(cite, title)| {
+    ///         let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, cite);
+    ///         resolver.title(title);
+    ///         resolver.resolve().unwrap()
+    ///     },
+    /// );
+    /// let biblio = Biblio::new(entries.collect());
+    ///
+    /// let similar = biblio.similar_titles();
+    ///
+    /// assert_eq!(1, similar.len());
+    /// assert_eq!("a", similar[0][0].cite());
+    /// assert_eq!("b", similar[0][1].cite());
+    /// ```
+    #[must_use]
+    pub fn similar_titles(&self) -> Vec<Vec<&Entry>> {
+        self.similar_titles_with(&Similarity::default())
+    }
+
+    /// Returns the groups of entries with titles that are similar using the `similarity`, see
+    /// [`Biblio::similar_titles`].
+    #[must_use]
+    pub fn similar_titles_with(&self, similarity: &Similarity) -> Vec<Vec<&Entry>> {
+        let mut entries = self
+            .entries
+            .values()
+            .map(|entry| (normalize_title(entry.title()), entry))
+            .filter(|(title, _)| !title.is_empty())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(_, entry)| entry.cite());
+
+        // the groups of the titles seen, so the same title is found without scoring the titles
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(String, usize, Vec<&Entry>)> = Vec::new();
+        for (title, entry) in entries {
+            let len = title.chars().count();
+            let group = seen.get(&title).copied().or_else(|| {
+                groups.iter().position(|(other, other_len, _)| {
+                    similarity.may_be_similar(len, *other_len)
+                        && similarity.is_similar(&title, other)
+                })
+            });
+            match group {
+                Some(i) => {
+                    groups[i].2.push(entry);
+                    seen.entry(title).or_insert(i);
+                }
+                None => {
+                    seen.insert(title.clone(), groups.len());
+                    groups.push((title, len, vec![entry]));
+                }
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(_, _, group)| group)
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+
+    /// Returns the entries that are likely the same work as the `entry`, which is not in this
+    /// [`Biblio`], sorted by cite key.
+    ///
+    /// An entry is likely the same work when its title is similar, in the same way as
+    /// [`Biblio::similar_titles`], and the entries share the family name of an author. Entries
+    /// without authors are compared by title only. This finds the duplicates that a DOI or ISBN
+    /// doesn't, such as the same work added from different identifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, Entry, EntryKind};
+    ///
+    /// let entry = |cite, author| {
+    ///     let mut resolver = Entry::resolver_with_cite(EntryKind::Book, cite);
+    ///     resolver.title("The Art of Computer Programming");
+    ///     resolver.author(author);
+    ///     resolver.publisher("Addison-Wesley");
+    ///     resolver.year("1968");
+    ///     resolver.resolve().unwrap()
+    /// };
+    /// let biblio = Biblio::new(vec![entry("knuth", "Knuth, Donald E.")]);
+    ///
+    /// assert_eq!(1, biblio.similar_to(&entry("taocp", "Donald Knuth")).len());
+    /// assert!(biblio.similar_to(&entry("other", "Lamport, Leslie")).is_empty());
+    /// ```
+    #[must_use]
+    pub fn similar_to(&self, entry: &Entry) -> Vec<&Entry> {
+        self.similar_to_with(entry, &Similarity::default())
+    }
+
+    /// Returns the entries that are likely the same work as the `entry` when the titles are
+    /// compared using the `similarity`, see [`Biblio::similar_to`].
+    #[must_use]
+    pub fn similar_to_with(&self, entry: &Entry, similarity: &Similarity) -> Vec<&Entry> {
+        let title = normalize_title(entry.title());
+        if title.is_empty() {
+            return Vec::new();
+        }
+        let families = |entry: &Entry| {
+            entry
+                .authors()
+                .into_iter()
+                .map(|author| author.family.to_lowercase())
+                .collect::<HashSet<_>>()
+        };
+        let authors = families(entry);
+
+        let len = title.chars().count();
+        let mut similar = self
+            .entries
+            .values()
+            .filter(|other| {
+                let other = normalize_title(other.title());
+                similarity.may_be_similar(len, other.chars().count())
+                    && similarity.is_similar(&title, &other)
+            })
+            .filter(|other| {
+                let others = families(other);
+                authors.is_empty() || others.is_empty() || !authors.is_disjoint(&others)
+            })
+            .collect::<Vec<_>>();
+        similar.sort_by_key(|other| other.cite());
+        similar
+    }
+}
+
+// the suffix used for the `i`th entry with the same cite key: a, b, ..., z, aa, ab, ...
+pub(crate) fn suffix(mut i: usize) -> String {
+    let mut suffix = Vec::new();
+    loop {
+        suffix.push(char::from(
+            b'a' + u8::try_from(i % 26).expect("Less than 26"),
+        ));
+        if i < 26 {
+            break;
+        }
+        i = i / 26 - 1;
+    }
+    suffix.iter().rev().collect()
+}
+
+/// The prefix of the provenance fields that seb adds to entries.
+///
+/// BibTeX styles ignore fields they don't know so these fields are never printed in a reference
+/// list.
+pub const PROVENANCE_PREFIX: &str = "x-seb-";
+
+/// The name of the provenance field that records the source of an entry, such as the name of the
+/// [`Provider`][P] that found it.
+///
+/// [P]: crate::provider::Provider
+pub const SOURCE_FIELD: &str = "x-seb-source";
+
+/// The name of the provenance field that records the date an entry was added.
+pub const ADDED_FIELD: &str = "x-seb-added";
+
+/// The name of the provenance field that records how the cite key of an entry read without one
+/// was generated, which is the placeholders of the [`KeyFormat`][K] used, such as `author+year`,
+/// or `hash` for a key made from a hash of the fields.
+///
+/// [K]: crate::ast::KeyFormat
+pub const KEY_FIELD: &str = "x-seb-key";
+
+/// The name of the field that keeps the DOI of an entry as it was written before it was
+/// lowercased, see [`lowercase_doi`].
+pub const DOI_ORIGINAL_FIELD: &str = "doi-original";
+
+/// The name of the field that lists the fields of an entry that are stored in the sidecar file
+/// of the bibliography, such as `abstract`, see [`Sidecar`][S].
+///
+/// [S]: crate::file::Sidecar
+pub const SIDECAR_FIELD: &str = "x-seb-sidecar";
+
+/// The name of the field that marks an entry as locked when its value is `true`.
+///
+/// Locked entries are skipped by operations that change many entries at once, such as
+/// [`Biblio::map_fields`] and [`Biblio::dedup_by_doi`].
+pub const LOCKED_FIELD: &str = "x-seb-locked";
+
+/// The name of the field that marks an entry that was added without all of its required fields,
+/// see [`Resolver::resolve_incomplete`].
+///
+/// The value is the kind the entry was meant to be and the required fields that were missing,
+/// for example `article: journal, year`.
+pub const INCOMPLETE_FIELD: &str = "x-seb-incomplete";
+
+/// The name of the field that records the date a URL was accessed, such as `2024-01-01`.
+///
+/// Classic `BibTeX` has no such field so the date is composed in the `note` field as
+/// `Accessed: 2024-01-01` and read back from there, see [`Dialect`][D].
+///
+/// [D]: crate::format::Dialect
+pub const URLDATE_FIELD: &str = "urldate";
+
+/// The name of the field of a `BibLaTeX` `@set` entry that lists the cite keys of the entries in
+/// the set, see [`Entry::entry_set`].
+pub const ENTRYSET_FIELD: &str = "entryset";
+
+/// The name of the `BibLaTeX` field that lists the cite keys of the entries related to an entry,
+/// such as the original of a translation, see [`Entry::related`].
+pub const RELATED_FIELD: &str = "related";
+
+/// The name of the `BibLaTeX` field that names the relationship of the entries in the
+/// [`RELATED_FIELD`], such as `translationof` or `reprintof`.
+pub const RELATEDTYPE_FIELD: &str = "relatedtype";
+
+/// The default source priority used when deduplicating entries, from most to least preferred.
+pub const DEFAULT_SOURCE_PRIORITY: &[&str] =
+    &["crossref", "google books", "ietf", "ads", "ieee", "manual"];
+
+// the URLs of the DOI resolver, which are followed by the DOI
+const DOI_URL_PREFIXES: [&str; 4] = [
+    "https://doi.org/",
+    "http://doi.org/",
+    "https://dx.doi.org/",
+    "http://dx.doi.org/",
+];
+
+/// Normalizes a DOI so that the same DOI written in different ways can be compared.
+///
+/// DOIs are case insensitive and can be written as a URL or with a `doi:` prefix.
+#[must_use]
+pub fn normalize_doi(doi: &str) -> String {
+    let doi = doi.trim().to_lowercase();
+    let doi = DOI_URL_PREFIXES
+        .into_iter()
+        .chain(["doi:"])
+        .find_map(|prefix| doi.strip_prefix(prefix))
+        .unwrap_or(&doi);
+    doi.trim().to_owned()
+}
+
+/// Returns the DOI of a link to the DOI resolver, such as `https://doi.org/10.1000/182`, or
+/// `None` when the `url` links anywhere else.
+///
+/// A link with a query or a fragment is not only a DOI so it is not a DOI link.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::doi_from_url;
+///
+/// assert_eq!(Some("10.1000/182"), doi_from_url("https://doi.org/10.1000/182"));
+/// assert_eq!(Some("10.1000/ABC"), doi_from_url("http://DX.doi.org/10.1000/ABC "));
+/// assert_eq!(None, doi_from_url("https://doi.org/10.1000/182?download=true"));
+/// assert_eq!(None, doi_from_url("https://example.com/10.1000/182"));
+/// ```
+#[must_use]
+pub fn doi_from_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let doi = DOI_URL_PREFIXES.into_iter().find_map(|prefix| {
+        let start = url.get(..prefix.len())?;
+        start
+            .eq_ignore_ascii_case(prefix)
+            .then(|| &url[prefix.len()..])
+    })?;
+    let valid = doi.starts_with("10.")
+        && doi.contains('/')
+        && !doi.contains(|c: char| c == '?' || c == '#' || c.is_whitespace());
+    valid.then_some(doi)
+}
+
+/// Moves a link to the DOI resolver out of the `url` field of the `entry`, and returns whether
+/// the entry changed.
+///
+/// The `url` is removed when it links to the DOI of the entry, as the DOI already links to the
+/// work, and an entry without a DOI has the DOI of the link moved to its `doi` field. A `url`
+/// that links to a different DOI, or anywhere else, is left as it is.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{move_doi_url, Entry, EntryKind, FieldQuery};
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("Title");
+/// resolver.set_field("url", "https://doi.org/10.1000/182");
+/// let mut entry = resolver.resolve().unwrap();
+///
+/// assert!(move_doi_url(&mut entry));
+/// assert_eq!("10.1000/182", &**entry.get_field("doi").unwrap());
+/// assert_eq!(None, entry.get_field("url"));
+/// assert!(!move_doi_url(&mut entry));
+/// ```
+pub fn move_doi_url(entry: &mut Entry) -> bool {
+    let Some(url_doi) = entry
+        .get_field("url")
+        .and_then(|url| doi_from_url(url))
+        .map(str::to_owned)
+    else {
+        return false;
+    };
+
+    match entry.get_field("doi").filter(|doi| !doi.trim().is_empty()) {
+        Some(doi) if normalize_doi(doi) != normalize_doi(&url_doi) => return false,
+        Some(_) => {}
+        None => entry.set_field("doi", url_doi),
+    }
+    entry.remove_field("url");
+    true
+}
+
+/// Lowercases the `doi` field of the `entry`, as DOIs are case insensitive, and returns whether
+/// it changed.
+///
+/// The DOI as it was is kept in the [`DOI_ORIGINAL_FIELD`] when lowercasing changes it.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{lowercase_doi, Entry, EntryKind, FieldQuery, DOI_ORIGINAL_FIELD};
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("Title");
+/// resolver.set_field("doi", "10.1007/978-3-319-13451-8_1_LNCS");
+/// let mut entry = resolver.resolve().unwrap();
+///
+/// assert!(lowercase_doi(&mut entry));
+/// assert_eq!("10.1007/978-3-319-13451-8_1_lncs", &**entry.get_field("doi").unwrap());
+/// assert_eq!(
+///     "10.1007/978-3-319-13451-8_1_LNCS",
+///     &**entry.get_field(DOI_ORIGINAL_FIELD).unwrap()
+/// );
+/// assert!(!lowercase_doi(&mut entry));
+/// ```
+pub fn lowercase_doi(entry: &mut Entry) -> bool {
+    let Some(doi) = entry.get_field("doi") else {
+        return false;
+    };
+    let lowercase = doi.map_parts(str::to_lowercase);
+    if lowercase == *doi {
+        return false;
+    }
+
+    let original = doi.clone();
+    entry.set_field("doi", lowercase);
+    entry.set_field(DOI_ORIGINAL_FIELD, original);
+    true
+}
+
+// The start of the note added to a published entry for its preprint removed as a duplicate.
+const PREPRINT_NOTE: &str = "Preprint: ";
+
+// Whether the `entry` is a published version, with a DOI that is not an arXiv DOI.
+fn is_published(entry: &Entry) -> bool {
+    entry
+        .get_field("doi")
+        .is_some_and(|doi| normalize_arxiv_id(doi).is_none())
+}
+
+/// Normalizes an arXiv ID so that the same preprint written in different ways can be compared, or
+/// returns `None` when the `id` is not an arXiv ID.
+///
+/// The ID can be written with an `arXiv:` prefix, as an arXiv URL or as an arXiv DOI, and the
+/// version is removed as every version is the same work. The archive of an old style ID is
+/// lowercased and its subject class keeps its case, such as `math.GT/0309136`.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::normalize_arxiv_id;
+///
+/// let id = Some("2106.01345".to_owned());
+/// assert_eq!(id, normalize_arxiv_id("arXiv:2106.01345v2"));
+/// assert_eq!(id, normalize_arxiv_id("https://arxiv.org/abs/2106.01345"));
+/// assert_eq!(id, normalize_arxiv_id("https://arxiv.org/pdf/2106.01345v1.pdf"));
+/// assert_eq!(id, normalize_arxiv_id("10.48550/arXiv.2106.01345"));
+/// assert_eq!(
+///     Some("hep-th/9901001".to_owned()),
+///     normalize_arxiv_id("hep-th/9901001v1")
+/// );
+/// assert_eq!(
+///     Some("math.GT/0309136".to_owned()),
+///     normalize_arxiv_id("arXiv:math.GT/0309136")
+/// );
+/// assert_eq!(None, normalize_arxiv_id("10.1007/s00453-019-00634-0"));
+/// ```
+#[must_use]
+pub fn normalize_arxiv_id(id: &str) -> Option<String> {
+    let id = id.trim();
+    // the prefixes are found in the ASCII lowercase ID, which has the same length, so that the
+    // case of the subject class of an old style ID is kept
+    let lower = id.to_ascii_lowercase();
+    let start = ["arxiv.org/abs/", "arxiv.org/pdf/", "10.48550/arxiv."]
+        .into_iter()
+        .find_map(|prefix| Some(lower.find(prefix)? + prefix.len()))
+        .or_else(|| lower.starts_with("arxiv:").then_some("arxiv:".len()))
+        .unwrap_or(0);
+    let id = &id[start..];
+    let id = if lower.ends_with(".pdf") {
+        &id[..id.len() - ".pdf".len()]
+    } else {
+        id
+    };
+
+    // the version is a `v` followed by digits at the end of the ID
+    let id = match id.rsplit_once(['v', 'V']) {
+        Some((id, version))
+            if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            id
+        }
+        _ => id,
+    };
+
+    let digits = |part: &str, lens: &[usize]| {
+        lens.contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit())
+    };
+    match id.split_once('.') {
+        // new style IDs, such as `2106.01345`
+        Some((yymm, number)) if digits(yymm, &[4]) => {
+            digits(number, &[4, 5]).then(|| id.to_owned())
+        }
+        // old style IDs have the archive, and its subject class, such as `math.GT/0309136`
+        _ => {
+            let (archive, number) = id.split_once('/')?;
+            let (archive, class) = archive.split_once('.').unwrap_or((archive, ""));
+            let name = |part: &str| part.chars().all(|c| c.is_ascii_alphabetic() || c == '-');
+            let valid = !archive.is_empty() && name(archive) && name(class) && digits(number, &[7]);
+            valid.then(|| {
+                let archive = archive.to_ascii_lowercase();
+                if class.is_empty() {
+                    format!("{archive}/{number}")
+                } else {
+                    format!("{archive}.{class}/{number}")
+                }
+            })
+        }
+    }
+}
+
+/// Returns the normalized arXiv ID of the `entry`, see [`normalize_arxiv_id`], from the `eprint`
+/// field, unless the `eprinttype` is another archive, or else from the `url` or `doi` field.
+#[must_use]
+pub fn arxiv_id(entry: &Entry) -> Option<String> {
+    let arxiv_eprint = entry
+        .get_field("eprinttype")
+        .is_none_or(|kind| kind.trim().eq_ignore_ascii_case("arxiv"));
+    let eprint = entry.get_field("eprint").filter(|_| arxiv_eprint);
+
+    [eprint, entry.get_field("url"), entry.get_field("doi")]
+        .into_iter()
+        .flatten()
+        .find_map(|id| normalize_arxiv_id(id))
+}
+
+/// Stores the arXiv ID of the `entry`, see [`arxiv_id`], in the `eprint` field with the
+/// `eprinttype` of `arxiv`, and returns whether the entry changed.
+///
+/// An entry with the `eprint` of another archive is left as it is.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{store_arxiv_id, Entry, EntryKind, FieldQuery};
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("Title");
+/// resolver.set_field("url", "https://arxiv.org/abs/2106.01345v2");
+/// let mut entry = resolver.resolve().unwrap();
+///
+/// assert!(store_arxiv_id(&mut entry));
+/// assert_eq!("2106.01345", &**entry.get_field("eprint").unwrap());
+/// assert_eq!("arxiv", &**entry.get_field("eprinttype").unwrap());
+/// assert!(!store_arxiv_id(&mut entry));
+/// ```
+pub fn store_arxiv_id(entry: &mut Entry) -> bool {
+    let Some(id) = arxiv_id(entry) else {
+        return false;
+    };
+    let other_archive = entry
+        .get_field("eprinttype")
+        .is_some_and(|kind| !kind.trim().eq_ignore_ascii_case("arxiv"));
+    if other_archive && entry.get_field("eprint").is_some() {
+        return false;
+    }
+
+    let mut changed = false;
+    if entry.get_field("eprint").map(|eprint| eprint.trim()) != Some(id.as_str()) {
+        entry.set_field("eprint", id);
+        changed = true;
+    }
+    if other_archive || entry.get_field("eprinttype").is_none() {
+        entry.set_field("eprinttype", "arxiv");
+        changed = true;
+    }
+    changed
+}
+
+// The root of the set of `i`, which is the same work for every entry in the set.
+fn root(sets: &mut [usize], mut i: usize) -> usize {
+    while sets[i] != i {
+        sets[i] = sets[sets[i]];
+        i = sets[i];
+    }
+    i
+}
+
+/// Normalizes a title so that the same title written in slightly different ways can be compared.
+///
+/// The title is lowercased, the `LaTeX` braces are removed, whitespace is collapsed to a single
+/// space and trailing punctuation is removed. This is only used for comparing titles, the titles
+/// of entries are never changed.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::normalize_title;
+///
+/// assert_eq!("foo bar", normalize_title("  {Foo}   Bar. "));
+/// ```
+#[must_use]
+pub fn normalize_title(title: &str) -> String {
+    let title = title
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}'))
+        .collect::<String>()
+        .to_lowercase();
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .trim_end()
+        .to_owned()
 }
 
 #[cfg(test)]
@@ -120,10 +1195,415 @@ mod tests {
 
     use std::collections::HashMap;
 
-    use crate::ast::Manual;
+    use crate::ast::{EntryKind, Manual};
 
     use super::*;
 
+    fn doi_entry(cite: &str, doi: &str, source: Option<&str>) -> Entry {
+        let mut optional = HashMap::from([("doi".to_owned(), doi.into())]);
+        if let Some(source) = source {
+            optional.insert(SOURCE_FIELD.to_owned(), source.into());
+        }
+        Entry::Manual(Manual {
+            cite: cite.to_owned(),
+            title: "Title".into(),
+            optional,
+        })
+    }
+
+    #[test]
+    fn dedup_by_doi_keeps_preferred_source() {
+        let mut biblio = Biblio::new(vec![
+            doi_entry("scholar", "10.1000/182", Some("scholar")),
+            doi_entry("crossref", "https://doi.org/10.1000/182", Some("crossref")),
+            doi_entry("unknown", "DOI:10.1000/182", None),
+            doi_entry("other", "10.1000/183", Some("scholar")),
+        ]);
+
+        let mut removed = biblio
+            .dedup_by_doi(&["crossref", "manual", "scholar"])
+            .into_iter()
+            .map(|entry| entry.cite().to_owned())
+            .collect::<Vec<_>>();
+        removed.sort();
+
+        assert_eq!(vec!["scholar", "unknown"], removed);
+        assert!(biblio.get("crossref").is_some());
+        assert!(biblio.get("other").is_some());
+        assert!(biblio.dirty());
+    }
+
+    #[test]
+    fn dedup_by_doi_without_duplicates_is_clean() {
+        let mut biblio = Biblio::new(vec![
+            doi_entry("a", "10.1000/182", None),
+            doi_entry("b", "10.1000/183", None),
+        ]);
+
+        assert!(biblio.dedup_by_doi(DEFAULT_SOURCE_PRIORITY).is_empty());
+        assert!(!biblio.dirty());
+    }
+
+    #[test]
+    fn redundant_doi_url_is_removed() {
+        let url_entry = |cite: &str, doi: &str, url: &str| {
+            let mut entry = doi_entry(cite, doi, None);
+            entry.set_field("url", url);
+            entry
+        };
+        let mut biblio = Biblio::new(vec![
+            url_entry("same", "10.1000/ABC", "https://dx.doi.org/10.1000/abc"),
+            url_entry("other", "10.1000/182", "https://doi.org/10.1000/183"),
+            url_entry("page", "10.1000/182", "https://example.com/paper"),
+            url_entry(
+                "query",
+                "10.1000/182",
+                "https://doi.org/10.1000/182?download=1",
+            ),
+        ]);
+
+        assert_eq!(1, biblio.move_doi_urls());
+
+        assert_eq!(None, biblio.get("same").unwrap().get_field("url"));
+        assert_eq!(
+            "10.1000/ABC",
+            &**biblio.get("same").unwrap().get_field("doi").unwrap()
+        );
+        for cite in ["other", "page", "query"] {
+            assert!(biblio.get(cite).unwrap().get_field("url").is_some());
+        }
+        assert!(biblio.dirty());
+    }
+
+    #[test]
+    fn doi_is_extracted_from_doi_url() {
+        let mut entry = doi_entry("cite", " ", None);
+        entry.set_field("url", "http://doi.org/10.1007/s00453-019-00634-0");
+        let mut locked = doi_entry("locked", "", None);
+        locked.set_field("url", "https://doi.org/10.1000/182");
+        locked.set_field(LOCKED_FIELD, "true");
+        let mut biblio = Biblio::new(vec![entry, locked]);
+
+        assert_eq!(1, biblio.move_doi_urls());
+
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!(
+            "10.1007/s00453-019-00634-0",
+            &**entry.get_field("doi").unwrap()
+        );
+        assert_eq!(None, entry.get_field("url"));
+        assert!(biblio.get("locked").unwrap().get_field("url").is_some());
+    }
+
+    #[test]
+    fn rekey_resolves_collisions_and_updates_crossref() {
+        let entry = |cite: &str, author: &str, crossref: Option<&str>| {
+            let mut optional = HashMap::from([
+                ("author".to_owned(), author.into()),
+                ("year".to_owned(), "2019".into()),
+            ]);
+            if let Some(crossref) = crossref {
+                optional.insert("crossref".to_owned(), crossref.into());
+            }
+            Entry::Manual(Manual {
+                cite: cite.to_owned(),
+                title: "Title".into(),
+                optional,
+            })
+        };
+        let mut biblio = Biblio::new(vec![
+            entry("book", "Stefan Edelkamp", None),
+            entry("chapter", "Edelkamp, Stefan", Some("book")),
+            // the crossref of a different case still references the chapter
+            entry("other", "Armin Weiss", Some("Chapter")),
+        ]);
+
+        let changed = biblio.rekey(&KeyFormat::default());
+
+        assert_eq!(
+            vec![
+                ("book".to_owned(), "Edelkamp2019a".to_owned()),
+                ("chapter".to_owned(), "Edelkamp2019b".to_owned()),
+                ("other".to_owned(), "Weiss2019".to_owned()),
+            ],
+            changed
+        );
+        let crossref = |cite: &str| {
+            biblio
+                .get(cite)
+                .and_then(|entry| entry.get_field("crossref"))
+                .map(|crossref| crossref.to_string())
+        };
+        assert_eq!(Some("Edelkamp2019a".to_owned()), crossref("Edelkamp2019b"));
+        assert_eq!(Some("Edelkamp2019b".to_owned()), crossref("Weiss2019"));
+        assert!(biblio.dirty());
+    }
+
+    #[test]
+    fn rekey_updates_how_a_generated_key_was_made() {
+        let mut biblio = Biblio::new(vec![Entry::Manual(Manual {
+            cite: "a1b2c3".to_owned(),
+            title: "Title".into(),
+            optional: HashMap::from([
+                ("author".to_owned(), "Donald Knuth".into()),
+                (KEY_FIELD.to_owned(), "hash".into()),
+            ]),
+        })]);
+
+        biblio.rekey(&"{author}{title}".parse().unwrap());
+
+        let entry = biblio.get("KnuthTitle").unwrap();
+        assert_eq!("author+title", &**entry.get_field(KEY_FIELD).unwrap());
+    }
+
+    #[test]
+    fn rekey_updates_related() {
+        let mut translation = Entry::Manual(Manual {
+            cite: "translation".to_owned(),
+            title: "Translation".into(),
+            optional: HashMap::from([
+                ("author".to_owned(), "Alan Turing".into()),
+                ("year".to_owned(), "1992".into()),
+                (RELATED_FIELD.to_owned(), "original, other".into()),
+            ]),
+        });
+        translation.set_field(RELATEDTYPE_FIELD, "translationof");
+        let original = Entry::Manual(Manual {
+            cite: "original".to_owned(),
+            title: "Original".into(),
+            optional: HashMap::from([
+                ("author".to_owned(), "Alan Turing".into()),
+                ("year".to_owned(), "1931".into()),
+            ]),
+        });
+        let mut biblio = Biblio::new(vec![translation, original]);
+
+        biblio.rekey(&KeyFormat::default());
+
+        assert_eq!(
+            Some(vec!["Turing1931", "other"]),
+            biblio.get("Turing1992").unwrap().related()
+        );
+    }
+
+    #[test]
+    fn rekey_updates_entry_set_members() {
+        let mut set = Entry::resolver_with_cite(EntryKind::Other("set".into()), "set");
+        set.set_field(ENTRYSET_FIELD, "original, other");
+        set.set_field(LOCKED_FIELD, "true");
+        let original = Entry::Manual(Manual {
+            cite: "original".to_owned(),
+            title: "Original".into(),
+            optional: HashMap::from([
+                ("author".to_owned(), "Alan Turing".into()),
+                ("year".to_owned(), "1931".into()),
+            ]),
+        });
+        let mut biblio = Biblio::new(vec![set.resolve().unwrap(), original]);
+
+        biblio.rekey(&KeyFormat::default());
+
+        assert_eq!(
+            Some(vec!["Turing1931", "other"]),
+            biblio.get("set").unwrap().entry_set()
+        );
+    }
+
+    #[test]
+    fn find_by_field_index_is_rebuilt_after_changes() {
+        let mut biblio = Biblio::new(vec![doi_entry("first", "10.1000/182", None)]);
+
+        assert_eq!(1, biblio.find_by_field("doi", "DOI:10.1000/182").len());
+        assert!(biblio.find_by_field("doi", "10.1000/183").is_empty());
+
+        biblio.insert(doi_entry("second", "10.1000/183", None));
+        assert_eq!(
+            "second",
+            biblio.find_by_field("doi", "10.1000/183")[0].cite()
+        );
+
+        biblio.remove("first");
+        assert!(biblio.find_by_field("doi", "10.1000/182").is_empty());
+
+        biblio
+            .get_mut("second")
+            .unwrap()
+            .set_field("isbn", "978-0-201-89683-1");
+        assert_eq!(1, biblio.find_by_field("isbn", "9780201896831").len());
+        assert_eq!(1, biblio.find_by_field("cite", "SECOND").len());
+    }
+
+    #[test]
+    fn find_by_field_key_is_the_bibtex_key_field() {
+        let mut entry = doi_entry("cite", "10.1000/182", None);
+        entry.set_field("key", "Sort key");
+        let biblio = Biblio::new(vec![entry]);
+
+        assert!(biblio.find_by_field("key", "cite").is_empty());
+        assert_eq!(1, biblio.find_by_field("key", "Sort key").len());
+    }
+
+    #[test]
+    fn find_by_field_not_indexed_is_exact() {
+        let biblio = Biblio::new(vec![doi_entry("cite", "10.1000/182", Some("crossref"))]);
+
+        assert_eq!(1, biblio.find_by_field(SOURCE_FIELD, "crossref").len());
+        assert!(biblio.find_by_field(SOURCE_FIELD, "Crossref").is_empty());
+    }
+
+    fn titled(cite: &str, title: &str) -> Entry {
+        Entry::Manual(Manual {
+            cite: cite.to_owned(),
+            title: title.into(),
+            optional: HashMap::new(),
+        })
+    }
+
+    #[test]
+    fn trailing_period_titles_are_similar() {
+        let biblio = Biblio::new(vec![titled("a", "Foo Bar."), titled("b", "Foo Bar")]);
+
+        let similar = biblio.similar_titles();
+
+        assert_eq!(1, similar.len());
+        assert_eq!(
+            vec!["a", "b"],
+            similar[0].iter().map(|e| e.cite()).collect::<Vec<_>>()
+        );
+        // the stored titles are unchanged
+        assert_eq!("Foo Bar.", &**biblio.get("a").unwrap().title());
+    }
+
+    #[test]
+    fn near_match_titles_are_similar() {
+        let biblio = Biblio::new(vec![
+            titled("typo", "The Art of Computer Programing"),
+            titled("correct", "The  {Art} of Computer Programming!"),
+            titled("other", "The Art of Computer Science"),
+            titled("short", "Foo"),
+            titled("shorter", "Fob"),
+        ]);
+
+        let similar = biblio
+            .similar_titles()
+            .into_iter()
+            .map(|group| group.into_iter().map(Entry::cite).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![vec!["correct", "typo"]], similar);
+    }
+
+    fn authored(cite: &str, title: &str, author: &str) -> Entry {
+        Entry::Manual(Manual {
+            cite: cite.to_owned(),
+            title: title.into(),
+            optional: HashMap::from([("author".to_owned(), author.into())]),
+        })
+    }
+
+    #[test]
+    fn similar_to_needs_a_similar_title_and_a_shared_author() {
+        let biblio = Biblio::new(vec![
+            authored(
+                "knuth",
+                "The Art of Computer Programming",
+                "Knuth, Donald E.",
+            ),
+            authored(
+                "lamport",
+                "The Art of Computer Programming",
+                "Leslie Lamport",
+            ),
+            authored("other", "Literate Programming", "Donald E. Knuth"),
+            titled("anonymous", "The Art of Computer Programing"),
+        ]);
+
+        let similar = biblio.similar_to(&authored(
+            "taocp",
+            "The {Art} of Computer Programming.",
+            "Donald Knuth and Someone Else",
+        ));
+
+        assert_eq!(
+            vec!["anonymous", "knuth"],
+            similar.into_iter().map(Entry::cite).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rekey_suffixes_do_not_depend_on_the_order_of_the_entries() {
+        let cites = ["h", "c", "f", "a", "e", "b", "g", "d"];
+        let entry = |cite: &str| {
+            let mut entry = titled(cite, "Title");
+            entry.set_field("author", "Donald Knuth");
+            entry.set_field("year", "1968");
+            entry
+        };
+        let expected = ["a", "b", "c", "d", "e", "f", "g", "h"]
+            .into_iter()
+            .map(|cite| (cite.to_owned(), format!("Knuth1968{cite}")))
+            .collect::<Vec<_>>();
+
+        // every map iterates the entries in a different order
+        for n in 0..cites.len() {
+            let mut entries = cites.map(entry).into_iter().collect::<Vec<_>>();
+            entries.rotate_left(n);
+            let mut biblio = Biblio::new(entries);
+
+            assert_eq!(expected, biblio.rekey(&KeyFormat::default()));
+        }
+    }
+
+    #[test]
+    fn rekey_suffixes() {
+        assert_eq!("a", suffix(0));
+        assert_eq!("z", suffix(25));
+        assert_eq!("aa", suffix(26));
+        assert_eq!("ba", suffix(52));
+    }
+
+    #[test]
+    fn preprint_and_published_version_with_same_eprint_are_duplicates() {
+        let mut preprint = doi_entry("preprint", "10.48550/arXiv.2106.01345", Some("arxiv"));
+        preprint.set_field("eprint", "arXiv:2106.01345v1");
+        let mut published = doi_entry("published", "10.1007/s00453-019-00634-0", Some("scholar"));
+        published.set_field("eprint", "2106.01345");
+        published.set_field("eprinttype", "arXiv");
+        let mut biblio = Biblio::new(vec![
+            preprint,
+            published,
+            doi_entry("other", "10.48550/arXiv.2106.01346", Some("arxiv")),
+        ]);
+
+        let removed = biblio.dedup_by_doi(&["arxiv", "scholar"]);
+
+        assert_eq!(
+            vec!["preprint"],
+            removed.iter().map(Entry::cite).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "Preprint: arXiv:2106.01345",
+            &**biblio.get("published").unwrap().get_field("note").unwrap()
+        );
+        assert!(biblio.get("other").is_some());
+    }
+
+    #[test]
+    fn dedup_by_doi_keeps_locked_entries() {
+        let mut locked = doi_entry("locked", "10.1000/182", None);
+        locked.set_field(LOCKED_FIELD, "true");
+        let mut biblio = Biblio::new(vec![
+            locked,
+            doi_entry("crossref", "10.1000/182", Some("crossref")),
+        ]);
+
+        let removed = biblio.dedup_by_doi(DEFAULT_SOURCE_PRIORITY);
+
+        assert_eq!(1, removed.len());
+        assert_eq!("crossref", removed[0].cite());
+        assert!(biblio.get("locked").is_some());
+    }
+
     #[test]
     fn insertion_of_entry_makes_biblio_dirty() {
         let mut biblio = Biblio::default();
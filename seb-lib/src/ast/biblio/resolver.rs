@@ -1,4 +1,4 @@
-use crate::ast::{Biblio, Entry, FieldQuery, Resolver};
+use crate::ast::{Biblio, Blocks, Entry, FieldQuery, Resolver};
 
 /// A [`Biblio`] resolver used for managing a set of entry resolvers until they all succeed in order
 /// to make a [`Biblio`] with valid entries in.
@@ -7,6 +7,7 @@ pub struct BiblioResolver {
     pub(super) failed: bool,
     pub(super) resolvers: Vec<Resolver>,
     pub(super) entries: Vec<Entry>,
+    pub(super) blocks: Blocks,
 }
 
 impl BiblioResolver {
@@ -26,11 +27,8 @@ impl BiblioResolver {
         if resolvers.is_empty() {
             Ok(Biblio {
                 dirty: self.failed,
-                entries: self
-                    .entries
-                    .into_iter()
-                    .map(|e| (e.cite().to_owned(), e))
-                    .collect(),
+                blocks: self.blocks,
+                ..Biblio::new(self.entries)
             })
         } else {
             self.resolvers = resolvers;
@@ -62,6 +60,35 @@ impl BiblioResolver {
         }
     }
 
+    /// Sets the field on every resolved and unresolved entry.
+    pub(crate) fn set_field_all(&mut self, name: &str, value: &str) {
+        self.entries
+            .iter_mut()
+            .for_each(|entry| entry.set_field(name, value));
+        self.resolvers
+            .iter_mut()
+            .for_each(|resolver| resolver.set_field(name, value));
+    }
+
+    /// Returns a [`Biblio`] of both the resolved and unresolved entries, where the required fields
+    /// missing from the unresolved entries are empty, see [`Resolver::resolve_partial`].
+    ///
+    /// This allows for viewing what a possibly unresolved Biblio contains without having to fully
+    /// resolve it first. The [`Biblio`] should not be written back as the missing fields would be
+    /// written as empty fields.
+    #[must_use]
+    pub fn into_partial(self) -> Biblio {
+        Biblio {
+            blocks: self.blocks,
+            ..Biblio::new(
+                self.entries
+                    .into_iter()
+                    .chain(self.resolvers.into_iter().map(Resolver::resolve_partial))
+                    .collect(),
+            )
+        }
+    }
+
     /// Returns an iterator of both resolved and unresolved entries which impl [`FieldQuery`].
     ///
     /// This allows for querying what a possibly unresolved Biblio contains without having to fully
@@ -117,6 +144,7 @@ mod tests {
             failed: false,
             resolvers: vec![],
             entries: vec![],
+            blocks: Blocks::default(),
         };
 
         assert_eq!(None, resolver.checked_remove(0).map(|_| ()));
@@ -134,6 +162,7 @@ mod tests {
             failed: false,
             resolvers: vec![],
             entries: vec![entry.clone()],
+            blocks: Blocks::default(),
         };
 
         let removed = resolver
@@ -152,6 +181,7 @@ mod tests {
             failed: false,
             resolvers: vec![resolver.clone()],
             entries: vec![],
+            blocks: Blocks::default(),
         };
 
         let removed = biblio_resolver
@@ -162,6 +192,30 @@ mod tests {
         assert_eq!(resolver, removed);
     }
 
+    #[test]
+    fn into_partial_contains_resolved_and_unresolved_entries() {
+        let entry = Entry::Manual(Manual {
+            cite: "manual".to_owned(),
+            title: "Title".into(),
+            optional: std::collections::HashMap::default(),
+        });
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "article");
+        resolver.title("Incomplete");
+
+        let biblio = BiblioResolver {
+            failed: false,
+            resolvers: vec![resolver],
+            entries: vec![entry.clone()],
+            blocks: Blocks::default(),
+        }
+        .into_partial();
+
+        assert_eq!(Some(&entry), biblio.get("manual"));
+        let article = biblio.get("article").unwrap();
+        assert_eq!("Incomplete", &**article.title());
+        assert_eq!(Some(""), article.get_field("journal").map(|qs| &**qs));
+    }
+
     #[test]
     fn checked_remove_indexes_resolved_before_unresolved() {
         let entry = Entry::Manual(Manual {
@@ -176,6 +230,7 @@ mod tests {
             failed: false,
             resolvers: vec![resolver.clone()],
             entries: vec![entry.clone()],
+            blocks: Blocks::default(),
         };
 
         let mut biblio_resolver = create_biblio_resolver_with_both();
@@ -233,6 +288,7 @@ mod tests {
             failed: false,
             resolvers: vec![resolver],
             entries: vec![entry],
+            blocks: Blocks::default(),
         };
 
         let mut iter = biblio_resolver.iter();
@@ -253,6 +309,7 @@ mod tests {
             failed: false,
             resolvers: vec![resolver_one.clone(), resolver_two.clone()],
             entries: vec![],
+            blocks: Blocks::default(),
         };
 
         let display = biblio_resolver.to_string();
@@ -1,6 +1,9 @@
 use std::{borrow::Cow, collections::HashMap};
 
-use super::{Field, QuotedString};
+use super::{
+    Author, Field, FieldFilter, List, ListField, Month, Pages, QuotedString, Role, ENTRYSET_FIELD,
+    LOCKED_FIELD, PROVENANCE_PREFIX, RELATED_FIELD,
+};
 
 mod resolver;
 
@@ -50,6 +53,12 @@ macro_rules! entry_impl {
         }
 
         impl EntryKind<'_> {
+            /// Returns every known entry kind, which is every kind except [`EntryKind::Other`].
+            #[must_use]
+            pub const fn known() -> &'static [EntryKind<'static>] {
+                &[$(EntryKind::$target,)*]
+            }
+
             /// Returns a slice of the required fields that need to be set in order to make this
             /// entry kind valid.
             #[must_use]
@@ -61,12 +70,35 @@ macro_rules! entry_impl {
             }
         }
 
+        /// The entry kind of a name, such as `in proceedings`, or of a `BibTeX` or `BibLaTeX` entry
+        /// type, such as `@inproceedings` or `conference`, ignoring case, spaces, underscores and
+        /// dashes.
+        ///
+        /// Any other name is a custom entry kind.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use seb::ast::EntryKind;
+        ///
+        /// for name in ["@inproceedings", "conference", "in proceedings", "InProceedings"] {
+        ///     assert_eq!(EntryKind::InProceedings, EntryKind::from(name));
+        /// }
+        /// assert_eq!(EntryKind::TechReport, EntryKind::from("report"));
+        /// assert_eq!(EntryKind::Other("online".into()), EntryKind::from("@online"));
+        /// ```
         impl From<&str> for EntryKind<'static> {
             fn from(s: &str) -> Self {
-                let s = s.to_lowercase();
-                match s.as_str() {
-                    $($display => Self::$target,)*
-                    _ => Self::Other(s.into()),
+                let s = s.trim().trim_start_matches('@').to_lowercase();
+                let compact = s.replace([' ', '_', '-'], "");
+                match compact.as_str() {
+                    $(name if $display.chars().filter(|c| *c != ' ').eq(name.chars()) => {
+                        Self::$target
+                    })*
+                    name => KIND_ALIASES
+                        .iter()
+                        .find(|(alias, _)| *alias == name)
+                        .map_or_else(|| Self::Other(s.into()), |(_, kind)| kind.clone()),
                 }
             }
         }
@@ -111,6 +143,92 @@ macro_rules! entry_impl {
                 }
             }
 
+            /// Returns a copy of this entry, with the same kind and fields, with the `cite` key.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, FieldQuery};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "manual");
+            /// resolver.title("Title");
+            /// resolver.set_field("year", "2020");
+            /// let entry = resolver.resolve().unwrap();
+            ///
+            /// let copy = entry.copy_with_cite("copy");
+            ///
+            /// assert_eq!("copy", copy.cite());
+            /// assert_eq!(entry.kind(), copy.kind());
+            /// assert_eq!(entry.get_field("year"), copy.get_field("year"));
+            /// ```
+            #[must_use]
+            pub fn copy_with_cite<S: Into<String>>(&self, cite: S) -> Self {
+                let mut resolver = Self::resolver_with_cite(self.kind(), cite);
+                resolver.set_fields_from_entry(self);
+                // the fields of a valid entry always resolve
+                resolver.resolve_partial()
+            }
+
+            /// Returns a copy of this entry as the `kind` given, with the same cite key and
+            /// fields.
+            ///
+            /// A required field of the `kind` that this entry does not have is taken from a field
+            /// naming the same kind of venue, so the `journal` of an article becomes the
+            /// `book_title` of a paper in proceedings and the `school` of a thesis becomes the
+            /// `institution` of a report. The field is moved rather than copied.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, FieldQuery};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "cite");
+            /// resolver.set_field("author", "Author");
+            /// resolver.title("Title");
+            /// resolver.set_field("journal", "Proceedings of the Conference");
+            /// resolver.set_field("year", "2020");
+            /// let entry = resolver.resolve().unwrap();
+            ///
+            /// let paper = entry.convert(EntryKind::InProceedings).unwrap();
+            ///
+            /// assert_eq!(EntryKind::InProceedings, paper.kind());
+            /// assert_eq!(
+            ///     "Proceedings of the Conference",
+            ///     &**paper.get_field("book_title").unwrap()
+            /// );
+            /// assert_eq!(None, paper.get_field("journal"));
+            /// ```
+            ///
+            /// # Errors
+            /// Returns `Err(Resolver)` with the fields of this entry when it does not have the
+            /// fields required by the `kind`.
+            pub fn convert(&self, kind: EntryKind<'_>) -> Result<Self, Resolver> {
+                let required = kind.required_fields();
+                let mut fields = self
+                    .fields()
+                    .into_iter()
+                    .map(|field| (field.name.into_owned(), field.value.into_owned()))
+                    .collect::<HashMap<_, _>>();
+                for name in VENUE_FIELDS.iter().filter(|name| required.contains(name)) {
+                    if fields.contains_key(*name) {
+                        continue;
+                    }
+                    let moved = VENUE_FIELDS.iter().find_map(|other| fields.remove(*other));
+                    if let Some(value) = moved {
+                        fields.insert((*name).to_owned(), value);
+                    }
+                }
+
+                let mut resolver = Self::resolver_with_cite(kind, self.cite());
+                for (name, value) in fields {
+                    // the names are taken verbatim like `set_fields_from_entry`
+                    resolver.set_normalized_field(name, value);
+                }
+                // the affiliation is moved even when the entry is locked as the kind is changed
+                resolver.move_affiliation();
+                resolver.resolve()
+            }
+
             /// Returns the `title` field value of this entry.
             ///
             /// Each [`Entry`] type is required to have a `title` field so this should always
@@ -125,8 +243,8 @@ macro_rules! entry_impl {
 
             /// Returns the [`Field`]s of the entry.
             ///
-            /// The fields returned include the required and optional fields in no particular
-            /// order.
+            /// The required fields are returned first followed by the optional fields sorted by
+            /// name.
             #[must_use]
             pub fn fields(&self) -> Vec<Field<'_>> {
                 match self {
@@ -135,6 +253,231 @@ macro_rules! entry_impl {
                 }
             }
 
+            /// Sets the value of the field with the `name` given, replacing any existing value.
+            ///
+            /// Fields which are not required by this type of entry are set as optional fields.
+            pub fn set_field<I: Into<QuotedString>>(&mut self, name: &str, value: I) {
+                match self {
+                    $(Self::$target(data) => data.set_field(name, value),)*
+                    Self::Other(data) => data.set_field(name, value),
+                }
+            }
+
+            /// Removes the optional field with the `name` given and returns its value.
+            ///
+            /// Required fields cannot be removed so `None` is returned for them.
+            pub fn remove_field(&mut self, name: &str) -> Option<QuotedString> {
+                match self {
+                    $(Self::$target(data) => data.optional.remove(&name.to_lowercase()),)*
+                    Self::Other(data) => data.optional.remove(&name.to_lowercase()),
+                }
+            }
+
+            /// Returns `true` when the [`LOCKED_FIELD`] of this entry is `true`.
+            ///
+            /// Locked entries are skipped by operations that change many entries at once.
+            #[must_use]
+            pub fn is_locked(&self) -> bool {
+                self.get_field(LOCKED_FIELD)
+                    .is_some_and(|locked| locked.trim().eq_ignore_ascii_case("true"))
+            }
+
+            /// Returns the year the entry was published.
+            ///
+            /// The year is read from the first four digits of the `year` field, or of the `date`
+            /// field when there is no `year` field, so values such as `c. 2020` and `2020-05-01`
+            /// are supported.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            /// resolver.title("Title");
+            /// resolver.set_field("date", "2020-05-01");
+            ///
+            /// assert_eq!(Some(2020), resolver.resolve().unwrap().year());
+            /// ```
+            #[must_use]
+            pub fn year(&self) -> Option<i32> {
+                ["year", "date"]
+                    .into_iter()
+                    .find_map(|name| self.get_field(name))
+                    .and_then(|value| parse_year(value))
+            }
+
+            /// Returns the month of the `month` field, which can be the macro, number or full
+            /// name of the month, see [`Month`].
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, Month};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            /// resolver.title("Title");
+            /// resolver.set_field("month", "January");
+            ///
+            /// assert_eq!(Some(Month::January), resolver.resolve().unwrap().month());
+            /// ```
+            #[must_use]
+            pub fn month(&self) -> Option<Month> {
+                self.get_field("month").and_then(|month| month.parse().ok())
+            }
+
+            /// Returns the parsed value of the `pages` field, see [`Pages`].
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, Pages};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            /// resolver.title("Title");
+            /// resolver.set_field("pages", "5--9");
+            ///
+            /// assert_eq!(Some(Pages::Range(5, 9)), resolver.resolve().unwrap().pages());
+            /// ```
+            #[must_use]
+            pub fn pages(&self) -> Option<Pages> {
+                self.get_field("pages").map(|pages| Pages::from(&**pages))
+            }
+
+            /// Returns the cite keys of the members of a `BibLaTeX` `@set` entry, in the order they
+            /// are listed in the [`ENTRYSET_FIELD`], or `None` when this entry is not a set.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Other("set".into()), "set");
+            /// resolver.set_field("entryset", "a, b,c");
+            ///
+            /// assert_eq!(Some(vec!["a", "b", "c"]), resolver.resolve().unwrap().entry_set());
+            /// ```
+            #[must_use]
+            pub fn entry_set(&self) -> Option<Vec<&str>> {
+                if self.kind() != EntryKind::Other(SET_KIND.into()) {
+                    return None;
+                }
+                let members = self.get_field(ENTRYSET_FIELD)?;
+                Some(
+                    members
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|member| !member.is_empty())
+                        .collect(),
+                )
+            }
+
+            /// Returns the cite keys of the entries in the [`RELATED_FIELD`] of this entry, in the
+            /// order they are listed, or `None` when this entry has no related entries.
+            ///
+            /// The relationship is named by the [`RELATEDTYPE_FIELD`][R], such as `translationof`.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "translation");
+            /// resolver.title("Translation");
+            /// resolver.set_field("related", "original, reprint");
+            /// resolver.set_field("relatedtype", "translationof");
+            ///
+            /// assert_eq!(
+            ///     Some(vec!["original", "reprint"]),
+            ///     resolver.resolve().unwrap().related()
+            /// );
+            /// ```
+            ///
+            /// [R]: crate::ast::RELATEDTYPE_FIELD
+            #[must_use]
+            pub fn related(&self) -> Option<Vec<&str>> {
+                let related = self.get_field(RELATED_FIELD)?;
+                Some(
+                    related
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|related| !related.is_empty())
+                        .collect(),
+                )
+            }
+
+            /// Returns the [`List`] of the list field `name`, such as the names of the `author`
+            /// field or the keywords of the `keywords` field, see [`ListField`].
+            ///
+            /// Returns `None` when this entry does not have the field or the field is not a list
+            /// field.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, List};
+            ///
+            /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            /// resolver.title("Title");
+            /// resolver.set_field("keywords", "sorting; searching");
+            /// let mut entry = resolver.resolve().unwrap();
+            ///
+            /// let keywords = entry.list("keywords").unwrap();
+            /// assert_eq!(vec!["sorting", "searching"], keywords.items());
+            ///
+            /// entry.set_list("keywords", &List::Items(vec!["hashing".to_owned()]));
+            /// assert_eq!("hashing", &**entry.get_field("keywords").unwrap());
+            /// ```
+            #[must_use]
+            pub fn list(&self, name: &str) -> Option<List> {
+                let kind = ListField::of(name)?;
+                Some(List::parse(kind, self.get_field(name)?))
+            }
+
+            /// Sets the field `name` to the `list`, joined by the separator of the list, see
+            /// [`List::value`].
+            pub fn set_list(&mut self, name: &str, list: &List) {
+                self.set_field(name, list.value());
+            }
+
+            /// Returns the names in the `author` field, see [`Author::parse_list`].
+            #[must_use]
+            pub fn authors(&self) -> Vec<Author> {
+                self.names(Role::Author)
+            }
+
+            /// Returns the names in the field of the `role`, see [`Author::parse_list`].
+            #[must_use]
+            pub fn names(&self, role: Role) -> Vec<Author> {
+                self.get_field(role.field())
+                    .map(|names| Author::parse_list(names))
+                    .unwrap_or_default()
+            }
+
+            /// Returns the names credited for this entry, which are the authors or the editors
+            /// when the entry has no authors, such as an edited volume.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use seb::ast::{Entry, EntryKind, Role};
+            ///
+            /// let mut resolver = Entry::resolver(EntryKind::Manual);
+            /// resolver.title("Title");
+            /// resolver.set_field("editor", "Donald E. Knuth");
+            /// let entry = resolver.resolve().unwrap();
+            ///
+            /// let (role, names) = entry.creators().unwrap();
+            /// assert_eq!("Donald E. Knuth (ed.)", role.format(&names));
+            /// ```
+            #[must_use]
+            pub fn creators(&self) -> Option<(Role, Vec<Author>)> {
+                [Role::Author, Role::Editor].into_iter().find_map(|role| {
+                    let names = self.names(role);
+                    (!names.is_empty()).then_some((role, names))
+                })
+            }
+
             /// Creates a new [`Resolver`] for this type to ensure that the required fields
             /// are set before the entry type can be built.
             ///
@@ -202,18 +545,28 @@ macro_rules! entry_impl {
 
                     /// Returns the [`Field`]s of the entry.
                     ///
-                    /// The fields returned include the required and optional fields in no particular
-                    /// order.
+                    /// The required fields are returned first followed by the optional fields sorted by
+                    /// name.
                     #[must_use]
                     pub fn fields(&self) -> Vec<Field<'_>> {
                         let mut fields: Vec<_> = [$((stringify!($req), &self.$req),)+]
                             .into_iter()
                             .map(Field::from)
                             .collect();
-                        fields.extend(self.optional.iter().map(Field::from));
+                        fields.extend(optional_fields(&self.optional));
                         fields
                     }
 
+                    /// Sets the value of the field with the `name` given, replacing any existing
+                    /// value.
+                    pub fn set_field<I: Into<QuotedString>>(&mut self, name: &str, value: I) {
+                        let normal_name = name.to_lowercase();
+                        match normal_name.as_str() {
+                            $(stringify!($req) => self.$req = value.into(),)+
+                            _ => { self.optional.insert(normal_name, value.into()); }
+                        }
+                    }
+
                     /// Creates a new [`Resolver`] for this type to ensure that the required fields
                     /// are set before the entry type can be built.
                     ///
@@ -313,7 +666,121 @@ macro_rules! entry_impl {
     }
 }
 
+// the kind of a `BibLaTeX` entry set, which groups other entries under one cite key
+const SET_KIND: &str = "set";
+
+// the `BibTeX` and `BibLaTeX` entry types that are not the name of an entry kind without spaces
+const KIND_ALIASES: &[(&str, EntryKind<'static>)] = &[
+    ("inbook", EntryKind::BookChapter),
+    ("incollection", EntryKind::BookSection),
+    ("conference", EntryKind::InProceedings),
+    ("mastersthesis", EntryKind::MasterThesis),
+    ("thesis", EntryKind::PhdThesis),
+    ("report", EntryKind::TechReport),
+];
+
+// the fields that name the same kind of venue in different entry kinds, which are moved from one
+// to the other when an entry is converted to a kind that requires the other
+const VENUE_FIELDS: [&str; 2] = ["journal", "book_title"];
+
+// the field that names the organization behind each kind of entry that has one, as it is named
+// in `BibTeX` and in `BibLaTeX`, which imported entries often put in one of the other fields
+pub(crate) const AFFILIATIONS: [(EntryKind<'static>, &str, &str); 4] = [
+    (EntryKind::TechReport, "institution", "institution"),
+    (EntryKind::Manual, "organization", "organization"),
+    (EntryKind::MasterThesis, "school", "institution"),
+    (EntryKind::PhdThesis, "school", "institution"),
+];
+
+// the `BibTeX` and `BibLaTeX` names of the affiliation field of the `kind`, see `AFFILIATIONS`
+pub(crate) fn affiliation_field(kind: &EntryKind<'_>) -> Option<(&'static str, &'static str)> {
+    AFFILIATIONS
+        .iter()
+        .find(|(affiliated, _, _)| affiliated == kind)
+        .map(|(_, bibtex, biblatex)| (*bibtex, *biblatex))
+}
+
+impl EntryKind<'_> {
+    /// Returns the fields that a bibliography style uses for this entry kind when they are set,
+    /// in addition to the [`required_fields`][Self::required_fields].
+    ///
+    /// These are the optional fields of the standard `BibTeX` styles, where a custom entry kind
+    /// has the optional fields of a `misc` entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::EntryKind;
+    ///
+    /// assert_eq!(
+    ///     &["volume", "number", "pages", "month", "note"],
+    ///     EntryKind::Article.optional_fields()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn optional_fields(&self) -> &'static [&'static str] {
+        match self {
+            Self::Article => &["volume", "number", "pages", "month", "note"],
+            Self::Book => &[
+                "editor", "volume", "number", "series", "address", "edition", "month", "note",
+            ],
+            Self::Booklet => &["author", "howpublished", "address", "month", "year", "note"],
+            Self::BookChapter => &[
+                "pages", "volume", "number", "series", "type", "address", "edition", "month",
+                "note",
+            ],
+            Self::BookPages => &[
+                "chapter", "volume", "number", "series", "type", "address", "edition", "month",
+                "note",
+            ],
+            Self::BookSection => &[
+                "editor", "volume", "number", "series", "type", "chapter", "pages", "address",
+                "edition", "month", "note",
+            ],
+            Self::InProceedings => &[
+                "editor",
+                "volume",
+                "number",
+                "series",
+                "pages",
+                "address",
+                "month",
+                "organization",
+                "publisher",
+                "note",
+            ],
+            Self::Manual => &[
+                "author",
+                "organization",
+                "address",
+                "edition",
+                "month",
+                "year",
+                "note",
+            ],
+            Self::MasterThesis | Self::PhdThesis => &["type", "address", "month", "note"],
+            Self::Proceedings => &[
+                "editor",
+                "volume",
+                "number",
+                "series",
+                "address",
+                "month",
+                "organization",
+                "publisher",
+                "note",
+            ],
+            Self::TechReport => &["type", "number", "address", "month", "note"],
+            Self::Unpublished => &["month", "year", "note"],
+            Self::Other(kind) if kind == SET_KIND => &[],
+            Self::Other(_) => &["author", "howpublished", "month", "year", "note"],
+        }
+    }
+}
+
 /// Any other resource not supported by other entry variants.
+///
+/// A `BibLaTeX` `set` has no title so the title of a set is empty and is left out of its fields.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Other {
     cite: String,
@@ -329,13 +796,24 @@ impl Other {
         &self.kind
     }
 
+    // a `BibLaTeX` set only requires the field that lists its members instead of a title
+    fn required_fields(kind: &str) -> Vec<Cow<'static, str>> {
+        let req = if kind == SET_KIND {
+            ENTRYSET_FIELD
+        } else {
+            "title"
+        };
+        vec![req.into()]
+    }
+
     #[must_use]
     fn resolver(kind: String) -> Resolver {
         Resolver {
+            req: Self::required_fields(&kind),
             target: EntryKind::Other(kind.into()),
             cite: None,
-            req: vec!["title".into()],
             fields: HashMap::new(),
+            transliterate: true,
             entry_resolve: Self::resolve,
         }
     }
@@ -345,10 +823,11 @@ impl Other {
     #[must_use]
     pub fn resolver_with_cite<S: Into<String>>(kind: String, cite: S) -> Resolver {
         Resolver {
+            req: Self::required_fields(&kind),
             target: EntryKind::Other(kind.into()),
             cite: Some(cite.into()),
-            req: vec!["title".into()],
             fields: HashMap::new(),
+            transliterate: true,
             entry_resolve: Self::resolve,
         }
     }
@@ -357,23 +836,37 @@ impl Other {
         Entry::Other(Other {
             cite: resolver.cite().to_string(),
             kind: resolver.target.to_string(),
-            title: resolver.fields.remove("title").unwrap(),
+            title: resolver.fields.remove("title").unwrap_or_default(),
             optional: resolver.fields,
         })
     }
     /// Returns the [`Field`]s of the entry.
     ///
-    /// The fields returned include the required and optional fields in no particular
-    /// order.
+    /// The required fields are returned first followed by the optional fields sorted by
+    /// name.
     #[must_use]
     pub fn fields(&self) -> Vec<Field<'_>> {
-        let field = Field::from(("title", &self.title));
-        let mut fields = vec![field];
-        fields.extend(self.optional.iter().map(Field::from));
+        let mut fields = Vec::with_capacity(self.optional.len() + 1);
+        if self.kind != SET_KIND || !self.title.is_empty() {
+            fields.push(Field::from(("title", &self.title)));
+        }
+        fields.extend(optional_fields(&self.optional));
         fields
     }
 }
 
+impl Other {
+    /// Sets the value of the field with the `name` given, replacing any existing value.
+    pub fn set_field<I: Into<QuotedString>>(&mut self, name: &str, value: I) {
+        let normal_name = name.to_lowercase();
+        if normal_name == "title" {
+            self.title = value.into();
+        } else {
+            self.optional.insert(normal_name, value.into());
+        }
+    }
+}
+
 impl FieldQuery for Other {
     fn get_field(&self, name: &str) -> Option<&QuotedString> {
         let normal_name = name.to_lowercase();
@@ -384,6 +877,86 @@ impl FieldQuery for Other {
     }
 }
 
+/// Serializes an [`Entry`] as a flat map of the entry `type`, `cite` and the field values.
+///
+/// The fields are serialized in name order so that the output is stable and the `keywords` are
+/// serialized as an array of the keywords, see [`List`].
+impl serde::Serialize for Entry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_entry(self, serializer, |_| true)
+    }
+}
+
+/// An [`Entry`] that serializes without the provenance fields, those starting with
+/// [`PROVENANCE_PREFIX`].
+#[derive(Clone, Copy, Debug)]
+pub struct WithoutProvenance<'a>(pub &'a Entry);
+
+impl serde::Serialize for WithoutProvenance<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_entry(self.0, serializer, |name| {
+            !name.starts_with(PROVENANCE_PREFIX)
+        })
+    }
+}
+
+/// An [`Entry`] that serializes with only the fields included by the [`FieldFilter`].
+#[derive(Clone, Copy, Debug)]
+pub struct WithFields<'a>(pub &'a Entry, pub &'a FieldFilter);
+
+impl serde::Serialize for WithFields<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_entry(self.0, serializer, |name| self.1.includes(name))
+    }
+}
+
+// the first run of four digits in the value
+fn parse_year(value: &str) -> Option<i32> {
+    let bytes = value.as_bytes();
+    bytes
+        .windows(4)
+        .enumerate()
+        .find(|(i, window)| {
+            window.iter().all(u8::is_ascii_digit)
+                && !bytes.get(i + 4).is_some_and(u8::is_ascii_digit)
+                && (*i == 0 || !bytes[i - 1].is_ascii_digit())
+        })
+        .and_then(|(i, _)| value[i..i + 4].parse().ok())
+}
+
+// optional fields are sorted by name so that the fields of an entry are always in the same order
+fn optional_fields(optional: &HashMap<String, QuotedString>) -> Vec<Field<'_>> {
+    let mut fields = optional.iter().map(Field::from).collect::<Vec<_>>();
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    fields
+}
+
+fn serialize_entry<S: serde::Serializer>(
+    entry: &Entry,
+    serializer: S,
+    include: impl Fn(&str) -> bool,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+
+    let mut fields = entry.fields();
+    fields.retain(|field| include(&field.name));
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut map = serializer.serialize_map(Some(fields.len() + 2))?;
+    map.serialize_entry("type", &entry.kind().to_string())?;
+    map.serialize_entry("cite", entry.cite())?;
+    for field in &fields {
+        // the keywords are an array so that other tools do not split them differently
+        if field.name.eq_ignore_ascii_case("keywords") {
+            let keywords = List::parse(ListField::Items, field.value());
+            map.serialize_entry(&field.name, &keywords.items())?;
+        } else {
+            map.serialize_entry(&field.name, field.value())?;
+        }
+    }
+    map.end()
+}
+
 entry_impl! {
     "article" => article:
         /// The article entry type represents an article
@@ -1,8 +1,14 @@
 use std::{borrow::Cow, collections::HashMap};
 
-use crate::ast::{FieldQuery, QuotedString};
+use crate::ast::{FieldQuery, QuotedString, INCOMPLETE_FIELD, LOCKED_FIELD};
 
-use super::{Entry, EntryKind};
+use super::{affiliation_field, Entry, EntryKind, AFFILIATIONS};
+
+/// The legacy eprint field names, used by arXiv, and the field names they are normalized to.
+const LEGACY_EPRINT_FIELDS: [(&str, &str); 2] = [
+    ("archiveprefix", "eprinttype"),
+    ("primaryclass", "eprintclass"),
+];
 
 /// A general `Entry` resolver that allows for retrying resolves of entries multiple times at runtime.
 ///
@@ -35,6 +41,7 @@ pub struct Resolver {
     pub(super) cite: Option<String>,
     pub(super) req: Vec<Cow<'static, str>>,
     pub(super) fields: HashMap<String, QuotedString>,
+    pub(super) transliterate: bool,
     pub(super) entry_resolve: fn(Self) -> Entry,
 }
 
@@ -56,13 +63,36 @@ impl Resolver {
             cite,
             req,
             fields: HashMap::default(),
+            transliterate: true,
             entry_resolve,
         }
     }
+
+    /// Sets whether a cite key generated by [`Resolver::cite`] is transliterated to ASCII, which
+    /// it is by default, like the keys of a [`KeyFormat`](crate::ast::KeyFormat).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind};
+    ///
+    /// let mut resolver = Entry::resolver(EntryKind::Manual);
+    /// resolver.set_field("author", "Иванов");
+    /// resolver.set_field("year", "2021");
+    /// assert_eq!("Ivanov2021", resolver.cite());
+    ///
+    /// resolver.set_transliterate(false);
+    /// assert_eq!("Иванов2021", resolver.cite());
+    /// ```
+    pub fn set_transliterate(&mut self, transliterate: bool) {
+        self.transliterate = transliterate;
+    }
     /// Returns the cite key for the entry being built.
     ///
     /// The cite key may either be a known value given to the resolver or will be generated using
-    /// the `author` and `year` field if available.
+    /// the `author` and `year` field if available. A generated cite key is transliterated to ASCII
+    /// so that an author in a non-Latin script still gives a usable key, unless this is turned off
+    /// by [`Resolver::set_transliterate`].
     #[must_use]
     pub fn cite(&self) -> Cow<'_, str> {
         if let Some(cite) = &self.cite {
@@ -71,7 +101,11 @@ impl Resolver {
             let author = self.get_field("author").map_or_else(
                 || "Unknown".to_owned(),
                 |qs| {
-                    let mut s = qs.to_string();
+                    let mut s = if self.transliterate {
+                        deunicode::deunicode(qs)
+                    } else {
+                        qs.to_string()
+                    };
                     s.retain(|c| !c.is_whitespace());
                     s
                 },
@@ -95,7 +129,8 @@ impl Resolver {
     /// # Errors
     /// Returns `Err(Self)` when the required fields have not been set to make a valid [`Entry`],
     /// returning `Self` allows for the user to retry.
-    pub fn resolve(self) -> Result<Entry, Self> {
+    pub fn resolve(mut self) -> Result<Entry, Self> {
+        self.normalize_affiliation();
         if self.req.is_empty() {
             Ok((self.entry_resolve)(self))
         } else {
@@ -103,6 +138,106 @@ impl Resolver {
         }
     }
 
+    /// Build an entry from the fields added in this resolver, where each required field that has
+    /// not been set is empty.
+    ///
+    /// This allows viewing what an incomplete entry contains, the entry built should not be
+    /// written back as the missing fields would be written as empty fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "cite");
+    /// resolver.title("Title");
+    ///
+    /// let entry = resolver.resolve_partial();
+    /// assert_eq!("Title", &**entry.title());
+    /// ```
+    #[must_use]
+    pub fn resolve_partial(mut self) -> Entry {
+        self.normalize_affiliation();
+        for name in std::mem::take(&mut self.req) {
+            self.fields
+                .entry(name.into_owned())
+                .or_insert_with(|| QuotedString::new(String::new()));
+        }
+        (self.entry_resolve)(self)
+    }
+
+    /// Build an entry from the fields added in this resolver even when required fields have not
+    /// been set, so that the fields found are kept and the missing fields can be added later.
+    ///
+    /// When a required field is missing the entry is built as a `misc` entry, which only requires
+    /// a title, and the kind it was meant to be along with the missing fields are recorded in the
+    /// [`INCOMPLETE_FIELD`]. Unlike [`Resolver::resolve_partial`] the entry built can be written
+    /// back as no empty fields are added for the missing fields, other than the title.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind, FieldQuery, INCOMPLETE_FIELD};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Book, "cite");
+    /// resolver.title("Title");
+    /// resolver.author("Author");
+    ///
+    /// let entry = resolver.resolve_incomplete();
+    ///
+    /// assert_eq!(EntryKind::Other("misc".into()), entry.kind());
+    /// assert_eq!("Author", &**entry.get_field("author").unwrap());
+    /// assert_eq!(
+    ///     "book: publisher, year",
+    ///     &**entry.get_field(INCOMPLETE_FIELD).unwrap()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn resolve_incomplete(mut self) -> Entry {
+        self.normalize_affiliation();
+        if self.req.is_empty() {
+            return (self.entry_resolve)(self);
+        }
+
+        let mut missing = self.req.iter().map(|name| &**name).collect::<Vec<_>>();
+        missing.sort_unstable();
+        let incomplete = format!("{}: {}", self.target, missing.join(", "));
+
+        let mut resolver = Entry::resolver_with_cite(
+            EntryKind::Other(Cow::Borrowed("misc")),
+            self.cite().into_owned(),
+        );
+        for (name, value) in self.fields {
+            resolver.set_normalized_field(name, value);
+        }
+        resolver.set_field(INCOMPLETE_FIELD, incomplete);
+        resolver.resolve_partial()
+    }
+
+    // Moves the affiliation of an entry that is not locked, see `move_affiliation`.
+    fn normalize_affiliation(&mut self) {
+        if !self.fields.contains_key(LOCKED_FIELD) {
+            self.move_affiliation();
+        }
+    }
+
+    // Moves the value of an affiliation field that the kind of entry does not use to the one that
+    // it does, such as the `institution` of a thesis to its `school`, unless that field is set.
+    pub(super) fn move_affiliation(&mut self) {
+        let Some((target, _)) = affiliation_field(&self.target) else {
+            return;
+        };
+        if self.fields.contains_key(target) {
+            return;
+        }
+        let value = AFFILIATIONS
+            .iter()
+            .find_map(|(_, name, _)| self.fields.remove(*name));
+        if let Some(value) = value {
+            self.set_normalized_field(target.to_owned(), value);
+        }
+    }
+
     /// Returns an iterator of the required fields that need to be set in order to make this
     /// resolver succeed.
     ///
@@ -170,20 +305,31 @@ impl Resolver {
     /// `String` this is equivalent to using [`QuotedString::new`] so make sure that
     /// quoting is not required, if it is then use either [`QuotedString::quote`] or
     /// [`QuotedString::from_quoted`]
+    ///
+    /// The legacy `archivePrefix` and `primaryClass` fields are set as the `eprinttype` and
+    /// `eprintclass` fields, unless those fields are already set.
     #[inline]
     pub fn set_field<I>(&mut self, name: &str, value: I)
     where
         I: Into<QuotedString>,
     {
         // normalize fields to lowercase
-        self.set_normalized_field(name.to_lowercase(), value.into());
+        let name = name.to_lowercase();
+        match LEGACY_EPRINT_FIELDS
+            .iter()
+            .find(|(legacy, _)| *legacy == name)
+        {
+            Some((_, field)) if self.fields.contains_key(*field) => {}
+            Some((_, field)) => self.set_normalized_field((*field).to_owned(), value.into()),
+            None => self.set_normalized_field(name, value.into()),
+        }
     }
 
     /// Set a normalized (lowercase name) field.
     ///
     /// Checks whether this field is a required field and will remove that name from the required
     /// set.
-    fn set_normalized_field(&mut self, name: String, value: QuotedString) {
+    pub(super) fn set_normalized_field(&mut self, name: String, value: QuotedString) {
         self.req.retain(|r| *r != name.as_str());
         self.fields.insert(name, value);
     }
@@ -327,7 +473,7 @@ impl_resolver!(
 
 #[cfg(test)]
 mod tests {
-    use crate::ast::{Book, BookChapter, Entry, FieldQuery, Manual, Other};
+    use crate::ast::{Book, BookChapter, Entry, FieldQuery, Manual, Other, PhdThesis, TechReport};
 
     #[test]
     fn resolver_entry_drop_reinserts_required_field() {
@@ -373,6 +519,62 @@ mod tests {
         assert_eq!("test", &**entry.title());
     }
 
+    #[test]
+    fn legacy_eprint_fields_are_normalized() {
+        let mut resolver = Manual::resolver();
+        resolver.set_field("archivePrefix", "arXiv");
+        resolver.set_field("primaryClass", "cs.LG");
+        resolver.set_field("eprinttype", "arxiv");
+        resolver.set_field("archivePrefix", "ignored");
+
+        assert_eq!("arxiv", &**resolver.get_field("eprinttype").unwrap());
+        assert_eq!("cs.LG", &**resolver.get_field("eprintclass").unwrap());
+        assert!(resolver.get_field("archiveprefix").is_none());
+    }
+
+    #[test]
+    fn affiliation_is_moved_to_the_field_of_the_entry_kind() {
+        let mut resolver = PhdThesis::resolver();
+        resolver.set_field("author", "Me");
+        resolver.set_field("title", "Thesis");
+        resolver.set_field("year", "2020");
+        resolver.set_field("institution", "University");
+
+        let thesis = resolver
+            .resolve()
+            .expect("The institution is the school of a thesis");
+        assert_eq!("University", &**thesis.get_field("school").unwrap());
+        assert!(thesis.get_field("institution").is_none());
+
+        let mut resolver = Manual::resolver();
+        resolver.set_field("title", "Manual");
+        resolver.set_field("institution", "Company");
+        let manual = resolver.resolve().unwrap();
+        assert_eq!("Company", &**manual.get_field("organization").unwrap());
+
+        let mut resolver = TechReport::resolver();
+        resolver.set_field("author", "Me");
+        resolver.set_field("title", "Report");
+        resolver.set_field("year", "2020");
+        resolver.set_field("institution", "Lab");
+        resolver.set_field("school", "University");
+        let report = resolver.resolve().unwrap();
+        assert_eq!("Lab", &**report.get_field("institution").unwrap());
+        assert_eq!("University", &**report.get_field("school").unwrap());
+    }
+
+    #[test]
+    fn affiliation_of_a_locked_entry_is_kept() {
+        let mut resolver = Manual::resolver();
+        resolver.set_field("title", "Manual");
+        resolver.set_field("institution", "Company");
+        resolver.set_field(LOCKED_FIELD, "true");
+
+        let manual = resolver.resolve().unwrap();
+        assert_eq!("Company", &**manual.get_field("institution").unwrap());
+        assert!(manual.get_field("organization").is_none());
+    }
+
     #[test]
     fn adding_new_fields_works() {
         // Manual only requires title initially
@@ -0,0 +1,418 @@
+use std::{collections::HashSet, str::FromStr};
+
+use crate::{Error, ErrorKind};
+
+use super::{suffix, Entry, FieldQuery};
+
+/// A template used to generate the cite key of an entry from its fields.
+///
+/// The template is made up of text and the following placeholders:
+///
+/// - `{author}`: the last name of the first author, or the first editor when there is no author,
+///   or `Unknown` when there are neither.
+/// - `{year}`: the year, or `year` when there is no year.
+/// - `{title}`: the first word of the title.
+///
+/// Only letters and digits are kept from the values of the placeholders. The values of the
+/// `{author}` and `{title}` placeholders are transliterated to ASCII, so a name in a non-Latin
+/// script, such as `Иванов`, gives a usable key, such as `Ivanov`, see
+/// [`KeyFormat::transliterate`]. The [`Default`] template is `{author}{year}`.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{Entry, EntryKind, KeyFormat};
+///
+/// let mut resolver = Entry::resolver(EntryKind::Book);
+/// resolver.author("Donald E. Knuth");
+/// resolver.title("The Art of Computer Programming");
+/// resolver.publisher("Addison-Wesley");
+/// resolver.year("1968");
+/// let entry = resolver.resolve().unwrap();
+///
+/// let format: KeyFormat = "{author}-{title}-{year}".parse().unwrap();
+///
+/// assert_eq!("Knuth-The-1968", format.key(&entry));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyFormat {
+    parts: Vec<Part>,
+    transliterate: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Author,
+    Year,
+    Title,
+}
+
+impl KeyFormat {
+    /// Sets whether the values of the `{author}` and `{title}` placeholders are transliterated
+    /// to ASCII, which is enabled by default.
+    ///
+    /// Only the key is transliterated, the fields of the entry are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind, KeyFormat};
+    ///
+    /// let mut resolver = Entry::resolver(EntryKind::Manual);
+    /// resolver.author("Иванов, Иван");
+    /// resolver.title("Title");
+    /// resolver.set_field("year", "2021");
+    /// let entry = resolver.resolve().unwrap();
+    ///
+    /// assert_eq!("Ivanov2021", KeyFormat::default().key(&entry));
+    /// assert_eq!(
+    ///     "Иванов2021",
+    ///     KeyFormat::default().transliterate(false).key(&entry)
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn transliterate(mut self, transliterate: bool) -> Self {
+        self.transliterate = transliterate;
+        self
+    }
+
+    // the names of the placeholders of this template, such as `author+year`
+    pub(crate) fn placeholders(&self) -> String {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Text(_) => None,
+                Part::Author => Some("author"),
+                Part::Year => Some("year"),
+                Part::Title => Some("title"),
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Returns the cite key generated for the `entry` using this template.
+    #[must_use]
+    pub fn key(&self, entry: &Entry) -> String {
+        self.parts
+            .iter()
+            .map(|part| self.value(part, entry).unwrap_or_else(|| part.missing()))
+            .collect()
+    }
+
+    /// Returns the cite key generated for the `entry` using this template, like
+    /// [`KeyFormat::key`], or [`None`] when the `entry` has none of the fields used by the
+    /// placeholders of this template.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind, KeyFormat};
+    ///
+    /// let mut resolver = Entry::resolver(EntryKind::Manual);
+    /// resolver.title("Title");
+    /// let entry = resolver.resolve().unwrap();
+    ///
+    /// assert_eq!(None, KeyFormat::default().try_key(&entry));
+    /// assert_eq!(Some("Title".to_owned()), "{title}".parse::<KeyFormat>().unwrap().try_key(&entry));
+    /// ```
+    #[must_use]
+    pub fn try_key(&self, entry: &Entry) -> Option<String> {
+        let values = self
+            .parts
+            .iter()
+            .map(|part| (part, self.value(part, entry)))
+            .collect::<Vec<_>>();
+
+        values
+            .iter()
+            .any(|(part, value)| !matches!(part, Part::Text(_)) && value.is_some())
+            .then(|| {
+                values
+                    .into_iter()
+                    .map(|(part, value)| value.unwrap_or_else(|| part.missing()))
+                    .collect()
+            })
+    }
+
+    fn value(&self, part: &Part, entry: &Entry) -> Option<String> {
+        let text = |value: &str| {
+            if self.transliterate {
+                alphanumeric(&deunicode::deunicode(value))
+            } else {
+                alphanumeric(value)
+            }
+        };
+
+        match part {
+            Part::Text(text) => Some(text.clone()),
+            Part::Author => entry
+                .creators()
+                .and_then(|(_, names)| names.into_iter().next())
+                .map(|first| text(&first.family))
+                .filter(|last| !last.is_empty()),
+            Part::Year => entry
+                .get_field("year")
+                .map(|year| alphanumeric(year))
+                .filter(|year| !year.is_empty()),
+            Part::Title => entry
+                .title()
+                .split_whitespace()
+                .map(text)
+                .find(|word| !word.is_empty()),
+        }
+    }
+}
+
+impl Part {
+    // the value used for a placeholder when the entry has no value for it
+    fn missing(&self) -> String {
+        match self {
+            Self::Author => "Unknown".to_owned(),
+            Self::Year => "year".to_owned(),
+            Self::Text(_) | Self::Title => String::new(),
+        }
+    }
+}
+
+/// Returns the cite key generated with the key `format` for an `entry` read without a cite key,
+/// which is not one of the `used` cite keys, and adds it to the `used` cite keys.
+///
+/// The `used` cite keys are lowercase as cite keys that only differ in case are the same cite key.
+///
+/// An entry with none of the fields used by the key `format` is given a cite key made from a hash
+/// of its fields, see [`hash_key`]. A cite key that is already used is given the suffix `a`, `b`,
+/// `c` and so on.
+///
+/// How the cite key was made is returned with it, which is the value of the
+/// [`KEY_FIELD`][super::KEY_FIELD].
+pub(crate) fn generated_key(
+    entry: &Entry,
+    format: &KeyFormat,
+    used: &mut HashSet<String>,
+) -> (String, String) {
+    let (key, how) = format.try_key(entry).map_or_else(
+        || (hash_key(entry), "hash".to_owned()),
+        |key| (key, format.placeholders()),
+    );
+    let mut cite = key.clone();
+    let mut i = 0;
+    while used.contains(&cite.to_lowercase()) {
+        cite = format!("{key}{}", suffix(i));
+        i += 1;
+    }
+    log::info!(
+        "The {} entry '{}' has no cite key - using the generated cite key '{cite}'",
+        entry.kind(),
+        &**entry.title()
+    );
+    used.insert(cite.to_lowercase());
+    (cite, how)
+}
+
+/// Returns a cite key made from a hash of the kind and fields of the `entry`, which is used for
+/// an entry that has none of the fields a [`KeyFormat`] uses.
+///
+/// The hash is FNV-1a so the same entry is always given the same cite key.
+fn hash_key(entry: &Entry) -> String {
+    let mut fields = entry
+        .fields()
+        .into_iter()
+        .map(|field| format!("{}={}", field.name, &**field.value))
+        .collect::<Vec<_>>();
+    fields.sort_unstable();
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in std::iter::once(entry.kind().to_string())
+        .chain(fields)
+        .flat_map(|text| text.into_bytes().into_iter().chain([0]))
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("entry{:08x}", hash >> 32)
+}
+
+impl Default for KeyFormat {
+    fn default() -> Self {
+        Self {
+            parts: vec![Part::Author, Part::Year],
+            transliterate: true,
+        }
+    }
+}
+
+fn alphanumeric(s: &str) -> String {
+    s.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+impl FromStr for KeyFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_owned()));
+            }
+            let Some(end) = rest[start..].find('}') else {
+                return Err(Error::new(
+                    ErrorKind::Deserialize,
+                    format!("Unclosed placeholder in the key format '{s}'"),
+                ));
+            };
+            parts.push(match &rest[start + 1..start + end] {
+                "author" => Part::Author,
+                "year" => Part::Year,
+                "title" => Part::Title,
+                other => {
+                    return Err(Error::new(
+                        ErrorKind::Deserialize,
+                        format!("Unknown placeholder '{{{other}}}' in the key format '{s}'"),
+                    ))
+                }
+            });
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_owned()));
+        }
+
+        if parts.is_empty() {
+            Err(Error::new(
+                ErrorKind::Deserialize,
+                "The key format is empty",
+            ))
+        } else {
+            Ok(Self {
+                parts,
+                transliterate: true,
+            })
+        }
+    }
+}
+
+impl TryFrom<String> for KeyFormat {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::ast::Manual;
+
+    use super::*;
+
+    fn manual(optional: &[(&str, &str)]) -> Entry {
+        Entry::Manual(Manual {
+            cite: "cite".to_owned(),
+            title: "{A} Manual".into(),
+            optional: optional
+                .iter()
+                .map(|(name, value)| ((*name).to_owned(), (*value).into()))
+                .collect::<HashMap<_, _>>(),
+        })
+    }
+
+    #[test]
+    fn last_name_of_first_author() {
+        let format = KeyFormat::default();
+
+        assert_eq!(
+            "Knuth1968",
+            format.key(&manual(&[
+                ("author", "Knuth, Donald and Other"),
+                ("year", "1968")
+            ]))
+        );
+        assert_eq!(
+            "Weiss2019",
+            format.key(&manual(&[("author", "Armin Wei{ss}"), ("year", "2019")]))
+        );
+        assert_eq!(
+            "Lamport1994",
+            format.key(&manual(&[("editor", "Leslie Lamport"), ("year", "1994")]))
+        );
+        assert_eq!("Unknownyear", format.key(&manual(&[])));
+    }
+
+    #[test]
+    fn non_latin_last_names_are_transliterated() {
+        let format = KeyFormat::default();
+
+        assert_eq!(
+            "Ivanov2021",
+            format.key(&manual(&[("author", "Иванов, Иван"), ("year", "2021")]))
+        );
+        assert_eq!(
+            "Wang2020",
+            format.key(&manual(&[("author", "王, 小龍"), ("year", "2020")]))
+        );
+        assert_eq!(
+            "LiXiaoLong2020",
+            format.key(&manual(&[("author", "李小龍"), ("year", "2020")]))
+        );
+    }
+
+    #[test]
+    fn transliteration_can_be_disabled() {
+        let entry = manual(&[("author", "Иванов, Иван"), ("year", "2021")]);
+
+        assert_eq!(
+            "Иванов2021",
+            KeyFormat::default().transliterate(false).key(&entry)
+        );
+        // the stored author is unchanged
+        assert_eq!("Иванов, Иван", &**entry.get_field("author").unwrap());
+    }
+
+    #[test]
+    fn title_placeholder_and_text() {
+        let format: KeyFormat = "key_{title}".parse().unwrap();
+
+        assert_eq!("key_A", format.key(&manual(&[])));
+    }
+
+    #[test]
+    fn try_key_needs_a_placeholder_value() {
+        let format = KeyFormat::default();
+
+        assert_eq!(None, format.try_key(&manual(&[])));
+        assert_eq!(
+            Some("Unknown1968".to_owned()),
+            format.try_key(&manual(&[("year", "1968")]))
+        );
+        assert_eq!(
+            None,
+            "key_{author}"
+                .parse::<KeyFormat>()
+                .unwrap()
+                .try_key(&manual(&[]))
+        );
+    }
+
+    #[test]
+    fn hash_key_is_deterministic() {
+        let key = hash_key(&manual(&[("note", "a note")]));
+
+        assert_eq!(key, hash_key(&manual(&[("note", "a note")])));
+        assert_ne!(key, hash_key(&manual(&[("note", "another note")])));
+        assert!(key.starts_with("entry"));
+        assert_eq!(13, key.len());
+    }
+
+    #[test]
+    fn invalid_formats_are_errors() {
+        assert!("{author".parse::<KeyFormat>().is_err());
+        assert!("{month}".parse::<KeyFormat>().is_err());
+        assert!("".parse::<KeyFormat>().is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use super::Author;
+
+/// The kind of list held by a field whose value is a list of items, such as the `author` and
+/// `keywords` fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListField {
+    /// Names separated by `and`, such as the `author`, `editor` and `translator` fields.
+    Names,
+    /// Items separated by commas or semicolons, such as the `keywords` field.
+    Items,
+}
+
+impl ListField {
+    /// Returns the kind of list held by the field `name`, or [`None`] when the field is not a
+    /// list field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::ListField;
+    ///
+    /// assert_eq!(Some(ListField::Names), ListField::of("Editor"));
+    /// assert_eq!(Some(ListField::Items), ListField::of("keywords"));
+    /// assert_eq!(None, ListField::of("title"));
+    /// ```
+    #[must_use]
+    pub fn of(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "author" | "editor" | "translator" => Some(Self::Names),
+            "keywords" => Some(Self::Items),
+            _ => None,
+        }
+    }
+}
+
+/// The value of a list field, see [`ListField`], such as the names of the `author` field or each
+/// keyword of the `keywords` field.
+///
+/// Every format stores a list field as a single value with the items joined by the separator of
+/// the list, which is how the field is written as `BibTeX`, and a format that has lists, such as
+/// JSON, writes the items of the list so that the list is never split differently by each
+/// command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum List {
+    /// The names of a name field, see [`Author::parse_list`].
+    Names(Vec<Author>),
+    /// The trimmed items of a field such as `keywords`, without the empty items.
+    Items(Vec<String>),
+}
+
+impl List {
+    /// Parses the `value` of a list field of the `kind` given into its items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Author, List, ListField};
+    ///
+    /// assert_eq!(
+    ///     List::Names(vec![
+    ///         Author::new("Knuth", Some("Donald E.")),
+    ///         Author::new("{Barnes and Noble}", None),
+    ///     ]),
+    ///     List::parse(ListField::Names, "Donald E. Knuth AND {Barnes and Noble}")
+    /// );
+    /// assert_eq!(
+    ///     List::Items(vec!["sorting".to_owned(), "data structures".to_owned()]),
+    ///     List::parse(ListField::Items, "sorting; data structures,")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn parse(kind: ListField, value: &str) -> Self {
+        match kind {
+            ListField::Names => Self::Names(Author::parse_list(value)),
+            ListField::Items => Self::Items(
+                value
+                    .split([',', ';'])
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the items of this list, where a name is written as "Last, First".
+    #[must_use]
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            Self::Names(names) => names.iter().map(ToString::to_string).collect(),
+            Self::Items(items) => items.clone(),
+        }
+    }
+
+    /// Returns the items of this list joined by the separator of the list, which is the value of
+    /// the field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Author, List};
+    ///
+    /// let keywords = List::Items(vec!["sorting".to_owned(), "searching".to_owned()]);
+    /// assert_eq!("sorting, searching", keywords.value());
+    ///
+    /// let names = List::Names(vec![
+    ///     Author::new("Knuth", Some("Donald E.")),
+    ///     Author::new("Lamport", Some("Leslie")),
+    /// ]);
+    /// assert_eq!("Knuth, Donald E. and Lamport, Leslie", names.value());
+    /// ```
+    #[must_use]
+    pub fn value(&self) -> String {
+        match self {
+            Self::Names(names) => Author::join(names),
+            Self::Items(items) => items.join(", "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_only_separated_by_and_outside_of_braces() {
+        let names = List::parse(
+            ListField::Names,
+            "Anderson, Sandra and {Andrews and Sons}\nand  Leslie Lamport",
+        );
+
+        assert_eq!(
+            vec!["Anderson, Sandra", "{Andrews and Sons}", "Lamport, Leslie"],
+            names.items()
+        );
+        assert_eq!(List::Names(Vec::new()), List::parse(ListField::Names, "  "));
+    }
+
+    #[test]
+    fn items_round_trip_through_the_value() {
+        let items = List::parse(ListField::Items, "a, , b;");
+
+        assert_eq!(vec!["a", "b"], items.items());
+        assert_eq!(items, List::parse(ListField::Items, &items.value()));
+    }
+}
@@ -1,13 +1,27 @@
 //! Structs for representing a generic bibliographic entry and all its parts.
+mod author;
 mod biblio;
 mod entry;
+mod key_format;
+mod list;
+mod month;
+mod pages;
 mod quoted_string;
+mod similarity;
 
 use std::borrow::Cow;
 
+pub use author::{Author, Role};
+pub(crate) use biblio::suffix;
 pub use biblio::*;
 pub use entry::*;
+pub(crate) use key_format::generated_key;
+pub use key_format::KeyFormat;
+pub use list::{List, ListField};
+pub use month::Month;
+pub use pages::Pages;
 pub use quoted_string::{EscapePattern, QuotedString};
+pub use similarity::{Metric, Similarity};
 
 /// An entry field which is essentially a key value pair.
 #[derive(Clone, Debug, PartialEq)]
@@ -43,3 +57,85 @@ impl<'entry> From<(&'entry str, &'entry QuotedString)> for Field<'entry> {
         }
     }
 }
+
+/// Chooses which fields of an entry are included, by field name, without changing the entry.
+///
+/// Field names are compared ignoring case and a name ending with `*` matches every field that
+/// starts with the rest of the name, such as `x-seb-*` for the provenance fields.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::FieldFilter;
+///
+/// let filter = FieldFilter::exclude(["abstract", "x-seb-*"]);
+///
+/// assert!(filter.includes("title"));
+/// assert!(!filter.includes("Abstract"));
+/// assert!(!filter.includes("x-seb-source"));
+///
+/// let filter = FieldFilter::only(["title", "year"]);
+///
+/// assert!(filter.includes("year"));
+/// assert!(!filter.includes("abstract"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldFilter {
+    only: Option<Vec<String>>,
+    exclude: Vec<String>,
+}
+
+impl FieldFilter {
+    /// Creates a [`FieldFilter`] that only includes the `fields` given.
+    #[must_use]
+    pub fn only<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            only: Some(fields.into_iter().map(Into::into).collect()),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Creates a [`FieldFilter`] that includes every field except the `fields` given.
+    #[must_use]
+    pub fn exclude<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            only: None,
+            exclude: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Excludes the `fields` given in addition to the fields already excluded by this filter.
+    #[must_use]
+    pub fn and_exclude<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns `true` when the field with the `name` given is included by this filter.
+    #[must_use]
+    pub fn includes(&self, name: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => name
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix)),
+            None => pattern.eq_ignore_ascii_case(name),
+        };
+
+        self.only
+            .as_ref()
+            .is_none_or(|only| only.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
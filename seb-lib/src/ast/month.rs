@@ -0,0 +1,174 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Error, ErrorKind};
+
+/// A month of the year, which is the canonical value of the `month` field.
+///
+/// The `month` field can be written as a `BibTeX` macro, such as `jan`, a number, such as `1`, or
+/// the full name, such as `January`, and every form is parsed to the same [`Month`] so that the
+/// field can always be composed in the same style.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::Month;
+///
+/// let months = ["jan", "1", "January"].map(|s| s.parse::<Month>().unwrap());
+///
+/// assert!(months.iter().all(|month| *month == Month::January));
+/// assert_eq!("jan", Month::January.abbreviation());
+/// assert_eq!(1, Month::January.number());
+/// ```
+#[allow(missing_docs)] // the variants are the names of the months
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Month {
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+const MONTHS: [Month; 12] = [
+    Month::January,
+    Month::February,
+    Month::March,
+    Month::April,
+    Month::May,
+    Month::June,
+    Month::July,
+    Month::August,
+    Month::September,
+    Month::October,
+    Month::November,
+    Month::December,
+];
+
+impl Month {
+    /// Returns the month with the `number`, from 1 for January to 12 for December.
+    #[must_use]
+    pub fn from_number(number: u8) -> Option<Self> {
+        MONTHS.get(usize::from(number).checked_sub(1)?).copied()
+    }
+
+    /// Returns the number of the month, from 1 for January to 12 for December.
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// Returns the full name of the month, such as `January`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::January => "January",
+            Self::February => "February",
+            Self::March => "March",
+            Self::April => "April",
+            Self::May => "May",
+            Self::June => "June",
+            Self::July => "July",
+            Self::August => "August",
+            Self::September => "September",
+            Self::October => "October",
+            Self::November => "November",
+            Self::December => "December",
+        }
+    }
+
+    /// Returns the three letter `BibTeX` macro of the month, such as `jan`.
+    #[must_use]
+    pub const fn abbreviation(self) -> &'static str {
+        match self {
+            Self::January => "jan",
+            Self::February => "feb",
+            Self::March => "mar",
+            Self::April => "apr",
+            Self::May => "may",
+            Self::June => "jun",
+            Self::July => "jul",
+            Self::August => "aug",
+            Self::September => "sep",
+            Self::October => "oct",
+            Self::November => "nov",
+            Self::December => "dec",
+        }
+    }
+}
+
+impl FromStr for Month {
+    type Err = Error;
+
+    /// Parses a month from the number or the name of the month, ignoring case, braces and
+    /// surrounding whitespace.
+    ///
+    /// A name is the full name or the three letter macro of the month, or `Sept` for September,
+    /// and an abbreviation can end with a full stop. Any other word, even one that starts with
+    /// the name of a month such as `junk`, is not a month.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s
+            .trim()
+            .trim_matches(|c| c == '{' || c == '}')
+            .trim()
+            .trim_end_matches('.')
+            .to_lowercase();
+
+        let month = match value.parse::<u8>() {
+            Ok(number) => Self::from_number(number),
+            Err(_) => MONTHS.into_iter().find(|month| {
+                value == month.abbreviation()
+                    || value == month.name().to_lowercase()
+                    || (*month == Self::September && value == "sept")
+            }),
+        };
+
+        month.ok_or_else(|| {
+            Error::new(
+                ErrorKind::Deserialize,
+                format!("'{s}' is not a month name, abbreviation or number"),
+            )
+        })
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_form_parses_to_the_same_month() {
+        for month in MONTHS {
+            let forms = [
+                month.abbreviation().to_owned(),
+                month.number().to_string(),
+                month.name().to_owned(),
+            ];
+            for form in forms {
+                assert_eq!(month, form.parse().unwrap(), "{form}");
+            }
+        }
+        assert_eq!(Month::March, " {MARCH} ".parse().unwrap());
+        assert_eq!(Month::March, "03".parse().unwrap());
+        assert_eq!(Month::September, "Sept.".parse().unwrap());
+    }
+
+    #[test]
+    fn invalid_months_are_errors() {
+        for invalid in ["0", "13", "ja", "jan 1", "spring", "junk", "decade", ""] {
+            assert!(invalid.parse::<Month>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+/// The pages of a work, which is the parsed value of the `pages` field.
+///
+/// A single page or a range of pages, written with any dash, is parsed into the page numbers
+/// and any other value, such as the article number `e12345`, an open range `5+` or roman
+/// numerals, is kept as written. A reference list shows the pages as `p. 5` or `pp. 5–9`.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::Pages;
+///
+/// assert_eq!(Pages::Range(5, 9), Pages::from("5--9"));
+/// assert_eq!("pp. 5–9", Pages::from("5-9").to_string());
+/// assert_eq!("p. 5", Pages::from("5").to_string());
+/// assert_eq!("e12345", Pages::from("e12345").to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Pages {
+    /// A single page.
+    Single(u32),
+    /// A range of pages from the first page to the last page.
+    Range(u32, u32),
+    /// Any other value, which is kept as written.
+    Verbatim(String),
+}
+
+impl Pages {
+    /// Returns the first page, or `None` when the pages are not numbers.
+    #[must_use]
+    pub const fn start(&self) -> Option<u32> {
+        match self {
+            Self::Single(start) | Self::Range(start, _) => Some(*start),
+            Self::Verbatim(_) => None,
+        }
+    }
+
+    /// Returns the last page, or `None` when the pages are not numbers.
+    #[must_use]
+    pub const fn end(&self) -> Option<u32> {
+        match self {
+            Self::Single(end) | Self::Range(_, end) => Some(*end),
+            Self::Verbatim(_) => None,
+        }
+    }
+}
+
+impl From<&str> for Pages {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        let page = |page: &str| {
+            let page = page.trim();
+            // `parse` alone would also accept a leading `+`, as in `+5`
+            page.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| page.parse::<u32>().ok())
+                .flatten()
+        };
+
+        let range = ["--", "-", "–", "—"]
+            .iter()
+            .find_map(|dash| value.split_once(dash));
+        match range.map(|(start, end)| (page(start), page(end))) {
+            Some((Some(start), Some(end))) if start == end => Self::Single(start),
+            Some((Some(start), Some(end))) if start < end => Self::Range(start, end),
+            None => page(value).map_or_else(|| Self::Verbatim(value.to_owned()), Self::Single),
+            _ => Self::Verbatim(value.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Pages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(page) => write!(f, "p. {page}"),
+            Self::Range(start, end) => write!(f, "pp. {start}–{end}"),
+            Self::Verbatim(pages) => f.write_str(pages),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_page() {
+        assert_eq!(Pages::Single(5), Pages::from(" 5 "));
+        assert_eq!(Pages::Single(5), Pages::from("5--5"));
+        assert_eq!("p. 5", Pages::Single(5).to_string());
+    }
+
+    #[test]
+    fn range_with_any_dash() {
+        for pages in ["5--9", "5-9", "5 – 9", "5—9"] {
+            assert_eq!(Pages::Range(5, 9), Pages::from(pages), "{pages}");
+        }
+        assert_eq!("pp. 5–9", Pages::Range(5, 9).to_string());
+    }
+
+    #[test]
+    fn odd_pages_are_verbatim() {
+        for pages in ["e12345", "5+", "xii--xv", "iv", "9-5", "+5", "e1-e7"] {
+            let parsed = Pages::from(pages);
+            assert_eq!(Pages::Verbatim(pages.to_owned()), parsed);
+            assert_eq!(pages, parsed.to_string());
+            assert_eq!(None, parsed.start());
+        }
+    }
+}
@@ -215,6 +215,61 @@ impl QuotedString {
         }
         res
     }
+
+    /// Transform every substring, quoted or not, using the closure provided to this method and
+    /// keep which of the transformed substrings are quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seb::ast::QuotedString;
+    ///
+    /// let string = QuotedString::from_quoted("foo $bar$", '$');
+    /// let upper = string.map_parts(str::to_uppercase);
+    ///
+    /// assert_eq!("FOO BAR", &*upper);
+    /// assert_eq!("FOO bar", upper.map_quoted(str::to_lowercase));
+    /// ```
+    #[must_use]
+    pub fn map_parts(&self, f: impl Fn(&str) -> String) -> Self {
+        let mut parts = self
+            .parts()
+            .into_iter()
+            .map(|(quoted, part)| (quoted, f(part)))
+            .collect::<Vec<_>>();
+        parts.retain(|(_, part)| !part.is_empty());
+
+        Self::from_parts(parts)
+    }
+
+    /// The substrings of this string in order, where the bool signifies that the substring is
+    /// quoted, which is the reverse of [`Self::from_parts`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::QuotedString;
+    ///
+    /// let string = QuotedString::from_quoted("foo $bar$", '$');
+    ///
+    /// assert_eq!(vec![(false, "foo "), (true, "bar")], string.parts());
+    /// ```
+    #[must_use]
+    pub fn parts(&self) -> Vec<(bool, &str)> {
+        let mut parts = Vec::new();
+        let mut quoted = false;
+        let mut pos = 0;
+
+        for marker in &self.markers {
+            parts.push((quoted, &self.value[pos..*marker]));
+            quoted = !quoted;
+            pos = *marker;
+        }
+        parts.push((quoted, &self.value[pos..]));
+        parts.retain(|(_, part)| !part.is_empty());
+
+        parts
+    }
 }
 
 impl From<&str> for QuotedString {
@@ -302,6 +357,19 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn map_parts_keeps_quoted_parts() {
+        let string = QuotedString::from_parts(vec![
+            (false, "a".to_owned()),
+            (true, "b".to_owned()),
+            (false, "c".to_owned()),
+        ]);
+        let mapped = string.map_parts(|s| s.repeat(2));
+
+        assert_eq!("aabbcc", &*mapped);
+        assert_eq!("aa{bb}cc", mapped.map_quoted(|s| format!("{{{s}}}")));
+    }
+
     #[test]
     fn empty_quoted_string_is_equiv_to_empty_string() {
         let string = QuotedString::default();
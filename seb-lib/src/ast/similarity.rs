@@ -0,0 +1,263 @@
+use serde::{Deserialize, Deserializer};
+
+/// How titles are compared when looking for entries that are possibly duplicates, see
+/// [`Biblio::similar_titles_with`][S].
+///
+/// The titles are [normalized][N] before they are scored and two titles are similar when their
+/// score is at least the `threshold`, from `0.0` where any titles are similar to `1.0` where only
+/// the same titles are similar. The [`Default`] is the [`Metric::Levenshtein`] score with a
+/// threshold of `0.95`, which is one edit for every 20 characters and rarely finds different
+/// works.
+///
+/// [S]: crate::ast::Biblio::similar_titles_with
+/// [N]: crate::ast::normalize_title
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{Metric, Similarity};
+///
+/// let strict = Similarity::default();
+/// let loose = Similarity { metric: Metric::JaroWinkler, threshold: 0.9 };
+///
+/// assert!(!strict.is_similar("the art of programming", "the art of computer programming"));
+/// assert!(loose.is_similar("the art of programming", "the art of computer programming"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Similarity {
+    /// The score used to compare titles.
+    pub metric: Metric,
+    /// The lowest score of similar titles, from `0.0` to `1.0`.
+    #[serde(deserialize_with = "threshold")]
+    pub threshold: f64,
+}
+
+/// The score of how similar two titles are, from `0.0` for nothing in common to `1.0` for the
+/// same titles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// One minus the [Levenshtein distance][L] divided by the length of the longer title, which
+    /// scores every edit anywhere in a title the same.
+    ///
+    /// [L]: https://en.wikipedia.org/wiki/Levenshtein_distance
+    Levenshtein,
+    /// The [Jaro-Winkler similarity][JW], which scores titles that start the same higher and is
+    /// more forgiving of words missing from the end of a title.
+    ///
+    /// [JW]: https://en.wikipedia.org/wiki/Jaro%E2%80%93Winkler_distance
+    JaroWinkler,
+}
+
+impl Default for Similarity {
+    fn default() -> Self {
+        Self {
+            metric: Metric::Levenshtein,
+            threshold: 0.95,
+        }
+    }
+}
+
+// the scores are divisions so are compared with a tolerance for rounding
+const TOLERANCE: f64 = 1e-9;
+
+impl Similarity {
+    /// Returns `true` when the titles `a` and `b`, which should already be normalized, score at
+    /// least the threshold.
+    #[must_use]
+    pub fn is_similar(&self, a: &str, b: &str) -> bool {
+        a == b || self.metric.score(a, b) + TOLERANCE >= self.threshold
+    }
+
+    /// Returns `false` when titles of `a` and `b` characters can never score the threshold, which
+    /// is checked before the titles are scored as it is much cheaper.
+    pub(crate) fn may_be_similar(&self, a: usize, b: usize) -> bool {
+        self.metric.max_score(a, b) + TOLERANCE >= self.threshold
+    }
+}
+
+impl Metric {
+    /// Returns the score of how similar `a` and `b` are, from `0.0` to `1.0`.
+    #[must_use]
+    pub fn score(self, a: &str, b: &str) -> f64 {
+        match self {
+            Self::Levenshtein => {
+                let len = a.chars().count().max(b.chars().count());
+                if len == 0 {
+                    1.0
+                } else {
+                    1.0 - ratio(levenshtein(a, b), len)
+                }
+            }
+            Self::JaroWinkler => jaro_winkler(a, b),
+        }
+    }
+
+    // the highest score of any titles of `a` and `b` characters
+    fn max_score(self, a: usize, b: usize) -> f64 {
+        let (short, long) = (a.min(b), a.max(b));
+        if long == 0 {
+            return 1.0;
+        }
+        match self {
+            // at least the difference in length is inserted
+            Self::Levenshtein => ratio(short, long),
+            // at most every character of the shorter title matches, and the common prefix only
+            // raises the score by up to 0.4 of what is left
+            Self::JaroWinkler => {
+                let jaro = (2.0 + ratio(short, long)) / 3.0;
+                jaro + 0.4 * (1.0 - jaro)
+            }
+        }
+    }
+}
+
+fn threshold<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let threshold = f64::deserialize(deserializer)?;
+    if (0.0..=1.0).contains(&threshold) {
+        Ok(threshold)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "The threshold {threshold} is not between 0.0 and 1.0"
+        )))
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // characters match when they are the same and no further apart than this
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !matched[j] && b[j] == *ca) {
+            matched[j] = true;
+            a_matches.push(*ca);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b
+        .iter()
+        .zip(&matched)
+        .filter_map(|(cb, matched)| matched.then_some(cb));
+    let transpositions = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(ca, cb)| ca != cb)
+        .count()
+        / 2;
+
+    let m = a_matches.len();
+    let jaro = (ratio(m, a.len()) + ratio(m, b.len()) + ratio(m - transpositions, m)) / 3.0;
+    let prefix = a
+        .iter()
+        .zip(&b)
+        .take(4)
+        .take_while(|(ca, cb)| ca == cb)
+        .count();
+    jaro + ratio(prefix, 10) * (1.0 - jaro)
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    let float = |n: usize| f64::from(u32::try_from(n).unwrap_or(u32::MAX));
+    float(numerator) / float(denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levenshtein_distance() {
+        assert_eq!(0, levenshtein("", ""));
+        assert_eq!(3, levenshtein("", "abc"));
+        assert_eq!(3, levenshtein("kitten", "sitting"));
+    }
+
+    #[test]
+    fn jaro_winkler_similarity() {
+        assert!((jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((jaro_winkler("dixon", "dicksonx") - 0.813).abs() < 0.001);
+        assert!((jaro_winkler("abc", "abc") - 1.0).abs() < f64::EPSILON);
+        assert!(jaro_winkler("abc", "xyz").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn max_score_is_never_below_the_score() {
+        let titles = [
+            "",
+            "a",
+            "martha",
+            "marhta",
+            "dicksonx",
+            "the art of programming",
+        ];
+        for metric in [Metric::Levenshtein, Metric::JaroWinkler] {
+            for a in titles {
+                for b in titles {
+                    let max = metric.max_score(a.chars().count(), b.chars().count());
+                    assert!(metric.score(a, b) <= max + TOLERANCE, "{metric:?} {a} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn slightly_different_titles_depend_on_the_threshold() {
+        let a = "quicksort a fast sorting scheme";
+        let b = "quicksort a fast sorting schema";
+        for metric in [Metric::Levenshtein, Metric::JaroWinkler] {
+            let loose = Similarity {
+                metric,
+                threshold: 0.9,
+            };
+            let strict = Similarity {
+                metric,
+                threshold: 0.99,
+            };
+
+            assert!(loose.is_similar(a, b), "{metric:?}");
+            assert!(!strict.is_similar(a, b), "{metric:?}");
+        }
+    }
+
+    #[test]
+    fn default_is_one_edit_for_every_20_characters() {
+        let similarity = Similarity::default();
+        let title = "a".repeat(40);
+
+        assert!(similarity.is_similar(&title, &format!("{}bb", "a".repeat(38))));
+        assert!(!similarity.is_similar(&title, &format!("{}bbb", "a".repeat(37))));
+    }
+}
@@ -14,6 +14,8 @@ pub struct Error {
     // This is a dyn std::error::Error so that we can hold many different types of errors
     // and by boxing it then the size of the Error struct is reduced.
     source: Option<DynError>,
+    // Optional suggestion of how to recover from the error.
+    hint: Option<String>,
 }
 
 /// Types of errors that make up an [`Error`].
@@ -34,6 +36,7 @@ impl Error {
             kind,
             message: Some(message.into()),
             source: None,
+            hint: None,
         }
     }
 
@@ -46,6 +49,7 @@ impl Error {
             kind,
             message: None,
             source: Some(source.into()),
+            hint: None,
         }
     }
 
@@ -59,9 +63,37 @@ impl Error {
             kind,
             message: Some(message.into()),
             source: Some(source.into()),
+            hint: None,
         }
     }
 
+    /// Adds a suggestion of how to recover from the error, such as the setting to change, which is
+    /// shown below the error message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::{Error, ErrorKind};
+    ///
+    /// let err = Error::new(ErrorKind::NoValue, "No token is set")
+    ///     .with_hint("Set the token in the SEB_TOKEN environment variable");
+    ///
+    /// assert_eq!(Some("Set the token in the SEB_TOKEN environment variable"), err.hint());
+    /// assert_eq!("No value error: No token is set", err.to_string());
+    /// ```
+    #[must_use]
+    pub fn with_hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Returns the suggestion of how to recover from the error, if one was added using
+    /// [`Self::with_hint`].
+    #[must_use]
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
     /// Returns the kind of error.
     #[must_use]
     pub const fn kind(&self) -> ErrorKind {
@@ -2,16 +2,21 @@
 //!
 //! This module contains basic methods for opening/creating files into a supported format and also
 //! provides types to read and write from those format files.
+//!
+//! With the `gzip` feature enabled, files with a `.gz` extension, or starting with the gzip magic
+//! bytes, are decompressed when read and files with a `.gz` extension are compressed when written.
 
 use std::{
-    fs::{File, OpenOptions},
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
     io::{Read, Seek, Write},
     marker::PhantomData,
     path::{Path, PathBuf},
 };
 
 use crate::{
-    format::{Format, Reader, Writer},
+    ast::{Biblio, QuotedString, SIDECAR_FIELD},
+    format::{Format, InputEncoding, Reader, Writer},
     Error, ErrorKind,
 };
 
@@ -26,19 +31,72 @@ use glob::{glob, Paths};
 pub struct FormatFile<F: Format> {
     // Raw file handler.
     file: File,
+    // The path the file was opened with.
+    path: PathBuf,
+    // Whether the content should be gzip compressed when written.
+    compressed: bool,
+    // The encoding the content is read in, which is detected when not set.
+    encoding: Option<InputEncoding>,
     // Generic F in PhantomData so that drop implementation knows that
     // FormatFile is not holding an actual F that needs dropping too.
     _format: PhantomData<F>,
 }
 
 impl<F: Format> FormatFile<F> {
-    fn new(file: File) -> Self {
+    fn new(file: File, path: PathBuf) -> Self {
         Self {
             file,
+            path,
+            compressed: false,
+            encoding: None,
             _format: PhantomData,
         }
     }
 
+    // Sets whether the content is gzip compressed, which is only ever true when the gzip feature
+    // is enabled.
+    const fn with_compression(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// Sets the `encoding` the content of the file is read in, or `None` to detect the encoding
+    /// when the file is read, which is the default.
+    ///
+    /// Content in an encoding other than UTF-8 is transcoded to UTF-8 when it is read and is
+    /// written as UTF-8.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seb::{
+    ///     file::FormatFile,
+    ///     format::{BibTex, InputEncoding, Reader},
+    /// };
+    ///
+    /// fn main() -> Result<(), seb::Error> {
+    ///     let mut f = FormatFile::<BibTex>::open("foo.bib")?.with_encoding(Some(InputEncoding::Latin1));
+    ///     let bibtex = f.read()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub const fn with_encoding(mut self, encoding: Option<InputEncoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the encoding the content of the file is read in, or `None` when it is detected.
+    #[must_use]
+    pub const fn encoding(&self) -> Option<InputEncoding> {
+        self.encoding
+    }
+
+    /// Returns the path of the file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Attempts to open a format file in read and write mode.
     ///
     /// # Errors
@@ -61,8 +119,9 @@ impl<F: Format> FormatFile<F> {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let path = path.as_ref();
         // ensure that the path also uses the correct extension
-        let path_buf = path.with_extension(F::ext());
+        let path_buf = format_path::<F>(path);
         open_file_for_read_and_write(path_buf.as_path())
+            .map(|f| f.with_compression(is_gzip_path(path)))
     }
 
     /// Attempts to find a single format file in the directory.
@@ -126,8 +185,99 @@ impl<F: Format> FormatFile<F> {
     /// ```
     pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let path = path.as_ref();
-        let path_buf = path.with_extension(F::ext());
+        let path_buf = format_path::<F>(path);
         create_file_for_read_and_write(path_buf.as_path())
+            .map(|f| f.with_compression(is_gzip_path(path)))
+    }
+}
+
+#[cfg(feature = "bibtex")]
+impl FormatFile<crate::format::BibTex> {
+    /// Finds the entry with the `cite` key in the file and parses it without reading the whole
+    /// file into memory, see [`BibTex::find_entry`][crate::format::BibTex::find_entry].
+    ///
+    /// The file is read in the encoding set with [`FormatFile::with_encoding`], or as UTF-8 when
+    /// it is detected. A file that is not UTF-8 and has no encoding set is read in full so that
+    /// its encoding can be detected, see [`Reader::read`].
+    ///
+    /// Returns `Ok(None)` when no entry has the `cite` key.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be read or the entry with the `cite`
+    /// key cannot be parsed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seb::{file::FormatFile, format::BibTex};
+    ///
+    /// fn main() -> Result<(), seb::Error> {
+    ///     let mut f = FormatFile::<BibTex>::open("large.bib")?;
+    ///     if let Some(Ok(entry)) = f.find_entry("knuth1984")? {
+    ///         println!("{}", entry.title());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn find_entry(
+        &mut self,
+        cite: &str,
+    ) -> Result<Option<Result<crate::ast::Entry, crate::ast::Resolver>>, Error> {
+        let rewind = |file: &mut File| {
+            file.rewind()
+                .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Cannot read contents of file"))
+        };
+
+        rewind(&mut self.file)?;
+        let encoding = self.encoding.unwrap_or(InputEncoding::Utf8);
+        let found = crate::format::BibTex::find_entry(buffered(&self.file)?, encoding, cite);
+        rewind(&mut self.file)?;
+        match found {
+            Err(e) if self.encoding.is_none() && e.kind() == ErrorKind::IO => {
+                log::trace!("cannot stream {} - reading it in full", self.path.display());
+                let found = self.read()?.parse_entry(cite);
+                rewind(&mut self.file)?;
+                found
+            }
+            found => found,
+        }
+    }
+}
+
+// The content of the file read in lines, which is decompressed when it starts with the gzip magic
+// bytes.
+#[cfg(all(feature = "bibtex", feature = "gzip"))]
+fn buffered(file: &File) -> Result<Box<dyn std::io::BufRead + '_>, Error> {
+    use std::io::{BufRead, BufReader};
+
+    let mut reader = BufReader::new(file);
+    let start = reader
+        .fill_buf()
+        .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Cannot read contents of file"))?;
+    if start.starts_with(&GZIP_MAGIC) {
+        let decoder = flate2::read::GzDecoder::new(reader);
+        return Ok(Box::new(BufReader::new(decoder)));
+    }
+    Ok(Box::new(reader))
+}
+
+#[cfg(all(feature = "bibtex", not(feature = "gzip")))]
+#[allow(clippy::unnecessary_wraps)]
+fn buffered(file: &File) -> Result<Box<dyn std::io::BufRead + '_>, Error> {
+    Ok(Box::new(std::io::BufReader::new(file)))
+}
+
+// Returns `true` when the path has a `.gz` extension and the gzip feature is enabled.
+fn is_gzip_path(path: &Path) -> bool {
+    cfg!(feature = "gzip") && path.extension().is_some_and(|ext| ext == "gz")
+}
+
+// Ensures the path uses the format extension, a compressed path is kept as is so that `foo.bib.gz`
+// is not changed to `foo.bib.bib`.
+fn format_path<F: Format>(path: &Path) -> PathBuf {
+    if is_gzip_path(path) {
+        path.to_path_buf()
+    } else {
+        path.with_extension(F::ext())
     }
 }
 
@@ -139,13 +289,56 @@ impl<F: Format> Reader for FormatFile<F> {
         // string with the associated Format type.
         //
         // Any IO error is wrapped by the crate Error type
-        read_file_to_string(&mut self.file)
-            .map(F::new)
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))
+        let content = read_file_to_bytes(&mut self.file).and_then(decompress)?;
+        let encoding = self.encoding.unwrap_or_else(|| {
+            let detected = InputEncoding::detect(&content);
+            if detected != InputEncoding::Utf8 {
+                log::warn!(
+                    "{} is not UTF-8 - reading it as {detected}, it is written as UTF-8",
+                    self.path.display()
+                );
+            }
+            detected
+        });
+
+        encoding.decode(content).map(F::new)
     }
 }
 
-fn read_file_to_string(file: &mut File) -> Result<String, Error> {
+// The first two bytes of any gzip stream.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Decompresses the content when it starts with the gzip magic bytes, otherwise the content is
+// returned unchanged.
+#[cfg(feature = "gzip")]
+fn decompress(content: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if !content.starts_with(&GZIP_MAGIC) {
+        return Ok(content);
+    }
+
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(content.as_slice())
+        .read_to_end(&mut decompressed)
+        .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Cannot decompress gzip file"))?;
+    log::trace!("{} bytes after decompression", decompressed.len());
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "gzip"))]
+#[allow(clippy::unnecessary_wraps)]
+const fn decompress(content: Vec<u8>) -> Result<Vec<u8>, Error> {
+    Ok(content)
+}
+
+#[cfg(feature = "gzip")]
+fn compress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+fn read_file_to_bytes(file: &mut File) -> Result<Vec<u8>, Error> {
     // Wraps an IO error when trying to access a file contents or metadata.
     #[inline]
     fn wrap_file_access_error(e: std::io::Error) -> Error {
@@ -169,8 +362,8 @@ fn read_file_to_string(file: &mut File) -> Result<String, Error> {
         .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "File too large!"))?;
 
     // allocate the correct amount of memory early before the read.
-    let mut content = String::with_capacity(file_len);
-    file.read_to_string(&mut content)
+    let mut content = Vec::with_capacity(file_len);
+    file.read_to_end(&mut content)
         .map_err(wrap_file_access_error)
         .map(move |bytes| {
             log::trace!("{bytes} read from the file");
@@ -193,18 +386,167 @@ impl<F: Format> Writer for FormatFile<F> {
 
         // Get raw contents of Format string as bytes
         let bytes = format.raw().into_bytes();
+        #[cfg(feature = "gzip")]
+        let bytes = if self.compressed {
+            compress(&bytes).map_err(|e| Error::wrap(ErrorKind::IO, e))?
+        } else {
+            bytes
+        };
         overrwrite_file_from_start(&mut self.file, &bytes)
             .map_err(|e| Error::wrap(ErrorKind::IO, e))
     }
 }
 
+/// The large fields of the entries of a bibliography file, such as abstracts, stored in a JSON
+/// file next to it so that the bibliography file stays small and its diffs are not noisy.
+///
+/// The sidecar of `refs.bib` is `refs.bib.json`, a JSON object of the sidecar fields of each entry
+/// keyed by the cite key. An entry with fields in the sidecar lists their names in the
+/// [`SIDECAR_FIELD`] so the bibliography file still shows where the fields are.
+///
+/// # Examples
+///
+/// ```no_run
+/// use seb::{
+///     file::{FormatFile, Sidecar},
+///     format::{BibTex, Format, Reader, Writer},
+/// };
+///
+/// fn main() -> Result<(), seb::Error> {
+///     let mut file = FormatFile::<BibTex>::open("refs.bib")?;
+///     let sidecar = Sidecar::new(file.path(), vec!["abstract".to_owned()]);
+///
+///     let mut biblio = file.read()?.parse()?.unwrap();
+///     // the abstracts are read from `refs.bib.json`
+///     sidecar.load(&mut biblio)?;
+///
+///     // the abstracts are written to `refs.bib.json` and not `refs.bib`
+///     sidecar.store(&mut biblio)?;
+///     file.write(BibTex::compose(&biblio))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Sidecar {
+    path: PathBuf,
+    fields: Vec<String>,
+}
+
+impl Sidecar {
+    /// Creates the sidecar of the `bibliography` file that stores the `fields`.
+    ///
+    /// A sidecar without any fields stores nothing but still loads the fields of an existing
+    /// sidecar file, so that the fields are written back to the bibliography file.
+    #[must_use]
+    pub fn new<P: AsRef<Path>>(bibliography: P, fields: Vec<String>) -> Self {
+        let mut path = bibliography.as_ref().as_os_str().to_owned();
+        path.push(".json");
+        Self {
+            path: PathBuf::from(path),
+            fields,
+        }
+    }
+
+    /// Returns the path of the sidecar file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the fields stored in the sidecar file on the entries of the `biblio` that list them
+    /// in the [`SIDECAR_FIELD`], which is removed, without marking the `biblio` as dirty.
+    ///
+    /// Nothing is loaded when the sidecar file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] when the sidecar file cannot be read or is not a JSON object of the
+    /// fields of each entry.
+    pub fn load(&self, biblio: &mut Biblio) -> Result<(), Error> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(Error::wrap_with(
+                    ErrorKind::IO,
+                    e,
+                    "Cannot read sidecar file",
+                ))
+            }
+        };
+        let mut stored: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_str(&json)
+            .map_err(|e| {
+                Error::wrap_with(
+                    ErrorKind::Deserialize,
+                    e,
+                    format!("{} is not a sidecar file", self.path.display()),
+                )
+            })?;
+
+        for entry in biblio.entries_mut_untracked() {
+            if entry.remove_field(SIDECAR_FIELD).is_none() {
+                continue;
+            }
+            let Some(fields) = stored.remove(entry.cite()) else {
+                log::warn!(
+                    "The fields of '{}' are not in {}",
+                    entry.cite(),
+                    self.path.display()
+                );
+                continue;
+            };
+            for (name, value) in fields {
+                // quoted parts are kept in braces as they are in BibTeX
+                entry.set_field(&name, QuotedString::from_quoted(&value, ['{', '}']));
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the sidecar fields of the entries of the `biblio` to the sidecar file, which is
+    /// written again, and lists them in the [`SIDECAR_FIELD`] of each entry, without marking the
+    /// `biblio` as dirty.
+    ///
+    /// Nothing is stored when this sidecar has no fields.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] when the sidecar file cannot be written.
+    pub fn store(&self, biblio: &mut Biblio) -> Result<(), Error> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+
+        let mut stored = BTreeMap::new();
+        for entry in biblio.entries_mut_untracked() {
+            let fields = self
+                .fields
+                .iter()
+                .filter_map(|name| {
+                    let value = entry.remove_field(name)?;
+                    Some((name.clone(), value.map_quoted(|s| format!("{{{s}}}"))))
+                })
+                .collect::<BTreeMap<_, _>>();
+            if !fields.is_empty() {
+                let names = fields.keys().map(String::as_str).collect::<Vec<_>>();
+                entry.set_field(SIDECAR_FIELD, names.join(", "));
+                stored.insert(entry.cite().to_owned(), fields);
+            }
+        }
+
+        let json =
+            serde_json::to_string_pretty(&stored).map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+        fs::write(&self.path, json + "\n")
+            .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Cannot write sidecar file"))
+    }
+}
+
 #[inline]
 fn open_file_for_read_and_write<F: Format>(path: &Path) -> Result<FormatFile<F>, Error> {
     OpenOptions::new()
         .read(true)
         .write(true)
         .open(path)
-        .map(FormatFile::<F>::new)
+        .map(|file| FormatFile::<F>::new(file, path.to_path_buf()))
         .map_err(|e| {
             Error::wrap_with(
                 ErrorKind::IO,
@@ -224,7 +566,7 @@ fn create_file_for_read_and_write<F: Format>(path: &Path) -> Result<FormatFile<F
         .read(true)
         .write(true)
         .open(path)
-        .map(FormatFile::<F>::new)
+        .map(|file| FormatFile::<F>::new(file, path.to_path_buf()))
         .map_err(|e| {
             Error::wrap_with(
                 ErrorKind::IO,
@@ -341,7 +683,7 @@ mod tests {
     use crate::format::BibTex;
 
     use assert_fs::{
-        fixture::{FileTouch, PathChild},
+        fixture::{FileTouch, FileWriteBin, PathChild},
         NamedTempFile, TempDir,
     };
 
@@ -418,16 +760,152 @@ mod tests {
             .pop()
             .unwrap();
 
-        dbg!(&bibtex);
-
         let file = std::fs::File::open("../seb-lib/tests/data/bibtex1.bib")
             .expect("Cannot open ../seb-lib/tests/data/bibtex1.bib file for test");
 
-        let mut file: FormatFile<BibTex> = FormatFile::new(file);
+        let mut file: FormatFile<BibTex> =
+            FormatFile::new(file, "../seb-lib/tests/data/bibtex1.bib".into());
 
         let biblio = file.read_ast().unwrap().unwrap();
         let res = biblio.entries().next().unwrap();
 
         assert_eq!(&expected, res);
     }
+
+    #[test]
+    fn read_latin1_bib_file_as_utf8() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("latin1.bib");
+        std::fs::write(
+            path.path(),
+            b"@book{godel, title = {\xdcber formal unentscheidbare S\xe4tze}, \
+            author = {G\xf6del, Kurt and Erd\xf6s, Paul}, publisher = {Springer}, year = {1931}}",
+        )
+        .unwrap();
+
+        for encoding in [None, Some(InputEncoding::Latin1)] {
+            let biblio = FormatFile::<BibTex>::open(path.path())
+                .unwrap()
+                .with_encoding(encoding)
+                .read_ast()
+                .unwrap()
+                .unwrap();
+            let entry = biblio.get("godel").unwrap();
+
+            assert_eq!("Über formal unentscheidbare Sätze", &**entry.title());
+            assert_eq!(
+                vec!["Gödel", "Erdös"],
+                entry
+                    .authors()
+                    .into_iter()
+                    .map(|author| author.family)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        let forced = FormatFile::<BibTex>::open(path.path())
+            .unwrap()
+            .with_encoding(Some(InputEncoding::Utf8))
+            .read();
+        assert!(forced.is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn read_gzip_bib_file_matches_uncompressed() {
+        let mut plain = FormatFile::<BibTex>::new(
+            std::fs::File::open("../seb-lib/tests/data/bibtex1.bib")
+                .expect("Cannot open ../seb-lib/tests/data/bibtex1.bib file for test"),
+            "../seb-lib/tests/data/bibtex1.bib".into(),
+        );
+        let mut compressed = FormatFile::<BibTex>::open("../seb-lib/tests/data/bibtex1.bib.gz")
+            .expect("Cannot open ../seb-lib/tests/data/bibtex1.bib.gz file for test");
+
+        let expected = plain.read_ast().unwrap().unwrap();
+        let biblio = compressed.read_ast().unwrap().unwrap();
+
+        assert_eq!(
+            expected.entries().collect::<Vec<_>>(),
+            biblio.entries().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn write_gzip_bib_file_when_path_ends_with_gz() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let path = dir.child("refs.bib.gz");
+        let bibtex = include_str!("../../seb-lib/tests/data/bibtex1.bib");
+
+        let mut file = FormatFile::<BibTex>::create(path.path())
+            .expect("Cannot create refs.bib.gz in temp directory");
+        file.write(BibTex::new(bibtex.to_owned())).unwrap();
+
+        let bytes = std::fs::read(path.path()).unwrap();
+        assert!(bytes.starts_with(&GZIP_MAGIC));
+
+        let mut file = FormatFile::<BibTex>::open(path.path()).unwrap();
+        assert_eq!(bibtex, file.read().unwrap().raw());
+    }
+
+    #[test]
+    fn abstract_is_stored_in_sidecar_not_bib_file() {
+        use crate::ast::FieldQuery;
+
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let path = dir.child("refs.bib");
+        let sidecar = Sidecar::new(path.path(), vec!["abstract".to_owned()]);
+        assert_eq!(dir.child("refs.bib.json").path(), sidecar.path());
+
+        let mut biblio = BibTex::new(
+            "@misc{knuth, title = {TAOCP}, abstract = {On {ALGOL} programs}}".to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        biblio.dirty();
+
+        sidecar.store(&mut biblio).unwrap();
+        assert!(!biblio.dirty());
+
+        let bibtex = BibTex::compose(&biblio).raw();
+        assert!(!bibtex.contains("ALGOL"));
+        assert!(bibtex.contains("x-seb-sidecar = {abstract}"));
+
+        let json = std::fs::read_to_string(sidecar.path()).unwrap();
+        assert!(json.contains(r#""abstract": "On {ALGOL} programs""#));
+
+        let mut biblio = BibTex::new(bibtex).parse().unwrap().unwrap();
+        biblio.dirty();
+        sidecar.load(&mut biblio).unwrap();
+        assert!(!biblio.dirty());
+
+        let entry = biblio.get("knuth").unwrap();
+        assert!(entry.get_field(SIDECAR_FIELD).is_none());
+        assert_eq!(
+            "On {ALGOL} programs",
+            entry
+                .get_field("abstract")
+                .unwrap()
+                .map_quoted(|s| format!("{{{s}}}"))
+        );
+    }
+
+    #[test]
+    fn find_entry_reads_a_file_that_is_not_utf8_in_full() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.child("latin1.bib");
+        path.write_binary(b"@manual{a, title = {A}}\n@manual{godel, title = {G\xf6del}}\n")
+            .unwrap();
+        let mut file = FormatFile::<BibTex>::open(path.path()).unwrap();
+
+        let entry = file.find_entry("godel").unwrap().unwrap().unwrap();
+        assert_eq!("G\u{f6}del", &**entry.title());
+        assert!(file.find_entry("missing").unwrap().is_none());
+        // the file is read from the start after finding an entry
+        assert_eq!(2, file.read().unwrap().raw_entries().count());
+
+        let mut file = file.with_encoding(Some(InputEncoding::Utf8));
+        assert!(file.find_entry("godel").is_err());
+    }
 }
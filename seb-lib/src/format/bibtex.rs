@@ -1,11 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map, HashMap, HashSet},
+    io::BufRead,
+};
 
 use crate::{
-    ast::{self, Biblio, BiblioResolver, QuotedString},
+    ast::{
+        self, generated_key, Biblio, BiblioResolver, Blocks, CommentPlacement, FieldFilter,
+        FieldQuery, KeyFormat, Month, QuotedString, Resolver, ADDED_FIELD, KEY_FIELD, SOURCE_FIELD,
+        URLDATE_FIELD,
+    },
+    normalize::unicode_to_latex,
     Error, ErrorKind,
 };
 
-use super::Format;
+use super::{Format, InputEncoding, Transforms};
 
 use biblatex::Bibliography;
 
@@ -19,40 +27,319 @@ impl Format for BibTex {
     }
 
     fn parse(self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        self.parse_with(&KeyFormat::default())
+    }
+
+    fn compose(biblio: &Biblio) -> Self {
+        Self::compose_with(biblio, &BibTexConfig::default())
+    }
+
+    fn compose_entry(entry: &ast::Entry) -> String {
+        Self::compose_entry_with(entry, &BibTexConfig::default())
+    }
+
+    fn raw(self) -> String {
+        self.0
+    }
+
+    fn name() -> &'static str {
+        "BibTex"
+    }
+
+    fn ext() -> &'static str {
+        "bib"
+    }
+}
+
+/// The style options used when composing `BibTeX`.
+///
+/// The [`Default`] style is the one used by [`Format::compose`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BibTexConfig {
+    /// The number of spaces to indent each field by.
+    pub indent: usize,
+    /// The casing of the field names.
+    pub field_case: FieldCase,
+    /// The delimiters to use around field values.
+    pub quote_style: QuoteStyle,
+    /// Whether the last field of an entry is followed by a comma.
+    pub trailing_comma: bool,
+    /// Whether every field of an entry is sorted by name, otherwise the required fields are
+    /// written first followed by the optional fields sorted by name.
+    pub sort_fields: bool,
+    /// How the `month` field is written.
+    pub month_style: MonthStyle,
+    /// The field names and entry types that are written.
+    pub dialect: Dialect,
+    /// The characters that are written.
+    pub output_encoding: OutputEncoding,
+    /// Whether each entry is followed by `%` comments explaining the content seb generated, such
+    /// as `% key generated by seb from author+year`, from the provenance fields of the entry.
+    ///
+    /// The comments are written again from the provenance fields every time so they are never
+    /// repeated, and a file without them is read the same.
+    pub annotate: bool,
+    /// Whether the date a URL was accessed is written at the end of the `note` field, as
+    /// `Accessed: 2024-01-01`, instead of in the `urldate` field, for the classic `BibTeX` styles
+    /// that have no `urldate` field.
+    ///
+    /// This only applies to the [`Dialect::BibTex`] dialect.
+    pub urldate_in_note: bool,
+    /// The fields that are composed, by default every field.
+    ///
+    /// This is never read from a config file so that fields are not lost when a bibliography
+    /// file is written.
+    #[serde(skip)]
+    pub fields: FieldFilter,
+    /// The transforms applied to each field, in order, before it is composed, by default none.
+    ///
+    /// This is only set in code, such as to register the [`PageDash`][super::PageDash] and
+    /// [`TitleProtection`][super::TitleProtection] transforms or a custom
+    /// [`FieldTransform`][super::FieldTransform].
+    #[serde(skip)]
+    pub transforms: Transforms,
+}
+
+impl Default for BibTexConfig {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            field_case: FieldCase::AsIs,
+            quote_style: QuoteStyle::Braces,
+            trailing_comma: true,
+            sort_fields: false,
+            month_style: MonthStyle::Macro,
+            dialect: Dialect::BibTex,
+            output_encoding: OutputEncoding::Utf8,
+            annotate: false,
+            urldate_in_note: false,
+            fields: FieldFilter::default(),
+            transforms: Transforms::default(),
+        }
+    }
+}
+
+/// The casing of field names when composing `BibTeX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldCase {
+    /// Field names are lowercase, for example `title`.
+    Lower,
+    /// Field names are uppercase, for example `TITLE`.
+    Upper,
+    /// Field names are left as they are.
+    AsIs,
+}
+
+/// The delimiters around field values when composing `BibTeX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteStyle {
+    /// Values are delimited by braces, for example `title = {Title}`.
+    Braces,
+    /// Values are delimited by double quotes, for example `title = "Title"`.
+    Quotes,
+}
+
+/// How the `month` field is written when composing `BibTeX`.
+///
+/// Only a value that is a [`Month`] is written in this style, any other value is written as it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MonthStyle {
+    /// The three letter macro without delimiters, for example `month = jan`.
+    Macro,
+    /// The number of the month, for example `month = {1}`.
+    Number,
+    /// The full name of the month, for example `month = {January}`.
+    Name,
+}
+
+/// The characters written when composing `BibTeX`.
+///
+/// The LaTeX commands in a parsed value are kept as they are, so that a file is written back the
+/// way it was read, and are only read as the characters they stand for by
+/// [`latex_to_unicode_quoted`] when a file is switched to [`OutputEncoding::Utf8`].
+///
+/// [`latex_to_unicode_quoted`]: crate::normalize::latex_to_unicode_quoted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputEncoding {
+    /// Every character is written as it is in UTF-8.
+    Utf8,
+    /// Only ASCII is written, any other character in a field value is written as a LaTeX command,
+    /// see [`unicode_to_latex`], and a cite key is transliterated to ASCII.
+    Ascii,
+}
+
+/// The vocabulary of field names and entry types used when composing `BibTeX`.
+///
+/// Both vocabularies are always accepted when parsing, where the `BibLaTeX` names are read as
+/// the `BibTeX` names, so a file can be switched from one dialect to the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+pub enum Dialect {
+    /// The classic `BibTeX` vocabulary, for example `journal` and `@phdthesis`.
+    #[serde(rename = "bibtex")]
+    BibTex,
+    /// The `BibLaTeX` vocabulary, for example `journaltitle` and `@thesis`.
+    #[serde(rename = "biblatex")]
+    BibLaTex,
+}
+
+// The `BibTeX` field names and the `BibLaTeX` field names with the same meaning, along with the
+// affiliation field of each entry kind, such as the `school` of a thesis that is its
+// `institution` in `BibLaTeX`.
+const BIBLATEX_FIELDS: [(&str, &str); 2] = [("journal", "journaltitle"), ("address", "location")];
+
+// The field that `BibLaTeX` and some exports name `issue` is the `number` of `BibTeX`, which is
+// the issue of an article in both dialects.
+const ISSUE_FIELD: (&str, &str) = ("number", "issue");
+
+// The `type` of a `BibLaTeX` `@thesis` for each kind of thesis.
+const BIBLATEX_THESIS_TYPES: [(&str, &str); 2] =
+    [("master thesis", "mathesis"), ("phd thesis", "phdthesis")];
+
+// Returns the `BibTeX` and `BibLaTeX` field names that are different for the `kind` of entry.
+fn biblatex_fields(
+    kind: &ast::EntryKind<'_>,
+) -> impl Iterator<Item = (&'static str, &'static str)> {
+    let affiliation = ast::affiliation_field(kind).filter(|(bibtex, biblatex)| bibtex != biblatex);
+    BIBLATEX_FIELDS.into_iter().chain(affiliation)
+}
+
+impl BibTex {
+    /// Parses this `BibTeX` into a [`Biblio`], see [`Format::parse`], where an entry without a
+    /// cite key is given one generated with the key `format`.
+    ///
+    /// An entry that has none of the fields used by the key `format` is given a cite key made
+    /// from a hash of its fields, so the same entry is always given the same cite key. A
+    /// generated cite key that is already used is given the suffix `a`, `b`, `c` and so on, and
+    /// every generated cite key is logged.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] if it's not possible to parse this `BibTeX` to [`Biblio`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::{ast::KeyFormat, format::{BibTex, Format}};
+    ///
+    /// let bibtex = BibTex::new("@misc{, title = {Title}, author = {Knuth, Donald}, year = {1968}}".to_owned());
+    /// let biblio = bibtex.parse_with(&KeyFormat::default()).unwrap().unwrap();
+    ///
+    /// assert!(biblio.get("Knuth1968").is_some());
+    /// ```
+    pub fn parse_with(self, format: &KeyFormat) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let blocks = self.blocks();
         let biblio = if self.0.is_empty() {
             Bibliography::new()
         } else {
             Bibliography::parse(&self.0)
-                .filter(|b| b.len() != 0)
+                // a file of only a preamble or comments has no entries
+                .filter(|b| b.len() != 0 || !blocks.is_empty())
                 .ok_or_else(|| {
                     Error::new(ErrorKind::Deserialize, "Unable to parse string as BibTeX")
                 })?
         };
+        let biblio = with_cite_keys(biblio, format);
+
+        // every key is in the bibliography so every entry is resolved
+        let resolvers = biblio
+            .iter()
+            .filter_map(|entry| biblio.get_resolved(&entry.key))
+            .map(ast::Resolver::from)
+            .collect();
+        Ok(Biblio::try_resolve_with_blocks(resolvers, blocks))
+    }
 
-        let entry_keys = biblio.iter().map(|entry| &entry.key);
-
-        let mut entries = Vec::with_capacity(entry_keys.len());
+    /// Composes a [`Biblio`] to `BibTeX` using the style in the `config`.
+    ///
+    /// The `@preamble` blocks of the [`Biblio`] are composed first, in the order they were read.
+    /// Entries are grouped by kind and sorted by cite key so that composing the same [`Biblio`]
+    /// always gives the same `BibTeX`, and each `@comment` block is composed where it was read
+    /// relative to the entries, see [`CommentPlacement`].
+    #[must_use]
+    pub fn compose_with(biblio: &Biblio, config: &BibTexConfig) -> Self {
+        let Blocks {
+            preambles,
+            comments,
+        } = biblio.blocks();
+        let ascii = config.output_encoding == OutputEncoding::Ascii;
+
+        let mut bib = String::new();
+        let mut before: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut end = Vec::new();
+        for preamble in preambles {
+            bib.push_str(&compose_block("preamble", preamble, ascii));
+            bib.push('\n');
+        }
+        for (placement, content) in comments {
+            let block = compose_block("comment", content, ascii);
+            match placement {
+                CommentPlacement::Start => {
+                    bib.push_str(&block);
+                    bib.push('\n');
+                }
+                // the comment is kept with its entry, which may have been removed
+                CommentPlacement::Before(cite) => match biblio.get(cite) {
+                    Some(entry) => before.entry(entry.cite()).or_default().push(block),
+                    None => end.push(block),
+                },
+                CommentPlacement::End => end.push(block),
+            }
+        }
 
-        for entry_key in entry_keys {
-            let entry = biblio.get_resolved(entry_key).unwrap();
-            entries.push(entry);
+        bib.push_str(&Self::compose_grouped(biblio.entries(), config, &before).0);
+        for block in end {
+            if !bib.is_empty() && !bib.ends_with("\n\n") {
+                bib.push('\n');
+            }
+            bib.push_str(&block);
         }
+        Self(bib)
+    }
 
-        let resolvers = entries.into_iter().map(ast::Resolver::from).collect();
-        Ok(Biblio::try_resolve(resolvers))
+    /// Composes the `entries` to `BibTeX` using the style in the `config`, see
+    /// [`BibTex::compose_with`].
+    #[must_use]
+    pub fn compose_entries_with<'a, I>(entries: I, config: &BibTexConfig) -> Self
+    where
+        I: IntoIterator<Item = &'a ast::Entry>,
+    {
+        Self::compose_grouped(entries, config, &HashMap::new())
     }
 
-    fn compose(biblio: &Biblio) -> Self {
+    // Composes the `entries` grouped by kind, with the blocks `before` an entry, by its cite key,
+    // just before it.
+    fn compose_grouped<'a, I>(
+        entries: I,
+        config: &BibTexConfig,
+        before: &HashMap<&str, Vec<String>>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a ast::Entry>,
+    {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.cite());
+
+        #[cfg(feature = "parallel")]
+        let composed = par_compose_entries(&entries, config);
+        #[cfg(not(feature = "parallel"))]
+        let composed = compose_entries(&entries, config);
+
         let mut map = HashMap::new();
 
-        biblio
-            .entries()
-            .map(|entry| (compose_variant(entry), Self::compose_entry(entry)))
-            .for_each(|(kind, entry)| {
-                map.entry(kind)
-                    .and_modify(|s: &mut String| s.push_str(&entry))
-                    .or_insert(format!("% {kind}\n{entry}\n"));
-            });
+        let cites = entries.iter().map(|entry| entry.cite());
+        for ((kind, mut entry), cite) in composed.into_iter().zip(cites) {
+            if let Some(blocks) = before.get(cite) {
+                entry.insert_str(0, &blocks.concat());
+            }
+            map.entry(kind)
+                .and_modify(|s: &mut String| s.push_str(&entry))
+                .or_insert(format!("% {kind}\n{entry}\n"));
+        }
 
         let mut pairs = map.into_iter().collect::<Vec<_>>();
         pairs.sort_by_key(|(k, _)| *k);
@@ -62,30 +349,932 @@ impl Format for BibTex {
         Self(bib)
     }
 
-    fn compose_entry(entry: &ast::Entry) -> String {
-        format!(
-            "@{}{{{},\n{}}}\n",
-            compose_variant(entry),
-            entry.cite(),
-            compose_fields(&entry.fields())
-        )
+    /// Composes an [`Entry`][E] to a `BibTeX` string using the style in the `config`.
+    ///
+    /// [E]: ast::Entry
+    #[must_use]
+    pub fn compose_entry_with(entry: &ast::Entry, config: &BibTexConfig) -> String {
+        let mut fields = entry.fields();
+        // how a generated cite key was made is only kept for the comments of the annotate mode
+        if !config.annotate {
+            fields.retain(|field| field.name != KEY_FIELD);
+        }
+        match config.dialect {
+            Dialect::BibTex if config.urldate_in_note => urldate_to_note(&mut fields),
+            Dialect::BibTex => {}
+            Dialect::BibLaTex => to_biblatex_fields(entry, &mut fields),
+        }
+        for field in &mut fields {
+            config.transforms.apply(entry, field);
+        }
+        if config.sort_fields {
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        group_eprint_fields(&mut fields);
+
+        let ascii = config.output_encoding == OutputEncoding::Ascii;
+        let cite = if ascii && !entry.cite().is_ascii() {
+            let cite = deunicode::deunicode(entry.cite());
+            log::warn!("The cite key '{}' is written as '{cite}'", entry.cite());
+            cite
+        } else {
+            entry.cite().to_owned()
+        };
+        let mut composed = format!(
+            "@{}{{{cite},\n{}}}\n",
+            compose_variant(entry, config.dialect),
+            compose_fields(&fields, config)
+        );
+        if config.annotate {
+            composed.extend(
+                annotations(entry)
+                    .into_iter()
+                    .map(|annotation| format!("% {annotation}\n")),
+            );
+        }
+
+        if !ascii {
+            return composed;
+        }
+        let Some((latex, unknown)) = unicode_to_latex(&composed) else {
+            return composed;
+        };
+        for c in unknown {
+            log::warn!(
+                "The character '{c}' in the entry '{cite}' has no LaTeX command and is written as \
+                its code point"
+            );
+        }
+        latex
     }
 
-    fn raw(self) -> String {
-        self.0
+    /// Returns an iterator that lazily splits this `BibTeX` string into the raw entries.
+    ///
+    /// Only the entry type and cite key are found when splitting, so no field values are parsed
+    /// until an entry is chosen, see [`BibTex::parse_entry`]. The whole string is in memory, see
+    /// [`BibTex::find_entry`] to find an entry without reading a whole file.
+    ///
+    /// `@comment`, `@preamble` and `@string` blocks are skipped by this iterator.
+    #[must_use]
+    pub fn raw_entries(&self) -> RawEntries<'_> {
+        RawEntries {
+            src: &self.0,
+            pos: 0,
+        }
     }
 
-    fn name() -> &'static str {
-        "BibTex"
+    /// Returns the contents of the `@comment` blocks in the order they appear.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, Format};
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@comment{first}\n@misc{cite, title = {Title}}\n@comment{second}".to_owned(),
+    /// );
+    ///
+    /// assert_eq!(vec!["first", "second"], bibtex.comments());
+    /// ```
+    #[must_use]
+    pub fn comments(&self) -> Vec<String> {
+        self.blocks()
+            .comments
+            .into_iter()
+            .map(|(_, content)| content)
+            .collect()
     }
 
-    fn ext() -> &'static str {
-        "bib"
+    // Returns the contents of the `@preamble` and `@comment` blocks in the order they appear,
+    // where each comment is placed before the next entry with a cite key.
+    fn blocks(&self) -> Blocks {
+        let mut blocks = Blocks::default();
+        let mut pending = Vec::new();
+        let mut first = true;
+        let mut raw = self.raw_entries();
+        while let Some(block) = raw.next_block() {
+            match block.kind.to_lowercase().as_str() {
+                "preamble" => blocks.preambles.push(block.body.to_owned()),
+                "comment" => pending.push(block.body.to_owned()),
+                "string" => {}
+                // a keyless entry is given its cite key later so the comments wait for the next
+                _ if block.key.is_empty() && !first => {}
+                _ => {
+                    let placement = if first {
+                        CommentPlacement::Start
+                    } else {
+                        CommentPlacement::Before(block.key.to_owned())
+                    };
+                    first = false;
+                    blocks.comments.extend(
+                        pending
+                            .drain(..)
+                            .map(|content| (placement.clone(), content)),
+                    );
+                }
+            }
+        }
+        let placement = if first {
+            CommentPlacement::Start
+        } else {
+            CommentPlacement::End
+        };
+        blocks.comments.extend(
+            pending
+                .into_iter()
+                .map(|content| (placement.clone(), content)),
+        );
+        blocks
+    }
+
+    /// Returns the cite keys that are used by more than one entry, ignoring case, along with the
+    /// positions of those entries, starting from 1, in the order they appear. Each cite key is
+    /// returned as it is written in its first entry.
+    ///
+    /// Parsing a `BibTeX` string into a [`Biblio`] keeps only one entry for each cite key so this
+    /// works on the raw entries instead, see [`BibTex::raw_entries`].
+    #[must_use]
+    pub fn duplicate_keys(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut keys: Vec<(&str, Vec<usize>)> = Vec::new();
+        // the index in `keys` of each lowercase cite key
+        let mut indices = HashMap::new();
+
+        for (i, entry) in self.raw_entries().enumerate() {
+            match indices.entry(entry.key().to_lowercase()) {
+                hash_map::Entry::Occupied(index) => keys[*index.get()].1.push(i + 1),
+                hash_map::Entry::Vacant(index) => {
+                    index.insert(keys.len());
+                    keys.push((entry.key(), vec![i + 1]));
+                }
+            }
+        }
+
+        keys.retain(|(_, positions)| positions.len() > 1);
+        keys
+    }
+
+    /// Returns the cite keys of the entries that cannot be parsed as `BibTeX`, such as an entry
+    /// with a missing comma between its fields, in the order the entries appear.
+    ///
+    /// [`Format::parse`] fails on the whole bibliography when any entry cannot be parsed, so this
+    /// finds the entries to fix. Each entry is parsed with the `@string` blocks of the bibliography.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::BibTex;
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@string{me = {Me}}\n\
+    ///      @manual{valid, title = {Valid}, author = me}\n\
+    ///      @manual{broken, title = {Broken} author = {Me}}\n"
+    ///         .to_owned(),
+    /// );
+    ///
+    /// assert_eq!(vec!["broken"], bibtex.invalid_entries());
+    /// ```
+    #[must_use]
+    pub fn invalid_entries(&self) -> Vec<&str> {
+        let mut blocks = self.raw_entries();
+        let strings = std::iter::from_fn(|| blocks.next_block())
+            .filter(|block| block.kind().eq_ignore_ascii_case("string"))
+            .map(|block| block.raw())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.raw_entries()
+            .filter(|entry| parse_raw(&format!("{strings}\n{}", entry.raw())).is_err())
+            .map(|entry| entry.key())
+            .collect()
+    }
+
+    /// Returns the cite keys of the entries with a `crossref` field that references a cite key no
+    /// entry has, along with the missing cite key, in the order the entries appear.
+    ///
+    /// `BibTeX` fails on a `crossref` to a missing entry so these should be fixed before the file
+    /// is used.
+    #[must_use]
+    pub fn dangling_crossrefs(&self) -> Vec<(&str, String)> {
+        let keys = self
+            .raw_entries()
+            .map(|entry| entry.key())
+            .collect::<HashSet<_>>();
+
+        self.crossrefs()
+            .into_iter()
+            .filter(|(_, parent)| !keys.contains(parent.as_str()))
+            .collect()
+    }
+
+    /// Returns the cite keys of the entries with a `related` field that lists a cite key no entry
+    /// has, along with the missing cite key, in the order the entries appear.
+    ///
+    /// `BibLaTeX` cannot print the relationship of an entry to a missing entry, such as the
+    /// original of a translation, so these should be fixed like a dangling `crossref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, Format};
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@misc{translation, title = {T}, related = {original}, relatedtype = {translationof}}"
+    ///         .to_owned(),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     vec![("translation", "original".to_owned())],
+    ///     bibtex.dangling_related()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn dangling_related(&self) -> Vec<(&str, String)> {
+        let keys = self
+            .raw_entries()
+            .map(|entry| entry.key())
+            .collect::<HashSet<_>>();
+
+        self.raw_entries()
+            .filter_map(|entry| {
+                let related = parse_raw(entry.raw())
+                    .ok()?
+                    .get(entry.key())?
+                    .get(ast::RELATED_FIELD)
+                    .map(|chunks| QuotedString::from(chunks.to_vec()))?;
+                Some((entry.key(), related))
+            })
+            .flat_map(|(key, related)| {
+                related
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|related| !related.is_empty() && !keys.contains(related))
+                    .map(|related| (key, related.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Returns the cite keys of the `@set` entries with a member that no entry has, along with the
+    /// missing cite key, in the order the sets appear.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, Format};
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@set{set, entryset = {a, missing}}\n@misc{a, title = {A}}".to_owned()
+    /// );
+    ///
+    /// assert_eq!(vec![("set", "missing".to_owned())], bibtex.missing_set_members());
+    ///
+    /// let bibtex = BibTex::new("@set{set, entryset = {A}}\n@misc{a, title = {A}}".to_owned());
+    /// assert!(bibtex.missing_set_members().is_empty());
+    /// ```
+    #[must_use]
+    pub fn missing_set_members(&self) -> Vec<(&str, String)> {
+        // cite keys are matched ignoring case, as they are by BibTeX
+        let keys = self
+            .raw_entries()
+            .map(|entry| entry.key().to_lowercase())
+            .collect::<HashSet<_>>();
+
+        self.raw_entries()
+            .filter(|entry| entry.kind().eq_ignore_ascii_case("set"))
+            .filter_map(|entry| {
+                let members = parse_raw(entry.raw())
+                    .ok()?
+                    .get(entry.key())?
+                    .get(ast::ENTRYSET_FIELD)
+                    .map(|chunks| QuotedString::from(chunks.to_vec()))?;
+                Some((entry.key(), members))
+            })
+            .flat_map(|(key, members)| {
+                members
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|member| !member.is_empty() && !keys.contains(&member.to_lowercase()))
+                    .map(|member| (key, member.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Returns the cycles of entries that reference each other using `crossref` fields, each
+    /// cycle starts from the lowest cite key and lists the cite keys in the order they reference
+    /// each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, Format};
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@inproceedings{b, crossref = {a}}\n@proceedings{a, crossref = {b}}".to_owned()
+    /// );
+    ///
+    /// assert_eq!(vec![vec!["a", "b"]], bibtex.crossref_cycles());
+    /// ```
+    #[must_use]
+    pub fn crossref_cycles(&self) -> Vec<Vec<&str>> {
+        let keys = self
+            .raw_entries()
+            .map(|entry| entry.key())
+            .collect::<HashSet<_>>();
+        // use the cite keys borrowed from self rather than the parsed crossref values
+        let parents = self
+            .crossrefs()
+            .into_iter()
+            .filter_map(|(key, parent)| Some((key, *keys.get(parent.as_str())?)))
+            .collect::<HashMap<_, _>>();
+
+        let mut cycles = Vec::new();
+        for start in parents.keys() {
+            let mut path = vec![*start];
+            while let Some(parent) = parents.get(path[path.len() - 1]) {
+                if let Some(i) = path.iter().position(|key| key == parent) {
+                    let mut cycle = path.split_off(i);
+                    // start each cycle from the lowest cite key so each cycle is only found once
+                    let lowest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(lowest);
+                    if !cycles.contains(&cycle) {
+                        cycles.push(cycle);
+                    }
+                    break;
+                }
+                path.push(parent);
+            }
+        }
+
+        cycles.sort();
+        cycles
+    }
+
+    /// Returns the `BibTeX` of only the entries with the cite `keys`, ignoring case, and the
+    /// entries they reference in a `crossref` field, along with the cite keys that no entry has.
+    ///
+    /// The entries are kept as they are written and in the order they appear, so the parents stay
+    /// after the entries that reference them. The `@string` and `@preamble` blocks are kept as the
+    /// entries may use them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, Format};
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@inproceedings{paper, author = {Me}, title = {Paper}, crossref = {conf}}\n\
+    ///      @misc{other, title = {Other}}\n\
+    ///      @proceedings{conf, title = {Conf}, year = {2020}}"
+    ///         .to_owned(),
+    /// );
+    ///
+    /// let (extracted, missing) = bibtex.extract(&["Paper", "missing"]);
+    ///
+    /// let keys = extracted.raw_entries().map(|entry| entry.key()).collect::<Vec<_>>();
+    /// assert_eq!(vec!["paper", "conf"], keys);
+    /// assert_eq!(vec!["missing"], missing);
+    /// ```
+    #[must_use]
+    pub fn extract<'k>(&self, keys: &[&'k str]) -> (Self, Vec<&'k str>) {
+        // cite keys are matched ignoring case, as BibTeX does
+        let known = self
+            .raw_entries()
+            .map(|entry| entry.key().to_lowercase())
+            .collect::<HashSet<_>>();
+        let parents = self
+            .crossrefs()
+            .into_iter()
+            .map(|(cite, parent)| (cite.to_lowercase(), parent.to_lowercase()))
+            .collect::<HashMap<_, _>>();
+
+        let mut next = Vec::new();
+        let mut missing = Vec::new();
+        for key in keys {
+            if known.contains(&key.to_lowercase()) {
+                next.push(key.to_lowercase());
+            } else {
+                missing.push(*key);
+            }
+        }
+
+        let mut selected = HashSet::new();
+        while let Some(key) = next.pop() {
+            let parent = parents.get(&key).cloned();
+            if selected.insert(key) {
+                next.extend(parent);
+            }
+        }
+
+        let mut src = String::new();
+        let mut blocks = self.raw_entries();
+        while let Some(block) = blocks.next_block() {
+            let keep = match block.kind.to_lowercase().as_str() {
+                "string" | "preamble" => true,
+                "comment" => false,
+                _ => selected.contains(&block.key.to_lowercase()),
+            };
+            if keep {
+                src.push_str(block.raw);
+                src.push('\n');
+            }
+        }
+        (Self(src), missing)
+    }
+
+    /// Returns the cite key and the `crossref` value of each entry with a `crossref` field, in the
+    /// order the entries appear. Entries that cannot be parsed are skipped.
+    ///
+    /// Parsing an entry inherits the fields of the entry it references and leaves out the
+    /// `crossref` field, so the references are only found in the raw entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::BibTex;
+    ///
+    /// let bibtex = BibTex::new(
+    ///     "@inproceedings{paper, author = {Me}, title = {Paper}, crossref = { conf }}\n\
+    ///      @proceedings{conf, title = {Conf}, year = {2020}}"
+    ///         .to_owned(),
+    /// );
+    ///
+    /// assert_eq!(vec![("paper", "conf".to_owned())], bibtex.crossrefs());
+    /// ```
+    #[must_use]
+    pub fn crossrefs(&self) -> Vec<(&str, String)> {
+        self.raw_entries()
+            .filter_map(|entry| {
+                let parent = parse_raw(entry.raw())
+                    .ok()?
+                    .get(entry.key())?
+                    .get("crossref")
+                    .map(|chunks| QuotedString::from(chunks.to_vec()))?;
+                Some((entry.key(), parent.trim().to_owned()))
+            })
+            .collect()
+    }
+
+    /// Parses only the entry with the `cite` key, ignoring case, and the entry it references in a
+    /// `crossref` field, if any, without parsing the rest of this `BibTeX` string.
+    ///
+    /// This is cheaper than [`Format::parse`] when only a single known entry is needed from a
+    /// large bibliography as the search stops at the first entry with a matching cite key.
+    ///
+    /// Returns `Ok(None)` when no entry has the `cite` key.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] if the entry with the `cite` key cannot be parsed as `BibTeX`.
+    pub fn parse_entry(&self, cite: &str) -> Result<Option<Result<ast::Entry, Resolver>>, Error> {
+        let Some(entry) = self
+            .raw_entries()
+            .find(|entry| entry.key().eq_ignore_ascii_case(cite))
+        else {
+            return Ok(None);
+        };
+
+        let mut src = entry.raw().to_owned();
+
+        // the crossref parent needs to be parsed along side the entry so that biblatex can
+        // resolve the inherited fields.
+        let parent = parse_raw(entry.raw())?
+            .get(entry.key())
+            .and_then(|e| {
+                e.get("crossref")
+                    .map(|chunks| QuotedString::from(chunks.to_vec()))
+            })
+            .and_then(|parent| {
+                self.raw_entries()
+                    .find(|e| e.key().eq_ignore_ascii_case(&parent))
+            });
+
+        if let Some(parent) = parent {
+            src.push('\n');
+            src.push_str(parent.raw());
+        }
+
+        Ok(parse_raw(&src)?
+            .get_resolved(entry.key())
+            .map(|entry| Resolver::from(entry).resolve()))
+    }
+
+    /// Finds the entry with the `cite` key, ignoring case, in a `BibTeX` stream in the `encoding` and parses it,
+    /// along with the `@string` blocks and the entry it references in a `crossref` field, without
+    /// reading the rest of the stream.
+    ///
+    /// The stream is read one line at a time and only the `@string` blocks and the block being
+    /// read are kept in memory, so a large bibliography file is never held in memory. The search
+    /// stops once the entry and its `crossref` parent are found. `BibTeX` requires the parent to
+    /// come after the entry, so a parent before the entry is not found.
+    ///
+    /// Returns `Ok(None)` when no entry has the `cite` key.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] if the stream cannot be read or decoded in the `encoding`, or the entry
+    /// with the `cite` key cannot be parsed as `BibTeX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::format::{BibTex, InputEncoding};
+    ///
+    /// let stream = "@string{me = {Me}}\n\
+    ///     @manual{other, title = {Other}}\n\
+    ///     @manual{wanted,\n  title = {Found},\n  organization = me\n}\n";
+    ///
+    /// let entry = BibTex::find_entry(stream.as_bytes(), InputEncoding::Utf8, "wanted")
+    ///     .unwrap()
+    ///     .expect("an entry has the cite key")
+    ///     .expect("the entry has the required fields");
+    /// assert_eq!("Found", &**entry.title());
+    /// assert!(BibTex::find_entry(stream.as_bytes(), InputEncoding::Utf8, "none")
+    ///     .unwrap()
+    ///     .is_none());
+    /// ```
+    pub fn find_entry<R: BufRead>(
+        reader: R,
+        encoding: InputEncoding,
+        cite: &str,
+    ) -> Result<Option<Result<ast::Entry, Resolver>>, Error> {
+        let mut src = String::new();
+        let mut found = None;
+        let mut parent: Option<String> = None;
+
+        for block in RawBlocks::new(reader, encoding) {
+            let block = Self::new(block?);
+            let Some(raw) = block.raw_entries().next_block() else {
+                continue;
+            };
+            let kind = raw.kind().to_lowercase();
+            if kind == "string" {
+                src.push_str(raw.raw());
+                src.push('\n');
+            } else if kind == "comment" || kind == "preamble" {
+                continue;
+            } else if found.is_some() {
+                if parent
+                    .as_deref()
+                    .is_some_and(|parent| parent.eq_ignore_ascii_case(raw.key()))
+                {
+                    src.push_str(raw.raw());
+                    break;
+                }
+            } else if raw.key().eq_ignore_ascii_case(cite) {
+                found = Some(raw.key().to_owned());
+                parent = block.crossrefs().pop().map(|(_, parent)| parent);
+                src.push_str(raw.raw());
+                src.push('\n');
+                if parent.is_none() {
+                    break;
+                }
+            }
+        }
+
+        let Some(key) = found else {
+            return Ok(None);
+        };
+        Ok(parse_raw(&src)?
+            .get_resolved(&key)
+            .map(|entry| Resolver::from(entry).resolve()))
+    }
+}
+
+// Gives every entry with a missing or empty cite key a cite key generated with the key `format`.
+fn with_cite_keys(biblio: Bibliography, format: &KeyFormat) -> Bibliography {
+    if biblio.iter().all(|entry| !entry.key.trim().is_empty()) {
+        return biblio;
+    }
+
+    let mut used = biblio
+        .iter()
+        .map(|entry| entry.key.trim().to_lowercase())
+        .filter(|key| !key.is_empty())
+        .collect::<HashSet<_>>();
+    let mut keyed = Bibliography::new();
+    for mut entry in biblio.into_vec() {
+        if entry.key.trim().is_empty() {
+            let partial = ast::Resolver::from(entry.clone()).resolve_partial();
+            let (key, how) = generated_key(&partial, format, &mut used);
+            entry.key = key;
+            entry
+                .fields
+                .insert(KEY_FIELD.to_owned(), vec![biblatex::Chunk::Normal(how)]);
+        }
+        keyed.insert(entry);
+    }
+    keyed
+}
+
+fn parse_raw(src: &str) -> Result<Bibliography, Error> {
+    Bibliography::parse(src)
+        .filter(|b| b.len() != 0)
+        .ok_or_else(|| Error::new(ErrorKind::Deserialize, "Unable to parse string as BibTeX"))
+}
+
+/// A single unparsed entry of a `BibTeX` string.
+///
+/// See [`BibTex::raw_entries`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RawEntry<'a> {
+    kind: &'a str,
+    key: &'a str,
+    raw: &'a str,
+    // the text between the delimiters of the block
+    body: &'a str,
+}
+
+impl<'a> RawEntry<'a> {
+    /// The entry type as it appears in the `BibTeX` string, for example `article`.
+    #[must_use]
+    pub const fn kind(&self) -> &'a str {
+        self.kind
+    }
+
+    /// The cite key of the entry.
+    #[must_use]
+    pub const fn key(&self) -> &'a str {
+        self.key
+    }
+
+    /// The raw `BibTeX` string of this entry, from the `@` to the closing brace.
+    #[must_use]
+    pub const fn raw(&self) -> &'a str {
+        self.raw
+    }
+}
+
+/// An iterator over the [`RawEntry`]s of a `BibTeX` string.
+///
+/// This struct is created by the [`BibTex::raw_entries`] method.
+#[derive(Debug)]
+pub struct RawEntries<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for RawEntries<'a> {
+    type Item = RawEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let block = self.next_block()?;
+            if !matches!(
+                block.kind.to_lowercase().as_str(),
+                "comment" | "preamble" | "string"
+            ) {
+                return Some(block);
+            }
+        }
+    }
+}
+
+impl<'a> RawEntries<'a> {
+    // Returns the next block of any kind, which includes the blocks that are not entries.
+    fn next_block(&mut self) -> Option<RawEntry<'a>> {
+        loop {
+            let start = self.pos + self.src[self.pos..].find('@')?;
+            let (kind, open) = block_open(self.src, start);
+
+            // not a block so this '@' is part of the text between blocks which BibTeX ignores
+            if !matches!(self.src[open..].chars().next(), Some('{' | '(')) {
+                self.pos = start + 1;
+                continue;
+            }
+
+            // an unbalanced block is the remainder of the string
+            let (end, body_end) = block_end(self.src, open)
+                .map_or((self.src.len(), self.src.len()), |end| (end, end - 1));
+            self.pos = end;
+
+            let body = &self.src[open + 1..body_end];
+            let key = body
+                .split([',', '}', ')'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+
+            return Some(RawEntry {
+                kind,
+                key,
+                raw: &self.src[start..end],
+                body,
+            });
+        }
+    }
+}
+
+/// Returns the kind of the block that starts with the `@` at `start` and the index after the
+/// kind and any whitespace, where the opening delimiter of the block should be.
+fn block_open(src: &str, start: usize) -> (&str, usize) {
+    let rest = &src[start + 1..];
+    let kind_len = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    let after_kind = &rest[kind_len..];
+    let open = start + 1 + kind_len + (after_kind.len() - after_kind.trim_start().len());
+    (&rest[..kind_len], open)
+}
+
+/// An iterator over the raw blocks of a `BibTeX` stream, which reads the stream one line at a
+/// time and keeps only the block being read.
+///
+/// The blocks are split as in [`BibTex::raw_entries`], except that the `@comment`, `@preamble`
+/// and `@string` blocks are included.
+struct RawBlocks<R> {
+    reader: R,
+    encoding: InputEncoding,
+    // the text read that is not part of a block returned yet
+    buf: String,
+    done: bool,
+}
+
+impl<R: BufRead> RawBlocks<R> {
+    const fn new(reader: R, encoding: InputEncoding) -> Self {
+        Self {
+            reader,
+            encoding,
+            buf: String::new(),
+            done: false,
+        }
+    }
+
+    // Takes the first whole block from the text read, or `None` when more of the stream needs to
+    // be read. At the end of the stream an unbalanced block is the rest of the text.
+    fn take_block(&mut self) -> Option<String> {
+        loop {
+            let Some(start) = self.buf.find('@') else {
+                self.buf.clear();
+                return None;
+            };
+            let (_, open) = block_open(&self.buf, start);
+            match self.buf[open..].chars().next() {
+                Some('{' | '(') => {}
+                // the opening delimiter can be on the next line
+                None if !self.done => {
+                    self.buf.drain(..start);
+                    return None;
+                }
+                // this '@' is part of the text between blocks which BibTeX ignores
+                _ => {
+                    self.buf.drain(..=start);
+                    continue;
+                }
+            }
+
+            let end = match block_end(&self.buf, open) {
+                Some(end) => end,
+                None if self.done => self.buf.len(),
+                None => {
+                    self.buf.drain(..start);
+                    return None;
+                }
+            };
+            let block = self.buf[start..end].to_owned();
+            self.buf.drain(..end);
+            return Some(block);
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for RawBlocks<R> {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = self.take_block() {
+                return Some(Ok(block));
+            }
+            if self.done {
+                return None;
+            }
+
+            let mut line = Vec::new();
+            let read = self
+                .reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Cannot read the BibTeX stream"))
+                .and_then(|read| self.encoding.decode(line).map(|line| (read, line)));
+            match read {
+                Ok((0, _)) => self.done = true,
+                Ok((_, line)) => self.buf.push_str(&line),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the index after the closing delimiter of the block opened at `open`.
+fn block_end(src: &str, open: usize) -> Option<usize> {
+    let parens = src[open..].starts_with('(');
+    let mut depth = 0_usize;
+
+    for (i, c) in src[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.checked_sub(1)?,
+            '(' if parens => depth += 1,
+            ')' if parens => depth = depth.checked_sub(1)?,
+            _ => continue,
+        }
+
+        if depth == 0 {
+            return Some(open + i + 1);
+        }
     }
+
+    None
+}
+
+// Explains the content of the `entry` that seb generated, from its provenance fields.
+fn annotations(entry: &ast::Entry) -> Vec<String> {
+    let key = entry.get_field(KEY_FIELD).map(|how| match &**how {
+        "hash" => "key generated by seb from a hash of the fields".to_owned(),
+        how => format!("key generated by seb from {how}"),
+    });
+    let source = entry.get_field(SOURCE_FIELD).map(|source| {
+        let source = match entry.get_field("doi") {
+            Some(doi) if &**source != "manual" => {
+                format!(
+                    "fields filled by seb from {} using the DOI {}",
+                    &**source, &**doi
+                )
+            }
+            _ => format!("added by seb from {}", &**source),
+        };
+        match entry.get_field(ADDED_FIELD) {
+            Some(added) => format!("{source} on {}", &**added),
+            None => source,
+        }
+    });
+
+    key.into_iter().chain(source).collect()
+}
+
+// Composes each entry paired with the variant name, in the same order as the entries given.
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn compose_entries<'a>(
+    entries: &[&'a ast::Entry],
+    config: &BibTexConfig,
+) -> Vec<(&'a str, String)> {
+    entries
+        .iter()
+        .map(|entry| {
+            (
+                compose_variant(entry, config.dialect),
+                BibTex::compose_entry_with(entry, config),
+            )
+        })
+        .collect()
 }
 
-const fn compose_variant(entry: &ast::Entry) -> &str {
+// Parallel version of `compose_entries` - each entry is composed independently and rayon
+// preserves the order of the entries when collecting, so the output is identical.
+#[cfg(feature = "parallel")]
+fn par_compose_entries<'a>(
+    entries: &[&'a ast::Entry],
+    config: &BibTexConfig,
+) -> Vec<(&'a str, String)> {
+    use rayon::prelude::*;
+
+    entries
+        .par_iter()
+        .map(|entry| {
+            (
+                compose_variant(entry, config.dialect),
+                BibTex::compose_entry_with(entry, config),
+            )
+        })
+        .collect()
+}
+
+// Composes a block that is not an entry, such as `@comment{content}`, with the characters
+// written as LaTeX commands when `ascii` is set.
+fn compose_block(kind: &str, content: &str, ascii: bool) -> String {
+    let block = format!("@{kind}{{{content}}}\n");
+    match unicode_to_latex(&block) {
+        Some((latex, _)) if ascii => latex,
+        _ => block,
+    }
+}
+
+fn compose_variant(entry: &ast::Entry, dialect: Dialect) -> &'static str {
+    if dialect == Dialect::BibLaTex {
+        match entry {
+            ast::Entry::MasterThesis(_) | ast::Entry::PhdThesis(_) => return "thesis",
+            ast::Entry::Other(other) if other.kind() == "online" => return "online",
+            ast::Entry::Other(other) if other.kind() == crate::STANDARD_KIND => return "standard",
+            _ => {}
+        }
+    }
+
     match entry {
+        // a set is only understood by `BibLaTeX` but is kept as a set in either dialect
+        ast::Entry::Other(other) if other.kind() == "set" => "set",
         ast::Entry::Article(_) => "article",
         ast::Entry::Book(_) => "book",
         ast::Entry::Booklet(_) => "booklet",
@@ -102,46 +1291,210 @@ const fn compose_variant(entry: &ast::Entry) -> &str {
     }
 }
 
+// Renames the fields to the `BibLaTeX` names and adds the `type` of a thesis.
+fn to_biblatex_fields(entry: &ast::Entry, fields: &mut Vec<ast::Field<'_>>) {
+    let kind = entry.kind();
+    for field in fields.iter_mut() {
+        if let Some((_, biblatex)) =
+            biblatex_fields(&kind).find(|(bibtex, _)| field.name == *bibtex)
+        {
+            field.name = biblatex.into();
+        }
+    }
+
+    let thesis_type = BIBLATEX_THESIS_TYPES
+        .iter()
+        .find(|(name, _)| ast::EntryKind::from(*name) == kind);
+    if let Some((_, thesis_type)) = thesis_type {
+        if entry.get_field("type").is_none() {
+            fields.push(ast::Field {
+                name: "type".into(),
+                value: std::borrow::Cow::Owned(QuotedString::new((*thesis_type).to_owned())),
+            });
+        }
+    }
+}
+
+// The text before the date a URL was accessed in the `note` field of classic `BibTeX`.
+const ACCESSED_NOTE: &str = "Accessed: ";
+
+// Moves the date a URL was accessed to the end of the `note` field, for the classic `BibTeX`
+// styles that have no `urldate` field.
+fn urldate_to_note(fields: &mut Vec<ast::Field<'_>>) {
+    let Some(i) = fields.iter().position(|field| field.name == URLDATE_FIELD) else {
+        return;
+    };
+    let urldate = fields.remove(i);
+    let accessed = format!("{ACCESSED_NOTE}{}", &**urldate.value);
+
+    match fields.iter_mut().find(|field| field.name == "note") {
+        Some(note) => {
+            let value = format!("{}; {accessed}", &**note.value);
+            note.value = std::borrow::Cow::Owned(QuotedString::new(value));
+        }
+        None => fields.push(ast::Field {
+            name: "note".into(),
+            value: std::borrow::Cow::Owned(QuotedString::new(accessed)),
+        }),
+    }
+}
+
+// Reads the date a URL was accessed from the `accessed` field, which some tools write, or from
+// the end of the `note` field, as written by `urldate_to_note`, unless there is a `urldate`.
+//
+// Only a note that ends with a full ISO date, such as `Accessed: 2024-01-01`, has the date
+// moved, so a note that only mentions access, such as `Accessed: 2020 survey data`, is kept.
+fn note_to_urldate(fields: &mut HashMap<String, biblatex::Chunks>) {
+    if fields.contains_key(URLDATE_FIELD) {
+        return;
+    }
+    if let Some(accessed) = fields.remove("accessed") {
+        fields.insert(URLDATE_FIELD.to_owned(), accessed);
+        return;
+    }
+
+    let Some(note) = fields
+        .get("note")
+        .map(|chunks| QuotedString::from(chunks.clone()))
+    else {
+        return;
+    };
+    let Some((rest, date)) = note.rsplit_once(ACCESSED_NOTE.trim_end()) else {
+        return;
+    };
+    let date = date.trim();
+    let rest = rest.trim_end().trim_end_matches([';', ',', '.']).trim_end();
+    if !is_iso_date(date) {
+        return;
+    }
+
+    fields.insert(
+        URLDATE_FIELD.to_owned(),
+        vec![biblatex::Chunk::Normal(date.to_owned())],
+    );
+    if rest.is_empty() {
+        fields.remove("note");
+    } else {
+        fields.insert(
+            "note".to_owned(),
+            vec![biblatex::Chunk::Normal(rest.to_owned())],
+        );
+    }
+}
+
+// A `YYYY-MM-DD` date with a month and day in range.
+fn is_iso_date(date: &str) -> bool {
+    let mut parts = date.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let number = |part: &str, len: usize| {
+        Some(part)
+            .filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|part| part.parse::<u16>().ok())
+    };
+    number(year, 4).is_some()
+        && number(month, 2).is_some_and(|month| (1..=12).contains(&month))
+        && number(day, 2).is_some_and(|day| (1..=31).contains(&day))
+}
+
 fn bibtex_esc(s: &str) -> String {
     format!("{{{s}}}")
 }
 
-fn compose_fields(fields: &[ast::Field<'_>]) -> String {
-    fields
+/// The eprint fields in the order they are composed.
+const EPRINT_FIELDS: [&str; 3] = ["eprint", "eprinttype", "eprintclass"];
+
+// Moves the eprint fields next to each other, where the first eprint field was, so that they are
+// read as a group.
+fn group_eprint_fields(fields: &mut Vec<ast::Field<'_>>) {
+    let position = |field: &ast::Field<'_>| EPRINT_FIELDS.iter().position(|f| *f == field.name);
+    let Some(first) = fields.iter().position(|field| position(field).is_some()) else {
+        return;
+    };
+
+    let (mut eprint, rest): (Vec<_>, Vec<_>) = fields
+        .drain(..)
+        .partition(|field| position(field).is_some());
+    eprint.sort_by_key(position);
+
+    let mut rest = rest.into_iter();
+    fields.extend(rest.by_ref().take(first));
+    fields.extend(eprint);
+    fields.extend(rest);
+}
+
+fn compose_fields(fields: &[ast::Field<'_>], config: &BibTexConfig) -> String {
+    let indent = " ".repeat(config.indent);
+    let fields = fields
         .iter()
-        .map(|field| {
-            let field = compose_field(field);
-            format!("    {field},\n")
+        // compare the composed name, such as `booktitle` for the `book_title` field
+        .filter(|field| config.fields.includes(&field.name.replace('_', "")))
+        .collect::<Vec<_>>();
+    let last = fields.len().saturating_sub(1);
+
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let field = compose_field(field, config);
+            let comma = if i < last || config.trailing_comma {
+                ","
+            } else {
+                ""
+            };
+            format!("{indent}{field}{comma}\n")
         })
         .collect()
 }
 
-fn compose_field(field: &ast::Field<'_>) -> String {
-    match field.name.replace('_', "").as_str() {
-        "month" => to_short_month(&field.value),
-        name => format!("{name} = {{{}}}", field.value.map_quoted(bibtex_esc)),
-    }
+fn compose_field(field: &ast::Field<'_>, config: &BibTexConfig) -> String {
+    let name = field.name.replace('_', "");
+    let month = (name == "month")
+        .then(|| field.value.parse::<Month>().ok())
+        .flatten();
+    let quote = |value: &str| match config.quote_style {
+        QuoteStyle::Braces => format!("{{{value}}}"),
+        QuoteStyle::Quotes => format!("\"{}\"", escape_double_quotes(value)),
+    };
+    let value = match (month, config.month_style) {
+        (Some(month), MonthStyle::Macro) => month.abbreviation().to_owned(),
+        (Some(month), MonthStyle::Number) => quote(&month.number().to_string()),
+        (Some(month), MonthStyle::Name) => quote(month.name()),
+        (None, _) => quote(&field.value.map_quoted(bibtex_esc)),
+    };
+
+    let name = match config.field_case {
+        FieldCase::Lower => name.to_lowercase(),
+        FieldCase::Upper => name.to_uppercase(),
+        FieldCase::AsIs => name,
+    };
+
+    format!("{name} = {value}")
 }
 
-fn to_short_month(month: &QuotedString) -> String {
-    let value = match month.parse() {
-        Ok(1) => "jan",
-        Ok(2) => "feb",
-        Ok(3) => "mar",
-        Ok(4) => "apr",
-        Ok(5) => "may",
-        Ok(6) => "jun",
-        Ok(7) => "jul",
-        Ok(8) => "aug",
-        Ok(9) => "sep",
-        Ok(10) => "oct",
-        Ok(11) => "nov",
-        Ok(12) => "dec",
-        _ => month.get(0..3).expect("invalid month value"),
+// A double quote would end a quote delimited value so it needs to be wrapped in braces, unless it
+// is already inside braces.
+fn escape_double_quotes(value: &str) -> String {
+    let mut depth = 0_usize;
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '"' if depth == 0 => {
+                escaped.push_str("{\"}");
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(c);
     }
-    .to_lowercase();
 
-    format!("month = {value}")
+    escaped
 }
 
 impl From<&biblatex::Entry> for ast::EntryKind<'static> {
@@ -149,6 +1502,26 @@ impl From<&biblatex::Entry> for ast::EntryKind<'static> {
         use ast::EntryKind;
         use biblatex::EntryType;
 
+        match entry.entry_type {
+            // the `type` of a `BibLaTeX` thesis, such as `mathesis`, is the kind of thesis
+            EntryType::Thesis => {
+                let master = entry.get("type").is_some_and(|chunks| {
+                    QuotedString::from(chunks.to_vec())
+                        .to_lowercase()
+                        .starts_with("ma")
+                });
+                return if master {
+                    EntryKind::MasterThesis
+                } else {
+                    EntryKind::PhdThesis
+                };
+            }
+            EntryType::Online => return EntryKind::Other("online".into()),
+            // `BibTeX` has no sets so the kind is kept rather than read as a `misc`
+            EntryType::Set => return EntryKind::Other("set".into()),
+            _ => {}
+        }
+
         match entry.entry_type.to_bibtex() {
             EntryType::Article => EntryKind::Article,
             EntryType::Book => EntryKind::Book,
@@ -203,6 +1576,32 @@ impl From<biblatex::Entry> for ast::Resolver {
             ..
         } = entry;
 
+        // the `BibLaTeX` names are read as the `BibTeX` names, which are kept when a field has both
+        for (bibtex, biblatex) in biblatex_fields(&kind) {
+            if let Some(value) = fields.remove(biblatex) {
+                fields.entry(bibtex.to_owned()).or_insert(value);
+            }
+        }
+        // an entry with both, such as a numbered special issue, keeps the name of its issue
+        let (number, issue) = ISSUE_FIELD;
+        if !fields.contains_key(number) {
+            if let Some(value) = fields.remove(issue) {
+                fields.insert(number.to_owned(), value);
+            }
+        }
+        note_to_urldate(&mut fields);
+        if let Some(thesis_type) = BIBLATEX_THESIS_TYPES
+            .iter()
+            .find(|(name, _)| ast::EntryKind::from(*name) == kind)
+        {
+            let is_default_type = fields.get("type").is_some_and(|chunks| {
+                QuotedString::from(chunks.clone()).eq_ignore_ascii_case(thesis_type.1)
+            });
+            if is_default_type {
+                fields.remove("type");
+            }
+        }
+
         let mut resolver = ast::Entry::resolver_with_cite(kind.clone(), cite);
 
         for (name, value) in fields.drain() {
@@ -303,7 +1702,10 @@ mod tests {
 
     use std::{borrow::Cow, collections::HashMap};
 
-    use crate::ast::{FieldQuery, Other};
+    use crate::{
+        ast::{FieldQuery, Other},
+        normalize::latex_to_unicode_quoted,
+    };
 
     use super::*;
 
@@ -331,7 +1733,146 @@ mod tests {
             .expect("Empty string is a valid BibTeX")
             .expect("Empty string is trivially resolved");
 
-        assert_eq!(Vec::<crate::ast::Entry>::new(), biblio.into_entries());
+        assert_eq!(Vec::<crate::ast::Entry>::new(), biblio.into_entries());
+    }
+
+    #[test]
+    fn duplicate_keys_found_in_raw_entries() {
+        let bibtex = BibTex::new(include_str!("../../tests/data/duplicate_keys.bib").to_owned());
+
+        assert_eq!(vec![("dup", vec![1, 3])], bibtex.duplicate_keys());
+    }
+
+    #[test]
+    fn duplicate_keys_ignore_case() {
+        let bibtex = BibTex::new(
+            "@misc{Knuth, title = {A}}\n@misc{other, title = {B}}\n@misc{knuth, title = {C}}"
+                .to_owned(),
+        );
+
+        assert_eq!(vec![("Knuth", vec![1, 3])], bibtex.duplicate_keys());
+    }
+
+    #[test]
+    fn extract_follows_crossref_chains_and_keeps_strings() {
+        let bibtex = BibTex::new(
+            "@string{conf = {Conference}}\n\
+             @comment{not kept}\n\
+             @inproceedings{a, author = {Me}, title = {A}, crossref = {proc}}\n\
+             @misc{b, title = {B}, howpublished = conf}\n\
+             @misc{unused, title = {Unused}}\n\
+             @proceedings{proc, title = {Proc}, crossref = {series}}\n\
+             @proceedings{series, title = {Series}}"
+                .to_owned(),
+        );
+
+        let (extracted, missing) = bibtex.extract(&["b", "a", "a"]);
+
+        assert!(missing.is_empty());
+        assert_eq!(
+            vec!["a", "b", "proc", "series"],
+            extracted
+                .raw_entries()
+                .map(|entry| entry.key())
+                .collect::<Vec<_>>()
+        );
+        assert!(extracted
+            .raw()
+            .starts_with("@string{conf = {Conference}}\n@inproceedings"));
+    }
+
+    #[test]
+    fn invalid_entries_are_found_without_the_other_entries() {
+        let src = "@manual{first, title = {First}}\n\
+                   @manual{comma, title = {Comma} author = {Me}}\n\
+                   @manual{last, title = {Last}}";
+
+        assert!(BibTex::new(src.to_owned()).parse().is_err());
+        assert_eq!(vec!["comma"], BibTex::new(src.to_owned()).invalid_entries());
+        assert!(BibTex::new("@manual{a, title = {A}}".to_owned())
+            .invalid_entries()
+            .is_empty());
+    }
+
+    #[test]
+    fn dangling_crossref_to_missing_proceedings() {
+        let bibtex = BibTex::new(
+            "@inproceedings{paper, author = {Me}, title = {Paper}, year = {2020}, \
+             crossref = {missing}}\n\
+             @proceedings{conf, title = {Conference}, year = {2020}}\n\
+             @inproceedings{other, author = {Me}, title = {Other}, crossref = {conf}}"
+                .to_owned(),
+        );
+
+        assert_eq!(
+            vec![("paper", "missing".to_owned())],
+            bibtex.dangling_crossrefs()
+        );
+        assert!(bibtex.crossref_cycles().is_empty());
+    }
+
+    #[test]
+    fn crossref_cycles_are_found_once() {
+        let bibtex = BibTex::new(
+            "@misc{c, title = {C}, crossref = {b}}\n\
+             @misc{b, title = {B}, crossref = {a}}\n\
+             @misc{a, title = {A}, crossref = {c}}\n\
+             @misc{self, title = {Self}, crossref = {self}}\n\
+             @misc{leaf, title = {Leaf}, crossref = {a}}"
+                .to_owned(),
+        );
+
+        assert_eq!(
+            vec![vec!["a", "c", "b"], vec!["self"]],
+            bibtex.crossref_cycles()
+        );
+    }
+
+    #[test]
+    fn related_fields_round_trip() {
+        use crate::ast::FieldQuery;
+
+        let bibtex = BibTex::new(
+            "@book{translation, author = {Gödel, Kurt}, title = {On Formally Undecidable \
+             Propositions}, publisher = {Dover}, year = {1992}, related = {original, reprint}, \
+             relatedtype = {translationof}}\n\
+             @misc{original, title = {Über formal unentscheidbare Sätze}}\n\
+             @misc{reprint, title = {Reprint}}"
+                .to_owned(),
+        );
+        assert!(bibtex.dangling_related().is_empty());
+
+        let biblio = bibtex.parse().unwrap().unwrap();
+        for dialect in [Dialect::BibTex, Dialect::BibLaTex] {
+            let config = BibTexConfig {
+                dialect,
+                ..BibTexConfig::default()
+            };
+            let biblio = BibTex::compose_with(&biblio, &config)
+                .parse()
+                .unwrap()
+                .unwrap();
+
+            let entry = biblio.get("translation").unwrap();
+            assert_eq!(Some(vec!["original", "reprint"]), entry.related());
+            assert_eq!("translationof", &**entry.get_field("relatedtype").unwrap());
+        }
+    }
+
+    #[test]
+    fn provenance_fields_round_trip() {
+        use crate::ast::{FieldQuery, ADDED_FIELD, SOURCE_FIELD};
+
+        let bibtex = BibTex::new(
+            "@manual{cite, title = {Title}, x-seb-source = {crossref}, x-seb-added = {2024-01-02}}"
+                .to_owned(),
+        );
+        let biblio = bibtex.parse().unwrap().unwrap();
+        let biblio = BibTex::compose(&biblio).parse().unwrap().unwrap();
+
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!("crossref", &**entry.get_field(SOURCE_FIELD).unwrap());
+        assert_eq!("2024-01-02", &**entry.get_field(ADDED_FIELD).unwrap());
     }
 
     #[test]
@@ -406,7 +1947,7 @@ mod tests {
     fn compose_month_name_field_in_short_name_without_braces() {
         let long_month_names = [
             ("jan", "January"),
-            ("feb", "Febuary"),
+            ("feb", "February"),
             ("mar", "march"), // shouldn't matter what the case is
             ("oct", "oCtober"),
             ("dec", "December"),
@@ -428,6 +1969,36 @@ mod tests {
         check_each_field_with_expected(month_nums);
     }
 
+    #[test]
+    fn month_macro_number_and_name_compose_to_the_macro() {
+        let raw = "@manual{a, title = {A}, month = jan}\n\
+                   @manual{b, title = {B}, month = {1}}\n\
+                   @manual{c, title = {C}, month = {January}}";
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+
+        for entry in biblio.entries() {
+            assert_eq!(Some(Month::January), entry.month());
+            assert!(BibTex::compose_entry_with(entry, &BibTexConfig::default())
+                .contains("month = jan,"));
+        }
+    }
+
+    #[test]
+    fn compose_month_in_configured_style() {
+        let compose = |month_style, value: &'static str| {
+            let config = BibTexConfig {
+                month_style,
+                ..BibTexConfig::default()
+            };
+            compose_field(&field! { "month": value }, &config)
+        };
+
+        assert_eq!("month = {3}", compose(MonthStyle::Number, "mar"));
+        assert_eq!("month = {March}", compose(MonthStyle::Name, "3"));
+        assert_eq!("month = {Spring}", compose(MonthStyle::Macro, "Spring"));
+        assert_eq!("month = {Junk}", compose(MonthStyle::Macro, "Junk"));
+    }
+
     #[test]
     fn normalize_date_fields_to_year_month_day_fields() {
         let raw = "@misc{cite, title={test}, date={2020-04-03},}";
@@ -468,7 +2039,7 @@ mod tests {
     fn check_each_field_with_expected<const N: usize>(slice: [(&'static str, &'static str); N]) {
         for (expected_month, month_value) in slice {
             let field = field! { "month": month_value };
-            let actual = compose_field(&field);
+            let actual = compose_field(&field, &BibTexConfig::default());
 
             assert_eq!(format!("month = {expected_month}"), actual);
         }
@@ -501,17 +2072,392 @@ mod tests {
     #[test]
     fn compose_fields_to_bibtex() {
         let fields = fields();
-        let result = compose_fields(&fields);
+        let result = compose_fields(&fields, &BibTexConfig::default());
 
         assert_eq!("    author = {Me},\n", result);
     }
 
+    #[test]
+    fn compose_fields_with_indent_width() {
+        let config = BibTexConfig {
+            indent: 2,
+            ..BibTexConfig::default()
+        };
+        let result = compose_fields(&fields(), &config);
+
+        assert_eq!("  author = {Me},\n", result);
+    }
+
+    #[test]
+    fn compose_fields_with_quote_style() {
+        let config = BibTexConfig {
+            quote_style: QuoteStyle::Quotes,
+            ..BibTexConfig::default()
+        };
+        let result = compose_fields(&fields(), &config);
+
+        assert_eq!("    author = \"Me\",\n", result);
+    }
+
+    #[test]
+    fn compose_in_quote_style_escapes_double_quotes() {
+        let fields = [ast::Field {
+            name: Cow::Borrowed("title"),
+            value: Cow::Owned(QuotedString::from_parts(vec![
+                (false, "The \"Best\" ".to_owned()),
+                (true, "LaTeX".to_owned()),
+                (false, " guide".to_owned()),
+            ])),
+        }];
+        let config = BibTexConfig {
+            quote_style: QuoteStyle::Quotes,
+            ..BibTexConfig::default()
+        };
+
+        assert_eq!(
+            "    title = \"The {\"}Best{\"} {LaTeX} guide\",\n",
+            compose_fields(&fields, &config)
+        );
+    }
+
+    #[test]
+    fn compose_in_quote_style_then_parse_is_equivalent() {
+        let raw = r#"@book{cite,
+            title = {The "Art" of {C}omputer {Programming}},
+            author = {Donald Knuth},
+            publisher = {Addison-Wesley},
+            year = {1968},
+        }"#;
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+        let config = BibTexConfig {
+            quote_style: QuoteStyle::Quotes,
+            ..BibTexConfig::default()
+        };
+
+        let composed = BibTex::compose_with(&biblio, &config);
+        assert!(composed
+            .0
+            .contains(r#"title = "The {"}Art{"} of {C}omputer {Programming}""#));
+
+        // the escaped double quotes are parsed back as brace protected text so compare the text of
+        // the fields
+        let field_values = |biblio: &Biblio| {
+            let mut fields = biblio.get("cite").unwrap().fields();
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+            fields
+                .into_iter()
+                .map(|field| (field.name.to_string(), field.value().to_owned()))
+                .collect::<Vec<_>>()
+        };
+        let parsed = composed.parse().unwrap().unwrap();
+        assert_eq!(field_values(&biblio), field_values(&parsed));
+    }
+
+    #[test]
+    fn compose_fields_with_field_case_and_without_trailing_comma() {
+        let fields = [field! { "title": "Title" }, field! { "year": "2020" }];
+        let config = BibTexConfig {
+            field_case: FieldCase::Upper,
+            trailing_comma: false,
+            ..BibTexConfig::default()
+        };
+        let result = compose_fields(&fields, &config);
+
+        assert_eq!("    TITLE = {Title},\n    YEAR = {2020}\n", result);
+    }
+
+    #[test]
+    fn compose_entry_with_sorted_fields() {
+        let entry = ast::Entry::Manual(ast::Manual {
+            cite: "cite".to_owned(),
+            title: "Title".into(),
+            optional: HashMap::from([
+                ("year".to_owned(), "2020".into()),
+                ("author".to_owned(), "Me".into()),
+            ]),
+        });
+        let config = BibTexConfig {
+            sort_fields: true,
+            ..BibTexConfig::default()
+        };
+
+        assert_eq!(
+            "@manual{cite,\n    author = {Me},\n    title = {Title},\n    year = {2020},\n}\n",
+            BibTex::compose_entry_with(&entry, &config)
+        );
+    }
+
+    #[test]
+    fn compose_annotations_only_in_annotate_mode() {
+        let biblio = BibTex::new(
+            "@misc{, title = {Title}, author = {Knuth, Donald}, year = {1968}}\n\
+             @misc{found, title = {Found}, doi = {10.1000/182}, x-seb-source = {crossref}, \
+             x-seb-added = {2024-01-01}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        let annotate = BibTexConfig {
+            annotate: true,
+            ..BibTexConfig::default()
+        };
+
+        let plain =
+            BibTex::compose_entry_with(biblio.get("Knuth1968").unwrap(), &BibTexConfig::default());
+        assert!(!plain.contains('%'));
+        assert!(!plain.contains(KEY_FIELD));
+        let annotated = BibTex::compose_entry_with(biblio.get("Knuth1968").unwrap(), &annotate);
+        assert!(annotated.contains("    x-seb-key = {author+year},\n"));
+        assert!(annotated.ends_with("}\n% key generated by seb from author+year\n"));
+        assert!(
+            BibTex::compose_entry_with(biblio.get("found").unwrap(), &annotate).ends_with(
+                "}\n% fields filled by seb from crossref using the DOI 10.1000/182 on 2024-01-01\n"
+            )
+        );
+
+        // the comments are not read back so composing again does not repeat them
+        let annotated = BibTex::compose_with(&biblio, &annotate).raw();
+        let reparsed = BibTex::new(annotated.clone()).parse().unwrap().unwrap();
+        assert_eq!(annotated, BibTex::compose_with(&reparsed, &annotate).raw());
+    }
+
+    #[test]
+    fn compose_entry_with_transforms() {
+        use crate::format::{FieldTransform, PageDash, TitleProtection};
+
+        // Writes the keywords in uppercase.
+        struct UpperKeywords;
+
+        impl FieldTransform for UpperKeywords {
+            fn name(&self) -> &'static str {
+                "upper-keywords"
+            }
+
+            fn transform(&self, _: &ast::Entry, field: &mut ast::Field<'_>) {
+                if field.name == "keywords" {
+                    field.value = std::borrow::Cow::Owned(field.value.map_parts(str::to_uppercase));
+                }
+            }
+        }
+
+        let entry = ast::Entry::Manual(ast::Manual {
+            cite: "cite".to_owned(),
+            title: "The DNA of TeX".into(),
+            optional: HashMap::from([
+                ("keywords".to_owned(), "dna, tex".into()),
+                ("pages".to_owned(), "5-9".into()),
+            ]),
+        });
+        let config = BibTexConfig {
+            sort_fields: true,
+            transforms: Transforms::new()
+                .with(UpperKeywords)
+                .with(PageDash)
+                .with(TitleProtection),
+            ..BibTexConfig::default()
+        };
+
+        assert_eq!(
+            "@manual{cite,\n    keywords = {DNA, TEX},\n    pages = {5--9},\n    \
+             title = {The {DNA} of {TeX}},\n}\n",
+            BibTex::compose_entry_with(&entry, &config)
+        );
+    }
+
+    #[test]
+    fn compose_truncates_long_abstracts_without_changing_the_entry() {
+        use crate::format::Truncate;
+
+        let long = "word ".repeat(200);
+        let raw = format!(
+            "@manual{{cite,\n    abstract = {{{}}},\n    title = {{Title}},\n}}\n",
+            long.trim()
+        );
+        let biblio = BibTex::new(raw).parse().unwrap().unwrap();
+        let config = BibTexConfig {
+            sort_fields: true,
+            transforms: Transforms::new().with(Truncate::new().field("abstract", 12)),
+            ..BibTexConfig::default()
+        };
+
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!(
+            "@manual{cite,\n    abstract = {word word wo...},\n    title = {Title},\n}\n",
+            BibTex::compose_entry_with(entry, &config)
+        );
+        assert_eq!(long.trim(), &**entry.get_field("abstract").unwrap());
+    }
+
+    #[test]
+    fn eprint_fields_round_trip() {
+        let raw = "@misc{cite,\n    eprint = {2106.01345},\n    eprinttype = {arxiv},\n    \
+                   title = {Title},\n}\n";
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+        let config = BibTexConfig {
+            sort_fields: true,
+            ..BibTexConfig::default()
+        };
+        let entry = biblio.get("cite").unwrap();
+
+        assert_eq!(raw, BibTex::compose_entry_with(entry, &config));
+        assert_eq!("2106.01345", &**entry.get_field("eprint").unwrap());
+        assert_eq!("arxiv", &**entry.get_field("eprinttype").unwrap());
+    }
+
+    #[test]
+    fn standard_number_round_trips() {
+        let raw = "@standard{IEEE754-2019,\n    number = {754-2019},\n    \
+                   organization = {IEEE},\n    \
+                   title = {IEEE Standard for Floating-Point Arithmetic},\n    year = {2019},\n}\n";
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+        let entry = biblio.get("IEEE754-2019").unwrap();
+        assert_eq!(
+            ast::EntryKind::Other(crate::STANDARD_KIND.into()),
+            entry.kind()
+        );
+
+        let config = BibTexConfig {
+            sort_fields: true,
+            dialect: Dialect::BibLaTex,
+            ..BibTexConfig::default()
+        };
+        assert_eq!(raw, BibTex::compose_entry_with(entry, &config));
+
+        // `BibTeX` has no standards so the number is kept in a `misc` entry
+        let config = BibTexConfig {
+            sort_fields: true,
+            ..BibTexConfig::default()
+        };
+        assert_eq!(
+            raw.replace("@standard", "@misc"),
+            BibTex::compose_entry_with(entry, &config)
+        );
+    }
+
+    #[test]
+    fn ascii_output_encoding_round_trips_to_unicode() {
+        let entry = ast::Entry::Manual(ast::Manual {
+            cite: "café".to_owned(),
+            title: QuotedString::from_parts(vec![
+                (false, "naïve café ".to_owned()),
+                (true, "Ångström".to_owned()),
+            ]),
+            optional: HashMap::new(),
+        });
+        let config = BibTexConfig {
+            output_encoding: OutputEncoding::Ascii,
+            ..BibTexConfig::default()
+        };
+
+        let composed = BibTex::compose_entry_with(&entry, &config);
+
+        assert!(composed.is_ascii(), "{composed}");
+        assert_eq!(
+            "@manual{cafe,\n    title = {na{\\\"{i}}ve caf{\\'{e}} {{\\r{A}}ngstr{\\\"{o}}m}},\n}\n",
+            composed
+        );
+        let biblio = BibTex::new(composed.clone()).parse().unwrap().unwrap();
+        let entry = biblio.get("cafe").unwrap();
+        // the commands are kept until the file is switched to utf8
+        assert_eq!(composed, BibTex::compose_entry(entry));
+        let title = latex_to_unicode_quoted(entry.title()).unwrap();
+        assert_eq!("naïve café Ångström", &*title);
+        assert_eq!(
+            "naïve café {Ångström}",
+            title.map_quoted(|s| format!("{{{s}}}"))
+        );
+    }
+
+    #[test]
+    fn entry_set_round_trips() {
+        let raw = "@set{set,\n    entryset = {a,b,c},\n}\n";
+        let biblio = BibTex::new(format!(
+            "{raw}@misc{{a, title = {{A}}}}\n@misc{{b, title = {{B}}}}\n@misc{{c, title = {{C}}}}"
+        ))
+        .parse()
+        .unwrap()
+        .unwrap();
+        let entry = biblio.get("set").unwrap();
+
+        assert_eq!(Some(vec!["a", "b", "c"]), entry.entry_set());
+        assert_eq!(
+            raw,
+            BibTex::compose_entry_with(entry, &BibTexConfig::default())
+        );
+        let dialect = BibTexConfig {
+            dialect: Dialect::BibLaTex,
+            ..BibTexConfig::default()
+        };
+        assert_eq!(raw, BibTex::compose_entry_with(entry, &dialect));
+        assert!(BibTex::compose(&biblio).missing_set_members().is_empty());
+    }
+
+    #[test]
+    fn legacy_eprint_fields_are_composed_together() {
+        let raw = "@misc{cite, archivePrefix = {arXiv}, author = {Me}, eprint = {2106.01345}, \
+                   primaryClass = {cs.LG}, title = {Title}}";
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+        let config = BibTexConfig {
+            sort_fields: true,
+            ..BibTexConfig::default()
+        };
+
+        assert_eq!(
+            "@misc{cite,\n    author = {Me},\n    eprint = {2106.01345},\n    \
+             eprinttype = {arXiv},\n    eprintclass = {cs.LG},\n    title = {Title},\n}\n",
+            BibTex::compose_entry_with(biblio.get("cite").unwrap(), &config)
+        );
+    }
+
+    #[test]
+    fn excluded_fields_are_not_composed_but_remain_in_biblio() {
+        let raw = "@manual{cite, title = {Title}, annote = {Private}, abstract = {Long}}";
+        let biblio = BibTex::new(raw.to_owned()).parse().unwrap().unwrap();
+        let config = BibTexConfig {
+            fields: FieldFilter::exclude(["annote", "abstract"]),
+            ..BibTexConfig::default()
+        };
+
+        let composed = BibTex::compose_with(&biblio, &config).raw();
+
+        assert_eq!(
+            "% manual\n@manual{cite,\n    title = {Title},\n}\n\n",
+            composed
+        );
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!("Private", &**entry.get_field("annote").unwrap());
+        assert_eq!("Long", &**entry.get_field("abstract").unwrap());
+    }
+
+    #[test]
+    fn only_fields_are_composed() {
+        let fields = [
+            field! { "title": "Title" },
+            field! { "book_title": "Book" },
+            field! { "year": "2020" },
+        ];
+        let config = BibTexConfig {
+            fields: FieldFilter::only(["title", "booktitle"]),
+            trailing_comma: false,
+            ..BibTexConfig::default()
+        };
+
+        assert_eq!(
+            "    title = {Title},\n    booktitle = {Book}\n",
+            compose_fields(&fields, &config)
+        );
+    }
+
     #[test]
     fn book_title_in_bibtex_should_be_booktitle() {
-        let result = compose_fields(&[ast::Field {
-            name: Cow::Borrowed("book_title"),
-            value: Cow::Owned("value".into()),
-        }]);
+        let result = compose_fields(
+            &[ast::Field {
+                name: Cow::Borrowed("book_title"),
+                value: Cow::Owned("value".into()),
+            }],
+            &BibTexConfig::default(),
+        );
 
         assert_eq!("    booktitle = {value},\n", result);
     }
@@ -568,6 +2514,262 @@ mod tests {
         assert_eq!(expected, result.raw());
     }
 
+    #[test]
+    fn raw_entries_skip_comments_and_text_between_entries() {
+        let bibtex = BibTex::new(
+            "@comment{ignore me}
+            some text with an email@example.com in it
+            @book{first, title={Braces {in} title}, year={2000},}
+            @misc(second, title={Parens})"
+                .to_owned(),
+        );
+
+        let entries = bibtex.raw_entries().collect::<Vec<_>>();
+
+        assert_eq!(2, entries.len());
+        assert_eq!(("book", "first"), (entries[0].kind(), entries[0].key()));
+        assert!(entries[0].raw().ends_with("year={2000},}"));
+        assert_eq!(("misc", "second"), (entries[1].kind(), entries[1].key()));
+    }
+
+    #[test]
+    fn preamble_and_comments_round_trip() {
+        let src = "@preamble{\"\\newcommand{\\noopsort}[1]{}\"}\n\n\
+                   @comment{jabref-meta: databaseType:bibtex;}\n\n\
+                   @comment{Second comment}\n\n\
+                   % manual\n\
+                   @manual{a,\n    title = {Title},\n}\n\n";
+
+        let mut biblio = BibTex::new(src.to_owned())
+            .parse()
+            .expect("Valid BibTeX string")
+            .expect("Entries have all the required fields");
+        assert_eq!(
+            vec!["\"\\newcommand{\\noopsort}[1]{}\"".to_owned()],
+            biblio.blocks().preambles
+        );
+
+        biblio.insert(
+            BibTex::new("@manual{b, title = {Added}}".to_owned())
+                .parse()
+                .unwrap()
+                .unwrap()
+                .into_entries()
+                .remove(0),
+        );
+        let composed = BibTex::compose(&biblio).raw();
+
+        assert!(composed.starts_with(&src[..src.find('%').unwrap()]));
+        assert!(composed.contains("@manual{b,"));
+    }
+
+    #[test]
+    fn comments_stay_in_place_relative_to_the_entries() {
+        let biblio = BibTex::new(
+            "@comment{Top}\n\
+             @manual{z, title = {Z}}\n\
+             @comment{Before b}\n\
+             @manual{b, title = {B}}\n\
+             @comment{Before removed}\n\
+             @manual{removed, title = {Removed}}\n\
+             @comment{Bottom}\n"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            vec![
+                (CommentPlacement::Start, "Top".to_owned()),
+                (
+                    CommentPlacement::Before("b".to_owned()),
+                    "Before b".to_owned()
+                ),
+                (
+                    CommentPlacement::Before("removed".to_owned()),
+                    "Before removed".to_owned()
+                ),
+                (CommentPlacement::End, "Bottom".to_owned()),
+            ],
+            biblio.blocks().comments
+        );
+
+        let mut biblio = biblio;
+        biblio.remove("removed");
+        let composed = BibTex::compose(&biblio).raw();
+
+        assert!(
+            composed.starts_with("@comment{Top}\n\n% manual\n"),
+            "{composed}"
+        );
+        assert!(
+            composed.contains("@comment{Before b}\n@manual{b,"),
+            "{composed}"
+        );
+        assert!(
+            composed.ends_with("}\n\n@comment{Before removed}\n\n@comment{Bottom}\n"),
+            "{composed}"
+        );
+        // the comments are read back in the same places
+        let read = BibTex::new(composed).parse().unwrap().unwrap();
+        assert_eq!(
+            Some(&(
+                CommentPlacement::Before("b".to_owned()),
+                "Before b".to_owned()
+            )),
+            read.blocks().comments.get(1)
+        );
+    }
+
+    #[test]
+    fn file_of_only_a_preamble_is_valid() {
+        let biblio = BibTex::new("@preamble{\"\\relax\"}".to_owned())
+            .parse()
+            .expect("Valid BibTeX string")
+            .expect("No entries to resolve");
+
+        assert_eq!(0, biblio.entries().count());
+        assert_eq!(1, biblio.blocks().preambles.len());
+    }
+
+    #[test]
+    fn keyless_entries_are_given_a_cite_key() {
+        let bibtex = BibTex::new(
+            "@manual{, title={Literate Programming}, author={Knuth, Donald}, year={1984}}
+            @manual{ , title={The TeXbook}, author={Knuth, Donald}, year={1984}}
+            @manual{,title={Anonymous}}
+            @manual{Knuth1984, title={Taken}}"
+                .to_owned(),
+        );
+
+        let biblio = bibtex
+            .parse_with(&KeyFormat::default())
+            .expect("Valid BibTeX string")
+            .expect("Entries have all the required fields");
+
+        let mut cites = biblio.entries().map(ast::Entry::cite).collect::<Vec<_>>();
+        cites.sort_unstable();
+        let hashed = cites.iter().find(|cite| cite.starts_with("entry")).unwrap();
+        assert_eq!(vec!["Knuth1984", "Knuth1984a", "Knuth1984b", hashed], cites);
+        assert_eq!("Anonymous", &**biblio.get(hashed).unwrap().title());
+        assert_eq!(
+            "Literate Programming",
+            &**biblio.get("Knuth1984a").unwrap().title()
+        );
+    }
+
+    #[test]
+    fn parse_entry_only_parses_the_entry_with_the_cite_key() {
+        // `incomplete` is missing required fields so a full parse would not resolve
+        let bibtex = BibTex::new(
+            "@book{incomplete, title={Missing author}}
+            @manual{wanted, title={Found me}}"
+                .to_owned(),
+        );
+
+        let entry = bibtex
+            .parse_entry("wanted")
+            .expect("Valid BibTeX string")
+            .expect("Entry with the cite key exists")
+            .expect("Entry has all the required fields");
+
+        assert_eq!("Found me", &**entry.title());
+        assert!(bibtex.parse_entry("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_entry_resolves_crossref_parent() {
+        let bibtex = BibTex::new(
+            "@inbook{inbook, chapter={Test Chapter}, title={InBook Title}, crossref={book},}
+            @book{book, title={My test book}, publisher={Me}, author={Also me}, year={2000},}"
+                .to_owned(),
+        );
+
+        let entry = bibtex
+            .parse_entry("inbook")
+            .expect("Valid BibTeX string")
+            .expect("Entry with the cite key exists")
+            .expect("Crossref parent provides the required fields");
+
+        assert_eq!("My test book", &**entry.get_field("book_title").unwrap());
+        assert_eq!("2000", &**entry.get_field("year").unwrap());
+    }
+
+    #[test]
+    fn find_entry_reads_the_stream_until_the_entry_and_its_parent() {
+        let stream = "@comment{wanted}\n\
+            text with an email@example.com in it\n\
+            @string{me = {Me}}\n\
+            @inbook{wanted,\n  chapter = {Test Chapter},\n  title = {InBook Title},\n  \
+            crossref = {book}\n}\n\
+            @manual{other, title = {Other}}\n\
+            @book\n{book, title={My test book}, publisher=me, author={Also me}, year={2000},}\n\
+            @manual{unread, title = {Never read";
+
+        let entry = BibTex::find_entry(stream.as_bytes(), InputEncoding::Utf8, "wanted")
+            .expect("Valid BibTeX stream")
+            .expect("Entry with the cite key exists")
+            .expect("Crossref parent provides the required fields");
+
+        assert_eq!("InBook Title", &**entry.title());
+        assert_eq!("My test book", &**entry.get_field("book_title").unwrap());
+        assert_eq!("Me", &**entry.get_field("publisher").unwrap());
+        let missing = BibTex::find_entry(stream.as_bytes(), InputEncoding::Utf8, "missing");
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[test]
+    fn cite_keys_are_found_ignoring_case() {
+        let src = "@manual{Knuth84, title = {Literate Programming}}\n";
+
+        let entry = BibTex::new(src.to_owned())
+            .parse_entry("knuth84")
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!("Knuth84", entry.cite());
+
+        let entry = BibTex::find_entry(src.as_bytes(), InputEncoding::Utf8, "KNUTH84")
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!("Knuth84", entry.cite());
+    }
+
+    #[test]
+    fn find_entry_decodes_the_stream_in_the_encoding() {
+        let stream: &[u8] = b"@manual{godel, title = {G\xf6del}}\n";
+
+        let entry = BibTex::find_entry(stream, InputEncoding::Latin1, "godel")
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!("G\u{f6}del", &**entry.title());
+        assert!(BibTex::find_entry(stream, InputEncoding::Utf8, "godel").is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_compose_is_identical_to_sequential() {
+        let entries = (0..100)
+            .map(|i| {
+                ast::Entry::Manual(ast::Manual {
+                    cite: format!("entry{i}"),
+                    title: format!("Title {i}").into(),
+                    optional: HashMap::from([("author".to_owned(), "Me".into())]),
+                })
+            })
+            .collect::<Vec<_>>();
+        let entries = entries.iter().collect::<Vec<_>>();
+
+        let config = BibTexConfig::default();
+        assert_eq!(
+            compose_entries(&entries, &config),
+            par_compose_entries(&entries, &config)
+        );
+    }
+
     #[test]
     fn other_entry_composes_to_misc() {
         // build other entry type with "software" kind
@@ -576,8 +2778,187 @@ mod tests {
         let other = resolver.resolve().expect("Valid misc entry");
 
         // composing any other kind should always become a "misc" for BibTeX
-        let composed_var = compose_variant(&other);
+        let composed_var = compose_variant(&other, Dialect::BibTex);
 
         assert_eq!("misc", composed_var);
     }
+
+    fn biblatex_config() -> BibTexConfig {
+        BibTexConfig {
+            dialect: Dialect::BibLaTex,
+            ..BibTexConfig::default()
+        }
+    }
+
+    #[test]
+    fn journal_composes_to_biblatex_journaltitle() {
+        let biblio = BibTex::new(
+            "@article{a, author = {Me}, title = {Title}, journal = {Journal}, year = {2020}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+        let entry = biblio.get("a").unwrap();
+
+        let biblatex = BibTex::compose_entry_with(entry, &biblatex_config());
+        assert!(biblatex.contains("journaltitle = {Journal}"));
+        assert!(!biblatex.contains("journal ="));
+
+        let bibtex = BibTex::compose_entry_with(entry, &BibTexConfig::default());
+        assert!(bibtex.contains("journal = {Journal}"));
+    }
+
+    #[test]
+    fn biblatex_journaltitle_parses_to_journal() {
+        let biblio = BibTex::new(
+            "@article{a, author = {Me}, title = {Title}, journaltitle = {Journal}, \
+             location = {Place}, year = {2020}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .expect("journaltitle is the required journal field");
+        let entry = biblio.get("a").unwrap();
+
+        assert_eq!("Journal", &**entry.get_field("journal").unwrap());
+        assert_eq!("Place", &**entry.get_field("address").unwrap());
+        assert!(entry.get_field("journaltitle").is_none());
+    }
+
+    #[test]
+    fn affiliation_parses_to_the_field_of_the_entry_type() {
+        let biblio = BibTex::new(
+            "@phdthesis{thesis, author = {Me}, title = {Thesis}, year = {2020}, \
+             institution = {University}}\n\
+             @techreport{report, author = {Me}, title = {Report}, year = {2020}, \
+             school = {Lab}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .expect("The affiliations are the required school and institution fields");
+
+        let thesis = biblio.get("thesis").unwrap();
+        assert_eq!("University", &**thesis.get_field("school").unwrap());
+        let report = biblio.get("report").unwrap();
+        assert_eq!("Lab", &**report.get_field("institution").unwrap());
+        assert!(report.get_field("school").is_none());
+    }
+
+    #[test]
+    fn issue_parses_to_number() {
+        let biblio = BibTex::new(
+            "@article{a, author = {Me}, title = {Title}, journal = {Journal}, year = {2020}, \
+             issue = {4}}\n\
+             @article{b, author = {Me}, title = {Title}, journal = {Journal}, year = {2020}, \
+             number = {4}, issue = {Special Issue}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let entry = biblio.get("a").unwrap();
+        assert_eq!("4", &**entry.get_field("number").unwrap());
+        assert!(entry.get_field("issue").is_none());
+        for dialect in [Dialect::BibTex, Dialect::BibLaTex] {
+            let config = BibTexConfig {
+                dialect,
+                ..BibTexConfig::default()
+            };
+            assert!(BibTex::compose_entry_with(entry, &config).contains("number = {4}"));
+        }
+
+        let entry = biblio.get("b").unwrap();
+        assert_eq!("4", &**entry.get_field("number").unwrap());
+        assert_eq!("Special Issue", &**entry.get_field("issue").unwrap());
+    }
+
+    #[test]
+    fn urldate_round_trips_in_both_dialects() {
+        let bibtex = "@online{a, title = {Title}, url = {https://example.com}, \
+                      urldate = {2024-01-01}}";
+        let biblio = BibTex::new(bibtex.to_owned()).parse().unwrap().unwrap();
+
+        let bibtex = BibTex::compose_with(&biblio, &BibTexConfig::default()).raw();
+        assert!(bibtex.contains("urldate = {2024-01-01}"));
+        assert!(!bibtex.contains("note"));
+        let note = BibTexConfig {
+            urldate_in_note: true,
+            ..BibTexConfig::default()
+        };
+        let composed = BibTex::compose_with(&biblio, &note).raw();
+        assert!(composed.contains("note = {Accessed: 2024-01-01}"));
+        assert!(!composed.contains("urldate"));
+        let biblatex = BibTex::compose_with(&biblio, &biblatex_config()).raw();
+        assert!(biblatex.contains("urldate = {2024-01-01}"));
+        assert!(!biblatex.contains("note"));
+
+        for composed in [bibtex, composed, biblatex] {
+            let biblio = BibTex::new(composed).parse().unwrap().unwrap();
+            let entry = biblio.get("a").unwrap();
+            assert_eq!("2024-01-01", &**entry.get_field(URLDATE_FIELD).unwrap());
+            assert!(entry.get_field("note").is_none());
+        }
+    }
+
+    #[test]
+    fn accessed_date_is_read_from_the_end_of_the_note() {
+        let biblio = BibTex::new(
+            "@misc{a, title = {A}, note = {Draft; Accessed: 2024-01-01}}\n\
+             @misc{b, title = {B}, accessed = {2023-05-06}}\n\
+             @misc{c, title = {C}, note = {Accessed by many}}\n\
+             @misc{d, title = {D}, note = {Accessed: 2020 survey data}}\n\
+             @misc{e, title = {E}, note = {Accessed: 2024-13-01}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let a = biblio.get("a").unwrap();
+        assert_eq!("2024-01-01", &**a.get_field(URLDATE_FIELD).unwrap());
+        assert_eq!("Draft", &**a.get_field("note").unwrap());
+        assert_eq!(
+            "2023-05-06",
+            &**biblio.get("b").unwrap().get_field(URLDATE_FIELD).unwrap()
+        );
+        let c = biblio.get("c").unwrap();
+        assert!(c.get_field(URLDATE_FIELD).is_none());
+        assert_eq!("Accessed by many", &**c.get_field("note").unwrap());
+        for (cite, note) in [
+            ("d", "Accessed: 2020 survey data"),
+            ("e", "Accessed: 2024-13-01"),
+        ] {
+            let entry = biblio.get(cite).unwrap();
+            assert!(entry.get_field(URLDATE_FIELD).is_none());
+            assert_eq!(note, &**entry.get_field("note").unwrap());
+        }
+
+        let note = BibTexConfig {
+            urldate_in_note: true,
+            ..BibTexConfig::default()
+        };
+        let composed = BibTex::compose_entry_with(a, &note);
+        assert!(composed.contains("note = {Draft; Accessed: 2024-01-01}"));
+    }
+
+    #[test]
+    fn thesis_round_trips_through_biblatex() {
+        let bibtex = "@mastersthesis{a, author = {Me}, title = {Title}, school = {Uni}, \
+                      year = {2020}}";
+        let biblio = BibTex::new(bibtex.to_owned()).parse().unwrap().unwrap();
+
+        let biblatex = BibTex::compose_with(&biblio, &biblatex_config()).raw();
+        assert!(biblatex.contains("@thesis{a,"));
+        assert!(biblatex.contains("institution = {Uni}"));
+        assert!(biblatex.contains("type = {mathesis}"));
+
+        let biblio = BibTex::new(biblatex).parse().unwrap().unwrap();
+        let entry = biblio.get("a").unwrap();
+        assert_eq!(ast::EntryKind::MasterThesis, entry.kind());
+        assert_eq!("Uni", &**entry.get_field("school").unwrap());
+        assert!(entry.get_field("type").is_none());
+    }
 }
@@ -0,0 +1,149 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Error, ErrorKind};
+
+/// The characters of the bytes read from a source, such as a bibliography file.
+///
+/// Every [`Format`][super::Format] is UTF-8 so a source in another encoding is transcoded to UTF-8
+/// when it is read, see [`InputEncoding::detect`].
+///
+/// # Examples
+///
+/// ```
+/// use seb::format::InputEncoding;
+///
+/// let latin1 = b"author = {G\xf6del, Kurt}";
+///
+/// assert_eq!(InputEncoding::Latin1, InputEncoding::detect(latin1));
+/// assert_eq!(
+///     "author = {Gödel, Kurt}",
+///     InputEncoding::Latin1.decode(latin1.to_vec()).unwrap()
+/// );
+/// assert_eq!(InputEncoding::Latin1, "iso-8859-1".parse().unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEncoding {
+    /// UTF-8, which is read as it is.
+    Utf8,
+    /// ISO-8859-1, where every byte is the character with the same code point.
+    Latin1,
+    /// Windows-1252, which is Latin-1 with printable characters, such as `€` and curly quotes, in
+    /// place of the control characters from `0x80` to `0x9f`.
+    Windows1252,
+}
+
+// The characters of the bytes from `0x80` to `0x9f` in Windows-1252, where the five bytes that are
+// not used are read as the Latin-1 control characters.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+impl InputEncoding {
+    /// Returns the most likely encoding of the `bytes`.
+    ///
+    /// Bytes that are valid UTF-8 are UTF-8, as text in a single byte encoding is very unlikely to
+    /// also be valid UTF-8. Any other bytes are Windows-1252 when they have a byte from `0x80` to
+    /// `0x9f`, which are control characters in Latin-1 that text does not use, otherwise Latin-1.
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Self {
+        if std::str::from_utf8(bytes).is_ok() {
+            Self::Utf8
+        } else if bytes.iter().any(|b| (0x80..=0x9f).contains(b)) {
+            Self::Windows1252
+        } else {
+            Self::Latin1
+        }
+    }
+
+    /// Transcodes the `bytes` in this encoding to UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] when the encoding is UTF-8 and the `bytes` are not valid UTF-8, every
+    /// byte is a character in the other encodings.
+    pub fn decode(self, bytes: Vec<u8>) -> Result<String, Error> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes)
+                .map_err(|e| Error::wrap_with(ErrorKind::IO, e, "Content is not valid UTF-8")),
+            Self::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+            Self::Windows1252 => Ok(bytes
+                .into_iter()
+                .map(|b| match b {
+                    0x80..=0x9f => WINDOWS_1252[usize::from(b - 0x80)],
+                    _ => char::from(b),
+                })
+                .collect()),
+        }
+    }
+
+    /// The name of the encoding, such as `UTF-8`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Latin1 => "Latin-1",
+            Self::Windows1252 => "Windows-1252",
+        }
+    }
+}
+
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for InputEncoding {
+    type Err = Error;
+
+    /// Parses the name of an encoding ignoring case, such as `utf-8`, `latin1`, `iso-8859-1` or
+    /// `cp1252`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Ok(Self::Utf8),
+            "latin1" | "iso88591" => Ok(Self::Latin1),
+            "windows1252" | "cp1252" => Ok(Self::Windows1252),
+            _ => Err(Error::new(
+                ErrorKind::Deserialize,
+                format!("Unknown encoding '{s}', expected utf-8, latin-1 or windows-1252"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_encoding() {
+        assert_eq!(
+            InputEncoding::Utf8,
+            InputEncoding::detect("Gödel".as_bytes())
+        );
+        assert_eq!(InputEncoding::Utf8, InputEncoding::detect(b"Godel"));
+        assert_eq!(InputEncoding::Latin1, InputEncoding::detect(b"G\xf6del"));
+        assert_eq!(
+            InputEncoding::Windows1252,
+            InputEncoding::detect(b"\x93G\xf6del\x94")
+        );
+    }
+
+    #[test]
+    fn decode_to_utf8() {
+        assert_eq!(
+            "“Gödel”",
+            InputEncoding::Windows1252
+                .decode(b"\x93G\xf6del\x94".to_vec())
+                .unwrap()
+        );
+        assert_eq!(
+            "\u{93}Gödel\u{94}",
+            InputEncoding::Latin1
+                .decode(b"\x93G\xf6del\x94".to_vec())
+                .unwrap()
+        );
+        assert!(InputEncoding::Utf8.decode(b"G\xf6del".to_vec()).is_err());
+    }
+}
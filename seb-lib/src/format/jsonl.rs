@@ -0,0 +1,129 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::{Error, ErrorKind};
+
+/// Writes each [`Entry`][E] as a JSON object on its own line ([JSON Lines](https://jsonlines.org/)).
+///
+/// Entries are written one at a time so the whole output is never held in memory, which makes
+/// this suitable for streaming large bibliographies to other tools. Entries can be wrapped in
+/// [`WithoutProvenance`][WP] to leave out the provenance fields added by seb.
+///
+/// [E]: crate::ast::Entry
+/// [WP]: crate::ast::WithoutProvenance
+///
+/// # Errors
+///
+/// Will return [`Err`] if an entry cannot be serialized or written to the `writer`.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{ast::Biblio, format::{BibTex, Format, write_json_lines}};
+///
+/// let biblio = BibTex::new("@manual{cite, title = {Title}}".to_owned())
+///     .parse()
+///     .unwrap()
+///     .unwrap();
+///
+/// let mut out = Vec::new();
+/// write_json_lines(biblio.entries(), &mut out).unwrap();
+///
+/// assert_eq!(
+///     r#"{"type":"manual","cite":"cite","title":"Title"}"#,
+///     String::from_utf8(out).unwrap().trim_end()
+/// );
+/// ```
+pub fn write_json_lines<I, W>(entries: I, mut writer: W) -> Result<(), Error>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+    W: Write,
+{
+    for entry in entries {
+        serde_json::to_writer(&mut writer, &entry).map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+        writer
+            .write_all(b"\n")
+            .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+    }
+    writer.flush().map_err(|e| Error::wrap(ErrorKind::IO, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::{Biblio, Entry, EntryKind, List, WithoutProvenance, SOURCE_FIELD},
+        format::{BibTex, Format},
+    };
+
+    #[test]
+    fn each_entry_is_written_on_its_own_line() {
+        let resolvers = (0..5)
+            .map(|i| {
+                let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, format!("cite{i}"));
+                resolver.title(format!("Title {i}"));
+                resolver
+            })
+            .collect();
+        let biblio = Biblio::try_resolve(resolvers).expect("All entries have a title");
+
+        let mut out = Vec::new();
+        write_json_lines(biblio.entries(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(5, out.lines().count());
+        for line in out.lines() {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!("manual", value["type"]);
+        }
+    }
+
+    #[test]
+    fn provenance_fields_can_be_excluded() {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+        resolver.title("Title");
+        resolver.set_field(SOURCE_FIELD, "crossref");
+        let entry = resolver.resolve().expect("Title is set");
+
+        let mut out = Vec::new();
+        write_json_lines([WithoutProvenance(&entry)], &mut out).unwrap();
+
+        assert_eq!(
+            "{\"type\":\"manual\",\"cite\":\"cite\",\"title\":\"Title\"}\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn keywords_round_trip_as_bibtex_and_json_lists() {
+        let bibtex = "@misc{cite,\n    title = {Title},\n    \
+            keywords = {sorting, searching},\n}\n";
+        let biblio = BibTex::new(bibtex.to_owned()).parse().unwrap().unwrap();
+        let entry = biblio.get("cite").unwrap();
+        let list = entry.list("keywords").unwrap();
+        assert_eq!(vec!["sorting", "searching"], list.items());
+        assert_eq!(bibtex, BibTex::compose_entry(entry));
+
+        let mut out = Vec::new();
+        write_json_lines(biblio.entries(), &mut out).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            serde_json::json!(["sorting", "searching"]),
+            value["keywords"]
+        );
+
+        let keywords = value["keywords"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|keyword| keyword.as_str().unwrap().to_owned())
+            .collect();
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Other("misc".into()), "cite");
+        resolver.title("Title");
+        let mut entry = resolver.resolve().unwrap();
+        entry.set_list("keywords", &List::Items(keywords));
+        assert_eq!(bibtex, BibTex::compose_entry(&entry));
+    }
+}
@@ -4,6 +4,11 @@
 use std::marker::PhantomData;
 
 mod bibtex;
+mod encoding;
+#[cfg(feature = "jsonl")]
+mod jsonl;
+mod ris;
+mod transform;
 
 use crate::{
     ast::{Biblio, BiblioResolver, Entry},
@@ -11,7 +16,15 @@ use crate::{
 };
 
 #[cfg(feature = "bibtex")]
-pub use bibtex::BibTex;
+pub use bibtex::{
+    BibTex, BibTexConfig, Dialect, FieldCase, MonthStyle, OutputEncoding, QuoteStyle, RawEntries,
+    RawEntry,
+};
+pub use encoding::InputEncoding;
+#[cfg(feature = "jsonl")]
+pub use jsonl::write_json_lines;
+pub use ris::Ris;
+pub use transform::{FieldTransform, PageDash, TitleProtection, Transforms, Truncate};
 
 // TODO: Consider defining Format so that it can wrap T types, where T: std::io::Write +
 // std::io::Read. This would allow Format to trivially uphold the same type bounds as T and would
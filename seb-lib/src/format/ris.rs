@@ -0,0 +1,466 @@
+use crate::{
+    ast::{
+        self, generated_key, Author, Biblio, BiblioResolver, EntryKind, FieldQuery, KeyFormat,
+        List, Resolver,
+    },
+    Error, ErrorKind,
+};
+
+use super::Format;
+
+/// A [`Format`] for the [RIS](https://en.wikipedia.org/wiki/RIS_(file_format)) tagged format used
+/// by reference managers, such as Zotero and Mendeley.
+///
+/// Each author of an RIS record is on its own `AU` line, which is parsed into an [`Author`] and
+/// joined into the `author` field, so the authors of an entry are the same whichever format the
+/// entry was read from. Only the fields that have an RIS tag are composed.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{ast::FieldQuery, format::{Format, Ris}};
+///
+/// let ris = Ris::new(
+///     "TY  - JOUR\nAU  - Knuth, Donald E.\nAU  - Lamport, Leslie\nTI  - Title\n\
+///      JO  - Journal\nPY  - 1984\nER  - \n"
+///         .to_owned(),
+/// );
+///
+/// let biblio = ris.parse().unwrap().unwrap();
+/// let entry = biblio.entries().next().unwrap();
+///
+/// assert_eq!(
+///     "Knuth, Donald E. and Lamport, Leslie",
+///     &**entry.get_field("author").unwrap()
+/// );
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ris(String);
+
+// The RIS types and the entry kinds, the first kind of each type is the one parsed and the first
+// type of each kind is the one composed.
+const TYPES: [(&str, &str); 13] = [
+    ("JOUR", "article"),
+    ("BOOK", "book"),
+    ("CHAP", "book section"),
+    ("CHAP", "book chapter"),
+    ("CHAP", "book pages"),
+    ("CONF", "in proceedings"),
+    ("CPAPER", "in proceedings"),
+    ("THES", "phd thesis"),
+    ("THES", "master thesis"),
+    ("RPRT", "tech report"),
+    ("UNPB", "unpublished"),
+    ("COMP", "manual"),
+    ("GEN", "misc"),
+];
+
+impl Format for Ris {
+    fn new(val: String) -> Self {
+        Self(val)
+    }
+
+    fn parse(self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        self.parse_with(&KeyFormat::default())
+    }
+
+    fn compose(biblio: &Biblio) -> Self {
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.cite().cmp(b.cite()));
+        Self(entries.into_iter().map(Self::compose_entry).collect())
+    }
+
+    fn compose_entry(entry: &ast::Entry) -> String {
+        let kind = entry.kind();
+        let ty = TYPES
+            .iter()
+            .find(|(_, k)| EntryKind::from(*k) == kind)
+            .map_or("GEN", |(ty, _)| ty);
+
+        let mut lines = vec![("TY", ty.to_owned()), ("ID", entry.cite().to_owned())];
+        lines.extend(
+            entry
+                .authors()
+                .iter()
+                .map(|author| ("AU", author.to_string())),
+        );
+        lines.extend(
+            entry
+                .get_field("editor")
+                .map(|editors| Author::parse_list(editors))
+                .unwrap_or_default()
+                .iter()
+                .map(|editor| ("ED", editor.to_string())),
+        );
+        lines.push(("TI", entry.title().to_string()));
+
+        let field = |name: &str| entry.get_field(name).map(|value| (**value).to_owned());
+        let pages = field("pages");
+        let (start, end) = match pages.as_deref().map(|pages| pages.split_once('-')) {
+            Some(Some((start, end))) => (
+                Some(start.to_owned()),
+                Some(end.trim_start_matches('-').to_owned()),
+            ),
+            Some(None) => (pages.clone(), None),
+            None => (None, None),
+        };
+        let tagged = [
+            ("T2", field("journal").or_else(|| field("booktitle"))),
+            ("PY", field("year")),
+            ("VL", field("volume")),
+            ("IS", field("number").or_else(|| field("issue"))),
+            ("SP", start),
+            ("EP", end),
+            ("PB", field("publisher")),
+            ("SN", field("isbn").or_else(|| field("issn"))),
+            ("DO", field("doi")),
+            ("UR", field("url")),
+            ("Y2", field(ast::URLDATE_FIELD)),
+            ("AB", field("abstract")),
+        ];
+        lines.extend(
+            tagged
+                .into_iter()
+                .filter_map(|(tag, value)| Some((tag, value?))),
+        );
+        lines.extend(
+            entry
+                .list("keywords")
+                .map(|keywords| keywords.items())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|keyword| ("KW", keyword)),
+        );
+
+        let mut record = String::new();
+        for (tag, value) in lines {
+            record.push_str(tag);
+            record.push_str("  - ");
+            record.push_str(&value);
+            record.push('\n');
+        }
+        record.push_str("ER  - \n\n");
+        record
+    }
+
+    fn raw(self) -> String {
+        self.0
+    }
+
+    fn name() -> &'static str {
+        "RIS"
+    }
+
+    fn ext() -> &'static str {
+        "ris"
+    }
+}
+
+impl Ris {
+    /// Parses this RIS to a [`Biblio`], like [`Format::parse`], where a record without an `ID`
+    /// is given a cite key generated with the key `format`.
+    ///
+    /// A generated cite key that is already used, ignoring case, is given the suffix `a`, `b`,
+    /// `c` and so on.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Err`] if it's not possible to parse this RIS to [`Biblio`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::{ast::KeyFormat, format::{Format, Ris}};
+    ///
+    /// let ris = Ris::new("TY  - GEN\nAU  - Knuth, Donald\nTI  - Title\nER  - \n".to_owned());
+    /// let format = "{author}_{title}".parse::<KeyFormat>().unwrap();
+    /// let biblio = ris.parse_with(&format).unwrap().unwrap();
+    ///
+    /// assert!(biblio.get("Knuth_Title").is_some());
+    /// ```
+    pub fn parse_with(self, format: &KeyFormat) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let mut records = Vec::new();
+        let mut record: Option<Record> = None;
+
+        for (number, line) in self.0.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((tag, value)) = line.split_once("  -").map(|(t, v)| (t.trim(), v.trim()))
+            else {
+                return Err(Error::new(
+                    ErrorKind::Deserialize,
+                    format!("Line {} is not an RIS tag: '{line}'", number + 1),
+                ));
+            };
+
+            match (tag, record.as_mut()) {
+                ("TY", None) => record = Some(Record::new(value)),
+                ("ER", Some(_)) => records.extend(record.take()),
+                (_, Some(record)) => record.push(tag, value),
+                (_, None) => {
+                    return Err(Error::new(
+                        ErrorKind::Deserialize,
+                        format!("Line {} is outside of an RIS record", number + 1),
+                    ))
+                }
+            }
+        }
+
+        if record.is_some() {
+            return Err(Error::new(
+                ErrorKind::Deserialize,
+                "The last RIS record is missing the ER tag",
+            ));
+        }
+
+        // a record without an ID is given a cite key generated from its fields
+        let mut used = records
+            .iter()
+            .filter_map(|record| record.cite.as_deref().map(str::to_lowercase))
+            .collect();
+        for record in &mut records {
+            if record.cite.is_none() {
+                let partial = record.clone().resolve().resolve_partial();
+                let (key, how) = generated_key(&partial, format, &mut used);
+                record.cite = Some(key);
+                record.fields.push((ast::KEY_FIELD, how));
+            }
+        }
+        Ok(Biblio::try_resolve(
+            records.into_iter().map(Record::resolve).collect(),
+        ))
+    }
+}
+
+// The tags of a single record until the ER tag.
+#[derive(Clone)]
+struct Record {
+    kind: EntryKind<'static>,
+    cite: Option<String>,
+    authors: Vec<Author>,
+    editors: Vec<Author>,
+    keywords: Vec<String>,
+    pages: (Option<String>, Option<String>),
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Record {
+    fn new(ty: &str) -> Self {
+        let kind = TYPES
+            .iter()
+            .find(|(t, _)| t.eq_ignore_ascii_case(ty))
+            .map_or("misc", |(_, kind)| kind);
+
+        Self {
+            kind: EntryKind::from(kind),
+            cite: None,
+            authors: Vec::new(),
+            editors: Vec::new(),
+            keywords: Vec::new(),
+            pages: (None, None),
+            fields: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, tag: &str, value: &str) {
+        let value = value.to_owned();
+        let name = match tag {
+            "ID" => {
+                self.cite = Some(value).filter(|cite| !cite.is_empty());
+                return;
+            }
+            "AU" | "A1" => {
+                self.authors.extend(Author::parse(&value));
+                return;
+            }
+            "ED" | "A2" => {
+                self.editors.extend(Author::parse(&value));
+                return;
+            }
+            "KW" => {
+                self.keywords.push(value);
+                return;
+            }
+            "SP" => {
+                self.pages.0 = Some(value);
+                return;
+            }
+            "EP" => {
+                self.pages.1 = Some(value);
+                return;
+            }
+            "TI" | "T1" => "title",
+            "T2" | "JO" | "JF" | "JA" => match self.kind {
+                EntryKind::Article => "journal",
+                _ => "booktitle",
+            },
+            "PY" | "Y1" | "DA" => "year",
+            "VL" => "volume",
+            "IS" => "number",
+            "PB" => "publisher",
+            "SN" => match self.kind {
+                EntryKind::Book => "isbn",
+                _ => "issn",
+            },
+            "DO" => "doi",
+            "UR" => "url",
+            "Y2" => ast::URLDATE_FIELD,
+            "AB" | "N2" => "abstract",
+            _ => {
+                log::trace!("ignoring the RIS tag {tag}");
+                return;
+            }
+        };
+        self.fields.push((name, value));
+    }
+
+    fn resolve(self) -> Resolver {
+        let mut resolver = match self.cite {
+            Some(cite) => ast::Entry::resolver_with_cite(self.kind, cite),
+            None => ast::Entry::resolver(self.kind),
+        };
+
+        if !self.authors.is_empty() {
+            resolver.author(Author::join(&self.authors));
+        }
+        if !self.editors.is_empty() {
+            resolver.set_field("editor", Author::join(&self.editors));
+        }
+        if !self.keywords.is_empty() {
+            resolver.set_field("keywords", List::Items(self.keywords).value());
+        }
+        match self.pages {
+            (Some(start), Some(end)) => resolver.set_field("pages", format!("{start}--{end}")),
+            (Some(page), None) | (None, Some(page)) => resolver.set_field("pages", page),
+            (None, None) => {}
+        }
+        for (name, value) in self.fields {
+            match name {
+                // the year is the first part of a date, such as `2020/05/01/`
+                "year" => resolver.year(value.split('/').next().unwrap_or_default().to_owned()),
+                "booktitle" => resolver.book_title(value),
+                name => resolver.set_field(name, value),
+            }
+        }
+        resolver
+    }
+}
+
+#[cfg(all(test, feature = "bibtex"))]
+mod tests {
+    use super::*;
+    use crate::format::BibTex;
+
+    const RECORD: &str = "TY  - JOUR\n\
+                          ID  - knuth1984\n\
+                          AU  - Knuth, Donald E.\n\
+                          AU  - Leslie Lamport\n\
+                          TI  - Literate Programming\n\
+                          JO  - The Computer Journal\n\
+                          PY  - 1984/05/01/\n\
+                          VL  - 27\n\
+                          SP  - 97\n\
+                          EP  - 111\n\
+                          KW  - literate programming\n\
+                          KW  - TeX\n\
+                          ER  - \n";
+
+    #[test]
+    fn two_author_record_composes_to_bibtex_with_and_join() {
+        let biblio = Ris::new(RECORD.to_owned()).parse().unwrap().unwrap();
+        let entry = biblio.get("knuth1984").unwrap();
+
+        assert_eq!(
+            vec![
+                Author::new("Knuth", Some("Donald E.")),
+                Author::new("Lamport", Some("Leslie")),
+            ],
+            entry.authors()
+        );
+
+        let bibtex = BibTex::compose_entry(entry);
+        assert!(bibtex.contains("author = {Knuth, Donald E. and Lamport, Leslie}"));
+        assert!(bibtex.contains("year = {1984}"));
+        assert!(bibtex.contains("pages = {97--111}"));
+        assert!(bibtex.contains("keywords = {literate programming, TeX}"));
+    }
+
+    #[test]
+    fn bibtex_authors_compose_to_one_au_line_each() {
+        let biblio = BibTex::new(
+            "@article{knuth1984, author = {Donald E. Knuth and Lamport, Leslie}, \
+             title = {Literate Programming}, journal = {The Computer Journal}, year = {1984}, \
+             pages = {97--111}, keywords = {literate programming, TeX}, volume = {27}}"
+                .to_owned(),
+        )
+        .parse()
+        .unwrap()
+        .unwrap();
+
+        let ris = Ris::compose(&biblio).raw();
+
+        assert_eq!(
+            RECORD
+                .replace("Leslie Lamport", "Lamport, Leslie")
+                .replace("1984/05/01/", "1984")
+                .trim_end(),
+            ris.replace("T2  - ", "JO  - ").trim_end()
+        );
+    }
+
+    #[test]
+    fn issue_composes_to_bibtex_number() {
+        let ris = "TY  - JOUR\nID  - paper\nAU  - Knuth, Donald E.\nTI  - Title\n\
+                   JO  - Journal\nPY  - 2020\nIS  - 4\nER  - \n";
+        let biblio = Ris::new(ris.to_owned()).parse().unwrap().unwrap();
+
+        let bibtex = BibTex::compose_entry(biblio.get("paper").unwrap());
+        assert!(bibtex.contains("number = {4}"));
+        assert!(!bibtex.contains("issue"));
+        assert!(Ris::compose(&biblio).raw().contains("\nIS  - 4\n"));
+    }
+
+    #[test]
+    fn access_date_is_the_urldate() {
+        let ris = "TY  - ELEC\nID  - site\nTI  - Site\nUR  - https://example.com\n\
+                   Y2  - 2024-01-01\nER  - \n";
+        let biblio = Ris::new(ris.to_owned()).parse().unwrap().unwrap();
+
+        let entry = biblio.get("site").unwrap();
+        assert_eq!("2024-01-01", &**entry.get_field("urldate").unwrap());
+        assert!(Ris::compose(&biblio).raw().contains("Y2  - 2024-01-01\n"));
+    }
+
+    #[test]
+    fn record_without_end_tag_is_an_error() {
+        assert!(Ris::new("TY  - JOUR\nTI  - Title\n".to_owned())
+            .parse()
+            .is_err());
+    }
+
+    #[test]
+    fn records_without_an_id_are_given_a_cite_key() {
+        let ris = "TY  - GEN\nAU  - Knuth, Donald E.\nTI  - First\nPY  - 1984\nER  - \n\
+                   TY  - GEN\nID  - \nAU  - Knuth, Donald E.\nTI  - Second\nPY  - 1984\nER  - \n";
+        let biblio = Ris::new(ris.to_owned()).parse().unwrap().unwrap();
+
+        assert_eq!("First", &**biblio.get("Knuth1984").unwrap().title());
+        assert_eq!("Second", &**biblio.get("Knuth1984a").unwrap().title());
+    }
+
+    #[test]
+    fn generated_cite_keys_use_the_key_format_and_ignore_case() {
+        let ris = "TY  - GEN\nID  - knuth_first\nTI  - First\nER  - \n\
+                   TY  - GEN\nAU  - Knuth, Donald E.\nTI  - First\nER  - \n";
+        let format = "{author}_{title}".parse::<KeyFormat>().unwrap();
+        let biblio = Ris::new(ris.to_owned())
+            .parse_with(&format)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(2, biblio.entries().count());
+        assert!(biblio.get("Knuth_Firsta").is_some());
+    }
+}
@@ -0,0 +1,433 @@
+use std::{borrow::Cow, collections::HashMap, fmt, sync::Arc};
+
+use crate::ast::{Entry, Field, Pages, QuotedString};
+
+/// A change made to each field of an entry as it is composed, without changing the entry.
+///
+/// Transforms are registered in [`Transforms`], which applies them in order, so a transform sees
+/// the field as changed by the transforms before it.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use seb::{
+///     ast::{Entry, Field},
+///     format::FieldTransform,
+/// };
+///
+/// // Writes the keywords in uppercase.
+/// struct UpperKeywords;
+///
+/// impl FieldTransform for UpperKeywords {
+///     fn name(&self) -> &'static str {
+///         "upper-keywords"
+///     }
+///
+///     fn transform(&self, _: &Entry, field: &mut Field<'_>) {
+///         if field.name == "keywords" {
+///             field.value = Cow::Owned(field.value.map_parts(str::to_uppercase));
+///         }
+///     }
+/// }
+/// ```
+pub trait FieldTransform: Send + Sync {
+    /// The name of the transform, which identifies it in [`Transforms`].
+    fn name(&self) -> &'static str;
+
+    /// Changes the name or value of the `field` of the `entry`.
+    fn transform(&self, entry: &Entry, field: &mut Field<'_>);
+}
+
+/// The ordered pipeline of [`FieldTransform`]s applied to each field of an entry as it is
+/// composed, which is empty by [`Default`].
+///
+/// # Examples
+///
+/// ```
+/// use seb::format::{PageDash, TitleProtection, Transforms};
+///
+/// let mut transforms = Transforms::new().with(TitleProtection);
+/// transforms.insert(0, PageDash);
+///
+/// assert_eq!(vec!["page-dash", "title-protection"], transforms.names().collect::<Vec<_>>());
+///
+/// assert!(transforms.remove("page-dash"));
+/// assert_eq!(vec!["title-protection"], transforms.names().collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Default)]
+pub struct Transforms(Vec<Arc<dyn FieldTransform>>);
+
+impl Transforms {
+    /// Creates an empty [`Transforms`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the `transform` after the transforms already registered.
+    #[must_use]
+    pub fn with<T: FieldTransform + 'static>(mut self, transform: T) -> Self {
+        self.0.push(Arc::new(transform));
+        self
+    }
+
+    /// Adds the `transform` at the `index`, so that it is applied before the transforms after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `index` is greater than the number of transforms.
+    pub fn insert<T: FieldTransform + 'static>(&mut self, index: usize, transform: T) {
+        self.0.insert(index, Arc::new(transform));
+    }
+
+    /// Removes the transforms with the `name` and returns whether any were removed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|transform| transform.name() != name);
+        self.0.len() != len
+    }
+
+    /// The names of the transforms in the order they are applied.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.iter().map(|transform| transform.name())
+    }
+
+    /// Applies every transform to the `field` of the `entry` in order.
+    pub fn apply(&self, entry: &Entry, field: &mut Field<'_>) {
+        for transform in &self.0 {
+            transform.transform(entry, field);
+        }
+    }
+}
+
+impl fmt::Debug for Transforms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+// a transform has no equality of its own, so transforms are only equal when they hold the same
+// registered transforms in the same order, such as a clone, as two transforms of the same name
+// may be configured differently, such as two `Truncate`s
+impl PartialEq for Transforms {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl Eq for Transforms {}
+
+/// Writes a range of pages with the `--` en dash of `BibTeX`, such as `5--9` for `5-9` or `5–9`.
+///
+/// Any other value of the `pages` field, see [`Pages`], is written as it is.
+#[derive(Clone, Copy, Debug)]
+pub struct PageDash;
+
+impl FieldTransform for PageDash {
+    fn name(&self) -> &'static str {
+        "page-dash"
+    }
+
+    fn transform(&self, _: &Entry, field: &mut Field<'_>) {
+        if field.name != "pages" {
+            return;
+        }
+        if let Pages::Range(start, end) = Pages::from(field.value()) {
+            field.value = Cow::Owned(QuotedString::new(format!("{start}--{end}")));
+        }
+    }
+}
+
+/// Cuts the values of the fields given to at most a number of characters, ending with `...`, such
+/// as to keep long abstracts out of an exported file without changing the entries.
+///
+/// The characters are counted without the braces of the value and a value that is cut is left
+/// with its braces balanced, and without the part of a LaTeX command, such as `\alpha`, that is
+/// cut. The field names are matched ignoring case and underscores, so `book_title` and
+/// `booktitle` are the same field.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{
+///     ast::{Entry, EntryKind, Field, QuotedString},
+///     format::{FieldTransform, Truncate},
+/// };
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("Title");
+/// let entry = resolver.resolve().unwrap();
+///
+/// let truncate = Truncate::new().field("abstract", 11);
+/// let value = QuotedString::from("The DNA of sorting algorithms");
+/// let mut field = Field::from(("abstract", &value));
+/// truncate.transform(&entry, &mut field);
+///
+/// assert_eq!("The DNA of...", field.value());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Truncate(HashMap<String, usize>);
+
+impl Truncate {
+    /// Creates a [`Truncate`] that does not cut any field.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cuts the field with the `name` to at most `max` characters.
+    #[must_use]
+    pub fn field(mut self, name: &str, max: usize) -> Self {
+        self.0.insert(field_key(name), max);
+        self
+    }
+
+    /// Returns `true` when no field is cut.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<(String, usize)> for Truncate {
+    fn from_iter<I: IntoIterator<Item = (String, usize)>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), |truncate, (name, max)| {
+            truncate.field(&name, max)
+        })
+    }
+}
+
+impl FieldTransform for Truncate {
+    fn name(&self) -> &'static str {
+        "truncate"
+    }
+
+    fn transform(&self, _: &Entry, field: &mut Field<'_>) {
+        let Some(&max) = self.0.get(&field_key(&field.name)) else {
+            return;
+        };
+        if field.value().chars().count() <= max {
+            return;
+        }
+
+        let mut left = max;
+        let mut parts = Vec::new();
+        for (quoted, part) in field.value.parts() {
+            if left == 0 {
+                break;
+            }
+            let end = part.char_indices().nth(left).map_or(part.len(), |(i, _)| i);
+            left -= part[..end].chars().count();
+            parts.push((quoted, part[..command_start(part, end)].to_owned()));
+        }
+        if let Some((_, last)) = parts.last_mut() {
+            last.truncate(last.trim_end().len());
+        }
+        parts.push((false, "...".to_owned()));
+        field.value = Cow::Owned(QuotedString::from_parts(parts));
+    }
+}
+
+// The name of a field as it is matched by `Truncate` and `TitleProtection`, the required fields
+// of an entry are named with an underscore, such as `book_title`, which is the `booktitle` field.
+fn field_key(name: &str) -> String {
+    name.to_lowercase().replace('_', "")
+}
+
+// The end of the `text` cut at `end` without a LaTeX command, such as `\alpha`, that the cut
+// splits, which is cut before its backslash.
+fn command_start(text: &str, end: usize) -> usize {
+    let Some(backslash) = text[..end].rfind('\\') else {
+        return end;
+    };
+    let name = &text[backslash + 1..end];
+    let continues = text[end..].starts_with(char::is_alphabetic);
+    if name.chars().all(char::is_alphabetic) && (name.is_empty() || continues) {
+        backslash
+    } else {
+        end
+    }
+}
+
+/// Protects the words of a title that have a capital letter after the first letter, such as
+/// `DNA` or `LaTeX`, with braces so that a bibliography style does not change their case.
+///
+/// Both the `title` and `booktitle` fields are protected and a word that is already protected is
+/// left as it is.
+#[derive(Clone, Copy, Debug)]
+pub struct TitleProtection;
+
+impl FieldTransform for TitleProtection {
+    fn name(&self) -> &'static str {
+        "title-protection"
+    }
+
+    fn transform(&self, _: &Entry, field: &mut Field<'_>) {
+        if !matches!(&*field_key(&field.name), "title" | "booktitle") {
+            return;
+        }
+
+        let mut parts = Vec::new();
+        for (quoted, part) in field.value.parts() {
+            if quoted {
+                parts.push((true, part.to_owned()));
+            } else {
+                parts.extend(protect_words(part));
+            }
+        }
+        field.value = Cow::Owned(QuotedString::from_parts(parts));
+    }
+}
+
+// Splits the `text` into the words that need protecting, which are quoted, and the rest.
+fn protect_words(text: &str) -> Vec<(bool, String)> {
+    let mut parts: Vec<(bool, String)> = Vec::new();
+    let mut push = |quoted: bool, s: &str| match parts.last_mut() {
+        Some((false, last)) if !quoted => last.push_str(s),
+        _ => parts.push((quoted, s.to_owned())),
+    };
+
+    let separators = text
+        .char_indices()
+        .filter(|(_, c)| !c.is_alphanumeric())
+        .map(|(i, c)| (i, i + c.len_utf8()))
+        .chain([(text.len(), text.len())]);
+    let mut start = 0;
+    for (end, next) in separators {
+        let word = &text[start..end];
+        push(word.chars().skip(1).any(char::is_uppercase), word);
+        push(false, &text[end..next]);
+        start = next;
+    }
+
+    parts.retain(|(_, part)| !part.is_empty());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::EntryKind;
+
+    use super::*;
+
+    fn field<'a>(name: &'a str, value: &'a QuotedString) -> Field<'a> {
+        Field::from((name, value))
+    }
+
+    fn entry() -> Entry {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "manual");
+        resolver.title("Title");
+        resolver.resolve().unwrap()
+    }
+
+    #[test]
+    fn page_ranges_use_the_en_dash() {
+        for (pages, expected) in [("5-9", "5--9"), ("5 – 9", "5--9"), ("e12345", "e12345")] {
+            let value = QuotedString::from(pages);
+            let mut field = field("pages", &value);
+            PageDash.transform(&entry(), &mut field);
+            assert_eq!(expected, field.value());
+        }
+    }
+
+    #[test]
+    fn capitalised_words_of_titles_are_protected() {
+        let value = QuotedString::from_quoted("The DNA of $LaTeX$ and QuickXsort: a Study", '$');
+        let mut title = field("title", &value);
+        TitleProtection.transform(&entry(), &mut title);
+
+        assert_eq!(
+            "The {DNA} of {LaTeX} and {QuickXsort}: a Study",
+            title.value.map_quoted(|s| format!("{{{s}}}"))
+        );
+
+        let mut note = field("note", &value);
+        TitleProtection.transform(&entry(), &mut note);
+        assert_eq!(value, *note.value);
+    }
+
+    #[test]
+    fn long_values_are_truncated_with_balanced_braces() {
+        let truncate = Truncate::new().field("Abstract", 6).field("book_title", 20);
+        let value = QuotedString::from_quoted("The $DNA$ of sorting", '$');
+
+        let mut cut = field("abstract", &value);
+        truncate.transform(&entry(), &mut cut);
+        assert_eq!("The {DN}...", cut.value.map_quoted(|s| format!("{{{s}}}")));
+
+        for name in ["book_title", "note"] {
+            let mut field = field(name, &value);
+            truncate.transform(&entry(), &mut field);
+            assert_eq!(value, *field.value);
+        }
+    }
+
+    #[test]
+    fn field_names_are_matched_ignoring_underscores() {
+        let value = QuotedString::from("Proceedings of the Symposium on Sorting");
+        for truncate in [
+            Truncate::new().field("book_title", 14),
+            Truncate::new().field("BookTitle", 14),
+        ] {
+            for name in ["book_title", "booktitle"] {
+                let mut field = field(name, &value);
+                truncate.transform(&entry(), &mut field);
+                assert_eq!("Proceedings of...", field.value(), "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn latex_commands_are_not_split() {
+        let truncate = Truncate::new().field("abstract", 10);
+        for (abstract_, expected) in [
+            ("The \\alphabet of sorting", "The..."),
+            ("Sorting a\\b", "Sorting a..."),
+            ("Sorting \\a bc", "Sorting \\a..."),
+            ("A value \\alpha", "A value..."),
+        ] {
+            let value = QuotedString::from(abstract_);
+            let mut field = field("abstract", &value);
+            truncate.transform(&entry(), &mut field);
+            assert_eq!(expected, field.value(), "{abstract_}");
+        }
+    }
+
+    #[test]
+    fn transforms_are_applied_in_order() {
+        struct Suffix(&'static str);
+
+        impl FieldTransform for Suffix {
+            fn name(&self) -> &'static str {
+                self.0
+            }
+
+            fn transform(&self, _: &Entry, field: &mut Field<'_>) {
+                field.value = Cow::Owned(format!("{}{}", field.value(), self.0).into());
+            }
+        }
+
+        let mut transforms = Transforms::new().with(Suffix("b")).with(Suffix("c"));
+        assert_ne!(Transforms::new(), transforms);
+        transforms.insert(0, Suffix("a"));
+        let value = QuotedString::from("-");
+        let mut field = field("note", &value);
+        transforms.apply(&entry(), &mut field);
+
+        assert_eq!("-abc", field.value());
+        assert!(transforms.remove("b"));
+        assert!(!transforms.remove("b"));
+        assert_eq!(vec!["a", "c"], transforms.names().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn transforms_of_the_same_name_are_not_equal() {
+        let short = Transforms::new().with(Truncate::new().field("abstract", 12));
+        let long = Transforms::new().with(Truncate::new().field("abstract", 120));
+
+        assert_eq!(short, short.clone());
+        assert_ne!(short, long);
+    }
+}
@@ -16,14 +16,24 @@ mod error;
 #[cfg(feature = "file")]
 pub mod file;
 pub mod format;
+pub mod normalize;
+pub mod provider;
 
+pub use api::{
+    ads::ADS_TOKEN_VAR,
+    cross_ref::{set_contact_email, Retraction, CONTACT_EMAIL_VAR},
+    http::{set_http_options, HttpOptions, HEADERS_VAR, PROXY_VAR},
+    ieee::{IEEE_TOKEN_VAR, STANDARD_KIND},
+    rate_limit::{set_rate_limits, RateLimits},
+};
 use ast::{Biblio, BiblioResolver};
 pub use error::{Error, ErrorKind};
 
 use format::Format;
 use log::trace;
+use provider::{Provider, Query};
 
-type Client = reqwest::blocking::Client;
+type Client = api::http::HttpClient;
 
 /// Search bibliographic entries by `doi` using the default API.
 ///
@@ -40,7 +50,7 @@ type Client = reqwest::blocking::Client;
 #[inline]
 pub fn entries_by_doi(doi: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
     trace!("Search entries by doi of '{doi}'");
-    api::cross_ref::get_entries_by_doi::<Client>(doi)
+    provider::CrossRef.lookup(&Query::Doi(doi))
 }
 
 /// Search bibliographic entries by `isbn` using the default API.
@@ -57,7 +67,7 @@ pub fn entries_by_doi(doi: &str) -> Result<Result<Biblio, BiblioResolver>, Error
 #[inline]
 pub fn entries_by_isbn(isbn: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
     trace!("Search entries by ISBN of '{isbn}'");
-    api::google_books::get_entries_by_isbn::<Client>(isbn)
+    provider::GoogleBooks.lookup(&Query::Isbn(isbn))
 }
 
 /// Search bibliographic entries by `IETF RFC number`.
@@ -75,7 +85,44 @@ pub fn entries_by_isbn(isbn: &str) -> Result<Result<Biblio, BiblioResolver>, Err
 #[inline]
 pub fn entries_by_rfc(number: usize) -> Result<Result<Biblio, BiblioResolver>, Error> {
     trace!("Search entries by IETF RFC number '{number}'");
-    api::ietf::get_entry_by_rfc::<Client>(number)
+    provider::Ietf.lookup(&Query::Rfc(number))
+}
+
+/// Search bibliographic entries by [ADS bibcode](https://ui.adsabs.harvard.edu/help/actions/bibcode)
+/// using the NASA Astrophysics Data System API.
+///
+/// The ADS API requires a token which is read from the `SEB_ADS_TOKEN` environment variable.
+///
+/// # Errors
+///
+/// An `Err` is returned when the `SEB_ADS_TOKEN` environment variable is not set.
+/// An `Err` is returned when no entry is found for the `bibcode`.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid [Entry][E].
+///
+/// [E]: ast::Entry
+#[inline]
+pub fn entries_by_bibcode(bibcode: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries by ADS bibcode of '{bibcode}'");
+    provider::Ads.lookup(&Query::Bibcode(bibcode))
+}
+
+/// Search bibliographic entries by an IEEE standard number, such as `IEEE 754` or
+/// `IEEE Std 754-2019`, using the IEEE Xplore API.
+///
+/// The IEEE Xplore API requires a key which is read from the `SEB_IEEE_TOKEN` environment variable.
+///
+/// # Errors
+///
+/// An `Err` is returned when the `standard` is not an IEEE standard.
+/// An `Err` is returned when the `SEB_IEEE_TOKEN` environment variable is not set.
+/// An `Err` is returned when no entry is found for the `standard`.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid [Entry][E].
+///
+/// [E]: ast::Entry
+#[inline]
+pub fn entries_by_standard(standard: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries by standard of '{standard}'");
+    provider::Ieee.lookup(&Query::Standard(standard))
 }
 
 /// Search bibliographic entries at a given `url` when the expected text format matches the `F:
@@ -94,8 +141,70 @@ pub fn entries_by_url<F: Format>(url: &str) -> Result<Result<Biblio, BiblioResol
     api::format_api::get_entry_by_url::<Client, F>(url)
 }
 
+/// Gets the text at the `url`, such as an RSS or Atom feed.
+///
+/// # Errors
+///
+/// Will return [`Err`] when the request fails or the response is empty.
+#[inline]
+pub fn text_by_url(url: &str) -> Result<String, Error> {
+    trace!("Get the text at url of '{url}'");
+    api::Client::get_text(&Client::default(), url)
+}
+
+/// The field that [`attach_oa_url`] stores the URL of the best open access PDF in.
+pub const OA_URL_FIELD: &str = api::unpaywall::OA_URL_FIELD;
+
+/// Looks up the DOI of the `entry` using the [Unpaywall](https://unpaywall.org/products/api) API
+/// and returns the URL of the best open access PDF, without changing the `entry`.
+///
+/// The Unpaywall API requires the `email` address of the user with every request. Returns `None`
+/// when the entry has no DOI or no open access PDF was found.
+///
+/// # Errors
+///
+/// An `Err` is returned when the DOI is unknown to Unpaywall or the response cannot be parsed.
+#[inline]
+pub fn oa_url(entry: &ast::Entry, email: &str) -> Result<Option<String>, Error> {
+    trace!("Search open access PDF of '{}'", entry.cite());
+    api::unpaywall::oa_url::<Client>(entry, email)
+}
+
+/// Looks up the DOI of the `entry` using the [Unpaywall](https://unpaywall.org/products/api) API
+/// and stores the URL of the best open access PDF in the [`OA_URL_FIELD`], see [`oa_url`]. The
+/// field is only set when the URL is different.
+///
+/// The Unpaywall API requires the `email` address of the user with every request. Returns `false`
+/// when the entry has no DOI or no open access PDF was found.
+///
+/// # Errors
+///
+/// An `Err` is returned when the DOI is unknown to Unpaywall or the response cannot be parsed.
+#[inline]
+pub fn attach_oa_url(entry: &mut ast::Entry, email: &str) -> Result<bool, Error> {
+    trace!("Search open access PDF of '{}'", entry.cite());
+    api::unpaywall::attach_oa_url::<Client>(entry, email)
+}
+
+/// Searches Crossref for the notices that update the work with the `doi`, which include the
+/// retractions collected by Retraction Watch, and returns the most serious [`Retraction`].
+///
+/// Returns `None` when the work has not been retracted and has no expression of concern.
+///
+/// # Errors
+///
+/// An `Err` is returned when the request fails or the response cannot be parsed.
+#[inline]
+pub fn retraction_by_doi(doi: &str) -> Result<Option<Retraction>, Error> {
+    trace!("Search retraction notices of doi '{doi}'");
+    api::cross_ref::get_retraction::<Client>(doi, api::cross_ref::contact_email().as_deref())
+}
+
 /// # Errors
 pub fn entry_stubs_by_title(title: &str) -> Result<Vec<(String, String)>, Error> {
     trace!("Search entries that have a title of '{title}'");
-    api::cross_ref::get_entry_stubs_by_title::<Client>(title)
+    api::cross_ref::get_entry_stubs_by_title::<Client>(
+        title,
+        api::cross_ref::contact_email().as_deref(),
+    )
 }
@@ -0,0 +1,856 @@
+//! Normalization of field values.
+//!
+//! Each normalization is a function that takes a field value and returns `Some` with the
+//! normalized value only when the value changes, so they can be used with
+//! [`Biblio::map_fields`][MF].
+//!
+//! [MF]: crate::ast::Biblio::map_fields
+
+use std::fmt::Write;
+
+use crate::ast::QuotedString;
+
+/// Repairs UTF-8 text that was decoded as Windows-1252 (or Latin-1) and encoded as UTF-8 again,
+/// known as mojibake, such as `"Ã©"` instead of `"é"`.
+///
+/// Only sequences of characters that are a complete UTF-8 encoded character when mapped back to
+/// their Windows-1252 bytes are repaired, all other text is left as it is.
+///
+/// Returns `None` when no mojibake is found.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::fix_mojibake;
+///
+/// assert_eq!(Some("Poincaré".to_owned()), fix_mojibake("PoincarÃ©"));
+/// assert_eq!(None, fix_mojibake("Poincaré"));
+/// ```
+#[must_use]
+pub fn fix_mojibake(value: &str) -> Option<String> {
+    let chars = value.chars().collect::<Vec<_>>();
+    let mut fixed = String::with_capacity(value.len());
+    let mut changed = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        if let Some((c, len)) = decode_mojibake(&chars[i..]) {
+            fixed.push(c);
+            changed = true;
+            i += len;
+        } else {
+            fixed.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    changed.then_some(fixed)
+}
+
+/// [`fix_mojibake`] for each part of a [`QuotedString`].
+#[must_use]
+pub fn fix_quoted_mojibake(value: &QuotedString) -> Option<QuotedString> {
+    let fixed = value.map_parts(|part| fix_mojibake(part).unwrap_or_else(|| part.to_owned()));
+    (fixed != *value).then_some(fixed)
+}
+
+/// The fields whose values are verbatim, such as a URL or a file path, so their whitespace is
+/// part of the value and is never collapsed.
+pub const VERBATIM_FIELDS: &[&str] = &["doi", "eprint", "file", "pdf", "url"];
+
+/// Collapses every run of whitespace, such as double spaces, tabs and newlines, to a single space
+/// and trims the whitespace from both ends.
+///
+/// A blank line between two paragraphs, such as in an abstract, is kept as a single blank line.
+///
+/// Returns `None` when the whitespace is already normalized.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::collapse_whitespace;
+///
+/// assert_eq!(
+///     Some("A title over two lines".to_owned()),
+///     collapse_whitespace(" A title\n  over two\tlines")
+/// );
+/// assert_eq!(
+///     Some("First paragraph.\n\nSecond paragraph.".to_owned()),
+///     collapse_whitespace("First  paragraph.\n  \n\nSecond paragraph.\n")
+/// );
+/// assert_eq!(None, collapse_whitespace("A title"));
+/// ```
+#[must_use]
+pub fn collapse_whitespace(value: &str) -> Option<String> {
+    let collapsed = collapse_paragraphs(value);
+    (collapsed != value).then_some(collapsed)
+}
+
+// Collapses the whitespace of each paragraph of the `value` to single spaces and joins the
+// paragraphs with a single blank line.
+fn collapse_paragraphs(value: &str) -> String {
+    let mut paragraphs = vec![Vec::new()];
+    for line in value.lines() {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match paragraphs.last_mut() {
+            Some(paragraph) if !words.is_empty() => paragraph.extend(words),
+            Some(paragraph) if !paragraph.is_empty() => paragraphs.push(Vec::new()),
+            _ => {}
+        }
+    }
+
+    paragraphs
+        .into_iter()
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// [`collapse_whitespace`] for the parts of a [`QuotedString`] that are not quoted, the quoted
+/// parts, such as the brace protected `{DNA}`, are kept exactly as they are.
+///
+/// This is not meant for the [`VERBATIM_FIELDS`], whose whitespace is part of the value.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{ast::QuotedString, normalize::collapse_quoted_whitespace};
+///
+/// let title = QuotedString::from_quoted("The  {DNA  of}\n {TeX} ", ['{', '}']);
+/// let collapsed = collapse_quoted_whitespace(&title).unwrap();
+///
+/// assert_eq!(
+///     vec![(false, "The "), (true, "DNA  of"), (false, " "), (true, "TeX")],
+///     collapsed.parts()
+/// );
+/// ```
+#[must_use]
+pub fn collapse_quoted_whitespace(value: &QuotedString) -> Option<QuotedString> {
+    let parts = value.parts();
+    let last = parts.len().saturating_sub(1);
+
+    let collapsed = parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (quoted, part))| {
+            if quoted {
+                return (true, part.to_owned());
+            }
+            let words = collapse_paragraphs(part);
+            // the whitespace next to a quoted part separates it from the next word
+            let before = i > 0 && part.starts_with(char::is_whitespace);
+            let after = i < last && part.ends_with(char::is_whitespace);
+            let part = match (words.is_empty(), before, after) {
+                (true, true, true) => " ".to_owned(),
+                (true, ..) => String::new(),
+                (false, before, after) => format!(
+                    "{}{words}{}",
+                    if before { " " } else { "" },
+                    if after { " " } else { "" }
+                ),
+            };
+            (false, part)
+        })
+        .filter(|(_, part)| !part.is_empty())
+        .collect();
+
+    let collapsed = QuotedString::from_parts(collapsed);
+    (collapsed != *value).then_some(collapsed)
+}
+
+/// Collapses a page range that starts and ends on the same page, such as `1-1` or `e5--e5`, to
+/// the single page, so that a reference list shows `p. 1` rather than `pp. 1–1`.
+///
+/// A range of more than one page and any other value, such as the article number `e12345`, are
+/// left as they are, see [`Pages`](crate::ast::Pages).
+///
+/// Returns `None` when the value is not a range of a single page.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::collapse_page_range;
+///
+/// assert_eq!(Some("1".to_owned()), collapse_page_range("1-1"));
+/// assert_eq!(None, collapse_page_range("5--9"));
+/// assert_eq!(None, collapse_page_range("e12345"));
+/// ```
+#[must_use]
+pub fn collapse_page_range(value: &str) -> Option<String> {
+    let (start, end) = ["--", "-", "–", "—"]
+        .iter()
+        .find_map(|dash| value.split_once(dash))?;
+    let (start, end) = (start.trim(), end.trim());
+    (!start.is_empty() && start == end).then(|| start.to_owned())
+}
+
+/// The acronyms that [`sentence_case`] keeps as they are written here, which the acronyms given
+/// to it are added to.
+///
+/// An acronym that is also a word, such as `US` or `SAT`, is not in the list since the word would
+/// be written as the acronym, and can be given to [`sentence_case`] when a title needs it.
+pub const ACRONYMS: &[&str] = &[
+    "2D", "3D", "AI", "API", "CPU", "DNA", "FPGA", "GPU", "GPS", "HIV", "HTML", "HTTP", "IEEE",
+    "IoT", "LLM", "MRI", "NLP", "PDF", "RNA", "SQL", "UK", "UML", "URL", "USA", "XML",
+];
+
+/// The share of the cased letters of a title that are uppercase for [`sentence_case`] to treat
+/// the title as written in all caps.
+const UPPERCASE_SHARE: f64 = 0.8;
+
+/// The punctuation that ends a sentence of a title, so that the next word starts with a capital.
+const SENTENCE_ENDS: [char; 4] = [':', '.', '?', '!'];
+
+/// Converts a title written in all caps, such as `"MACHINE LEARNING FOR DNA"`, to sentence case,
+/// `"Machine learning for DNA"`.
+///
+/// The first word of the title and of each sentence after a `:`, `.`, `?` or `!` starts with a
+/// capital letter, the [`ACRONYMS`] and the `acronyms` given are written as they are in the
+/// list, and words with a digit, such as `COVID-19`, are left as they are. A title is only
+/// converted when most of its letters are uppercase, so a title in any other case is never
+/// changed.
+///
+/// Returns `None` when the title is not written in all caps.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::sentence_case;
+///
+/// assert_eq!(
+///     Some("Machine learning for DNA: A CRISPR survey".to_owned()),
+///     sentence_case("MACHINE LEARNING FOR DNA: A CRISPR SURVEY", &["CRISPR"])
+/// );
+/// assert_eq!(None, sentence_case("Machine Learning for DNA", &["CRISPR"]));
+/// ```
+#[must_use]
+pub fn sentence_case<S: AsRef<str>>(value: &str, acronyms: &[S]) -> Option<String> {
+    let quoted = QuotedString::new(value.to_owned());
+    sentence_case_quoted(&quoted, acronyms).map(|title| title.to_string())
+}
+
+/// [`sentence_case`] for the parts of a [`QuotedString`] that are not quoted, the quoted parts,
+/// such as the brace protected `{TeX}`, are kept exactly as they are.
+///
+/// Only the letters outside of the quoted parts decide whether the title is in all caps.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{ast::QuotedString, normalize::sentence_case_quoted};
+///
+/// let title = QuotedString::from_quoted("THE {TeX}BOOK", ['{', '}']);
+/// let title = sentence_case_quoted(&title, &[] as &[&str]).unwrap();
+///
+/// assert_eq!(vec![(false, "The "), (true, "TeX"), (false, "book")], title.parts());
+/// ```
+#[must_use]
+pub fn sentence_case_quoted<S: AsRef<str>>(
+    value: &QuotedString,
+    acronyms: &[S],
+) -> Option<QuotedString> {
+    let parts = value.parts();
+    let (mut upper, mut cased) = (0_u32, 0_u32);
+    for c in parts
+        .iter()
+        .filter(|(quoted, _)| !quoted)
+        .flat_map(|(_, part)| part.chars())
+    {
+        if c.is_uppercase() {
+            upper += 1;
+            cased += 1;
+        } else if c.is_lowercase() {
+            cased += 1;
+        }
+    }
+    if cased == 0 || f64::from(upper) < f64::from(cased) * UPPERCASE_SHARE {
+        return None;
+    }
+
+    let acronym = |word: &str| {
+        ACRONYMS
+            .iter()
+            .copied()
+            .chain(acronyms.iter().map(AsRef::as_ref))
+            .find(|acronym| acronym.to_lowercase() == word.to_lowercase())
+    };
+    let mut sentence_start = true;
+    let mut converted = Vec::new();
+    for (quoted, part) in parts {
+        if quoted {
+            sentence_start = false;
+            converted.push((true, part.to_owned()));
+            continue;
+        }
+
+        let mut text = String::with_capacity(part.len());
+        for token in part.split_inclusive(char::is_whitespace) {
+            // a word with a digit is a name or a designation, such as `COVID-19` or `GPT-4`
+            if token.contains(char::is_numeric) {
+                text.push_str(token);
+                sentence_start = token.trim_end().ends_with(SENTENCE_ENDS);
+                continue;
+            }
+            for (is_word, run) in runs(token) {
+                if !is_word {
+                    sentence_start |= run.contains(SENTENCE_ENDS);
+                    text.push_str(run);
+                } else if let Some(acronym) = acronym(run) {
+                    text.push_str(acronym);
+                    sentence_start = false;
+                } else if sentence_start {
+                    let mut chars = run.chars();
+                    text.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+                    text.push_str(&chars.as_str().to_lowercase());
+                    sentence_start = false;
+                } else {
+                    text.push_str(&run.to_lowercase());
+                }
+            }
+        }
+        converted.push((false, text));
+    }
+
+    let converted = QuotedString::from_parts(converted);
+    (converted != *value).then_some(converted)
+}
+
+// Splits the `text` into the runs of alphanumeric characters, the words, and the runs of the other
+// characters between them.
+fn runs(text: &str) -> Vec<(bool, &str)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut is_word = None;
+    for (i, c) in text.char_indices() {
+        let alphanumeric = c.is_alphanumeric();
+        if is_word.is_some_and(|is_word| is_word != alphanumeric) {
+            runs.push((!alphanumeric, &text[start..i]));
+            start = i;
+        }
+        is_word = Some(alphanumeric);
+    }
+    if let Some(is_word) = is_word {
+        runs.push((is_word, &text[start..]));
+    }
+    runs
+}
+
+// Decodes the character at the start of `chars` when it and the following characters are the
+// Windows-1252 representation of the UTF-8 bytes of a single character.
+fn decode_mojibake(chars: &[char]) -> Option<(char, usize)> {
+    let lead = cp1252_byte(*chars.first()?)?;
+    let len = match lead {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+
+    let mut bytes = vec![lead];
+    for c in chars.get(1..len)? {
+        bytes.push(cp1252_byte(*c).filter(|b| (0x80..=0xBF).contains(b))?);
+    }
+
+    let decoded = std::str::from_utf8(&bytes).ok()?.chars().next()?;
+    Some((decoded, len))
+}
+
+// The Windows-1252 byte of a character, bytes 0x80 to 0x9F are mostly printable characters in
+// Windows-1252 and the rest are C1 control characters in Latin-1.
+fn cp1252_byte(c: char) -> Option<u8> {
+    let byte = match c {
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8A,
+        '‹' => 0x8B,
+        'Œ' => 0x8C,
+        'Ž' => 0x8E,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9A,
+        '›' => 0x9B,
+        'œ' => 0x9C,
+        'ž' => 0x9E,
+        'Ÿ' => 0x9F,
+        c => return u8::try_from(u32::from(c)).ok(),
+    };
+    Some(byte)
+}
+
+/// Writes every character that is not ASCII as the LaTeX command for it, such as `{\"{i}}` for
+/// `"ï"`, so that the value is pure ASCII.
+///
+/// Dashes, quotation marks and non-breaking spaces are written as their text commands, such as
+/// `{\textendash}` for `"–"`, so that they are read back as the characters. A character without a
+/// known LaTeX command is written as its code point, such as `{\char"263A}` for `"☺"`, and is also
+/// returned so that it can be reported.
+///
+/// Returns `None` when the value is already ASCII, see [`latex_to_unicode`] for the reverse.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::unicode_to_latex;
+///
+/// assert_eq!(
+///     Some((r#"na{\"{i}}ve caf{\'{e}}"#.to_owned(), vec![])),
+///     unicode_to_latex("naïve café")
+/// );
+/// assert_eq!(
+///     Some((r#"{\char"263A}"#.to_owned(), vec!['☺'])),
+///     unicode_to_latex("☺")
+/// );
+/// assert_eq!(None, unicode_to_latex("naive"));
+/// ```
+#[must_use]
+pub fn unicode_to_latex(value: &str) -> Option<(String, Vec<char>)> {
+    if value.is_ascii() {
+        return None;
+    }
+
+    let mut latex = String::with_capacity(value.len() + 16);
+    let mut unknown = Vec::new();
+    for c in value.chars() {
+        if c.is_ascii() {
+            latex.push(c);
+        } else if let Some(command) = latex_command(c) {
+            latex.push('{');
+            latex.push_str(&command);
+            latex.push('}');
+        } else {
+            let _ = write!(latex, r#"{{\char"{:X}}}"#, u32::from(c));
+            unknown.push(c);
+        }
+    }
+
+    Some((latex, unknown))
+}
+
+/// Reads the LaTeX commands for accented letters and symbols as the characters they stand for,
+/// such as `"ï"` for `\"{i}` or `\"i`, which is the reverse of [`unicode_to_latex`].
+///
+/// The braces around a single command, such as `{\ss}`, are read with the command. Any other
+/// command, such as `\LaTeX`, is left as it is. Returns `None` when no command is read.
+///
+/// # Examples
+///
+/// ```
+/// use seb::normalize::latex_to_unicode;
+///
+/// assert_eq!(Some("naïve Weiß".to_owned()), latex_to_unicode(r#"na\"{i}ve Wei\ss"#));
+/// assert_eq!(None, latex_to_unicode(r"The \LaTeX{} guide"));
+/// ```
+#[must_use]
+pub fn latex_to_unicode(value: &str) -> Option<String> {
+    if !value.contains('\\') {
+        return None;
+    }
+
+    let mut decoded = String::with_capacity(value.len());
+    let mut changed = false;
+    let mut rest = value;
+    while let Some(i) = rest.find('\\') {
+        // the braces around a single command, such as `{\ss}`, are only there to protect it
+        let braced = rest[..i].ends_with('{');
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some((c, len)) = decode_command(rest) {
+            if braced && rest[len..].starts_with('}') {
+                decoded.pop();
+                rest = &rest[len + 1..];
+            } else {
+                rest = &rest[len..];
+            }
+            decoded.push(c);
+            changed = true;
+        } else {
+            decoded.push('\\');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+
+    changed.then_some(decoded)
+}
+
+/// [`latex_to_unicode`] for each part of a [`QuotedString`], where the braces around a single
+/// command, such as `{\"{i}}`, only protect the command so the character is not quoted.
+///
+/// Returns `None` when no command is read.
+///
+/// # Examples
+///
+/// ```
+/// use seb::{ast::QuotedString, normalize::latex_to_unicode_quoted};
+///
+/// let value = QuotedString::from_parts(vec![
+///     (false, "na".to_owned()),
+///     (true, r#"\"{i}"#.to_owned()),
+///     (false, "ve ".to_owned()),
+///     (true, r"T\'{e}X".to_owned()),
+/// ]);
+/// let value = latex_to_unicode_quoted(&value).unwrap();
+///
+/// assert_eq!("naïve {TéX}", value.map_quoted(|s| format!("{{{s}}}")));
+/// ```
+#[must_use]
+pub fn latex_to_unicode_quoted(value: &QuotedString) -> Option<QuotedString> {
+    let mut changed = false;
+    let parts = value
+        .parts()
+        .into_iter()
+        .map(|(quoted, part)| match latex_to_unicode(part) {
+            Some(decoded) => {
+                changed = true;
+                let command = part.starts_with('\\') && decoded.chars().count() == 1;
+                (quoted && !command, decoded)
+            }
+            None => (quoted, part.to_owned()),
+        })
+        .collect::<Vec<_>>();
+
+    changed.then(|| QuotedString::from_parts(parts))
+}
+
+// The accents with the letters they are applied to and the accented letters, in the same order.
+const ACCENTS: &[(&str, &str, &str)] = &[
+    ("`", "AEIOUaeiou", "ÀÈÌÒÙàèìòù"),
+    ("'", "AEIOUYaeiouyCcLlNnRrSsZz", "ÁÉÍÓÚÝáéíóúýĆćĹĺŃńŔŕŚśŹź"),
+    ("^", "AEIOUaeiouCcGgHhJjSsWwYy", "ÂÊÎÔÛâêîôûĈĉĜĝĤĥĴĵŜŝŴŵŶŷ"),
+    ("\"", "AEIOUaeiouyY", "ÄËÏÖÜäëïöüÿŸ"),
+    ("~", "ANOanoIiUu", "ÃÑÕãñõĨĩŨũ"),
+    ("=", "AaEeIiOoUu", "ĀāĒēĪīŌōŪū"),
+    (".", "CcEeGgIZz", "ĊċĖėĠġİŻż"),
+    ("u", "AaEeGgIiOoUu", "ĂăĔĕĞğĬĭŎŏŬŭ"),
+    ("v", "CcDdEeNnRrSsTtZz", "ČčĎďĚěŇňŘřŠšŤťŽž"),
+    ("H", "OoUu", "ŐőŰű"),
+    ("c", "CcSsTtGgKkLlNnRr", "ÇçŞşŢţĢģĶķĻļŅņŖŗ"),
+    ("k", "AaEeIiUu", "ĄąĘęĮįŲų"),
+    ("r", "AaUu", "ÅåŮů"),
+];
+
+// The characters written as a LaTeX command without an argument.
+const SYMBOLS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('æ', "ae"),
+    ('Æ', "AE"),
+    ('ø', "o"),
+    ('Ø', "O"),
+    ('œ', "oe"),
+    ('Œ', "OE"),
+    ('ł', "l"),
+    ('Ł', "L"),
+    ('ı', "i"),
+    ('ȷ', "j"),
+    ('å', "aa"),
+    ('Å', "AA"),
+    ('ð', "dh"),
+    ('Ð', "DH"),
+    ('þ', "th"),
+    ('Þ', "TH"),
+    ('…', "ldots"),
+    ('§', "S"),
+    ('¶', "P"),
+    ('©', "copyright"),
+    ('£', "pounds"),
+    ('¡', "textexclamdown"),
+    ('¿', "textquestiondown"),
+    ('–', "textendash"),
+    ('—', "textemdash"),
+    ('‘', "textquoteleft"),
+    ('’', "textquoteright"),
+    ('“', "textquotedblleft"),
+    ('”', "textquotedblright"),
+    ('\u{a0}', "nobreakspace"),
+];
+
+// The LaTeX command for an accented letter or symbol, without the surrounding braces.
+fn latex_command(c: char) -> Option<String> {
+    ACCENTS
+        .iter()
+        .find_map(|(accent, bases, accented)| {
+            let i = accented.chars().position(|a| a == c)?;
+            let base = bases.chars().nth(i)?;
+            Some(format!("\\{accent}{{{base}}}"))
+        })
+        .or_else(|| {
+            SYMBOLS
+                .iter()
+                .find(|(symbol, _)| *symbol == c)
+                .map(|(_, name)| format!("\\{name}"))
+        })
+}
+
+// Decodes the LaTeX command at the start of `src`, returning the character it stands for and the
+// length of the command.
+fn decode_command(src: &str) -> Option<(char, usize)> {
+    let body = src.strip_prefix('\\')?;
+    let first = body.chars().next()?;
+    if !first.is_ascii_alphabetic() {
+        // an accent that is a symbol, such as `\"`, is followed by the letter with or without braces
+        let (base, len) = accent_argument(&body[first.len_utf8()..], false)?;
+        let c = accented(&first.to_string(), base)?;
+        return Some((c, 1 + first.len_utf8() + len));
+    }
+
+    let name_len = body
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(body.len());
+    let (name, after) = body.split_at(name_len);
+    if name == "char" {
+        let hex = after.strip_prefix('"')?;
+        let digits = hex
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(hex.len());
+        let c = u32::from_str_radix(&hex[..digits], 16)
+            .ok()
+            .and_then(char::from_u32)?;
+        return Some((c, 1 + name_len + 1 + digits));
+    }
+    if let Some((c, _)) = SYMBOLS.iter().find(|(_, symbol)| *symbol == name) {
+        let empty = if after.starts_with("{}") { 2 } else { 0 };
+        return Some((*c, 1 + name_len + empty));
+    }
+
+    // an accent that is a letter, such as `\v`, is followed by a space when the letter has no braces
+    let (base, len) = accent_argument(after, true)?;
+    accented(name, base).map(|c| (c, 1 + name_len + len))
+}
+
+// The letter an accent is applied to, written as `{i}`, `{\i}` or `i`, and the length of it.
+fn accent_argument(src: &str, needs_space: bool) -> Option<(char, usize)> {
+    if let Some(braced) = src.strip_prefix('{') {
+        let end = braced.find('}')?;
+        let base = match &braced[..end] {
+            r"\i" => 'i',
+            r"\j" => 'j',
+            base => {
+                let mut chars = base.chars();
+                chars.next().filter(|_| chars.next().is_none())?
+            }
+        };
+        return Some((base, end + 2));
+    }
+
+    let spaces = src.len() - src.trim_start_matches(' ').len();
+    if needs_space && spaces == 0 {
+        return None;
+    }
+    let base = src[spaces..]
+        .chars()
+        .next()
+        .filter(char::is_ascii_alphabetic)?;
+    Some((base, spaces + 1))
+}
+
+fn accented(accent: &str, base: char) -> Option<char> {
+    let (_, bases, accented) = ACCENTS.iter().find(|(a, _, _)| *a == accent)?;
+    let i = bases.chars().position(|b| b == base)?;
+    accented.chars().nth(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_is_collapsed_outside_braces() {
+        let title = QuotedString::from_quoted(
+            "  Sorting\n   in place:\tthe {QuickXsort  Algorithm}\n",
+            ['{', '}'],
+        );
+        let collapsed = collapse_quoted_whitespace(&title).expect("whitespace changes");
+
+        assert_eq!(
+            vec![
+                (false, "Sorting in place: the "),
+                (true, "QuickXsort  Algorithm")
+            ],
+            collapsed.parts()
+        );
+        assert_eq!(None, collapse_quoted_whitespace(&collapsed));
+    }
+
+    #[test]
+    fn paragraph_breaks_are_kept() {
+        let abstract_ = QuotedString::from_quoted(
+            "We sort  {in place}.\n\n\n  The results\nare {QuickXsort}.\n",
+            ['{', '}'],
+        );
+
+        let collapsed = collapse_quoted_whitespace(&abstract_).expect("whitespace changes");
+
+        assert_eq!(
+            vec![
+                (false, "We sort "),
+                (true, "in place"),
+                (false, ".\n\nThe results are "),
+                (true, "QuickXsort"),
+                (false, "."),
+            ],
+            collapsed.parts()
+        );
+    }
+
+    #[test]
+    fn all_caps_title_is_sentence_cased() {
+        assert_eq!(
+            Some("Machine learning for DNA sequencing. A study of COVID-19 in the USA".to_owned()),
+            sentence_case(
+                "MACHINE LEARNING FOR DNA SEQUENCING. A STUDY OF COVID-19 IN THE USA",
+                &[] as &[&str]
+            )
+        );
+        assert_eq!(
+            Some("Don't stop: The IoT and CRISPR-based tools".to_owned()),
+            sentence_case("DON'T STOP: THE IOT AND CRISPR-BASED TOOLS", &["CRISPR"])
+        );
+        // a word is not written as an acronym unless the acronym is given
+        assert_eq!(
+            Some("Let us go: The SAT solver".to_owned()),
+            sentence_case("LET US GO: THE SAT SOLVER", &["SAT"])
+        );
+    }
+
+    #[test]
+    fn titles_not_in_all_caps_are_unchanged() {
+        for title in [
+            "Machine Learning for DNA",
+            "Machine learning for DNA",
+            "A Study of the HTTP API",
+            "2021",
+        ] {
+            assert_eq!(None, sentence_case(title, &[] as &[&str]), "{title}");
+        }
+
+        let protected = QuotedString::from_quoted("{THE DNA OF} Sorting", ['{', '}']);
+        assert_eq!(None, sentence_case_quoted(&protected, &[] as &[&str]));
+    }
+
+    #[test]
+    fn single_page_range_is_collapsed() {
+        for pages in ["1-1", "1--1", "1 – 1", "1—1"] {
+            assert_eq!(Some("1".to_owned()), collapse_page_range(pages), "{pages}");
+        }
+        // an article number written as a range of a single page
+        assert_eq!(
+            Some("e1234".to_owned()),
+            collapse_page_range("e1234--e1234")
+        );
+    }
+
+    #[test]
+    fn page_ranges_and_article_numbers_are_unchanged() {
+        for pages in [
+            "5-9", "5--9", "9-5", "e1-e7", "e12345", "12345", "xii--xv", "5+", "-",
+        ] {
+            assert_eq!(None, collapse_page_range(pages), "{pages}");
+        }
+    }
+
+    #[test]
+    fn known_mojibake_is_repaired() {
+        let cases = [
+            ("Ã©", "é"),
+            ("MÃ¼ller", "Müller"),
+            ("Ã…ngstrÃ¶m", "Ångström"),
+            ("donâ€™t", "don’t"),
+            ("â€œquotedâ€\u{9d}", "“quoted”"),
+            ("Ã±", "ñ"),
+            ("æ—¥æœ¬", "日本"),
+        ];
+
+        for (mojibake, expected) in cases {
+            assert_eq!(
+                Some(expected.to_owned()),
+                fix_mojibake(mojibake),
+                "{mojibake}"
+            );
+        }
+    }
+
+    #[test]
+    fn correct_text_is_left_alone() {
+        for correct in [
+            "Poincaré",
+            "Müller and Ångström",
+            "日本",
+            "plain ASCII",
+            "Ã alone",
+        ] {
+            assert_eq!(None, fix_mojibake(correct), "{correct}");
+        }
+    }
+
+    #[test]
+    fn quoted_parts_are_kept() {
+        let value = QuotedString::from_parts(vec![
+            (false, "MÃ¼ller on ".to_owned()),
+            (true, "TÃ©X".to_owned()),
+        ]);
+        let fixed = fix_quoted_mojibake(&value).expect("Contains mojibake");
+
+        assert_eq!("Müller on {TéX}", fixed.map_quoted(|s| format!("{{{s}}}")));
+    }
+
+    #[test]
+    fn every_accent_has_a_letter_for_each_accented_letter() {
+        for (accent, bases, accented) in ACCENTS {
+            assert_eq!(bases.chars().count(), accented.chars().count(), "{accent}");
+        }
+    }
+
+    #[test]
+    fn latex_round_trips_to_unicode() {
+        let letters = ACCENTS
+            .iter()
+            .flat_map(|(_, _, accented)| accented.chars())
+            .chain(SYMBOLS.iter().map(|(c, _)| *c))
+            .chain(['☺', '日'])
+            .collect::<String>();
+
+        let (latex, unknown) = unicode_to_latex(&letters).unwrap();
+
+        assert!(latex.is_ascii());
+        assert_eq!(vec!['☺', '日'], unknown);
+        assert_eq!(Some(letters), latex_to_unicode(&latex));
+    }
+
+    #[test]
+    fn other_forms_of_accents_are_read() {
+        for (latex, expected) in [
+            (r#"\"i"#, "ï"),
+            (r#"\"{\i}"#, "ï"),
+            (r"\v c", "č"),
+            (r"\v{C}", "Č"),
+            (r"\ss{}", "ß"),
+            (r"\aa", "å"),
+            (r"Wei{\ss}", "Weiß"),
+            (r#"{{\"{o}}}"#, "{ö}"),
+        ] {
+            assert_eq!(
+                Some(expected.to_owned()),
+                latex_to_unicode(latex),
+                "{latex}"
+            );
+        }
+        for latex in [r"\vc", r"\LaTeX", r#"\"{ab}"#, r"\", r"\char"] {
+            assert_eq!(None, latex_to_unicode(latex), "{latex}");
+        }
+    }
+}
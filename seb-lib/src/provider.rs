@@ -0,0 +1,571 @@
+//! Providers of bibliographic entries.
+//!
+//! A [`Provider`] is anything that can take a [`Query`], such as a DOI or ISBN, and return the
+//! matching bibliographic entries. The built-in providers ([`CrossRef`], [`GoogleBooks`], [`Ietf`],
+//! [`Ads`] and [`Ieee`]) are registered by default in [`Providers`] and custom providers can be registered
+//! along side them.
+//!
+//! # Examples
+//!
+//! ```
+//! use seb::{
+//!     ast::{Biblio, BiblioResolver, Entry, EntryKind},
+//!     provider::{Provider, Providers, Query},
+//!     Error, ErrorKind,
+//! };
+//!
+//! /// A provider for an institution's private reference API.
+//! struct Institution;
+//!
+//! impl Provider for Institution {
+//!     fn name(&self) -> &'static str {
+//!         "institution"
+//!     }
+//!
+//!     fn supports(&self, query: &Query<'_>) -> bool {
+//!         matches!(query, Query::Other { kind: "institution", .. })
+//!     }
+//!
+//!     fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+//!         let id = match query {
+//!             Query::Other { id, .. } => id,
+//!             _ => return Err(Error::new(ErrorKind::NoValue, "Unsupported query")),
+//!         };
+//!         let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, *id);
+//!         resolver.title("Internal report");
+//!         Ok(Biblio::try_resolve(vec![resolver]))
+//!     }
+//! }
+//!
+//! let mut providers = Providers::default();
+//! providers.register(Institution);
+//!
+//! let biblio = providers
+//!     .lookup(&Query::Other { kind: "institution", id: "report-42" })
+//!     .expect("Institution provider supports this query")
+//!     .expect("Institution provider always resolves the entry");
+//!
+//! assert!(biblio.get("report-42").is_some());
+//! ```
+
+use std::collections::HashMap;
+
+use log::{debug, trace, warn};
+
+use crate::{
+    api,
+    ast::{Biblio, BiblioResolver, SOURCE_FIELD},
+    Client, Error, ErrorKind,
+};
+
+/// The hint of an entry that no provider finds.
+const ORDER_HINT: &str = "The entry may be found by another provider, change the order that the \
+                          providers are searched in";
+
+/// A search query for a [`Provider`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Query<'a> {
+    /// Search by [Digital Object Identifier](https://en.wikipedia.org/wiki/Digital_object_identifier).
+    Doi(&'a str),
+    /// Search by [International Standard Book Number](https://en.wikipedia.org/wiki/International_Standard_Book_Number).
+    Isbn(&'a str),
+    /// Search by [IETF RFC number](https://www.ietf.org/standards/rfcs/).
+    Rfc(usize),
+    /// Search by [ADS bibcode](https://ui.adsabs.harvard.edu/help/actions/bibcode).
+    Bibcode(&'a str),
+    /// Search by a standard, the organization that published it followed by its number such as
+    /// `IEEE 754` or `ISO 8601`.
+    Standard(&'a str),
+    /// Search by an identifier that is not known to seb, `kind` names the type of identifier so
+    /// that custom providers can recognise the queries they support.
+    Other {
+        /// The type of identifier.
+        kind: &'a str,
+        /// The identifier value.
+        id: &'a str,
+    },
+}
+
+impl Query<'_> {
+    /// The name of the type of identifier used by this query, such as `doi` or `isbn`.
+    ///
+    /// [`Query::Other`] uses its `kind` as the name.
+    #[must_use]
+    pub const fn kind(&self) -> &str {
+        match self {
+            Self::Doi(_) => "doi",
+            Self::Isbn(_) => "isbn",
+            Self::Rfc(_) => "rfc",
+            Self::Bibcode(_) => "bibcode",
+            Self::Standard(_) => "standard",
+            Self::Other { kind, .. } => kind,
+        }
+    }
+}
+
+impl std::fmt::Display for Query<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Doi(doi) => write!(f, "doi '{doi}'"),
+            Self::Isbn(isbn) => write!(f, "ISBN '{isbn}'"),
+            Self::Rfc(number) => write!(f, "IETF RFC number '{number}'"),
+            Self::Bibcode(bibcode) => write!(f, "ADS bibcode '{bibcode}'"),
+            Self::Standard(standard) => write!(f, "standard '{standard}'"),
+            Self::Other { kind, id } => write!(f, "{kind} '{id}'"),
+        }
+    }
+}
+
+/// A source of bibliographic entries.
+///
+/// Implement this trait to add a new source of entries and then register it using
+/// [`Providers::register`] so that it is consulted alongside the built-in providers.
+///
+/// Providers must be [`Send`] and [`Sync`] so that lookups can be made concurrently.
+pub trait Provider: Send + Sync {
+    /// The display name of this provider.
+    fn name(&self) -> &'static str;
+
+    /// Returns `true` when this provider can search for entries using the `query`.
+    fn supports(&self, query: &Query<'_>) -> bool;
+
+    /// The host of the API this provider sends requests to, such as `api.crossref.org`, or
+    /// [`None`] when it doesn't use the network.
+    ///
+    /// The default is [`None`].
+    fn host(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Search for the entries matching the `query`.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no entry is found for the `query` or when the entries found cannot
+    /// be parsed into a valid [Entry][E].
+    ///
+    /// [E]: crate::ast::Entry
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error>;
+}
+
+/// The [Crossref](https://www.crossref.org/) provider which supports [`Query::Doi`].
+///
+/// The contact email address set by [`set_contact_email`](crate::set_contact_email), or in the
+/// [`CONTACT_EMAIL_VAR`](crate::CONTACT_EMAIL_VAR) environment variable, is sent with every
+/// request so that Crossref serves them from its faster pool.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrossRef;
+
+impl Provider for CrossRef {
+    fn name(&self) -> &'static str {
+        "crossref"
+    }
+
+    fn supports(&self, query: &Query<'_>) -> bool {
+        matches!(query, Query::Doi(_))
+    }
+
+    fn host(&self) -> Option<&'static str> {
+        Some("api.crossref.org")
+    }
+
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match query {
+            Query::Doi(doi) => api::cross_ref::get_entries_by_doi::<Client>(
+                doi,
+                api::cross_ref::contact_email().as_deref(),
+            ),
+            _ => Err(unsupported(self, query)),
+        }
+    }
+}
+
+/// The [Google Books](https://books.google.com/) provider which supports [`Query::Isbn`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GoogleBooks;
+
+impl Provider for GoogleBooks {
+    fn name(&self) -> &'static str {
+        "google books"
+    }
+
+    fn supports(&self, query: &Query<'_>) -> bool {
+        matches!(query, Query::Isbn(_))
+    }
+
+    fn host(&self) -> Option<&'static str> {
+        Some("www.googleapis.com")
+    }
+
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match query {
+            Query::Isbn(isbn) => api::google_books::get_entries_by_isbn::<Client>(isbn),
+            _ => Err(unsupported(self, query)),
+        }
+    }
+}
+
+/// The [IETF Datatracker](https://datatracker.ietf.org/) provider which supports [`Query::Rfc`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ietf;
+
+impl Provider for Ietf {
+    fn name(&self) -> &'static str {
+        "ietf"
+    }
+
+    fn supports(&self, query: &Query<'_>) -> bool {
+        matches!(query, Query::Rfc(_))
+    }
+
+    fn host(&self) -> Option<&'static str> {
+        Some("datatracker.ietf.org")
+    }
+
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match query {
+            Query::Rfc(number) => api::ietf::get_entry_by_rfc::<Client>(*number),
+            _ => Err(unsupported(self, query)),
+        }
+    }
+}
+
+/// The [NASA Astrophysics Data System](https://ui.adsabs.harvard.edu/) provider which supports
+/// [`Query::Bibcode`].
+///
+/// The ADS API requires a token which is read from the [`ADS_TOKEN_VAR`](crate::ADS_TOKEN_VAR)
+/// environment variable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ads;
+
+impl Provider for Ads {
+    fn name(&self) -> &'static str {
+        "ads"
+    }
+
+    fn supports(&self, query: &Query<'_>) -> bool {
+        matches!(query, Query::Bibcode(_))
+    }
+
+    fn host(&self) -> Option<&'static str> {
+        Some("api.adsabs.harvard.edu")
+    }
+
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match query {
+            Query::Bibcode(bibcode) => {
+                api::ads::get_entries_by_bibcode::<Client>(bibcode, &api::ads::token()?)
+            }
+            _ => Err(unsupported(self, query)),
+        }
+    }
+}
+
+/// The [IEEE Xplore](https://ieeexplore.ieee.org/) provider which supports [`Query::Standard`]
+/// for IEEE standards.
+///
+/// The IEEE Xplore API requires a key which is read from the
+/// [`IEEE_TOKEN_VAR`](crate::IEEE_TOKEN_VAR) environment variable. The entries found are
+/// [`STANDARD_KIND`](crate::STANDARD_KIND) entries with the standard number in the `number` field.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ieee;
+
+impl Provider for Ieee {
+    fn name(&self) -> &'static str {
+        "ieee"
+    }
+
+    fn supports(&self, query: &Query<'_>) -> bool {
+        match query {
+            Query::Standard(standard) => api::ieee::split_standard(standard)
+                .is_some_and(|(organization, _)| organization.eq_ignore_ascii_case("IEEE")),
+            _ => false,
+        }
+    }
+
+    fn host(&self) -> Option<&'static str> {
+        Some("ieeexploreapi.ieee.org")
+    }
+
+    fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match query {
+            Query::Standard(standard) if self.supports(query) => {
+                let (_, number) =
+                    api::ieee::split_standard(standard).expect("supported standards have a number");
+                api::ieee::get_entries_by_standard::<Client>(number, &api::ieee::token()?)
+            }
+            _ => Err(unsupported(self, query)),
+        }
+    }
+}
+
+fn unsupported(provider: &(impl Provider + ?Sized), query: &Query<'_>) -> Error {
+    Error::new(
+        ErrorKind::NoValue,
+        format!(
+            "The {} provider does not support searching by {query}",
+            provider.name()
+        ),
+    )
+}
+
+/// A registry of [`Provider`]s that are consulted in the order that they were registered, unless
+/// an order is set for the kind of query using [`Providers::set_order`].
+///
+/// The [`Default`] registry contains the built-in providers.
+pub struct Providers {
+    providers: Vec<Box<dyn Provider>>,
+    // the names of the providers consulted for each kind of query, in order
+    order: HashMap<String, Vec<String>>,
+}
+
+impl Default for Providers {
+    fn default() -> Self {
+        let mut providers = Self::new();
+        providers.register(CrossRef);
+        providers.register(GoogleBooks);
+        providers.register(Ietf);
+        providers.register(Ads);
+        providers.register(Ieee);
+        providers
+    }
+}
+
+impl Providers {
+    /// Creates an empty registry without any of the built-in providers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+            order: HashMap::new(),
+        }
+    }
+
+    /// Register a new [`Provider`].
+    ///
+    /// Providers are consulted in the order that they are registered, so this provider will only
+    /// be used when the providers registered before it fail to find an entry.
+    pub fn register<P: Provider + 'static>(&mut self, provider: P) {
+        self.providers.push(Box::new(provider));
+    }
+
+    /// Returns an iterator of the registered providers in the order they are consulted.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Provider> {
+        self.providers.iter().map(|p| &**p)
+    }
+
+    /// Sets the order that the providers are consulted for queries of the `kind`, see
+    /// [`Query::kind`], using the names of the providers.
+    ///
+    /// Only the named providers are consulted for the `kind` of query, registered providers that
+    /// are not named are skipped. Names are compared ignoring case and names that don't match a
+    /// registered provider are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::provider::{Providers, Query};
+    ///
+    /// let mut providers = Providers::default();
+    /// providers.set_order("doi", ["crossref"]);
+    ///
+    /// let names = providers
+    ///     .consulted(&Query::Doi("10.1000/182"))
+    ///     .map(|p| p.name())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(vec!["crossref"], names);
+    /// ```
+    pub fn set_order<I, S>(&mut self, kind: &str, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect::<Vec<_>>();
+        for name in &names {
+            if !self.iter().any(|p| p.name().eq_ignore_ascii_case(name)) {
+                warn!("No provider named '{name}' is registered - it will be skipped");
+            }
+        }
+        self.order.insert(kind.to_lowercase(), names);
+    }
+
+    /// Returns an iterator of the providers that support the `query` in the order they are
+    /// consulted.
+    pub fn consulted<'a>(&'a self, query: &'a Query<'_>) -> impl Iterator<Item = &'a dyn Provider> {
+        let providers: Vec<&dyn Provider> = match self.order.get(&query.kind().to_lowercase()) {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| self.iter().find(|p| p.name().eq_ignore_ascii_case(name)))
+                .collect(),
+            None => self.iter().collect(),
+        };
+        providers.into_iter().filter(|p| p.supports(query))
+    }
+
+    /// Search the registered providers that support the `query` and return the result of the
+    /// first provider that finds an entry.
+    ///
+    /// The providers are consulted in the order set for the kind of query, see
+    /// [`Self::set_order`], or otherwise in the order they were registered.
+    ///
+    /// Each entry found is stamped with the name of the provider in the [`SOURCE_FIELD`].
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no registered provider supports the `query`.
+    /// An `Err` is returned with the last provider error when none of the providers that support
+    /// the `query` find an entry.
+    pub fn lookup(&self, query: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let mut last_err = None;
+
+        for provider in self.consulted(query) {
+            trace!(
+                "Searching by {query} using the {} provider",
+                provider.name()
+            );
+            match provider.lookup(query) {
+                Ok(mut res) => {
+                    match &mut res {
+                        Ok(biblio) => biblio.set_field_all(SOURCE_FIELD, provider.name()),
+                        Err(resolver) => resolver.set_field_all(SOURCE_FIELD, provider.name()),
+                    }
+                    return Ok(res);
+                }
+                Err(err) => {
+                    debug!("The {} provider failed with: {err}", provider.name());
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        match last_err {
+            // an entry that one provider cannot find may be found by another
+            Some(err) if err.kind() == ErrorKind::NoValue && err.hint().is_none() => {
+                Err(err.with_hint(ORDER_HINT))
+            }
+            Some(err) => Err(err),
+            None => Err(Error::new(
+                ErrorKind::NoValue,
+                format!("No provider supports searching by {query}"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Entry, EntryKind, FieldQuery};
+
+    struct Fixed(&'static str, Option<&'static str>);
+
+    impl Provider for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn supports(&self, query: &Query<'_>) -> bool {
+            matches!(query, Query::Other { kind: "test", .. })
+        }
+
+        fn lookup(&self, _: &Query<'_>) -> Result<Result<Biblio, BiblioResolver>, Error> {
+            let title = self
+                .1
+                .ok_or_else(|| Error::new(ErrorKind::NoValue, "Not found"))?;
+            let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            resolver.title(title);
+            Ok(Biblio::try_resolve(vec![resolver]))
+        }
+    }
+
+    const TEST_QUERY: Query<'static> = Query::Other {
+        kind: "test",
+        id: "id",
+    };
+
+    #[test]
+    fn error_when_no_provider_supports_query() {
+        let providers = Providers::new();
+        let err = providers
+            .lookup(&TEST_QUERY)
+            .expect_err("No providers are registered");
+
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+
+    #[test]
+    fn first_successful_provider_is_used() {
+        let mut providers = Providers::new();
+        providers.register(Fixed("missing", None));
+        providers.register(Fixed("first", Some("First")));
+        providers.register(Fixed("second", Some("Second")));
+
+        let biblio = providers
+            .lookup(&TEST_QUERY)
+            .expect("The first provider fails but the others should succeed")
+            .expect("Entry is resolved");
+
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!("First", &**entry.title());
+        assert_eq!("first", &**entry.get_field(SOURCE_FIELD).unwrap());
+    }
+
+    #[test]
+    fn first_successful_provider_in_order_is_used() {
+        let mut providers = Providers::new();
+        providers.register(Fixed("first", Some("First")));
+        providers.register(Fixed("missing", None));
+        providers.register(Fixed("second", Some("Second")));
+        providers.set_order("TEST", ["missing", "second", "unknown"]);
+
+        let biblio = providers
+            .lookup(&TEST_QUERY)
+            .expect("The second provider in the order should succeed")
+            .expect("Entry is resolved");
+
+        let entry = biblio.get("cite").unwrap();
+        assert_eq!("Second", &**entry.title());
+        assert_eq!("second", &**entry.get_field(SOURCE_FIELD).unwrap());
+    }
+
+    #[test]
+    fn unlisted_providers_are_skipped() {
+        let mut providers = Providers::new();
+        providers.register(Fixed("first", Some("First")));
+
+        // the order only applies to the kind of query it is set for
+        providers.set_order("doi", ["missing"]);
+        assert_eq!(1, providers.consulted(&TEST_QUERY).count());
+
+        providers.set_order("test", ["missing"]);
+        assert_eq!(0, providers.consulted(&TEST_QUERY).count());
+        assert!(providers.lookup(&TEST_QUERY).is_err());
+    }
+
+    #[test]
+    fn last_error_returned_when_all_providers_fail() {
+        let mut providers = Providers::new();
+        providers.register(Fixed("missing", None));
+
+        let err = providers
+            .lookup(&TEST_QUERY)
+            .expect_err("The only provider fails");
+
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+
+    #[test]
+    fn built_in_providers_only_support_their_queries() {
+        assert!(CrossRef.supports(&Query::Doi("10.1000/182")));
+        assert!(!CrossRef.supports(&Query::Isbn("0735619670")));
+        assert!(GoogleBooks.supports(&Query::Isbn("0735619670")));
+        assert!(!GoogleBooks.supports(&Query::Rfc(7230)));
+        assert!(Ietf.supports(&Query::Rfc(7230)));
+        assert!(!Ietf.supports(&Query::Doi("10.1000/182")));
+        assert!(Ads.supports(&Query::Bibcode("2019A&A...623A..72S")));
+        assert!(!Ads.supports(&Query::Doi("10.1000/182")));
+        assert!(Ieee.supports(&Query::Standard("IEEE Std 754-2019")));
+        assert!(!Ieee.supports(&Query::Standard("ISO 8601")));
+    }
+}